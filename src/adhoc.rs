@@ -0,0 +1,106 @@
+use crate::{RuleConfig, Severity};
+
+const KEYS: &str = "id, message, regex, severity, includes, excludes";
+
+/// Parses an inline `--rule` definition like `id=tmp;severity=error;regex=unwrap\(\)`.
+/// Entries are separated by `;` (written `\;` inside a value), and `includes`/`excludes`
+/// take comma-separated globs. Unset ids default to `adhoc-<n>` and severities to error.
+pub fn parse_rule(spec: &str, index: usize) -> anyhow::Result<RuleConfig> {
+    let mut rule = RuleConfig {
+        id: format!("adhoc-{}", index + 1),
+        source: Some(String::from("--rule")),
+        ..Default::default()
+    };
+
+    for entry in split_entries(spec) {
+        if entry.trim().is_empty() {
+            continue;
+        }
+        let Some((key, value)) = entry.split_once('=') else {
+            anyhow::bail!(
+                "Invalid --rule {spec:?}: entry {entry:?} isn't key=value (keys: {KEYS})"
+            );
+        };
+        match key.trim() {
+            "id" => rule.id = value.to_owned(),
+            "message" => rule.message = Some(value.to_owned()),
+            "regex" => rule.regex = Some(value.to_owned()),
+            "severity" => rule.severity = Some(parse_severity(value, spec)?),
+            "includes" => rule.includes = Some(split_globs(value)),
+            "excludes" => rule.excludes = Some(split_globs(value)),
+            other => {
+                anyhow::bail!("Invalid --rule {spec:?}: unknown key {other:?} (keys: {KEYS})")
+            }
+        }
+    }
+
+    finish(rule, spec)
+}
+
+/// Builds the rule described by the `--rule-regex` family of flags.
+pub fn rule_from_flags(
+    regex: &str,
+    id: Option<&str>,
+    severity: Option<Severity>,
+    message: Option<&str>,
+    includes: &[String],
+) -> anyhow::Result<RuleConfig> {
+    let rule = RuleConfig {
+        id: id.unwrap_or("adhoc").to_owned(),
+        message: message.map(str::to_owned),
+        regex: Some(regex.to_owned()),
+        severity,
+        includes: (!includes.is_empty()).then(|| includes.to_vec()),
+        source: Some(String::from("--rule-regex")),
+        ..Default::default()
+    };
+    finish(rule, regex)
+}
+
+fn finish(mut rule: RuleConfig, spec: &str) -> anyhow::Result<RuleConfig> {
+    if rule.regex.is_none() {
+        anyhow::bail!("Invalid --rule {spec:?}: missing key \"regex\"");
+    }
+    if rule.id.is_empty() {
+        anyhow::bail!("Invalid --rule {spec:?}: key \"id\" can't be empty");
+    }
+    rule.severity.get_or_insert(Severity::Error);
+    rule.message
+        .get_or_insert_with(|| format!("Matched ad-hoc rule {}", rule.id));
+    Ok(rule)
+}
+
+fn parse_severity(value: &str, spec: &str) -> anyhow::Result<Severity> {
+    match value.trim() {
+        "warning" => Ok(Severity::Warning),
+        "error" => Ok(Severity::Error),
+        other => anyhow::bail!(
+            "Invalid --rule {spec:?}: key \"severity\" must be warning or error, not {other:?}"
+        ),
+    }
+}
+
+fn split_entries(spec: &str) -> Vec<String> {
+    let mut entries = vec![String::new()];
+    let mut chars = spec.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&';') => {
+                entries.last_mut().unwrap().push(';');
+                chars.next();
+            }
+            ';' => entries.push(String::new()),
+            _ => entries.last_mut().unwrap().push(c),
+        }
+    }
+    entries
+}
+
+fn split_globs(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|glob| !glob.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
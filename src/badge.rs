@@ -0,0 +1,36 @@
+use crate::outcome::Outcome;
+use serde::Serialize;
+
+pub const DEFAULT_MESSAGE: &str = "{errors} errors, {warnings} warnings";
+
+/// The JSON shields.io reads from an endpoint badge.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Endpoint {
+    schema_version: u32,
+    label: &'static str,
+    message: String,
+    color: &'static str,
+}
+
+/// Renders a shields.io endpoint badge for the run. `message` may use the `{errors}`,
+/// `{warnings}`, and `{total}` placeholders.
+pub fn render(outcome: &Outcome, message: &str) -> anyhow::Result<String> {
+    let color = if outcome.errors > 0 {
+        "red"
+    } else if outcome.warnings > 0 {
+        "yellow"
+    } else {
+        "brightgreen"
+    };
+    let endpoint = Endpoint {
+        schema_version: 1,
+        label: "linty",
+        message: message
+            .replace("{errors}", &outcome.errors.to_string())
+            .replace("{warnings}", &outcome.warnings.to_string())
+            .replace("{total}", &(outcome.errors + outcome.warnings).to_string()),
+        color,
+    };
+    Ok(serde_json::to_string(&endpoint)?)
+}
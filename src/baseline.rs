@@ -0,0 +1,131 @@
+use crate::Violation;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub const DEFAULT_BASELINE_PATH: &str = ".lintybaseline.json";
+
+/// Version of the baseline file's shape, bumped on incompatible changes.
+const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct BaselineFile {
+    schema_version: u32,
+    entries: Vec<Entry>,
+}
+
+/// Known matches of one rule in one file with the same matched text. Keying on the text
+/// rather than the line keeps entries valid when unrelated edits move the match.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    rule_id: String,
+    file: String,
+    text: String,
+    count: usize,
+}
+
+type Key = (String, String, String);
+
+fn key(rule_id: &str, file: &str, text: &str) -> Key {
+    (rule_id.to_owned(), file.to_owned(), text.to_owned())
+}
+
+/// Whether a violation is a real finding rather than a note about the scan itself.
+fn is_finding(violation: &Violation) -> bool {
+    !violation.rule_id.starts_with("linty::")
+}
+
+/// Serializes every match of `violations` as a baseline, sorted so regenerating it only
+/// changes the entries that did.
+pub fn render(violations: &[Violation]) -> anyhow::Result<String> {
+    let mut counts: BTreeMap<Key, usize> = BTreeMap::new();
+    for violation in violations.iter().filter(|violation| is_finding(violation)) {
+        for m in &violation.matches {
+            *counts
+                .entry(key(&violation.rule_id, &violation.file, &m.text))
+                .or_default() += 1;
+        }
+    }
+    let baseline = BaselineFile {
+        schema_version: SCHEMA_VERSION,
+        entries: counts
+            .into_iter()
+            .map(|((rule_id, file, text), count)| Entry {
+                rule_id,
+                file,
+                text,
+                count,
+            })
+            .collect(),
+    };
+    Ok(serde_json::to_string_pretty(&baseline)? + "\n")
+}
+
+/// How applying a baseline changed the run's violations, per rule id, like the counts of
+/// suppressed matches.
+#[derive(Debug, Default)]
+pub struct Applied {
+    /// Matches the baseline already knew about, left out of the report.
+    pub known: BTreeMap<String, usize>,
+    /// Baselined matches that no longer occur.
+    pub resolved: BTreeMap<String, usize>,
+}
+
+impl Applied {
+    pub fn known_total(&self) -> usize {
+        self.known.values().sum()
+    }
+
+    pub fn resolved_total(&self) -> usize {
+        self.resolved.values().sum()
+    }
+}
+
+/// Drops the matches `path`'s baseline knows about from `violations`, and violations left
+/// without matches.
+pub fn apply(path: &Path, violations: Vec<Violation>) -> anyhow::Result<(Vec<Violation>, Applied)> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| anyhow::anyhow!("Failed to read baseline {}: {err}", path.display()))?;
+    let baseline: BaselineFile = serde_json::from_str(&contents)
+        .map_err(|err| anyhow::anyhow!("Failed to parse baseline {}: {err}", path.display()))?;
+    if baseline.schema_version != SCHEMA_VERSION {
+        anyhow::bail!(
+            "Baseline {} has schema version {}, but this linty writes version {SCHEMA_VERSION}; regenerate it with linty baseline",
+            path.display(),
+            baseline.schema_version
+        );
+    }
+    let mut remaining: BTreeMap<Key, usize> = baseline
+        .entries
+        .into_iter()
+        .map(|entry| ((entry.rule_id, entry.file, entry.text), entry.count))
+        .collect();
+
+    let mut applied = Applied::default();
+    let mut kept = Vec::with_capacity(violations.len());
+    for mut violation in violations {
+        if is_finding(&violation) {
+            let (rule_id, file) = (&violation.rule_id, &violation.file);
+            violation
+                .matches
+                .retain(|m| match remaining.get_mut(&key(rule_id, file, &m.text)) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        *applied.known.entry(rule_id.clone()).or_default() += 1;
+                        false
+                    }
+                    _ => true,
+                });
+            if violation.matches.is_empty() {
+                continue;
+            }
+        }
+        kept.push(violation);
+    }
+    for ((rule_id, _, _), count) in remaining {
+        if count > 0 {
+            *applied.resolved.entry(rule_id).or_default() += count;
+        }
+    }
+    Ok((kept, applied))
+}
@@ -0,0 +1,244 @@
+use crate::git::Repo;
+use crate::{Rule, Severity, Violation};
+use serde::Serialize;
+use std::cell::OnceCell;
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BlameInfo {
+    pub author: String,
+    pub date: String,
+    /// Whole days since the line's commit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_days: Option<u64>,
+}
+
+/// Caches `git blame` results per file so a file is only blamed once per run.
+#[derive(Default)]
+pub struct BlameCache {
+    /// The repository files are blamed in, looked for when first needed unless given, and
+    /// `None` outside of one.
+    repo: OnceCell<Option<Repo>>,
+    files: HashMap<String, HashMap<usize, BlameInfo>>,
+}
+
+impl BlameCache {
+    /// A cache that blames files in `repo`.
+    pub fn new(repo: Repo) -> BlameCache {
+        BlameCache {
+            repo: OnceCell::from(Some(repo)),
+            files: HashMap::new(),
+        }
+    }
+
+    /// Blames the given lines of `path`, batching them into a single `git blame` call.
+    /// Returns an empty map outside a repository, or when the file isn't tracked or git
+    /// fails for any reason.
+    pub fn blame_lines(&mut self, path: &Path, lines: &[usize]) -> HashMap<usize, BlameInfo> {
+        let key = path.to_string_lossy().into_owned();
+        let cached = self.files.entry(key).or_default();
+
+        let missing: Vec<usize> = lines
+            .iter()
+            .copied()
+            .filter(|line| !cached.contains_key(line))
+            .collect();
+        if !missing.is_empty() {
+            if let Some(repo) = self.repo.get_or_init(|| Repo::discover().ok()) {
+                cached.extend(run_blame(repo, path, &missing));
+            }
+        }
+
+        lines
+            .iter()
+            .filter_map(|line| cached.get(line).map(|info| (*line, info.clone())))
+            .collect()
+    }
+}
+
+/// Applies the `max_age_days` and `escalate_after_days` of each violation's rule: matches
+/// on lines younger than `max_age_days` are dropped, and matches on lines older than
+/// `escalate_after_days` move into a separate error violation. The blamed lines are kept
+/// on the violations so the report shows their age and author. Lines git can't blame,
+/// such as outside a repository, are always reported.
+pub fn apply_age_limits(
+    violations: Vec<Violation>,
+    rules: &[Rule],
+    cache: &mut BlameCache,
+    verbose: bool,
+) -> Vec<Violation> {
+    let aged: HashMap<&str, &Rule> = rules
+        .iter()
+        .filter(|rule| rule.max_age_days.is_some() || rule.escalate_after_days.is_some())
+        .map(|rule| (rule.id.as_str(), rule))
+        .collect();
+    if aged.is_empty() {
+        return violations;
+    }
+
+    // Every file's lines are blamed in one batch, however many rules matched it.
+    let mut lines_by_path: HashMap<PathBuf, BTreeSet<usize>> = HashMap::new();
+    for violation in &violations {
+        if aged.contains_key(violation.rule_id.as_str()) {
+            lines_by_path
+                .entry(violation.path.clone())
+                .or_default()
+                .extend(violation.matches.iter().map(|m| m.line));
+        }
+    }
+    let mut blamed: HashMap<PathBuf, HashMap<usize, BlameInfo>> = HashMap::new();
+    for (path, lines) in lines_by_path {
+        let lines: Vec<usize> = lines.into_iter().collect();
+        let blame = cache.blame_lines(&path, &lines);
+        if blame.is_empty() && verbose {
+            eprintln!(
+                "Couldn't blame {}; reporting its matches regardless of age",
+                crate::display_path(&path)
+            );
+        }
+        blamed.insert(path, blame);
+    }
+
+    let mut kept = Vec::with_capacity(violations.len());
+    for mut violation in violations {
+        let Some(rule) = aged.get(violation.rule_id.as_str()) else {
+            kept.push(violation);
+            continue;
+        };
+        let blame = &blamed[&violation.path];
+        let age = |line: usize| blame.get(&line).and_then(|info| info.age_days);
+
+        let mut escalated = Vec::new();
+        let mut remaining = Vec::new();
+        for violation_match in std::mem::take(&mut violation.matches) {
+            match (age(violation_match.line), rule.max_age_days) {
+                (Some(age), Some(max_age)) if age < max_age => continue,
+                _ => {}
+            }
+            match (age(violation_match.line), rule.escalate_after_days) {
+                (Some(age), Some(escalate_after)) if age >= escalate_after => {
+                    escalated.push(violation_match)
+                }
+                _ => remaining.push(violation_match),
+            }
+        }
+
+        for (matches, severity) in [
+            (remaining, violation.severity),
+            (escalated, Severity::Error),
+        ] {
+            if matches.is_empty() {
+                continue;
+            }
+            let blame = matches
+                .iter()
+                .filter_map(|m| Some((m.line, blame.get(&m.line)?.clone())))
+                .collect();
+            kept.push(Violation {
+                rule_id: violation.rule_id.clone(),
+                severity,
+                file: violation.file.clone(),
+                path: violation.path.clone(),
+                matches,
+                blame,
+                discarded: violation.discarded,
+                untracked: violation.untracked,
+                report_once: violation.report_once,
+            });
+        }
+    }
+    kept
+}
+
+fn run_blame(repo: &Repo, path: &Path, lines: &[usize]) -> HashMap<usize, BlameInfo> {
+    let mut args = vec![OsString::from("blame"), OsString::from("--porcelain")];
+    for line in lines {
+        args.push(OsString::from("-L"));
+        args.push(OsString::from(format!("{line},{line}")));
+    }
+    args.push(OsString::from("--"));
+    args.push(repo.root_path(path).into_os_string());
+
+    match repo.output(&args) {
+        Ok(stdout) => parse_porcelain(&String::from_utf8_lossy(&stdout)),
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// Parses `git blame --porcelain` output into a map from final line number to blame info.
+/// Commit headers (author, author-time) are only printed the first time a commit appears,
+/// so they're remembered per commit hash.
+fn parse_porcelain(output: &str) -> HashMap<usize, BlameInfo> {
+    let mut authors: HashMap<String, String> = HashMap::new();
+    let mut times: HashMap<String, i64> = HashMap::new();
+    let mut line_commits: Vec<(usize, String)> = Vec::new();
+    let mut current_commit = String::new();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64);
+
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            continue;
+        }
+        if let Some(author) = line.strip_prefix("author ") {
+            authors.insert(current_commit.clone(), author.to_owned());
+        } else if let Some(time) = line.strip_prefix("author-time ") {
+            if let Ok(time) = time.parse() {
+                times.insert(current_commit.clone(), time);
+            }
+        } else {
+            let mut fields = line.split(' ');
+            let (Some(hash), Some(_), Some(final_line)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            if hash.len() != 40 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                continue;
+            }
+            let Ok(final_line) = final_line.parse() else {
+                continue;
+            };
+            current_commit = hash.to_owned();
+            line_commits.push((final_line, current_commit.clone()));
+        }
+    }
+
+    line_commits
+        .into_iter()
+        .map(|(line, commit)| {
+            let time = times.get(&commit).copied();
+            let info = BlameInfo {
+                author: authors.get(&commit).cloned().unwrap_or_default(),
+                date: time.map(format_date).unwrap_or_default(),
+                age_days: time.map(|time| (now - time).max(0) as u64 / 86_400),
+            };
+            (line, info)
+        })
+        .collect()
+}
+
+/// Formats a Unix timestamp as a UTC `YYYY-MM-DD` date.
+fn format_date(timestamp: i64) -> String {
+    // Days-to-civil conversion from Howard Hinnant's date algorithms.
+    let days = timestamp.div_euclid(86_400);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
@@ -0,0 +1,229 @@
+use crate::scan::Match;
+use crate::Severity;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_CACHE_PATH: &str = ".linty-cache";
+
+/// Version of the cache file's shape, bumped on incompatible changes. A cache with another
+/// version is discarded rather than read.
+const SCHEMA_VERSION: u32 = 1;
+
+/// Files modified this recently aren't cached, since a second edit within the filesystem's
+/// timestamp granularity could leave the size and modification time unchanged.
+const SETTLE_TIME: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    schema_version: u32,
+    /// Hash of everything besides a file's contents that decides what a scan of it finds.
+    key: String,
+    /// Keyed by the path as walked.
+    entries: BTreeMap<PathBuf, Entry>,
+}
+
+/// What scanning one file found, for `--cache` to replay while the file is unchanged.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Entry {
+    pub stamp: Stamp,
+    // Most files have nothing to record past being scanned, so defaults are left out to
+    // keep the cache small.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub scanned: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub binary_skipped: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub large_skipped: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub encoding_skipped: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<CachedViolation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dropped: Vec<(String, Severity)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressed: Vec<(String, usize)>,
+    /// Microseconds the rules took to check the file, for estimating what replaying the
+    /// entry saves.
+    #[serde(default)]
+    pub cost_micros: u64,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// A file's size and modification time, which tell whether it changed since it was cached
+/// without reading it.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Stamp {
+    size: u64,
+    modified_secs: u64,
+    modified_nanos: u32,
+}
+
+impl Stamp {
+    /// The stamp of a file with `size` bytes last modified at `modified`, or `None` when
+    /// it was modified too recently to trust.
+    pub fn new(size: u64, modified: SystemTime) -> Option<Stamp> {
+        let settled = SystemTime::now()
+            .duration_since(modified)
+            .is_ok_and(|age| age >= SETTLE_TIME);
+        let since_epoch = modified.duration_since(UNIX_EPOCH).ok()?;
+        settled.then_some(Stamp {
+            size,
+            modified_secs: since_epoch.as_secs(),
+            modified_nanos: since_epoch.subsec_nanos(),
+        })
+    }
+}
+
+/// A violation as cached; the file it's in is the entry's.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CachedViolation {
+    pub rule_id: String,
+    pub severity: Severity,
+    pub matches: Vec<Match>,
+    pub discarded: usize,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub report_once: bool,
+}
+
+/// Results of a previous run's scan, keyed on a hash of the rules and the settings that
+/// affect them, from `--cache`.
+pub struct ResultCache {
+    path: PathBuf,
+    /// `path` made absolute, so the walk can leave the cache file itself out.
+    absolute_path: PathBuf,
+    key: String,
+    entries: BTreeMap<PathBuf, Entry>,
+}
+
+impl ResultCache {
+    /// Reads the cache at `path`, starting empty when there isn't one yet or it was written
+    /// by another version of linty or under another `key`. One that can't be parsed is
+    /// also discarded, with a warning to print.
+    pub fn load(path: &Path, key: String) -> (ResultCache, Option<String>) {
+        let mut warning = None;
+        let mut ignore = |err: serde_json::Error| {
+            warning = Some(format!("Warning: Ignoring cache {}: {err}", path.display()));
+            BTreeMap::new()
+        };
+        let entries = match std::fs::read_to_string(path) {
+            Ok(contents) => match serde_json::from_str::<serde_json::Value>(&contents) {
+                // The version is checked before the rest is parsed, since another version's
+                // entries may not parse at all.
+                Ok(value) if value["schema_version"] != SCHEMA_VERSION => BTreeMap::new(),
+                Ok(value) => match serde_json::from_value::<CacheFile>(value) {
+                    Ok(cache) if cache.key == key => cache.entries,
+                    Ok(_) => BTreeMap::new(),
+                    Err(err) => ignore(err),
+                },
+                Err(err) => ignore(err),
+            },
+            Err(_) => BTreeMap::new(),
+        };
+        let cache = ResultCache {
+            path: path.to_owned(),
+            absolute_path: crate::scan::absolute(path),
+            key,
+            entries,
+        };
+        (cache, warning)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the walked `path` is the cache file itself, which changes every run.
+    pub fn is_cache_file(&self, path: &Path) -> bool {
+        path.file_name() == self.absolute_path.file_name()
+            && crate::scan::absolute(path) == self.absolute_path
+    }
+
+    /// The cached results for the file at `path`, if it's unchanged since.
+    pub fn get(&self, path: &Path, stamp: Stamp) -> Option<&Entry> {
+        self.entries.get(path).filter(|entry| entry.stamp == stamp)
+    }
+
+    /// Serializes the cache with `updates` from this run's scan, dropping entries for files
+    /// that no longer exist.
+    pub fn render(&self, updates: Vec<(PathBuf, Entry)>) -> anyhow::Result<String> {
+        let mut entries: BTreeMap<PathBuf, Entry> = self
+            .entries
+            .iter()
+            .filter(|(path, _)| path.exists())
+            .map(|(path, entry)| (path.clone(), entry.clone()))
+            .collect();
+        entries.extend(updates);
+        let cache = CacheFile {
+            schema_version: SCHEMA_VERSION,
+            key: self.key.clone(),
+            entries,
+        };
+        Ok(serde_json::to_string(&cache)? + "\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("linty-cache-{name}-{}", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn missing_cache_starts_empty_without_a_warning() {
+        let path = std::env::temp_dir().join("linty-cache-that-doesnt-exist");
+        let (cache, warning) = ResultCache::load(&path, String::from("key"));
+        assert!(cache.entries.is_empty());
+        assert_eq!(warning, None);
+    }
+
+    #[test]
+    fn unparsable_cache_is_discarded_with_a_warning() {
+        for (name, contents) in [
+            ("not-json", "{"),
+            ("bad-entries", r#"{"schema_version":1}"#),
+        ] {
+            let path = cache_file(name, contents);
+            let (cache, warning) = ResultCache::load(&path, String::from("key"));
+            assert!(cache.entries.is_empty());
+            let warning = warning.unwrap();
+            assert!(
+                warning.starts_with(&format!("Warning: Ignoring cache {}: ", path.display())),
+                "{warning}"
+            );
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn cache_from_another_version_or_key_is_discarded_quietly() {
+        let empty = ResultCache::load(Path::new("unused"), String::from("old")).0;
+        let written = empty.render(Vec::new()).unwrap();
+        for (name, contents, key) in [
+            ("other-key", written.as_str(), "new"),
+            (
+                "other-version",
+                r#"{"schema_version":0,"entries":5}"#,
+                "old",
+            ),
+        ] {
+            let path = cache_file(name, contents);
+            let (cache, warning) = ResultCache::load(&path, String::from(key));
+            assert!(cache.entries.is_empty());
+            assert_eq!(warning, None);
+            std::fs::remove_file(path).unwrap();
+        }
+
+        let path = cache_file("same-key", &written);
+        assert_eq!(ResultCache::load(&path, String::from("old")).1, None);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::Duration;
+
+/// Why a scan stopped before visiting every file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cancellation {
+    Interrupted,
+    TimedOut,
+}
+
+impl Cancellation {
+    pub fn banner(self) -> &'static str {
+        match self {
+            Cancellation::Interrupted => "Scan interrupted — results are partial",
+            Cancellation::TimedOut => "Scan timed out — results are partial",
+        }
+    }
+
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Cancellation::Interrupted => 130,
+            Cancellation::TimedOut => 124,
+        }
+    }
+
+    fn tag(self) -> u8 {
+        match self {
+            Cancellation::Interrupted => 1,
+            Cancellation::TimedOut => 2,
+        }
+    }
+}
+
+static CANCELLED: AtomicU8 = AtomicU8::new(0);
+static SCANNING: AtomicBool = AtomicBool::new(false);
+
+/// Asks the running scan to stop. The first reason given wins.
+pub fn cancel(reason: Cancellation) {
+    let _ = CANCELLED.compare_exchange(0, reason.tag(), Ordering::SeqCst, Ordering::SeqCst);
+}
+
+pub fn cancelled() -> Option<Cancellation> {
+    match CANCELLED.load(Ordering::SeqCst) {
+        1 => Some(Cancellation::Interrupted),
+        2 => Some(Cancellation::TimedOut),
+        _ => None,
+    }
+}
+
+/// Marks whether a scan is in progress, so Ctrl-C outside of one exits right away.
+pub fn set_scanning(scanning: bool) {
+    SCANNING.store(scanning, Ordering::SeqCst);
+}
+
+pub fn scanning() -> bool {
+    SCANNING.load(Ordering::SeqCst)
+}
+
+/// Cancels the run once `timeout` has elapsed, sharing the path Ctrl-C takes.
+pub fn start_timer(timeout: Duration) {
+    std::thread::spawn(move || {
+        std::thread::sleep(timeout);
+        cancel(Cancellation::TimedOut);
+    });
+}
@@ -0,0 +1,420 @@
+use crate::Violation;
+use linty::git::Repo;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+/// The lines a diff adds to each file, for `--changed-lines-only`.
+#[derive(Debug, Default)]
+pub struct ChangedLines {
+    /// Keyed by walked path, as [`Repo::walk_path`] gives the files git lists for the walk.
+    files: HashMap<PathBuf, Vec<RangeInclusive<usize>>>,
+}
+
+impl ChangedLines {
+    /// Runs `git diff --unified=0` in `repo` with `diff_args`, such as `--staged` or a base
+    /// ref, and collects the lines it adds.
+    pub fn from_git(repo: &Repo, diff_args: &[&str]) -> ChangedLines {
+        // The prefixes are spelled out so a diff.noprefix or diff.mnemonicPrefix setting
+        // can't change the headers being parsed.
+        let mut args = vec![
+            "diff",
+            "--unified=0",
+            "--no-color",
+            "--no-ext-diff",
+            "--no-textconv",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+        ];
+        args.extend(diff_args);
+        let output = repo
+            .output(&args)
+            .unwrap_or_else(|err| crate::git_failure("--changed-lines-only", err));
+        ChangedLines::parse(&String::from_utf8_lossy(&output), repo)
+    }
+
+    /// Reads the added line ranges out of a unified diff's `+++` and `@@` headers, with
+    /// paths relative to `repo`'s root. Deleted files add nothing, and renamed files count
+    /// under their new path.
+    pub fn parse(diff: &str, repo: &Repo) -> ChangedLines {
+        let mut changed = ChangedLines::default();
+        let mut current: Option<PathBuf> = None;
+        // The lines left in the current hunk on its old and new sides, so one starting
+        // with `+++ ` or `--- ` isn't taken for a header.
+        let mut hunk_left: (usize, usize) = (0, 0);
+        for line in diff.lines() {
+            if hunk_left != (0, 0) {
+                match line.as_bytes().first() {
+                    Some(b'-') => hunk_left.0 = hunk_left.0.saturating_sub(1),
+                    Some(b'+') => hunk_left.1 = hunk_left.1.saturating_sub(1),
+                    Some(b' ') => {
+                        hunk_left = (hunk_left.0.saturating_sub(1), hunk_left.1.saturating_sub(1))
+                    }
+                    // `\ No newline at end of file` belongs to the line before it.
+                    _ => {}
+                }
+                continue;
+            }
+            if let Some(path) = line.strip_prefix("+++ ") {
+                // Git ends the header with a tab when the path has a space in it.
+                current = unquote(path.trim_end_matches('\t'))
+                    .strip_prefix("b/")
+                    .map(|path| repo.walk_path(Path::new(path)));
+                if let Some(path) = &current {
+                    changed.files.entry(path.clone()).or_default();
+                }
+            } else if line.starts_with("@@ ") {
+                let Some(hunk) = Hunk::parse(line) else {
+                    continue;
+                };
+                hunk_left = (hunk.old_count, hunk.new_count);
+                let (Some(path), Some(added)) = (&current, hunk.added_lines()) else {
+                    continue;
+                };
+                changed.files.entry(path.clone()).or_default().push(added);
+            }
+        }
+        changed
+    }
+
+    /// Whether the diff adds any line from `first` to `last` of the file at `path`.
+    pub fn contains(&self, path: &Path, first: usize, last: usize) -> bool {
+        self.files.get(path).is_some_and(|ranges| {
+            ranges
+                .iter()
+                .any(|range| *range.start() <= last && first <= *range.end())
+        })
+    }
+
+    /// Drops the matches that don't touch an added line, and violations left without
+    /// matches. A match spanning several lines stays when any of them was added, and a
+    /// path rule's match stays when the file changed at all.
+    /// Untracked files are new in their entirety, so nothing in them is dropped.
+    pub fn retain(&self, violations: Vec<Violation>) -> Vec<Violation> {
+        violations
+            .into_iter()
+            .filter_map(|mut violation| {
+                if violation.untracked {
+                    return Some(violation);
+                }
+                violation.matches.retain(|m| {
+                    // A path rule's match is about the file, so any change to it counts.
+                    if !m.has_position() {
+                        return self.files.contains_key(&violation.path);
+                    }
+                    self.contains(&violation.path, m.line, m.last_line())
+                });
+                (!violation.matches.is_empty()).then_some(violation)
+            })
+            .collect()
+    }
+}
+
+/// The line numbers and counts of a hunk header like `@@ -10,2 +12,3 @@`, where a missing
+/// count means one line.
+struct Hunk {
+    old_count: usize,
+    new_start: usize,
+    new_count: usize,
+}
+
+impl Hunk {
+    fn parse(header: &str) -> Option<Hunk> {
+        let mut parts = header.split(' ').skip(1);
+        let (_, old_count) = side(parts.next()?.strip_prefix('-')?)?;
+        let (new_start, new_count) = side(parts.next()?.strip_prefix('+')?)?;
+        Some(Hunk {
+            old_count,
+            new_start,
+            new_count,
+        })
+    }
+
+    /// The lines the hunk adds, or `None` when it only removes lines. Git diffs with no
+    /// context, so every new-side line is added.
+    fn added_lines(&self) -> Option<RangeInclusive<usize>> {
+        (self.new_count > 0).then(|| self.new_start..=self.new_start + self.new_count - 1)
+    }
+}
+
+/// One side of a hunk header, like `12,3`, as its start and count.
+fn side(side: &str) -> Option<(usize, usize)> {
+    match side.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((side.parse().ok()?, 1)),
+    }
+}
+
+/// Undoes the C-style quoting git applies to paths with unusual characters, like
+/// `"b/caf\303\251.txt"`. Unquoted paths are returned as they are.
+fn unquote(path: &str) -> String {
+    let Some(quoted) = path
+        .strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+    else {
+        return path.to_owned();
+    };
+    let mut bytes = Vec::new();
+    let mut chars = quoted.chars();
+    while let Some(character) = chars.next() {
+        if character != '\\' {
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(character.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('n') => bytes.push(b'\n'),
+            Some('t') => bytes.push(b'\t'),
+            Some('r') => bytes.push(b'\r'),
+            Some('a') => bytes.push(0x07),
+            Some('b') => bytes.push(0x08),
+            Some('f') => bytes.push(0x0c),
+            Some('v') => bytes.push(0x0b),
+            Some(digit @ '0'..='7') => {
+                let mut value = digit.to_digit(8).unwrap_or_default();
+                for _ in 0..2 {
+                    if let Some(digit) = chars.clone().next().and_then(|c| c.to_digit(8)) {
+                        value = value * 8 + digit;
+                        chars.next();
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => {
+                let mut buffer = [0; 4];
+                bytes.extend_from_slice(other.encode_utf8(&mut buffer).as_bytes());
+            }
+            None => bytes.push(b'\\'),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linty::scan::Match;
+    use linty::Severity;
+
+    fn root() -> Repo {
+        Repo::parse("/repo\n\n\n").unwrap()
+    }
+
+    fn ranges(changed: &ChangedLines, path: &str) -> Option<Vec<(usize, usize)>> {
+        changed.files.get(Path::new(path)).map(|ranges| {
+            ranges
+                .iter()
+                .map(|range| (*range.start(), *range.end()))
+                .collect()
+        })
+    }
+
+    const MULTIPLE_HUNKS: &str = "\
+diff --git a/src/main.rs b/src/main.rs
+index 1111111..2222222 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -3 +3 @@ fn main() {
+-    old();
++    new();
+@@ -10,0 +11,3 @@ fn helper() {
++    one();
++    two();
++    three();
+@@ -20,2 +23,0 @@ fn gone() {
+-    removed();
+-    removed();
+@@ -40 +41,2 @@
+-x
++y
++z
+";
+
+    #[test]
+    fn collects_the_added_lines_of_every_hunk() {
+        let changed = ChangedLines::parse(MULTIPLE_HUNKS, &root());
+        assert_eq!(
+            ranges(&changed, "./src/main.rs"),
+            Some(vec![(3, 3), (11, 13), (41, 42)])
+        );
+        assert!(changed.contains(Path::new("./src/main.rs"), 12, 12));
+        assert!(!changed.contains(Path::new("./src/main.rs"), 14, 40));
+        assert!(changed.contains(Path::new("./src/main.rs"), 14, 41));
+    }
+
+    #[test]
+    fn renamed_file_counts_under_its_new_path() {
+        let diff = "\
+diff --git a/old/name.rs b/new/name.rs
+similarity index 90%
+rename from old/name.rs
+rename to new/name.rs
+index 1111111..2222222 100644
+--- a/old/name.rs
++++ b/new/name.rs
+@@ -5 +5 @@
+-before
++after
+";
+        let changed = ChangedLines::parse(diff, &root());
+        assert_eq!(ranges(&changed, "./new/name.rs"), Some(vec![(5, 5)]));
+        assert_eq!(ranges(&changed, "./old/name.rs"), None);
+    }
+
+    #[test]
+    fn pure_rename_changes_the_file_without_lines() {
+        let diff = "\
+diff --git a/a.rs b/b.rs
+similarity index 100%
+rename from a.rs
+rename to b.rs
+";
+        let changed = ChangedLines::parse(diff, &root());
+        assert_eq!(ranges(&changed, "./b.rs"), None);
+    }
+
+    #[test]
+    fn no_newline_at_end_of_file_is_not_a_line() {
+        let diff = "\
+diff --git a/a.txt b/a.txt
+--- a/a.txt
++++ b/a.txt
+@@ -2 +2,2 @@
+-last
+\\ No newline at end of file
++last
++more
+\\ No newline at end of file
+diff --git a/b.txt b/b.txt
+--- a/b.txt
++++ b/b.txt
+@@ -1 +1 @@
+-b
++c
+";
+        let changed = ChangedLines::parse(diff, &root());
+        assert_eq!(ranges(&changed, "./a.txt"), Some(vec![(2, 3)]));
+        assert_eq!(ranges(&changed, "./b.txt"), Some(vec![(1, 1)]));
+    }
+
+    #[test]
+    fn added_lines_that_look_like_headers_stay_in_their_hunk() {
+        let diff = "\
+diff --git a/notes.md b/notes.md
+--- a/notes.md
++++ b/notes.md
+@@ -1,2 +1,2 @@
+--- b/other.md
+-@@ -1 +1 @@
++++ b/other.md
++@@ -9 +9 @@
+";
+        let changed = ChangedLines::parse(diff, &root());
+        assert_eq!(ranges(&changed, "./notes.md"), Some(vec![(1, 2)]));
+        assert_eq!(ranges(&changed, "./other.md"), None);
+    }
+
+    #[test]
+    fn new_and_deleted_files() {
+        let diff = "\
+diff --git a/new.txt b/new.txt
+new file mode 100644
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1,2 @@
++a
++b
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+--- a/gone.txt
++++ /dev/null
+@@ -1,2 +0,0 @@
+-a
+-b
+";
+        let changed = ChangedLines::parse(diff, &root());
+        assert_eq!(ranges(&changed, "./new.txt"), Some(vec![(1, 2)]));
+        assert_eq!(ranges(&changed, "./gone.txt"), None);
+    }
+
+    #[test]
+    fn quoted_paths_and_paths_with_spaces() {
+        let diff = "\
+--- a/with space.txt\t
++++ b/with space.txt\t
+@@ -1 +1 @@
+-a
++b
+--- \"a/caf\\303\\251.txt\"
++++ \"b/caf\\303\\251.txt\"
+@@ -1 +1 @@
+-a
++b
+";
+        let changed = ChangedLines::parse(diff, &root());
+        assert_eq!(ranges(&changed, "./with space.txt"), Some(vec![(1, 1)]));
+        assert_eq!(ranges(&changed, "./café.txt"), Some(vec![(1, 1)]));
+    }
+
+    #[test]
+    fn paths_are_relative_to_the_current_directory() {
+        let repo = Repo::parse("/repo\nsrc/\n../\n").unwrap();
+        let changed = ChangedLines::parse(MULTIPLE_HUNKS, &repo);
+        assert!(ranges(&changed, "./main.rs").is_some());
+        let diff = MULTIPLE_HUNKS.replace("src/main.rs", "docs/x.md");
+        let changed = ChangedLines::parse(&diff, &repo);
+        assert!(ranges(&changed, "../docs/x.md").is_some());
+    }
+
+    fn at(line: usize, end_line: usize) -> Match {
+        Match {
+            line,
+            column: 1,
+            end_line,
+            end_column: 2,
+            text: String::new(),
+            fingerprint: String::new(),
+            replacement: None,
+            message: None,
+            truncated: false,
+        }
+    }
+
+    fn violation(path: &str, matches: Vec<Match>) -> Violation {
+        Violation {
+            rule_id: String::from("Rule"),
+            severity: Severity::Error,
+            file: path.trim_start_matches("./").to_owned(),
+            path: PathBuf::from(path),
+            matches,
+            blame: HashMap::new(),
+            discarded: 0,
+            untracked: false,
+            report_once: false,
+        }
+    }
+
+    #[test]
+    fn keeps_matches_touching_an_added_line() {
+        let changed = ChangedLines::parse(MULTIPLE_HUNKS, &root());
+        let kept = changed.retain(vec![
+            violation(
+                "./src/main.rs",
+                // Line 3 was added, 5 wasn't, and 9 to 11 spans into an added hunk.
+                vec![at(3, 3), at(5, 5), at(9, 11)],
+            ),
+            violation("./src/other.rs", vec![at(3, 3)]),
+        ]);
+        assert_eq!(kept.len(), 1);
+        let lines: Vec<usize> = kept[0].matches.iter().map(|m| m.line).collect();
+        assert_eq!(lines, [3, 9]);
+    }
+
+    #[test]
+    fn untracked_files_keep_every_match() {
+        let mut untracked = violation("./new.rs", vec![at(1, 1)]);
+        untracked.untracked = true;
+        let kept = ChangedLines::default().retain(vec![untracked]);
+        assert_eq!(kept.len(), 1);
+    }
+}
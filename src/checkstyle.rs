@@ -0,0 +1,57 @@
+use crate::xml::escape;
+use crate::{severity_name, Violation};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+
+/// Writes a Checkstyle XML report with an `<error>` per match, grouped by file and sorted
+/// by line, for tools like Warnings-NG and reviewdog.
+pub fn write_report(
+    mut writer: impl Write,
+    violations: &[Violation],
+    messages: &HashMap<&str, &str>,
+) -> anyhow::Result<()> {
+    let mut by_file: BTreeMap<&str, Vec<&Violation>> = BTreeMap::new();
+    for violation in violations {
+        by_file.entry(&violation.file).or_default().push(violation);
+    }
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, r#"<checkstyle version="4.3">"#)?;
+    for (file, violations) in by_file {
+        writeln!(writer, r#"  <file name="{}">"#, escape(file))?;
+        let mut errors: Vec<(usize, usize, String)> = Vec::new();
+        for violation in violations {
+            let id = violation.rule_id.as_str();
+            let message = messages
+                .get(id)
+                .copied()
+                .filter(|message| !message.is_empty())
+                .unwrap_or(id);
+            for m in &violation.matches {
+                let attributes = format!(
+                    r#"severity="{}" message="{}" source="{}""#,
+                    severity_name(violation.severity),
+                    escape(m.message(message)),
+                    escape(id)
+                );
+                // A path rule's match is about the whole file, so it has no position.
+                let error = if m.has_position() {
+                    format!(
+                        r#"<error line="{}" column="{}" {attributes}/>"#,
+                        m.line, m.column
+                    )
+                } else {
+                    format!("<error {attributes}/>")
+                };
+                errors.push((m.line, m.column, error));
+            }
+        }
+        errors.sort();
+        for (_, _, error) in errors {
+            writeln!(writer, "    {error}")?;
+        }
+        writeln!(writer, "  </file>")?;
+    }
+    writeln!(writer, "</checkstyle>")?;
+    Ok(())
+}
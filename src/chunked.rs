@@ -0,0 +1,124 @@
+use crate::InvalidUtf8;
+use regex_syntax::hir::{Class, Hir, HirKind, Look};
+use std::io::{BufRead, BufReader, Read};
+use std::ops::Range;
+
+/// Files larger than this are scanned in chunks of about this size when every rule that
+/// applies to them is line-local.
+pub const CHUNK_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Whether every match of `pattern`, compiled with the given flags, lies within a single
+/// line, and doesn't depend on where the file starts or ends. Such rules see the same
+/// matches in any run of whole lines, so they can be applied chunk by chunk.
+pub fn is_line_local(pattern: &str, multi_line: bool, dot_matches_new_line: bool) -> bool {
+    let parsed = regex_syntax::ParserBuilder::new()
+        .multi_line(multi_line)
+        .dot_matches_new_line(dot_matches_new_line)
+        .build()
+        .parse(pattern);
+    match parsed {
+        Ok(hir) => !can_span_lines(&hir),
+        Err(_) => false,
+    }
+}
+
+fn can_span_lines(hir: &Hir) -> bool {
+    match hir.kind() {
+        HirKind::Empty => false,
+        HirKind::Literal(literal) => literal.0.contains(&b'\n'),
+        HirKind::Class(Class::Unicode(class)) => class
+            .ranges()
+            .iter()
+            .any(|range| range.start() <= '\n' && '\n' <= range.end()),
+        HirKind::Class(Class::Bytes(class)) => class
+            .ranges()
+            .iter()
+            .any(|range| range.start() <= b'\n' && b'\n' <= range.end()),
+        // `\A` and `\z` (and `^` and `$` without `(?m)`) anchor to the whole file.
+        HirKind::Look(look) => matches!(look, Look::Start | Look::End),
+        HirKind::Repetition(repetition) => can_span_lines(&repetition.sub),
+        HirKind::Capture(capture) => can_span_lines(&capture.sub),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => hirs.iter().any(can_span_lines),
+    }
+}
+
+/// A run of whole lines from a file, with the line before and after it included as
+/// context so fingerprints match a whole-file scan.
+pub struct Chunk {
+    pub text: String,
+    /// The part of `text` this chunk is responsible for; only matches starting here count.
+    pub core: Range<usize>,
+    /// The 1-based line number of the first line of `text`.
+    pub first_line: usize,
+}
+
+/// Reads a file as consecutive chunks of at least `CHUNK_BYTES`, split after a newline.
+pub struct Chunks<R> {
+    reader: BufReader<R>,
+    /// The next chunk's lines, read ahead to supply the current chunk's trailing context.
+    next: Option<String>,
+    /// The last line of the previous chunk.
+    previous_line: String,
+    /// Lines in the chunks returned so far.
+    lines_before: usize,
+    invalid_utf8: InvalidUtf8,
+}
+
+impl<R: Read> Chunks<R> {
+    pub fn new(reader: R, invalid_utf8: InvalidUtf8) -> std::io::Result<Chunks<R>> {
+        let mut chunks = Chunks {
+            reader: BufReader::new(reader),
+            invalid_utf8,
+            next: None,
+            previous_line: String::new(),
+            lines_before: 0,
+        };
+        chunks.next = chunks.read_core()?;
+        Ok(chunks)
+    }
+
+    pub fn next_chunk(&mut self) -> std::io::Result<Option<Chunk>> {
+        let Some(core) = self.next.take() else {
+            return Ok(None);
+        };
+        self.next = self.read_core()?;
+        let trailing = self.next.as_deref().map_or("", |next| {
+            next.find('\n').map_or(next, |index| &next[..=index])
+        });
+
+        let leading = std::mem::take(&mut self.previous_line);
+        let first_line = self.lines_before + 1 - usize::from(!leading.is_empty());
+        self.lines_before += core.matches('\n').count();
+        let last_line_start = core[..core.len() - 1]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        self.previous_line = core[last_line_start..].to_owned();
+
+        let mut text = leading;
+        let core_start = text.len();
+        text.push_str(&core);
+        let core_end = text.len();
+        text.push_str(trailing);
+        Ok(Some(Chunk {
+            text,
+            core: core_start..core_end,
+            first_line,
+        }))
+    }
+
+    /// Reads the next `CHUNK_BYTES`, extended to the end of the line they stop in.
+    fn read_core(&mut self) -> std::io::Result<Option<String>> {
+        let mut bytes = Vec::new();
+        (&mut self.reader)
+            .take(CHUNK_BYTES)
+            .read_to_end(&mut bytes)?;
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        if bytes.last() != Some(&b'\n') {
+            self.reader.read_until(b'\n', &mut bytes)?;
+        }
+        // Chunks end at line breaks, so they never split a UTF-8 sequence.
+        crate::scan::decode(bytes, self.invalid_utf8).map(Some)
+    }
+}
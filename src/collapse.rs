@@ -0,0 +1,121 @@
+use crate::Violation;
+
+/// A rule needs more than this many files with the same finding to be collapsed.
+const THRESHOLD: usize = 10;
+/// How many of the similar files are still listed.
+const SHOWN: usize = 3;
+
+/// For `--collapse-similar`: picks which of a rule's violations to list. Violations that
+/// are a single match on line 1, typical of header rules, are similar, and when there are
+/// more than `THRESHOLD` of them only the first `SHOWN` by path are listed. Returns the
+/// violations to list, in their original order, and how many similar ones were left out.
+pub fn collapse_similar(violations: &[Violation]) -> (Vec<&Violation>, usize) {
+    let is_similar = |violation: &Violation| violation.lines() == [1];
+    let mut similar: Vec<&Violation> = violations
+        .iter()
+        .filter(|violation| is_similar(violation))
+        .collect();
+    if similar.len() <= THRESHOLD {
+        return (violations.iter().collect(), 0);
+    }
+
+    similar.sort_by(|a, b| a.path.cmp(&b.path));
+    let listed = &similar[..SHOWN];
+    let shown = violations
+        .iter()
+        .filter(|violation| {
+            !is_similar(violation)
+                || listed
+                    .iter()
+                    .any(|listed| std::ptr::eq(*listed, *violation))
+        })
+        .collect();
+    (shown, similar.len() - SHOWN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linty::scan::Match;
+    use linty::Severity;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    /// A violation in `path` with a match starting on each of `lines`.
+    fn violation(path: &str, lines: &[usize]) -> Violation {
+        let matches = lines
+            .iter()
+            .map(|&line| Match {
+                line,
+                column: 1,
+                end_line: line,
+                end_column: 2,
+                text: String::new(),
+                fingerprint: String::new(),
+                replacement: None,
+                message: None,
+                truncated: false,
+            })
+            .collect();
+        Violation {
+            rule_id: String::from("Header"),
+            severity: Severity::Error,
+            file: path.to_owned(),
+            path: PathBuf::from(path),
+            matches,
+            blame: HashMap::new(),
+            discarded: 0,
+            untracked: false,
+            report_once: false,
+        }
+    }
+
+    fn files(shown: &[&Violation]) -> Vec<String> {
+        shown
+            .iter()
+            .map(|violation| violation.file.clone())
+            .collect()
+    }
+
+    #[test]
+    fn up_to_the_threshold_everything_is_listed() {
+        let violations: Vec<Violation> = (0..THRESHOLD)
+            .map(|index| violation(&format!("f{index:02}.rs"), &[1]))
+            .collect();
+        let (shown, hidden) = collapse_similar(&violations);
+        assert_eq!(shown.len(), THRESHOLD);
+        assert_eq!(hidden, 0);
+    }
+
+    #[test]
+    fn over_the_threshold_the_first_paths_are_listed() {
+        // Out of path order, so the listed ones are picked by path, not by position.
+        let mut violations: Vec<Violation> = (0..=THRESHOLD)
+            .rev()
+            .map(|index| violation(&format!("f{index:02}.rs"), &[1]))
+            .collect();
+        violations.insert(4, violation("a/other.rs", &[1, 5]));
+        violations.push(violation("b/later.rs", &[2]));
+
+        let (shown, hidden) = collapse_similar(&violations);
+        assert_eq!(hidden, THRESHOLD + 1 - SHOWN);
+        // What isn't similar is always listed, and everything keeps its order.
+        assert_eq!(
+            files(&shown),
+            ["a/other.rs", "f02.rs", "f01.rs", "f00.rs", "b/later.rs"]
+        );
+        // The same input collapses the same way every time.
+        assert_eq!(files(&collapse_similar(&violations).0), files(&shown));
+    }
+
+    #[test]
+    fn only_single_matches_on_the_first_line_are_similar() {
+        let mut violations: Vec<Violation> = (0..=THRESHOLD)
+            .map(|index| violation(&format!("f{index:02}.rs"), &[1, 2]))
+            .collect();
+        violations.push(violation("g.rs", &[1]));
+        let (shown, hidden) = collapse_similar(&violations);
+        assert_eq!(shown.len(), violations.len());
+        assert_eq!(hidden, 0);
+    }
+}
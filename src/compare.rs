@@ -0,0 +1,134 @@
+use crate::{Severity, Violation};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Counts of how the current run's violations relate to a previous JSON report.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Comparison {
+    pub new: usize,
+    pub fixed: usize,
+    pub unchanged: usize,
+    pub new_errors: usize,
+    pub new_warnings: usize,
+}
+
+#[derive(Deserialize)]
+struct PreviousReport {
+    violations: Vec<PreviousViolation>,
+}
+
+#[derive(Deserialize)]
+struct PreviousViolation {
+    rule_id: String,
+    file: String,
+    #[serde(default)]
+    fingerprints: Vec<String>,
+}
+
+/// Matches the current violations against a previous `--format json` report by
+/// (rule id, path, fingerprint), so unrelated edits that shift line numbers don't
+/// count as new or fixed violations.
+pub fn compare(previous_path: &Path, violations: &[Violation]) -> anyhow::Result<Comparison> {
+    let file = File::open(previous_path).map_err(|err| {
+        anyhow::anyhow!(
+            "Failed to open previous report {}: {err}",
+            previous_path.display()
+        )
+    })?;
+    let previous: PreviousReport =
+        serde_json::from_reader(BufReader::new(file)).map_err(|err| {
+            anyhow::anyhow!(
+                "Failed to parse previous report {}: {err}",
+                previous_path.display()
+            )
+        })?;
+
+    let mut remaining: HashMap<(String, String, String), usize> = HashMap::new();
+    for violation in previous.violations {
+        for fingerprint in violation.fingerprints {
+            *remaining
+                .entry((
+                    violation.rule_id.clone(),
+                    violation.file.clone(),
+                    fingerprint,
+                ))
+                .or_default() += 1;
+        }
+    }
+
+    let mut comparison = Comparison::default();
+    for violation in violations {
+        if violation.rule_id == crate::scan::OVERFLOW_RULE_ID {
+            continue;
+        }
+        let file = crate::display_path(&violation.path);
+        for violation_match in &violation.matches {
+            let key = (
+                violation.rule_id.clone(),
+                file.clone(),
+                violation_match.fingerprint.clone(),
+            );
+            match remaining.get_mut(&key) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    comparison.unchanged += 1;
+                }
+                _ => {
+                    comparison.new += 1;
+                    match violation.severity {
+                        Severity::Warning => comparison.new_warnings += 1,
+                        Severity::Error => comparison.new_errors += 1,
+                    }
+                }
+            }
+        }
+    }
+    comparison.fixed = remaining.values().sum();
+
+    Ok(comparison)
+}
+
+/// Fingerprints the match at `offset` by the content of its line plus a small hash of
+/// the surrounding lines. Leading and trailing whitespace is ignored so re-indentation
+/// and CRLF line endings don't change the fingerprint.
+pub fn fingerprint(contents: &str, offset: usize) -> String {
+    let (start, end) = line_bounds(contents, offset);
+    let previous = if start == 0 {
+        ""
+    } else {
+        let (previous_start, previous_end) = line_bounds(contents, start - 1);
+        &contents[previous_start..previous_end]
+    };
+    let next = if end >= contents.len() {
+        ""
+    } else {
+        let (next_start, next_end) = line_bounds(contents, end + 1);
+        &contents[next_start..next_end]
+    };
+
+    let line_hash = fnv1a(contents[start..end].trim().as_bytes());
+    let context_hash = fnv1a(format!("{}\n{}", previous.trim(), next.trim()).as_bytes());
+    format!("{line_hash:016x}{:04x}", context_hash & 0xffff)
+}
+
+fn line_bounds(contents: &str, offset: usize) -> (usize, usize) {
+    let start = contents[..offset].rfind('\n').map_or(0, |index| index + 1);
+    let end = contents[offset..]
+        .find('\n')
+        .map_or(contents.len(), |index| offset + index);
+    (start, end)
+}
+
+/// 64-bit FNV-1a, used instead of `DefaultHasher` because fingerprints are persisted
+/// across runs and must not change between Rust releases.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
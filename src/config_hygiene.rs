@@ -0,0 +1,123 @@
+use crate::outcome::{self, Reason};
+use crate::{Config, Severity};
+use regex::Regex;
+use regex_syntax::hir::{Class, Hir, HirKind};
+
+/// Messages longer than this are unlikely to be read in full in a report.
+const MAX_MESSAGE_CHARS: usize = 200;
+
+/// A problem with the config itself rather than with the files it checks.
+struct Diagnostic {
+    id: &'static str,
+    message: String,
+}
+
+/// Checks the config for mistakes that make rules misbehave without failing to load,
+/// printing each as a warning, or failing the run when `strict` is set.
+pub fn check(config: &Config, strict: bool) {
+    let diagnostics = diagnostics(config);
+    if diagnostics.is_empty() {
+        return;
+    }
+    let label = if strict { "Error" } else { "Warning" };
+    for diagnostic in &diagnostics {
+        eprintln!("{label}: {} ({})", diagnostic.message, diagnostic.id);
+    }
+    if strict {
+        eprintln!("Failing due to config problems (--strict-config)");
+        outcome::fail(Reason::ConfigError);
+    }
+}
+
+fn diagnostics(config: &Config) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for rule in &config.rules {
+        let id = &rule.id;
+        // Regexes that don't compile are reported when the rules are built.
+        if let Some(Ok(regex)) = rule.pattern().as_deref().map(Regex::new) {
+            if regex.find("").is_some() {
+                diagnostics.push(Diagnostic {
+                    id: "linty::config/empty-match",
+                    message: format!(
+                        "Rule {id}'s regex {:?} matches the empty string, so it matches at every position of every file",
+                        regex.as_str()
+                    ),
+                });
+            }
+            if regex_syntax::ParserBuilder::new()
+                .dot_matches_new_line(rule.dot_matches_new_line.unwrap_or(false))
+                .build()
+                .parse(regex.as_str())
+                .is_ok_and(|hir| has_unbounded_any(&hir))
+            {
+                diagnostics.push(Diagnostic {
+                    id: "linty::config/unbounded-dotall",
+                    message: format!(
+                        "Rule {id}'s regex {:?} repeats any character, newlines included, without a limit, so one match can run to the end of the file",
+                        regex.as_str()
+                    ),
+                });
+            }
+        }
+
+        let message = rule.message.as_deref().unwrap_or_default();
+        if rule.severity == Some(Severity::Error) && message.trim().is_empty() {
+            diagnostics.push(Diagnostic {
+                id: "linty::config/missing-message",
+                message: format!("Rule {id} has severity error but no message"),
+            });
+        }
+        let length = message.chars().count();
+        if length > MAX_MESSAGE_CHARS {
+            diagnostics.push(Diagnostic {
+                id: "linty::config/long-message",
+                message: format!(
+                    "Rule {id}'s message is {length} characters long; keep it under {MAX_MESSAGE_CHARS}"
+                ),
+            });
+        }
+
+        let globs = rule.includes.iter().chain(rule.excludes.iter()).flatten();
+        for glob in globs.filter(|glob| has_path_backslash(glob)) {
+            diagnostics.push(Diagnostic {
+                id: "linty::config/backslash-glob",
+                message: format!(
+                    "Rule {id}'s glob {glob:?} separates paths with backslashes; globs always use /"
+                ),
+            });
+        }
+    }
+    diagnostics
+}
+
+/// Whether `hir` has an unbounded repetition of any character including newlines, like
+/// `(?s).*` or `[\s\S]+`.
+fn has_unbounded_any(hir: &Hir) -> bool {
+    match hir.kind() {
+        HirKind::Repetition(repetition) => {
+            let any = match repetition.sub.kind() {
+                HirKind::Class(Class::Unicode(class)) => class
+                    .ranges()
+                    .first()
+                    .is_some_and(|range| range.start() == '\0' && range.end() == char::MAX),
+                HirKind::Class(Class::Bytes(class)) => class
+                    .ranges()
+                    .first()
+                    .is_some_and(|range| range.start() == 0 && range.end() == u8::MAX),
+                _ => false,
+            };
+            (any && repetition.max.is_none()) || has_unbounded_any(&repetition.sub)
+        }
+        HirKind::Capture(capture) => has_unbounded_any(&capture.sub),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => hirs.iter().any(has_unbounded_any),
+        _ => false,
+    }
+}
+
+/// Whether `glob` has a backslash that reads as a Windows path separator, like `src\main`,
+/// rather than an escape of a special character, like `\*`.
+fn has_path_backslash(glob: &str) -> bool {
+    glob.split('\\')
+        .skip(1)
+        .any(|after| after.starts_with(|character: char| character.is_alphanumeric()))
+}
@@ -0,0 +1,111 @@
+use linty::git::{GitError, Repo};
+use std::path::{Component, Path, PathBuf};
+
+/// What a config may reference. A config found in the working tree could come from a
+/// cloned repository, so unless it's trusted it may only reference files inside the
+/// repository root, and never remote URLs.
+#[derive(Debug, Clone)]
+pub struct ConfigTrust {
+    /// Set by `--trusted-config`, or for a config picked with `--config-path`.
+    trusted: bool,
+    allow_remote: bool,
+    root: PathBuf,
+}
+
+impl ConfigTrust {
+    pub fn new(trusted: bool, allow_remote: bool) -> ConfigTrust {
+        ConfigTrust {
+            trusted,
+            allow_remote,
+            root: repository_root(),
+        }
+    }
+
+    /// Checks that the config file at `path`, after following symlinks, is inside the
+    /// repository root.
+    pub fn check_config_file(&self, path: &str) -> Result<(), String> {
+        if self.trusted {
+            return Ok(());
+        }
+        let Ok(resolved) = linty::canonicalize(Path::new(path)) else {
+            // Missing files are reported when the config is read.
+            return Ok(());
+        };
+        if resolved.starts_with(&self.root) {
+            return Ok(());
+        }
+        Err(format!(
+            "Refusing to use the config at {path}: it resolves to {}, outside the repository at {} (pass --trusted-config if you trust it)",
+            resolved.display(),
+            self.root.display()
+        ))
+    }
+
+    /// Checks a reference from the config, like an `extends` entry, that isn't a built-in
+    /// name. Relative paths are resolved against the config file's directory.
+    pub fn check_reference(&self, config_path: &str, reference: &str) -> Result<(), String> {
+        if is_remote(reference) {
+            if self.allow_remote {
+                return Ok(());
+            }
+            return Err(format!(
+                "Refusing to fetch {reference} referenced by the config: remote configs require --allow-remote-config"
+            ));
+        }
+        if self.trusted {
+            return Ok(());
+        }
+        let base = Path::new(config_path)
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let resolved = resolve(&base.join(reference));
+        if resolved.starts_with(&self.root) {
+            return Ok(());
+        }
+        Err(format!(
+            "Refusing to read {reference} referenced by the config: it resolves to {}, outside the repository at {} (pass --trusted-config if you trust it)",
+            resolved.display(),
+            self.root.display()
+        ))
+    }
+}
+
+fn is_remote(reference: &str) -> bool {
+    reference.contains("://")
+}
+
+/// The repository's top-level directory, or the current directory outside of git.
+fn repository_root() -> PathBuf {
+    let root = match Repo::discover() {
+        Ok(repo) => repo.root().to_path_buf(),
+        Err(GitError::NotARepository | GitError::NotInstalled) => PathBuf::from("."),
+        Err(err) => crate::git_failure("Checking the config's references", err),
+    };
+    linty::canonicalize(&root).unwrap_or(root)
+}
+
+/// Resolves `path` one component at a time, following symlinks in the parts that exist,
+/// so neither `..` nor a symlink can escape the containment check for a missing file.
+fn resolve(path: &Path) -> PathBuf {
+    let current_dir = std::env::current_dir().unwrap_or_default();
+    let mut resolved = linty::canonicalize(&current_dir).unwrap_or(current_dir);
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => {
+                resolved = PathBuf::from(component.as_os_str());
+            }
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::Normal(name) => {
+                resolved.push(name);
+                if let Ok(canonical) = linty::canonicalize(&resolved) {
+                    resolved = canonical;
+                }
+            }
+        }
+    }
+    resolved
+}
@@ -0,0 +1,59 @@
+use crate::Rule;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+const SCHEMA_VERSION: u32 = 1;
+
+/// Which files each rule examined, for `--coverage-json`. Lists are sorted by path so
+/// reports from different runs can be diffed.
+#[derive(Serialize, Debug)]
+pub struct Coverage {
+    schema_version: u32,
+    /// The files each rule's globs and overrides selected, by rule id. Every rule is
+    /// listed, even one that selected nothing.
+    rules: BTreeMap<String, Vec<RuleFile>>,
+    /// Bytes read from each scanned file, by path.
+    files: BTreeMap<String, u64>,
+}
+
+#[derive(Serialize, Debug)]
+struct RuleFile {
+    path: String,
+    /// False when the file was selected but couldn't be read, so the regex never ran.
+    executed: bool,
+}
+
+impl Coverage {
+    pub fn new(rules: &[Rule]) -> Coverage {
+        Coverage {
+            schema_version: SCHEMA_VERSION,
+            rules: rules
+                .iter()
+                .map(|rule| (rule.id.clone(), Vec::new()))
+                .collect(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    pub fn record_file(&mut self, path: &Path, bytes_read: u64) {
+        self.files.insert(crate::display_path(path), bytes_read);
+    }
+
+    pub fn record_rule(&mut self, rule_id: &str, path: &Path, executed: bool) {
+        self.rules
+            .entry(rule_id.to_owned())
+            .or_default()
+            .push(RuleFile {
+                path: crate::display_path(path),
+                executed,
+            });
+    }
+
+    pub fn into_json(mut self) -> anyhow::Result<String> {
+        for files in self.rules.values_mut() {
+            files.sort_by(|a, b| a.path.cmp(&b.path));
+        }
+        Ok(serde_json::to_string_pretty(&self)? + "\n")
+    }
+}
@@ -0,0 +1,34 @@
+/// Sends `--notify` desktop notifications with a run's summary, skipping runs whose error
+/// and warning counts match the previous run's.
+#[derive(Default)]
+pub struct Notifier {
+    last_counts: Option<(usize, usize)>,
+    unavailable: bool,
+}
+
+impl Notifier {
+    pub fn notify(&mut self, errors: usize, warnings: usize, summary: &str) {
+        if self.unavailable || self.last_counts == Some((errors, warnings)) {
+            return;
+        }
+        self.last_counts = Some((errors, warnings));
+        if let Err(err) = send(summary) {
+            eprintln!("Warning: Can't send desktop notifications: {err}");
+            self.unavailable = true;
+        }
+    }
+}
+
+#[cfg(feature = "notify")]
+fn send(summary: &str) -> anyhow::Result<()> {
+    notify_rust::Notification::new()
+        .summary("linty")
+        .body(summary)
+        .show()?;
+    Ok(())
+}
+
+#[cfg(not(feature = "notify"))]
+fn send(_summary: &str) -> anyhow::Result<()> {
+    anyhow::bail!("linty was built without the notify feature")
+}
@@ -0,0 +1,134 @@
+use crate::{severity_name, Severity, SeverityDisplay};
+use clap::ValueEnum;
+use std::collections::HashMap;
+use std::io::IsTerminal;
+
+const COLORS: [(&str, &str); 8] = [
+    ("black", "30"),
+    ("red", "31"),
+    ("green", "32"),
+    ("yellow", "33"),
+    ("blue", "34"),
+    ("magenta", "35"),
+    ("cyan", "36"),
+    ("white", "37"),
+];
+
+#[derive(ValueEnum, Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color when stdout and stderr are terminals and `NO_COLOR` isn't set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// How the human-readable reporters print each severity, from the config's `display`
+/// section. Structured formats always use the canonical severity names.
+pub struct DisplayTable {
+    warning: Entry,
+    error: Entry,
+    color: bool,
+}
+
+struct Entry {
+    label: String,
+    ansi_code: Option<&'static str>,
+}
+
+impl DisplayTable {
+    /// Validates the configured colors, which default to yellow warnings and red errors.
+    /// With `ColorChoice::Auto`, colors only apply when both stdout and stderr are
+    /// terminals, since errors are reported on stderr, and `NO_COLOR` isn't set.
+    pub fn new(
+        display: Option<&HashMap<Severity, SeverityDisplay>>,
+        choice: ColorChoice,
+    ) -> anyhow::Result<Self> {
+        let entry = |severity: Severity| -> anyhow::Result<Entry> {
+            let configured = display.and_then(|display| display.get(&severity));
+            let ansi_code = match configured.and_then(|entry| entry.color.as_deref()) {
+                Some(color) => Some(
+                    COLORS
+                        .iter()
+                        .find(|(name, _)| *name == color)
+                        .map(|(_, code)| *code)
+                        .ok_or_else(|| {
+                            anyhow::anyhow!(
+                                "Unknown color {color:?} for {} in display; expected one of {}",
+                                severity_name(severity),
+                                COLORS.map(|(name, _)| name).join(", ")
+                            )
+                        })?,
+                ),
+                None => Some(match severity {
+                    Severity::Warning => "33",
+                    Severity::Error => "31",
+                }),
+            };
+            Ok(Entry {
+                label: configured
+                    .and_then(|entry| entry.label.clone())
+                    .unwrap_or_else(|| severity_name(severity).to_owned()),
+                ansi_code,
+            })
+        };
+
+        Ok(DisplayTable {
+            warning: entry(Severity::Warning)?,
+            error: entry(Severity::Error)?,
+            color: match choice {
+                ColorChoice::Always => true,
+                ColorChoice::Never => false,
+                ColorChoice::Auto => {
+                    std::io::stdout().is_terminal()
+                        && std::io::stderr().is_terminal()
+                        && std::env::var_os("NO_COLOR").is_none()
+                }
+            },
+        })
+    }
+
+    fn entry(&self, severity: Severity) -> &Entry {
+        match severity {
+            Severity::Warning => &self.warning,
+            Severity::Error => &self.error,
+        }
+    }
+
+    /// The plain label, like `warning` or a configured `BLOCKER`.
+    pub fn label(&self, severity: Severity) -> &str {
+        &self.entry(severity).label
+    }
+
+    /// The label with its first letter capitalized, for the start of a line.
+    pub fn title(&self, severity: Severity) -> String {
+        let label = self.label(severity);
+        let mut chars = label.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    }
+
+    /// Wraps `text` in the severity's color when color output is on.
+    pub fn paint(&self, severity: Severity, text: &str) -> String {
+        self.style(self.entry(severity).ansi_code, text)
+    }
+
+    /// Bolds `text`, like a rule id, when color output is on.
+    pub fn bold(&self, text: &str) -> String {
+        self.style(Some("1"), text)
+    }
+
+    /// Dims `text`, like a file location, when color output is on.
+    pub fn dim(&self, text: &str) -> String {
+        self.style(Some("2"), text)
+    }
+
+    fn style(&self, ansi_code: Option<&str>, text: &str) -> String {
+        match ansi_code {
+            Some(code) if self.color => format!("\x1b[{code}m{text}\x1b[0m"),
+            _ => text.to_owned(),
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use crate::{RuleConfig, Severity};
+use ignore::WalkBuilder;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+const EDITORCONFIG_FILENAME: &str = ".editorconfig";
+
+/// A parsed `.editorconfig` file.
+struct EditorConfig {
+    /// The file's directory as a walk path, like `.` or `./docs`, or `None` for a file
+    /// above the current directory.
+    dir: Option<String>,
+    path: PathBuf,
+    root: bool,
+    sections: Vec<Section>,
+}
+
+struct Section {
+    /// The section's glob, rewritten relative to the current directory. `None` when it
+    /// can't be, like a path-anchored section in a file above the current directory.
+    glob: Option<String>,
+    properties: Vec<(String, String)>,
+}
+
+impl Section {
+    fn sets(&self, key: &str) -> bool {
+        self.properties.iter().any(|(name, _)| name == key)
+    }
+}
+
+/// For a top-level `"editorconfig": true`: adds rules equivalent to the `.editorconfig`
+/// files in and above the current directory, with ids like `editorconfig/max-line-length`.
+/// A configured rule with the same id takes precedence over the generated ones.
+pub fn add_rules(rules: &mut Vec<RuleConfig>) -> anyhow::Result<()> {
+    let mut files = ancestor_files()?;
+    files.extend(walked_files()?);
+
+    let explicit: HashSet<String> = rules.iter().map(|rule| rule.id.clone()).collect();
+    let mut warned: HashSet<&str> = HashSet::new();
+    for (file_index, file) in files.iter().enumerate() {
+        for (section_index, section) in file.sections.iter().enumerate() {
+            let Some(glob) = &section.glob else {
+                continue;
+            };
+            for (key, value) in &section.properties {
+                let Some((id, regex, message)) = property_rule(key, value) else {
+                    continue;
+                };
+                if explicit.contains(id) {
+                    if warned.insert(id) {
+                        eprintln!(
+                            "Warning: Using the configured rule {id} instead of the one from .editorconfig"
+                        );
+                    }
+                    continue;
+                }
+
+                rules.push(RuleConfig {
+                    id: id.to_owned(),
+                    message: Some(message),
+                    regex: Some(regex),
+                    severity: Some(Severity::Error),
+                    includes: Some(vec![glob.clone()]),
+                    excludes: Some(overridden_by(&files, file_index, section_index, key)),
+                    source: Some(format!("editorconfig:{}", file.path.display())),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Globs for files where a later section of the same file, or a closer `.editorconfig`,
+/// decides `key` instead of `files[file_index].sections[section_index]`.
+fn overridden_by(
+    files: &[EditorConfig],
+    file_index: usize,
+    section_index: usize,
+    key: &str,
+) -> Vec<String> {
+    let file = &files[file_index];
+    let mut excludes: Vec<String> = file.sections[section_index + 1..]
+        .iter()
+        .filter(|section| section.sets(key))
+        .filter_map(|section| section.glob.clone())
+        .collect();
+
+    for closer in &files[file_index + 1..] {
+        let Some(closer_dir) = &closer.dir else {
+            continue;
+        };
+        let is_nested = match &file.dir {
+            None => true,
+            Some(dir) => Path::new(closer_dir).starts_with(dir) && closer_dir != dir,
+        };
+        if !is_nested {
+            continue;
+        }
+        if closer.root {
+            excludes.push(format!("{closer_dir}/**"));
+        } else {
+            excludes.extend(
+                closer
+                    .sections
+                    .iter()
+                    .filter(|section| section.sets(key))
+                    .filter_map(|section| section.glob.clone()),
+            );
+        }
+    }
+    excludes
+}
+
+/// The regex rule for an `.editorconfig` property, as `(id, regex, message)`, for the
+/// properties that can be checked by matching.
+fn property_rule(key: &str, value: &str) -> Option<(&'static str, String, String)> {
+    match (key, value) {
+        ("max_line_length", length) => {
+            let length: usize = length.parse().ok()?;
+            Some((
+                "editorconfig/max-line-length",
+                format!("(?m)^[^\\r\\n]{{{},}}", length + 1),
+                format!("Line is longer than {length} characters"),
+            ))
+        }
+        ("trim_trailing_whitespace", "true") => Some((
+            "editorconfig/trim-trailing-whitespace",
+            String::from("(?m)[ \\t]+\\r?$"),
+            String::from("Trailing whitespace"),
+        )),
+        ("insert_final_newline", "true") => Some((
+            "editorconfig/insert-final-newline",
+            String::from("[^\\n]\\z"),
+            String::from("File doesn't end with a newline"),
+        )),
+        ("charset", "utf-8") => Some((
+            "editorconfig/charset",
+            String::from("\\A\\x{FEFF}"),
+            String::from("File starts with a byte order mark but charset is utf-8"),
+        )),
+        ("charset", "utf-8-bom") => Some((
+            "editorconfig/charset",
+            String::from("\\A[^\\x{FEFF}]"),
+            String::from("File doesn't start with a byte order mark but charset is utf-8-bom"),
+        )),
+        ("end_of_line", "lf") => Some((
+            "editorconfig/end-of-line",
+            String::from("\\r\\n"),
+            String::from("CRLF line ending but end_of_line is lf"),
+        )),
+        ("end_of_line", "crlf") => Some((
+            "editorconfig/end-of-line",
+            String::from("(?m)(?:^|[^\\r])\\n"),
+            String::from("LF line ending but end_of_line is crlf"),
+        )),
+        ("indent_style", "space") => Some((
+            "editorconfig/indent-style",
+            String::from("(?m)^ *\\t"),
+            String::from("Tab indentation but indent_style is space"),
+        )),
+        _ => None,
+    }
+}
+
+/// `.editorconfig` files above the current directory, outermost first, stopping at one
+/// with `root = true`.
+fn ancestor_files() -> anyhow::Result<Vec<EditorConfig>> {
+    let current_dir = std::env::current_dir()?;
+    let mut files = Vec::new();
+    for dir in current_dir.ancestors().skip(1) {
+        let path = dir.join(EDITORCONFIG_FILENAME);
+        if !path.is_file() {
+            continue;
+        }
+        let file = parse(&std::fs::read_to_string(&path)?, path, None);
+        let root = file.root;
+        files.push(file);
+        if root {
+            break;
+        }
+    }
+    files.reverse();
+    Ok(files)
+}
+
+/// `.editorconfig` files in the current directory and below, parents before children.
+fn walked_files() -> anyhow::Result<Vec<EditorConfig>> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    // Hidden files have to be walked to find `.editorconfig`, but not git's internals.
+    for entry in WalkBuilder::new("./")
+        .hidden(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build()
+    {
+        let entry = entry?;
+        if entry.file_name() == EDITORCONFIG_FILENAME && entry.path().is_file() {
+            paths.push(entry.into_path());
+        }
+    }
+    paths.sort_by_key(|path| path.components().count());
+
+    let mut files = Vec::new();
+    for path in paths {
+        let dir = path
+            .parent()
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .unwrap_or_else(|| String::from("."));
+        files.push(parse(&std::fs::read_to_string(&path)?, path, Some(dir)));
+    }
+    Ok(files)
+}
+
+fn parse(contents: &str, path: PathBuf, dir: Option<String>) -> EditorConfig {
+    let mut file = EditorConfig {
+        dir,
+        path,
+        root: false,
+        sections: Vec::new(),
+    };
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(pattern) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            let glob = section_glob(file.dir.as_deref(), pattern);
+            file.sections.push(Section {
+                glob,
+                properties: Vec::new(),
+            });
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().to_lowercase();
+        let value = value.trim().to_lowercase();
+        match file.sections.last_mut() {
+            Some(section) => section.properties.push((key, value)),
+            None if key == "root" => file.root = value == "true",
+            None => {}
+        }
+    }
+    file
+}
+
+/// Rewrites a section pattern as a glob over walk paths. Patterns without a `/` match
+/// file names at any depth below the `.editorconfig`'s directory.
+fn section_glob(dir: Option<&str>, pattern: &str) -> Option<String> {
+    match (dir, pattern.contains('/')) {
+        (Some(dir), false) => Some(format!("{dir}/**/{pattern}")),
+        (Some(dir), true) => Some(format!("{dir}/{}", pattern.trim_start_matches('/'))),
+        (None, false) => Some(format!("**/{pattern}")),
+        (None, true) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn walked(dir: &str, contents: &str) -> EditorConfig {
+        let path = PathBuf::from(format!("{dir}/{EDITORCONFIG_FILENAME}"));
+        parse(contents, path, Some(dir.to_owned()))
+    }
+
+    #[test]
+    fn sections_become_globs_under_their_directory() {
+        let file = walked(
+            "./docs",
+            "root = true\n; a comment\n[*.md]\nMax_Line_Length = 100\n[/api/*.md]\nindent_style = space\n",
+        );
+        assert!(file.root);
+        let globs: Vec<Option<&str>> = file
+            .sections
+            .iter()
+            .map(|section| section.glob.as_deref())
+            .collect();
+        assert_eq!(globs, [Some("./docs/**/*.md"), Some("./docs/api/*.md")]);
+        assert_eq!(
+            file.sections[0].properties,
+            [(String::from("max_line_length"), String::from("100"))]
+        );
+
+        // Above the current directory, only patterns without a path can be placed.
+        let above = parse(
+            "[*.rs]\n[src/*.rs]\n",
+            PathBuf::from("../.editorconfig"),
+            None,
+        );
+        assert_eq!(above.sections[0].glob.as_deref(), Some("**/*.rs"));
+        assert_eq!(above.sections[1].glob, None);
+    }
+
+    #[test]
+    fn nested_root_file_takes_its_whole_directory() {
+        let files = [
+            walked(".", "root = true\n[*]\nmax_line_length = 80\n"),
+            walked("./vendor", "root = true\n[*.c]\nindent_style = space\n"),
+            walked("./docs", "[*.md]\nmax_line_length = 120\n"),
+        ];
+        // Nothing in vendor follows the outer file, whatever vendor's file sets; in docs,
+        // only what the nested file sets itself is taken from it.
+        assert_eq!(
+            overridden_by(&files, 0, 0, "max_line_length"),
+            ["./vendor/**", "./docs/**/*.md"]
+        );
+        assert_eq!(overridden_by(&files, 0, 0, "indent_style"), ["./vendor/**"]);
+        assert!(overridden_by(&files, 2, 0, "max_line_length").is_empty());
+    }
+
+    #[test]
+    fn later_sections_of_a_file_win() {
+        let files = [walked(
+            ".",
+            "[*]\nmax_line_length = 80\n[*.md]\nmax_line_length = 120\n[*.rs]\nindent_style = space\n",
+        )];
+        assert_eq!(
+            overridden_by(&files, 0, 0, "max_line_length"),
+            ["./**/*.md"]
+        );
+    }
+}
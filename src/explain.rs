@@ -0,0 +1,371 @@
+use crate::outcome::{self, Outcome, Reason};
+use linty::scan::{self, FileSkip, RuleDecision, WalkOptions};
+use linty::{gitattributes, ignore_explain, severity_name, Override, Rule};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Prints, for `linty explain`, whether a full run reaches the file at `file`, whether
+/// it's skipped before any rule is looked at, and whether each rule checks it and why.
+/// The file's path is normalized and matched against globs as the walk would do it, with
+/// the same decisions the scan makes. With `run`, the rules are run against the file and
+/// their matches counted.
+pub fn run(
+    file: &str,
+    run: bool,
+    rules: &[Rule],
+    overrides: &[Override],
+    options: &WalkOptions,
+    current_dir: &Path,
+) -> anyhow::Result<Outcome> {
+    let canonical_dir = linty::canonicalize(current_dir)?;
+    let path = match crate::walk_path(file, current_dir, &canonical_dir, &options.glob_base) {
+        Ok(path) => path,
+        Err(err) => {
+            eprintln!("Error: {file}: {err}");
+            outcome::fail(Reason::ConfigError);
+        }
+    };
+    if path.is_dir() {
+        eprintln!("Error: {file} is a directory; explain takes a file");
+        outcome::fail(Reason::ConfigError);
+    }
+    let glob_path = options.glob_path(&path);
+    println!(
+        "File {}, matched by globs as {}",
+        linty::display_path(&path),
+        glob_path.display()
+    );
+    println!("Walk: {}", walk_reason(options, &path, current_dir));
+
+    let mut generated_matcher = gitattributes::GeneratedMatcher::new(current_dir);
+    // A full run reaches the file through the walk, which already applies `.lintyignore`.
+    let file_skip = scan::file_skip(options, &path, &glob_path, &mut generated_matcher, None);
+    match &file_skip {
+        Some(FileSkip::Generated(attribute)) => println!(
+            "Skipped: marked {attribute} in .gitattributes (use --include-generated), so no rule checks it"
+        ),
+        Some(skip @ (FileSkip::Excluded(_) | FileSkip::LintyIgnored(_))) => {
+            println!("Skipped: {}, so no rule checks it", skip.reason());
+        }
+        None => {}
+    }
+
+    let content_skip = content_skip(options, &path)?;
+    let counts = if run && file_skip.is_none() {
+        Some(match_counts(options, rules, overrides, current_dir, &path)?)
+    } else {
+        None
+    };
+    println!("Rules:");
+    for rule in rules {
+        let decision = scan::rule_decision(rule, overrides, &glob_path);
+        let RuleDecision::Applies { severity, .. } = decision else {
+            println!("  {}: skipped: {}", rule.id, decision.reason(rule));
+            continue;
+        };
+        if let Some(skip) = content_skip.as_ref().filter(|skip| skip.skips(rule)) {
+            println!("  {}: skipped: {}", rule.id, skip.reason());
+            continue;
+        }
+        let mut line = format!(
+            "  {}: {} at {}: {}",
+            rule.id,
+            if file_skip.is_some() {
+                "would check it"
+            } else {
+                "checks it"
+            },
+            severity_name(severity),
+            decision.reason(rule)
+        );
+        if let Some((matches, suppressed)) = &counts {
+            line.push_str(&format!(
+                "; {} match(es)",
+                matches.get(&rule.id).copied().unwrap_or(0)
+            ));
+            if let Some(count) = suppressed.get(&rule.id) {
+                line.push_str(&format!(", {count} suppressed"));
+            }
+        }
+        println!("{line}");
+    }
+    if rules.is_empty() {
+        println!("  none are enabled");
+    }
+    Ok(Outcome::clean())
+}
+
+/// Whether the walk of the current directory a full run does reaches `path`, and if not,
+/// the hidden or ignored path that stops it. Naming a file checks it either way.
+fn walk_reason(options: &WalkOptions, path: &Path, current_dir: &Path) -> String {
+    if path.is_absolute() || path.starts_with("..") {
+        return String::from("outside the current directory, so only naming it checks it");
+    }
+    let Some(stop) = scan::walk_stop(options, path) else {
+        return String::from("reached by a full run");
+    };
+    let hidden = stop
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+    let why = if hidden && !options.hidden {
+        String::from("hidden (use --hidden)")
+    } else {
+        ignore_explain::explain(&stop, current_dir, options.ignore_sources)
+    };
+    format!(
+        "skipped by a full run, since {} is {why}; naming the file still checks it",
+        linty::display_path(&stop)
+    )
+}
+
+/// Why the file's contents aren't read even for rules that apply to it.
+enum ContentSkip {
+    Large { size: u64, max_filesize: u64 },
+    Binary,
+}
+
+impl ContentSkip {
+    /// Path rules never read the contents, and rules with `binary: true` read binary files.
+    fn skips(&self, rule: &Rule) -> bool {
+        match self {
+            ContentSkip::Large { .. } => rule.kind.reads_contents(),
+            ContentSkip::Binary => rule.kind.reads_contents() && !rule.binary,
+        }
+    }
+
+    fn reason(&self) -> String {
+        match self {
+            ContentSkip::Large { size, max_filesize } => {
+                format!("{size} bytes is over --max-filesize {max_filesize}")
+            }
+            ContentSkip::Binary => String::from(
+                "the file looks binary and the rule doesn't set binary: true (use --no-skip-binary)",
+            ),
+        }
+    }
+}
+
+fn content_skip(options: &WalkOptions, path: &Path) -> anyhow::Result<Option<ContentSkip>> {
+    let size = std::fs::metadata(path)?.len();
+    if let Some(max_filesize) = options.max_filesize.filter(|&max| size > max) {
+        return Ok(Some(ContentSkip::Large { size, max_filesize }));
+    }
+    if options.skip_binary && scan::file_looks_binary(path) {
+        return Ok(Some(ContentSkip::Binary));
+    }
+    Ok(None)
+}
+
+/// Scans the file as naming it on the command line would, counting each rule's matches
+/// and the ones suppression comments dropped, with no cap on matches per file.
+fn match_counts(
+    options: &WalkOptions,
+    rules: &[Rule],
+    overrides: &[Override],
+    current_dir: &Path,
+    path: &Path,
+) -> anyhow::Result<(HashMap<String, usize>, HashMap<String, usize>)> {
+    let options = WalkOptions {
+        roots: vec![PathBuf::from(path)],
+        max_violations_per_file: None,
+        ..options.clone()
+    };
+    let result = scan::scan(&options, rules, overrides, current_dir)?;
+    crate::print_problems(&result);
+    if result.encoding_skipped > 0 {
+        println!("Skipped: not valid UTF-8, so no rule read its contents");
+    }
+    let mut matches = HashMap::new();
+    for violation in &result.violations {
+        *matches.entry(violation.rule_id.clone()).or_insert(0) += violation.matches.len();
+    }
+    let suppressed = result.suppressed.into_iter().collect();
+    Ok((matches, suppressed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use linty::path_globs::PathGlobs;
+    use linty::{build_rules, generate_overrides_from_config, parse_config, ConfigFormat};
+    use std::sync::Arc;
+
+    /// The walk options of a plain `linty` run over the current directory.
+    fn options() -> WalkOptions {
+        let args = crate::Args::parse_from(["linty"]);
+        crate::walk_options(&args, vec![PathBuf::from("./")], None).unwrap()
+    }
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("linty-explain-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Each rule of a JSON config's `rules` and `overrides`, with why it does or doesn't
+    /// check `glob_path`, as `linty explain` gives it.
+    fn decisions(rules_and_overrides: &str, glob_path: &str) -> Vec<(String, bool, String)> {
+        let config = parse_config(
+            &format!("{{{rules_and_overrides}}}"),
+            ConfigFormat::Json,
+            "test config",
+        )
+        .unwrap();
+        let (rules, problems) = build_rules(&config, false);
+        assert!(problems.is_empty(), "{problems:?}");
+        let overrides = generate_overrides_from_config(&config).unwrap();
+        rules
+            .iter()
+            .map(|rule| {
+                let decision = scan::rule_decision(rule, &overrides, Path::new(glob_path));
+                let applies = matches!(decision, RuleDecision::Applies { .. });
+                (rule.id.clone(), applies, decision.reason(rule))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn each_rule_decision_gives_its_reason() {
+        let rule = |id: &str, extra: &str| {
+            format!(r#"{{"id":"{id}","message":"m","regex":"x","severity":"warning"{extra}}}"#)
+        };
+        let config = format!(
+            r#""rules":[{}],"overrides":[{{"paths":["src/gen/**"],"rules":{{"Off":"off","Raised":{{"severity":"error"}}}}}}]"#,
+            [
+                rule("Everywhere", ""),
+                rule("Docs", r#","includes":["*.md"]"#),
+                rule("Src", r#","includes":["src/**","!src/gen/**"]"#),
+                rule("NoGen", r#","excludes":["src/gen/**"]"#),
+                rule("Back", r#","excludes":["src/**","!src/gen/**"]"#),
+                rule("Backend", r#","directory":"backend""#),
+                rule("Off", ""),
+                rule("Raised", ""),
+            ]
+            .join(",")
+        );
+        let reason =
+            |id: &str, applies: bool, reason: &str| (id.to_owned(), applies, reason.to_owned());
+        assert_eq!(
+            decisions(&config, "src/gen/a.rs"),
+            [
+                reason("Everywhere", true, "no includes, so every file applies"),
+                reason("Docs", false, "not matched by includes"),
+                reason("Src", false, "left out by includes pattern !src/gen/**"),
+                reason("NoGen", false, "matched by excludes pattern src/gen/**"),
+                reason(
+                    "Back",
+                    true,
+                    "no includes, so every file applies; excludes pattern !src/gen/** takes it back"
+                ),
+                reason("Backend", false, "outside the directory of its nested config"),
+                reason("Off", false, "turned off by override overrides[0]"),
+                reason(
+                    "Raised",
+                    true,
+                    "no includes, so every file applies; override overrides[0] sets it to error"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn file_skips_give_their_reasons() {
+        let dir = scratch("file-skip");
+        std::fs::create_dir(dir.join(".git")).unwrap();
+        std::fs::write(dir.join(".gitattributes"), "gen.rs linguist-generated\n").unwrap();
+        let options = WalkOptions {
+            excludes: Arc::new(PathGlobs::new(&[String::from("vendor/**")]).unwrap()),
+            ..options()
+        };
+        let skip = |path: &str| {
+            let mut matcher = gitattributes::GeneratedMatcher::new(&dir);
+            scan::file_skip(
+                &options,
+                Path::new(path),
+                Path::new(path),
+                &mut matcher,
+                None,
+            )
+            .map(|skip| skip.reason())
+        };
+        assert_eq!(
+            skip("gen.rs").as_deref(),
+            Some("marked linguist-generated in .gitattributes")
+        );
+        assert_eq!(
+            skip("vendor/a.rs").as_deref(),
+            Some("matched by the config's global excludes pattern vendor/**")
+        );
+        assert_eq!(skip("src/a.rs"), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn content_skips_give_their_reasons_and_spare_rules_that_dont_read() {
+        let dir = scratch("content-skip");
+        std::fs::write(dir.join("large.txt"), "x".repeat(20)).unwrap();
+        std::fs::write(dir.join("binary.bin"), b"ab\0cd").unwrap();
+        std::fs::write(dir.join("small.txt"), "x\n").unwrap();
+        let options = WalkOptions {
+            max_filesize: Some(10),
+            skip_binary: true,
+            ..options()
+        };
+        let large = content_skip(&options, &dir.join("large.txt"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(large.reason(), "20 bytes is over --max-filesize 10");
+        let binary = content_skip(&options, &dir.join("binary.bin"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            binary.reason(),
+            "the file looks binary and the rule doesn't set binary: true (use --no-skip-binary)"
+        );
+        assert!(content_skip(&options, &dir.join("small.txt"))
+            .unwrap()
+            .is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let config = parse_config(
+            r#"{"rules":[
+                {"id":"Text","message":"m","regex":"x","severity":"error"},
+                {"id":"Bytes","message":"m","regex":"x","severity":"error","binary":true},
+                {"id":"Path","message":"m","regex":"x","severity":"error","target":"path"}
+            ]}"#,
+            ConfigFormat::Json,
+            "test config",
+        )
+        .unwrap();
+        let (rules, _) = build_rules(&config, false);
+        let skipped = |skip: &ContentSkip| -> Vec<bool> {
+            rules.iter().map(|rule| skip.skips(rule)).collect()
+        };
+        assert_eq!(skipped(&large), [true, true, false]);
+        assert_eq!(skipped(&binary), [true, false, false]);
+    }
+
+    #[test]
+    fn walk_reasons_say_whether_a_full_run_reaches_the_file() {
+        let options = options();
+        let current_dir = std::env::current_dir().unwrap();
+        assert_eq!(
+            walk_reason(&options, Path::new("./src/explain.rs"), &current_dir),
+            "reached by a full run"
+        );
+        assert_eq!(
+            walk_reason(&options, Path::new("./.github/workflows"), &current_dir),
+            "skipped by a full run, since .github is hidden (use --hidden); naming the file still checks it"
+        );
+        assert_eq!(
+            walk_reason(&options, &current_dir.join("src/explain.rs"), &current_dir),
+            "outside the current directory, so only naming it checks it"
+        );
+        assert_eq!(
+            walk_reason(&options, Path::new("../elsewhere.rs"), &current_dir),
+            "outside the current directory, so only naming it checks it"
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use regex::Regex;
+use std::ops::Range;
+
+/// The text a rule's `fix` template would replace the match at `range` in `contents`
+/// with, expanding `$1` and `${name}` capture references. `None` when the regex doesn't
+/// match exactly that range on the raw contents, as happens for matches found in
+/// Unicode-normalized text.
+pub fn replacement(
+    regex: &Regex,
+    template: &str,
+    contents: &str,
+    range: Range<usize>,
+) -> Option<String> {
+    let captures = regex.captures_at(contents, range.start)?;
+    let whole = captures.get(0)?;
+    if whole.range() != range {
+        return None;
+    }
+    let mut replacement = String::new();
+    captures.expand(template, &mut replacement);
+    Some(replacement)
+}
+
+/// A replacement of the text a match spans, positioned like `Match`: 1-based lines, and
+/// 1-based columns counted in characters, with the end exclusive.
+pub struct Edit<'a> {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub replacement: &'a str,
+}
+
+/// The edits resolved to byte ranges of one file's contents, in order, without overlaps.
+pub struct Fixes<'a> {
+    edits: Vec<(Range<usize>, &'a str)>,
+    /// Edits dropped because they overlapped an earlier one; the rescan reports them again.
+    pub skipped: usize,
+    /// Edits dropped because their positions aren't in the contents, as when the file
+    /// changed after it was scanned.
+    pub stale: usize,
+}
+
+impl<'a> Fixes<'a> {
+    pub fn new(contents: &str, edits: Vec<Edit<'a>>) -> Fixes<'a> {
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(contents.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        // The offset of a position, which may be just past the end of its line.
+        let offset = |line: usize, column: usize| {
+            let start = *line_starts.get(line.checked_sub(1)?)?;
+            let text = contents[start..].split('\n').next().unwrap_or_default();
+            let column = column.checked_sub(1)?;
+            text.char_indices()
+                .map(|(index, _)| index)
+                .chain([text.len()])
+                .nth(column)
+                .map(|index| start + index)
+        };
+        let total = edits.len();
+        let mut resolved: Vec<(Range<usize>, &str)> = edits
+            .into_iter()
+            .filter_map(|edit| {
+                let start = offset(edit.line, edit.column)?;
+                let end = offset(edit.end_line, edit.end_column)?;
+                (start <= end).then_some((start..end, edit.replacement))
+            })
+            .collect();
+        resolved.sort_by_key(|(range, _)| (range.start, range.end));
+
+        let mut fixes = Fixes {
+            edits: Vec::with_capacity(resolved.len()),
+            skipped: 0,
+            stale: total - resolved.len(),
+        };
+        for (range, replacement) in resolved {
+            match fixes.edits.last() {
+                Some((previous, _)) if range.start < previous.end || range == *previous => {
+                    fixes.skipped += 1;
+                }
+                _ => fixes.edits.push((range, replacement)),
+            }
+        }
+        fixes
+    }
+
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// `contents` with every edit applied.
+    pub fn apply(&self, contents: &str) -> String {
+        apply_in(contents, 0..contents.len(), &self.edits)
+    }
+
+    /// A unified-style diff of the edits, with the changed lines of each group of edits
+    /// that share lines.
+    pub fn diff(&self, path: &str, contents: &str) -> String {
+        let mut diff = format!("--- a/{path}\n+++ b/{path}\n");
+        let mut index = 0;
+        while index < self.edits.len() {
+            let start = line_start(contents, self.edits[index].0.start);
+            let mut end = line_end(contents, self.edits[index].0.end);
+            let mut group_end = index + 1;
+            while group_end < self.edits.len() && self.edits[group_end].0.start < end {
+                end = end.max(line_end(contents, self.edits[group_end].0.end));
+                group_end += 1;
+            }
+            let line = contents[..start].matches('\n').count() + 1;
+            let before = &contents[start..end];
+            let after = apply_in(contents, start..end, &self.edits[index..group_end]);
+            diff.push_str(&format!("@@ line {line} @@\n"));
+            for removed in before.lines() {
+                diff.push_str(&format!("-{removed}\n"));
+            }
+            for added in after.lines() {
+                diff.push_str(&format!("+{added}\n"));
+            }
+            index = group_end;
+        }
+        diff
+    }
+}
+
+/// The `span` of `contents` with `edits`, which all lie inside it, applied.
+fn apply_in(contents: &str, span: Range<usize>, edits: &[(Range<usize>, &str)]) -> String {
+    let mut fixed = String::with_capacity(span.len());
+    let mut copied = span.start;
+    for (range, replacement) in edits {
+        fixed.push_str(&contents[copied..range.start]);
+        fixed.push_str(replacement);
+        copied = range.end;
+    }
+    fixed.push_str(&contents[copied..span.end]);
+    fixed
+}
+
+fn line_start(contents: &str, offset: usize) -> usize {
+    contents[..offset].rfind('\n').map_or(0, |index| index + 1)
+}
+
+/// The end of the line containing `offset`, including its newline.
+fn line_end(contents: &str, offset: usize) -> usize {
+    contents[offset..]
+        .find('\n')
+        .map_or(contents.len(), |index| offset + index + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(line: usize, column: usize, end_column: usize) -> Edit<'static> {
+        Edit {
+            line,
+            column,
+            end_line: line,
+            end_column,
+            replacement: "X",
+        }
+    }
+
+    #[test]
+    fn applies_edits_by_position() {
+        let contents = "ab\ncd\n";
+        let fixes = Fixes::new(contents, vec![edit(2, 1, 2), edit(1, 2, 3)]);
+        assert_eq!(fixes.apply(contents), "aX\nXd\n");
+        assert_eq!((fixes.skipped, fixes.stale), (0, 0));
+    }
+
+    #[test]
+    fn edits_past_the_contents_are_stale_rather_than_a_panic() {
+        let contents = "ab\ncd\n";
+        let fixes = Fixes::new(
+            contents,
+            vec![edit(4, 1, 2), edit(1, 5, 6), edit(0, 1, 1), edit(1, 1, 2)],
+        );
+        assert_eq!(fixes.stale, 3);
+        assert_eq!(fixes.apply(contents), "Xb\ncd\n");
+    }
+
+    #[test]
+    fn an_edit_may_end_at_the_end_of_a_line() {
+        let contents = "ab\ncd";
+        let fixes = Fixes::new(contents, vec![edit(1, 1, 3), edit(2, 2, 3)]);
+        assert_eq!(fixes.apply(contents), "X\ncX");
+    }
+
+    #[test]
+    fn overlapping_edits_are_skipped() {
+        let contents = "abcd\n";
+        let fixes = Fixes::new(contents, vec![edit(1, 1, 3), edit(1, 2, 4)]);
+        assert_eq!(fixes.skipped, 1);
+        assert_eq!(fixes.apply(contents), "Xcd\n");
+    }
+}
@@ -0,0 +1,82 @@
+use crate::Violation;
+use linty::git::{GitError, Repo};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// A Gerrit `ReviewInput` carrying only robot comments, the body of a set-review call.
+#[derive(Serialize)]
+struct ReviewInput<'a> {
+    robot_comments: BTreeMap<String, Vec<RobotComment<'a>>>,
+}
+
+#[derive(Serialize)]
+struct RobotComment<'a> {
+    robot_id: &'static str,
+    robot_run_id: &'a str,
+    /// Both left out for a path rule's match, which makes it a comment on the whole file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    range: Option<CommentRange>,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<&'a str>,
+}
+
+/// The commented span; Gerrit counts characters from 0 and lines from 1.
+#[derive(Serialize)]
+struct CommentRange {
+    start_line: usize,
+    start_character: usize,
+    end_line: usize,
+    end_character: usize,
+}
+
+/// Writes one robot comment per match, keyed by the file's path from the repository root.
+pub fn write_review(
+    writer: impl std::io::Write,
+    violations: &[Violation],
+    messages: &HashMap<&str, &str>,
+    urls: &HashMap<&str, &str>,
+    run_id: &str,
+) -> anyhow::Result<()> {
+    let prefix = repository_prefix();
+    let mut robot_comments: BTreeMap<String, Vec<RobotComment>> = BTreeMap::new();
+    for violation in violations {
+        let id = violation.rule_id.as_str();
+        let rule_message = messages.get(id).copied().unwrap_or_default();
+        let comments = robot_comments
+            .entry(format!("{prefix}{}", violation.file))
+            .or_default();
+        for m in &violation.matches {
+            comments.push(RobotComment {
+                robot_id: "linty",
+                robot_run_id: run_id,
+                line: m.has_position().then_some(m.line),
+                range: m.has_position().then_some(CommentRange {
+                    start_line: m.line,
+                    start_character: m.column - 1,
+                    end_line: m.end_line,
+                    end_character: m.end_column - 1,
+                }),
+                message: match m.message(rule_message) {
+                    "" => id.to_owned(),
+                    message => format!("{message} ({id})"),
+                },
+                url: urls.get(id).copied(),
+            });
+        }
+    }
+    serde_json::to_writer_pretty(writer, &ReviewInput { robot_comments })?;
+    Ok(())
+}
+
+/// The current directory's path within the git work tree, like `services/api/`, so
+/// paths relative to it can be made relative to the repository root. Empty outside git.
+fn repository_prefix() -> String {
+    match Repo::discover() {
+        Ok(repo) => repo.prefix().to_string_lossy().into_owned(),
+        Err(GitError::NotARepository | GitError::NotInstalled) => String::new(),
+        Err(err) => crate::git_failure("--format gerrit", err),
+    }
+}
@@ -0,0 +1,267 @@
+use std::ffi::OsStr;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Why a git command gave no answer, told apart so each can get its own message.
+#[derive(Debug)]
+pub enum GitError {
+    /// There's no `git` to run, because it isn't installed or isn't on `PATH`.
+    NotInstalled,
+    /// The current directory isn't in a git work tree.
+    NotARepository,
+    /// Git ran and failed, with the subcommand and the first line it printed to stderr.
+    Failed { command: String, message: String },
+    /// Git couldn't be started for another reason, or printed something unreadable.
+    Io(io::Error),
+}
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitError::NotInstalled => write!(f, "git isn't installed or isn't on PATH"),
+            GitError::NotARepository => {
+                write!(f, "the current directory isn't in a git repository")
+            }
+            GitError::Failed { command, message } => write!(f, "git {command} failed: {message}"),
+            GitError::Io(err) => write!(f, "couldn't run git: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for GitError {}
+
+/// The git work tree the current directory is in. Commands run from its root, so what
+/// they print doesn't depend on which subdirectory Linty was run from, and the paths they
+/// list are turned into walked paths relative to the current directory.
+#[derive(Debug, Clone)]
+pub struct Repo {
+    root: PathBuf,
+    /// The current directory relative to the root, like `src/`, empty at the root.
+    prefix: PathBuf,
+    /// The root relative to the current directory, like `../`, empty at the root.
+    cdup: PathBuf,
+}
+
+impl Repo {
+    /// Finds the repository the current directory is in.
+    pub fn discover() -> Result<Repo, GitError> {
+        let output = run(Command::new("git").args([
+            "rev-parse",
+            "--show-toplevel",
+            "--show-prefix",
+            "--show-cdup",
+        ]))?;
+        if !output.status.success() {
+            let message = first_line(&output.stderr);
+            // Git's message has read this way since its early days.
+            if message.contains("not a git repository") {
+                return Err(GitError::NotARepository);
+            }
+            return Err(GitError::Failed {
+                command: String::from("rev-parse"),
+                message,
+            });
+        }
+        let stdout = String::from_utf8(output.stdout)
+            .map_err(|err| GitError::Io(io::Error::new(io::ErrorKind::InvalidData, err)))?;
+        Repo::parse(&stdout).ok_or_else(|| GitError::Failed {
+            command: String::from("rev-parse"),
+            message: String::from("it printed no repository root"),
+        })
+    }
+
+    /// Reads the root, prefix and cdup lines `git rev-parse` prints, in that order. The
+    /// prefix and cdup lines are empty at the root.
+    pub fn parse(rev_parse: &str) -> Option<Repo> {
+        let mut lines = rev_parse.lines();
+        let root = lines.next().filter(|root| !root.is_empty())?;
+        Some(Repo {
+            root: PathBuf::from(root),
+            prefix: PathBuf::from(lines.next().unwrap_or_default()),
+            cdup: PathBuf::from(lines.next().unwrap_or_default()),
+        })
+    }
+
+    /// The work tree's top-level directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The current directory relative to the root, like `src/`, empty at the root.
+    pub fn prefix(&self) -> &Path {
+        &self.prefix
+    }
+
+    /// A `git` command that runs from the root.
+    pub fn command(&self) -> Command {
+        let mut command = Command::new("git");
+        command.current_dir(&self.root);
+        command
+    }
+
+    /// Whether the git command `args` exits successfully.
+    pub fn succeeds(&self, args: &[&str]) -> Result<bool, GitError> {
+        Ok(run(self.command().args(args))?.status.success())
+    }
+
+    /// What the git command `args` prints, failing with the first line of stderr if it
+    /// exits unsuccessfully.
+    pub fn output(&self, args: &[impl AsRef<OsStr>]) -> Result<Vec<u8>, GitError> {
+        let output = run(self.command().args(args))?;
+        if !output.status.success() {
+            return Err(GitError::Failed {
+                command: args
+                    .first()
+                    .map(|arg| arg.as_ref().to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                message: first_line(&output.stderr),
+            });
+        }
+        Ok(output.stdout)
+    }
+
+    /// Runs a git command that lists paths relative to the root, like `git diff
+    /// --name-only`, with `-z` after the subcommand, and returns them as walked paths.
+    pub fn paths(&self, args: &[&str]) -> Result<Vec<PathBuf>, GitError> {
+        let mut z_args = args.to_vec();
+        z_args.insert(args.len().min(1), "-z");
+        let stdout = String::from_utf8(self.output(&z_args)?)
+            .map_err(|err| GitError::Io(io::Error::new(io::ErrorKind::InvalidData, err)))?;
+        Ok(parse_paths(&stdout)
+            .map(|path| self.walk_path(Path::new(path)))
+            .collect())
+    }
+
+    /// The walked path for `path`, relative to the root: `./` and the rest under the
+    /// current directory, like a full walk gives it, and through `../` elsewhere in the
+    /// repository.
+    pub fn walk_path(&self, path: &Path) -> PathBuf {
+        match path.strip_prefix(&self.prefix) {
+            Ok(relative) => Path::new("./").join(relative),
+            Err(_) => self.cdup.join(path),
+        }
+    }
+
+    /// The path relative to the root for `walked`, a walked path relative to the current
+    /// directory, as git takes paths in commands run from the root.
+    pub fn root_path(&self, walked: &Path) -> PathBuf {
+        crate::normalize_lexically(&self.prefix.join(walked))
+    }
+}
+
+/// Splits NUL-separated `-z` output, which leaves paths with spaces or unusual characters
+/// unquoted, into its paths.
+pub fn parse_paths(stdout: &str) -> impl Iterator<Item = &str> {
+    stdout
+        .split_terminator('\0')
+        .filter(|path| !path.is_empty())
+}
+
+fn run(command: &mut Command) -> Result<Output, GitError> {
+    command.output().map_err(|err| match err.kind() {
+        io::ErrorKind::NotFound => GitError::NotInstalled,
+        _ => GitError::Io(err),
+    })
+}
+
+fn first_line(stderr: &[u8]) -> String {
+    String::from_utf8_lossy(stderr)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("it printed nothing")
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rev_parse_at_the_root() {
+        let repo = Repo::parse("/work/repo\n\n\n").unwrap();
+        assert_eq!(repo.root, Path::new("/work/repo"));
+        assert_eq!(
+            repo.walk_path(Path::new("src/main.rs")),
+            Path::new("./src/main.rs")
+        );
+    }
+
+    #[test]
+    fn parses_rev_parse_in_a_subdirectory() {
+        let repo = Repo::parse("/work/repo\nsrc/\n../\n").unwrap();
+        assert_eq!(
+            repo.walk_path(Path::new("src/main.rs")),
+            Path::new("./main.rs")
+        );
+        assert_eq!(
+            repo.walk_path(Path::new("docs/index.md")),
+            Path::new("../docs/index.md")
+        );
+        // A sibling whose name starts like the current directory's isn't under it.
+        assert_eq!(
+            repo.walk_path(Path::new("srcs/x.rs")),
+            Path::new("../srcs/x.rs")
+        );
+    }
+
+    #[test]
+    fn parses_rev_parse_without_trailing_lines() {
+        let repo = Repo::parse("/work/repo").unwrap();
+        assert_eq!(repo.walk_path(Path::new("a.txt")), Path::new("./a.txt"));
+    }
+
+    #[test]
+    fn root_paths_undo_walk_paths() {
+        let repo = Repo::parse("/work/repo\nsrc/\n../\n").unwrap();
+        for path in ["src/main.rs", "docs/index.md", "README.md"] {
+            assert_eq!(
+                repo.root_path(&repo.walk_path(Path::new(path))),
+                Path::new(path)
+            );
+        }
+        let root = Repo::parse("/work/repo\n\n\n").unwrap();
+        assert_eq!(root.root_path(Path::new("./a/b.txt")), Path::new("a/b.txt"));
+    }
+
+    #[test]
+    fn rejects_rev_parse_without_a_root() {
+        assert!(Repo::parse("").is_none());
+        assert!(Repo::parse("\nsrc/\n").is_none());
+    }
+
+    #[test]
+    fn splits_nul_separated_paths() {
+        let paths: Vec<&str> = parse_paths("a.txt\0dir/with space.rs\0new\nline.md\0").collect();
+        assert_eq!(paths, ["a.txt", "dir/with space.rs", "new\nline.md"]);
+        assert_eq!(parse_paths("").count(), 0);
+        assert_eq!(parse_paths("a\0\0b").collect::<Vec<_>>(), ["a", "b"]);
+    }
+
+    #[test]
+    fn first_line_skips_blank_lines() {
+        assert_eq!(
+            first_line(b"\n  fatal: bad revision 'nope'\nmore\n"),
+            "fatal: bad revision 'nope'"
+        );
+        assert_eq!(first_line(b""), "it printed nothing");
+    }
+
+    #[test]
+    fn errors_read_as_one_line() {
+        let failed = GitError::Failed {
+            command: String::from("diff"),
+            message: String::from("fatal: bad revision 'nope'"),
+        };
+        assert_eq!(
+            failed.to_string(),
+            "git diff failed: fatal: bad revision 'nope'"
+        );
+        assert_eq!(
+            GitError::NotInstalled.to_string(),
+            "git isn't installed or isn't on PATH"
+        );
+    }
+}
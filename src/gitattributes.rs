@@ -0,0 +1,218 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One `.gitattributes` line that sets or unsets the linguist attributes we care about.
+struct AttributeLine {
+    matcher: Gitignore,
+    generated: Option<bool>,
+    vendored: Option<bool>,
+}
+
+/// Answers whether a path is marked `linguist-generated` or `linguist-vendored` by the
+/// `.gitattributes` files in its ancestor directories, up to the repository root.
+/// Each directory's file is parsed at most once per run.
+pub struct GeneratedMatcher {
+    current_dir: PathBuf,
+    repo_root: Option<PathBuf>,
+    cache: HashMap<PathBuf, Vec<AttributeLine>>,
+}
+
+impl GeneratedMatcher {
+    pub fn new(current_dir: &Path) -> GeneratedMatcher {
+        let repo_root = current_dir
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .map(Path::to_path_buf);
+        GeneratedMatcher {
+            current_dir: current_dir.to_path_buf(),
+            repo_root,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Returns the attribute that marks `path` as generated, if any.
+    pub fn generated_attribute(&mut self, path: &Path) -> Option<&'static str> {
+        let path = self.current_dir.join(path);
+        let parent = path.parent()?;
+
+        // Shallower files are applied first so deeper files and later lines win, as in git.
+        let mut directories: Vec<PathBuf> = parent
+            .ancestors()
+            .take_while(|dir| match &self.repo_root {
+                Some(root) => dir.starts_with(root),
+                None => dir.starts_with(&self.current_dir),
+            })
+            .map(Path::to_path_buf)
+            .collect();
+        directories.reverse();
+
+        let mut generated = false;
+        let mut vendored = false;
+        for directory in directories {
+            let lines = self
+                .cache
+                .entry(directory.clone())
+                .or_insert_with(|| parse_gitattributes(&directory));
+            for line in lines.iter() {
+                if !line.matcher.matched(&path, false).is_ignore() {
+                    continue;
+                }
+                if let Some(value) = line.generated {
+                    generated = value;
+                }
+                if let Some(value) = line.vendored {
+                    vendored = value;
+                }
+            }
+        }
+
+        if generated {
+            Some("linguist-generated")
+        } else if vendored {
+            Some("linguist-vendored")
+        } else {
+            None
+        }
+    }
+}
+
+fn parse_gitattributes(directory: &Path) -> Vec<AttributeLine> {
+    let Ok(contents) = fs::read_to_string(directory.join(".gitattributes")) else {
+        return Vec::new();
+    };
+
+    let mut lines = Vec::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let Some(pattern) = fields.next() else {
+            continue;
+        };
+        // Negative patterns are forbidden in .gitattributes, and git ignores them.
+        if pattern.starts_with('!') {
+            continue;
+        }
+
+        let mut generated = None;
+        let mut vendored = None;
+        for attribute in fields {
+            if let Some(value) = attribute_value(attribute, "linguist-generated") {
+                generated = Some(value);
+            }
+            if let Some(value) = attribute_value(attribute, "linguist-vendored") {
+                vendored = Some(value);
+            }
+        }
+        if generated.is_none() && vendored.is_none() {
+            continue;
+        }
+
+        let mut builder = GitignoreBuilder::new(directory);
+        if builder.add_line(None, pattern).is_err() {
+            continue;
+        }
+        let Ok(matcher) = builder.build() else {
+            continue;
+        };
+        lines.push(AttributeLine {
+            matcher,
+            generated,
+            vendored,
+        });
+    }
+    lines
+}
+
+/// Interprets `name`, `name=true`, `-name`, `!name`, and `name=false` attribute specs.
+fn attribute_value(attribute: &str, name: &str) -> Option<bool> {
+    if attribute == name {
+        return Some(true);
+    }
+    if let Some(rest) = attribute.strip_prefix('-').or(attribute.strip_prefix('!')) {
+        return (rest == name).then_some(false);
+    }
+    match attribute.split_once('=') {
+        Some((attribute_name, value)) if attribute_name == name => {
+            Some(!matches!(value, "false" | "0"))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A repository in a scratch directory with `files` written into it.
+    fn repo(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("linty-gitattributes-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        for (path, contents) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn nested_files_override_and_unset_the_root_marker() {
+        let dir = repo(
+            "nested",
+            &[
+                (".gitattributes", "gen/** linguist-generated\nvendor/** linguist-vendored\n"),
+                (
+                    "gen/.gitattributes",
+                    "kept.rs -linguist-generated\nunset.rs !linguist-generated\nfalse.rs linguist-generated=false\n",
+                ),
+                ("gen/deep/.gitattributes", "again.rs linguist-generated=true\n"),
+                ("vendor/.gitattributes", "ours.rs linguist-generated\n"),
+            ],
+        );
+        let mut matcher = GeneratedMatcher::new(&dir);
+        let mut attribute = |path: &str| matcher.generated_attribute(Path::new(path));
+        assert_eq!(attribute("gen/api.rs"), Some("linguist-generated"));
+        assert_eq!(attribute("gen/deep/api.rs"), Some("linguist-generated"));
+        assert_eq!(attribute("gen/kept.rs"), None);
+        assert_eq!(attribute("gen/unset.rs"), None);
+        assert_eq!(attribute("gen/false.rs"), None);
+        // A pattern without a slash matches at any depth below its file.
+        assert_eq!(attribute("gen/deep/kept.rs"), None);
+        assert_eq!(attribute("gen/deep/again.rs"), Some("linguist-generated"));
+        assert_eq!(attribute("vendor/lib.rs"), Some("linguist-vendored"));
+        // Being generated takes precedence over being vendored.
+        assert_eq!(attribute("vendor/ours.rs"), Some("linguist-generated"));
+        assert_eq!(attribute("src/main.rs"), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn later_lines_of_one_file_win() {
+        let dir = repo(
+            "order",
+            &[(
+                ".gitattributes",
+                "*.pb.go linguist-generated\nkeep.pb.go -linguist-generated\n# a comment\n!neg.pb.go -linguist-generated\n",
+            )],
+        );
+        let mut matcher = GeneratedMatcher::new(&dir);
+        assert_eq!(
+            matcher.generated_attribute(Path::new("api.pb.go")),
+            Some("linguist-generated")
+        );
+        assert_eq!(matcher.generated_attribute(Path::new("keep.pb.go")), None);
+        // Negative patterns are ignored, as git ignores them.
+        assert_eq!(
+            matcher.generated_attribute(Path::new("neg.pb.go")),
+            Some("linguist-generated")
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
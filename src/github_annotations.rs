@@ -0,0 +1,67 @@
+use crate::{Severity, Violation};
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Whether the run is a GitHub Actions step, where `--format github` is the default.
+pub fn in_github_actions() -> bool {
+    std::env::var_os("GITHUB_ACTIONS").is_some_and(|value| value == "true")
+}
+
+/// Writes a `::warning` or `::error` workflow command for each line with a match, which
+/// Actions shows as an annotation on that line of the diff. Matches sharing a line become
+/// one annotation, positioned at the first.
+pub fn write_annotations(
+    mut writer: impl Write,
+    violations: &[Violation],
+    messages: &HashMap<&str, &str>,
+) -> std::io::Result<()> {
+    for violation in violations {
+        let command = match violation.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        let file = escape_property(&violation.file);
+        let rule_message = messages
+            .get(violation.rule_id.as_str())
+            .copied()
+            .unwrap_or_default();
+        let mut previous_line = None;
+        for m in &violation.matches {
+            let text = match m.message(rule_message) {
+                "" => escape_data(&violation.rule_id),
+                message => escape_data(&format!("{}: {message}", violation.rule_id)),
+            };
+            if !m.has_position() {
+                writeln!(writer, "::{command} file={file}::{text}")?;
+                continue;
+            }
+            if previous_line == Some(m.line) {
+                continue;
+            }
+            previous_line = Some(m.line);
+            // A match spanning lines marks all of them.
+            let end_line = match m.last_line() {
+                last_line if last_line > m.line => format!(",endLine={last_line}"),
+                _ => String::new(),
+            };
+            writeln!(
+                writer,
+                "::{command} file={file},line={}{end_line},col={}::{text}",
+                m.line, m.column
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Escapes a command's message, where `%` and line breaks are special.
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes a command property, where `:` and `,` also delimit.
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
@@ -0,0 +1,186 @@
+use crate::config_trust::ConfigTrust;
+use crate::log;
+use crate::outcome::{self, Reason};
+use linty::Config;
+use std::path::{Path, PathBuf};
+
+/// File names of the user's global config, in the `linty` directory of the platform's
+/// config directory, like `~/.config/linty` or `$XDG_CONFIG_HOME/linty`.
+const GLOBAL_CONFIG_NAMES: [&str; 2] = ["config.json", "config.toml"];
+
+/// The global config files linty looks for, whether or not they exist, or none when the
+/// platform has no config directory.
+pub fn candidates() -> Vec<PathBuf> {
+    dirs::config_dir()
+        .map(|dir| {
+            GLOBAL_CONFIG_NAMES
+                .iter()
+                .map(|name| dir.join("linty").join(name))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Adds the rules of the user's global config after `config`'s, leaving out any with an id
+/// `config` already has, so the project's rule wins. Globs anchored with a `/` are rewritten
+/// to start at the current directory, `glob_base` relative to the project config, since a
+/// global config belongs to no directory. Only the global config's `rules` are used.
+pub fn add_rules(config: &mut Config, trust: &ConfigTrust, glob_base: &Path) -> anyhow::Result<()> {
+    let candidates = candidates();
+    let found: Vec<&PathBuf> = candidates.iter().filter(|path| path.is_file()).collect();
+    let path = match found[..] {
+        [] => {
+            let names: Vec<String> = candidates
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect();
+            log::log!(
+                log::Level::Info,
+                "No global config found at {}",
+                if names.is_empty() {
+                    String::from("any path, since there's no config directory")
+                } else {
+                    names.join(" or ")
+                }
+            );
+            return Ok(());
+        }
+        [path] => path.to_string_lossy().into_owned(),
+        _ => {
+            let names: Vec<String> = found
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect();
+            eprintln!("Found multiple global config files: {}", names.join(", "));
+            eprintln!("Remove all but one, or pass --no-global-config");
+            outcome::fail(Reason::ConfigError);
+        }
+    };
+    let mut global = crate::read_config(&path, None);
+    crate::apply_extends(&mut global, trust, &path)?;
+    let (added, replaced) = merge_rules(config, global, glob_base);
+    log::log!(
+        log::Level::Info,
+        "Using global config {}: {}{}",
+        crate::config_path_display(&path),
+        if added.is_empty() {
+            String::from("adds no rules")
+        } else {
+            format!("adds {}", added.join(", "))
+        },
+        if replaced.is_empty() {
+            String::new()
+        } else {
+            format!("; the project's rules replace {}", replaced.join(", "))
+        }
+    );
+    Ok(())
+}
+
+/// Adds `global`'s rules after `config`'s, with their globs scoped by `glob_base`, and
+/// returns the ids of those added and of those left out for having an id `config` has.
+fn merge_rules(
+    config: &mut Config,
+    global: Config,
+    glob_base: &Path,
+) -> (Vec<String>, Vec<String>) {
+    let mut added = Vec::new();
+    let mut replaced = Vec::new();
+    for mut rule in global.rules {
+        if config.rules.iter().any(|existing| existing.id == rule.id) {
+            replaced.push(rule.id);
+            continue;
+        }
+        for glob in rule
+            .includes
+            .iter_mut()
+            .chain(rule.excludes.iter_mut())
+            .flatten()
+        {
+            *glob = scope_glob(glob, glob_base);
+        }
+        added.push(rule.id.clone());
+        config.rules.push(rule);
+    }
+    (added, replaced)
+}
+
+/// `glob` anchored at the current directory, `glob_base` relative to the project config's
+/// directory, which globs are matched from. A glob without a `/` matches in any directory,
+/// so it's left alone.
+fn scope_glob(glob: &str, glob_base: &Path) -> String {
+    if !glob.contains('/') || glob_base.as_os_str().is_empty() {
+        return glob.to_owned();
+    }
+    if let Some(negated) = glob.strip_prefix('!') {
+        return format!("!{}", scope_glob(negated, glob_base));
+    }
+    let anchored = glob
+        .strip_prefix("./")
+        .or_else(|| glob.strip_prefix('/'))
+        .unwrap_or(glob);
+    format!(
+        "./{}",
+        linty::normalize_lexically(&glob_base.join(anchored)).display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linty::{parse_config, ConfigFormat, Severity};
+
+    fn config(rules: &str) -> Config {
+        parse_config(
+            &format!(r#"{{"rules":[{rules}]}}"#),
+            ConfigFormat::Json,
+            "test config",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn project_rules_win_over_global_ones_with_the_same_id() {
+        let mut project =
+            config(r#"{"id":"NoTodo","message":"Project","regex":"TODO","severity":"warning"}"#);
+        let global = config(
+            r#"{"id":"NoTodo","message":"Global","regex":"todo","severity":"error"},
+               {"id":"NoFixme","message":"Global","regex":"FIXME","severity":"error"}"#,
+        );
+        let (added, replaced) = merge_rules(&mut project, global, Path::new(""));
+        assert_eq!(added, ["NoFixme"]);
+        assert_eq!(replaced, ["NoTodo"]);
+
+        let rules: Vec<(&str, Option<&str>, Option<Severity>)> = project
+            .rules
+            .iter()
+            .map(|rule| (rule.id.as_str(), rule.message.as_deref(), rule.severity))
+            .collect();
+        assert_eq!(
+            rules,
+            [
+                ("NoTodo", Some("Project"), Some(Severity::Warning)),
+                ("NoFixme", Some("Global"), Some(Severity::Error))
+            ]
+        );
+    }
+
+    #[test]
+    fn global_globs_with_a_slash_are_scoped_to_the_current_directory() {
+        let mut project = config("");
+        let global = config(
+            r#"{"id":"NoTodo","message":"m","regex":"TODO","severity":"error",
+                "includes":["*.rs","/src/**","./lib/*.rs"],"excludes":["!src/gen/**"]}"#,
+        );
+        merge_rules(&mut project, global, Path::new("app"));
+        let rule = &project.rules[0];
+        assert_eq!(
+            rule.includes.as_deref().unwrap(),
+            ["*.rs", "./app/src/**", "./app/lib/*.rs"]
+        );
+        assert_eq!(rule.excludes.as_deref().unwrap(), ["!./app/src/gen/**"]);
+
+        // From the project config's own directory, globs are left as they are.
+        assert_eq!(scope_glob("/src/**", Path::new("")), "/src/**");
+    }
+}
@@ -0,0 +1,126 @@
+use crate::outcome::{self, Reason};
+use clap::ValueEnum;
+use std::path::{Path, PathBuf};
+
+/// Marks hook scripts written by `linty install-hooks`, so uninstalling never removes a
+/// hook someone wrote by hand.
+const MARKER: &str = "# Installed by linty install-hooks; linty uninstall-hooks removes it.";
+
+/// Suffix of the copy kept of a hook replaced with `--force`, restored on uninstall.
+const BACKUP_SUFFIX: &str = ".linty-backup";
+
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Hook {
+    /// Lint the staged files before each commit
+    PreCommit,
+    /// Lint the files changed since the upstream branch before each push
+    PrePush,
+}
+
+impl Hook {
+    const ALL: [Hook; 2] = [Hook::PreCommit, Hook::PrePush];
+
+    fn file_name(self) -> &'static str {
+        match self {
+            Hook::PreCommit => "pre-commit",
+            Hook::PrePush => "pre-push",
+        }
+    }
+
+    fn script(self) -> String {
+        let command = match self {
+            Hook::PreCommit => "exec linty --pre-commit --no-confirm\n",
+            // Nothing to compare against until the branch has an upstream.
+            Hook::PrePush => concat!(
+                "upstream=$(git rev-parse --abbrev-ref --symbolic-full-name '@{upstream}' 2>/dev/null) || {\n",
+                "    echo \"linty: no upstream branch to diff against; skipping\" >&2\n",
+                "    exit 0\n",
+                "}\n",
+                "exec linty --diff-base \"$upstream\" --no-confirm\n",
+            ),
+        };
+        format!("#!/bin/sh\n{MARKER}\n{command}")
+    }
+}
+
+/// The repository's hooks directory, honoring `core.hooksPath`, relative to the current
+/// directory unless it's configured as an absolute path.
+fn hooks_dir(mode: &str) -> PathBuf {
+    let repo = crate::git_repo(mode);
+    let stdout = repo
+        .output(&["rev-parse", "--git-path", "hooks"])
+        .unwrap_or_else(|err| crate::git_failure(mode, err));
+    let dir = String::from_utf8_lossy(&stdout);
+    crate::normalize_lexically(&repo.walk_path(Path::new(dir.trim_end())))
+}
+
+fn installed_by_linty(path: &Path) -> bool {
+    std::fs::read_to_string(path).is_ok_and(|contents| contents.contains(MARKER))
+}
+
+/// Writes `hook` into the hooks directory and prints it. A hook linty didn't write is
+/// only replaced with `force`, and is then kept alongside with a backup suffix.
+pub fn install(hook: Hook, force: bool) -> anyhow::Result<()> {
+    let dir = hooks_dir("install-hooks");
+    let path = dir.join(hook.file_name());
+    if path.exists() && !installed_by_linty(&path) {
+        if !force {
+            eprintln!(
+                "A {} hook already exists at {}; pass --force to back it up and replace it",
+                hook.file_name(),
+                path.display()
+            );
+            outcome::fail(Reason::ConfigError);
+        }
+        let mut backup = path.clone().into_os_string();
+        backup.push(BACKUP_SUFFIX);
+        std::fs::rename(&path, &backup)?;
+        println!(
+            "Moved the existing hook to {}",
+            PathBuf::from(backup).display()
+        );
+    }
+
+    std::fs::create_dir_all(&dir)?;
+    let script = hook.script();
+    std::fs::write(&path, &script)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    println!("Wrote {}:\n{script}", path.display());
+    Ok(())
+}
+
+/// Removes `hook`, or every kind when it's `None`, where linty wrote it, restoring any
+/// hook `install` backed up in its place. Hooks linty didn't write are left alone.
+pub fn uninstall(hook: Option<Hook>) -> anyhow::Result<()> {
+    let dir = hooks_dir("uninstall-hooks");
+    let mut removed = 0;
+    for hook in hook.map_or(Hook::ALL.to_vec(), |hook| vec![hook]) {
+        let path = dir.join(hook.file_name());
+        if !path.exists() {
+            continue;
+        }
+        if !installed_by_linty(&path) {
+            println!("Leaving {}, which linty didn't install", path.display());
+            continue;
+        }
+        std::fs::remove_file(&path)?;
+        removed += 1;
+        println!("Removed {}", path.display());
+
+        let mut backup = path.clone().into_os_string();
+        backup.push(BACKUP_SUFFIX);
+        let backup = PathBuf::from(backup);
+        if backup.exists() {
+            std::fs::rename(&backup, &path)?;
+            println!("Restored the previous hook from {}", backup.display());
+        }
+    }
+    if removed == 0 {
+        println!("No hooks installed by linty in {}", dir.display());
+    }
+    Ok(())
+}
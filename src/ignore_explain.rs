@@ -0,0 +1,215 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Lint-specific ignore file, honored in every directory regardless of `--no-ignore*`.
+pub const LINTY_IGNORE_FILENAME: &str = ".lintyignore";
+
+/// Which ignore sources the main walk honored, mirroring the `--no-ignore-*` flags.
+#[derive(Clone, Copy)]
+pub struct IgnoreSources {
+    pub vcs: bool,
+    pub dot: bool,
+    pub parent: bool,
+}
+
+/// Answers whether the `.lintyignore` files above a path ignore it, for the files the walk
+/// takes as given rather than reaching through those files, like staged or named ones. As
+/// in the walk, a path is ignored when it or a directory leading to it is, nearer files
+/// take precedence, and a `!` pattern can't take back a file in an ignored directory.
+/// Files above the current directory count only when the walk reads parent ignore files.
+/// Each directory's file is parsed at most once per run.
+pub struct LintyIgnoreMatcher {
+    current_dir: PathBuf,
+    parents: bool,
+    cache: HashMap<PathBuf, Option<Gitignore>>,
+}
+
+impl LintyIgnoreMatcher {
+    pub fn new(current_dir: &Path, parents: bool) -> LintyIgnoreMatcher {
+        LintyIgnoreMatcher {
+            current_dir: current_dir.to_path_buf(),
+            parents,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// The `.lintyignore` file that ignores the file at `path`, a walk path like
+    /// `./src/main.rs`, if any.
+    pub fn ignored_by(&mut self, path: &Path) -> Option<PathBuf> {
+        let path = crate::normalize_lexically(&self.current_dir.join(path));
+        let directories: Vec<PathBuf> = path
+            .parent()?
+            .ancestors()
+            .take_while(|dir| self.parents || dir.starts_with(&self.current_dir))
+            .map(Path::to_path_buf)
+            .collect();
+        for directory in &directories {
+            self.cache
+                .entry(directory.clone())
+                .or_insert_with(|| parse_ignore_file(directory));
+        }
+        // Deepest first, as nearer files take precedence.
+        let ignores: Vec<(&PathBuf, &Gitignore)> = directories
+            .iter()
+            .filter_map(|dir| Some((dir, self.cache[dir].as_ref()?)))
+            .collect();
+        if ignores.is_empty() {
+            return None;
+        }
+
+        // The walk reaches `path` through each directory leading to it, from the top.
+        let mut steps: Vec<&Path> = path.ancestors().collect();
+        steps.reverse();
+        for step in steps {
+            let is_dir = step != path;
+            for (dir, ignore) in ignores.iter().filter(|(dir, _)| {
+                step.strip_prefix(dir)
+                    .is_ok_and(|rest| !rest.as_os_str().is_empty())
+            }) {
+                match ignore.matched(step, is_dir) {
+                    ignore::Match::Ignore(_) => return Some(dir.join(LINTY_IGNORE_FILENAME)),
+                    ignore::Match::Whitelist(_) => break,
+                    ignore::Match::None => {}
+                }
+            }
+        }
+        None
+    }
+}
+
+/// The `.lintyignore` file in `directory`, or `None` when there isn't a readable one.
+fn parse_ignore_file(directory: &Path) -> Option<Gitignore> {
+    let ignore_file = directory.join(LINTY_IGNORE_FILENAME);
+    if !ignore_file.is_file() {
+        return None;
+    }
+    let mut builder = GitignoreBuilder::new(directory);
+    if builder.add(ignore_file).is_some() {
+        return None;
+    }
+    builder.build().ok()
+}
+
+/// Finds the paths the main walk skipped because of ignore files, by re-walking with
+/// ignore files disabled and diffing against the paths that were visited.
+/// Ignored directories are reported once rather than descended into.
+pub fn skipped_paths(visited: HashSet<PathBuf>, roots: &[PathBuf], hidden: bool) -> Vec<PathBuf> {
+    let visited = Arc::new(visited);
+    let skipped = Arc::new(Mutex::new(Vec::new()));
+
+    let filter_visited = Arc::clone(&visited);
+    let filter_skipped = Arc::clone(&skipped);
+    let mut walk_builder = WalkBuilder::new(&roots[0]);
+    for root in &roots[1..] {
+        walk_builder.add(root);
+    }
+    for _ in walk_builder
+        .standard_filters(false)
+        .hidden(hidden)
+        .filter_entry(move |entry| {
+            if entry.depth() == 0 || filter_visited.contains(entry.path()) {
+                return true;
+            }
+            filter_skipped.lock().unwrap().push(entry.path().to_owned());
+            false
+        })
+        .build()
+    {}
+
+    let mut skipped = std::mem::take(&mut *skipped.lock().unwrap());
+    skipped.sort();
+    skipped
+}
+
+/// Names the ignore file that excludes `path`, plus the flag that would include it.
+pub fn explain(path: &Path, current_dir: &Path, sources: IgnoreSources) -> String {
+    // Drop the walker's `./` so anchored patterns like `build/*` match.
+    let absolute: PathBuf = current_dir
+        .join(path)
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect();
+    let is_dir = absolute.is_dir();
+    let Some(parent) = absolute.parent() else {
+        return String::from("ignored by an ignore file");
+    };
+
+    // Nearer ignore files take precedence, so check from the deepest directory up.
+    for directory in parent.ancestors() {
+        let in_walk_root = directory.starts_with(current_dir);
+        if !in_walk_root && !sources.parent {
+            break;
+        }
+        let flag = |own_flag: &str| {
+            if in_walk_root {
+                own_flag.to_owned()
+            } else {
+                format!("{own_flag} or --no-ignore-parent")
+            }
+        };
+
+        let ignore_file = directory.join(LINTY_IGNORE_FILENAME);
+        if matches(directory, &ignore_file, &absolute, is_dir) {
+            return format!("ignored by {}", ignore_file.display());
+        }
+        if sources.dot {
+            let ignore_file = directory.join(".ignore");
+            if matches(directory, &ignore_file, &absolute, is_dir) {
+                return format!(
+                    "ignored by {} (use {})",
+                    ignore_file.display(),
+                    flag("--no-ignore-dot")
+                );
+            }
+        }
+        if sources.vcs {
+            let ignore_file = directory.join(".gitignore");
+            if matches(directory, &ignore_file, &absolute, is_dir) {
+                return format!(
+                    "ignored by {} (use {})",
+                    ignore_file.display(),
+                    flag("--no-ignore-vcs")
+                );
+            }
+            let ignore_file = directory.join(".git").join("info").join("exclude");
+            if matches(directory, &ignore_file, &absolute, is_dir) {
+                return format!("ignored by {} (use --no-ignore-vcs)", ignore_file.display());
+            }
+        }
+    }
+
+    if sources.vcs {
+        let (global, _) = Gitignore::global();
+        if global
+            .matched_path_or_any_parents(&absolute, is_dir)
+            .is_ignore()
+        {
+            return format!(
+                "ignored by the global gitignore {} (use --no-ignore-vcs)",
+                global.path().display()
+            );
+        }
+    }
+
+    String::from("ignored by an ignore file (use --no-ignore)")
+}
+
+/// Whether `ignore_file`, with patterns relative to `root`, ignores `path` or a parent.
+fn matches(root: &Path, ignore_file: &Path, path: &Path, is_dir: bool) -> bool {
+    if !ignore_file.is_file() {
+        return false;
+    }
+    let mut builder = GitignoreBuilder::new(root);
+    if builder.add(ignore_file).is_some() {
+        return false;
+    }
+    let Ok(matcher) = builder.build() else {
+        return false;
+    };
+    matcher
+        .matched_path_or_any_parents(path, is_dir)
+        .is_ignore()
+}
@@ -0,0 +1,335 @@
+use crate::baseline::Applied;
+use crate::blame::BlameInfo;
+use crate::compare::Comparison;
+use crate::outcome::Outcome;
+use crate::post_results::RepositoryInfo;
+use crate::{Config, RuleConfig, Severity, Violation};
+use linty::git::Repo;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::time::SystemTime;
+
+/// Version of the JSON report's shape. Bump it whenever a field is renamed or removed or
+/// changes type; adding a field doesn't need a bump.
+const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    schema_version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    meta: Option<&'a ReportMeta>,
+    violations: Vec<JsonViolation<'a>>,
+    summary: JsonSummary<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comparison: Option<&'a Comparison>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    outcome: Option<&'a Outcome>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    repository: Option<&'a RepositoryInfo>,
+}
+
+#[derive(Serialize)]
+struct JsonViolation<'a> {
+    rule_id: &'a str,
+    message: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<&'a str>,
+    severity: Severity,
+    file: String,
+    lines: Vec<usize>,
+    /// Where each match starts and ends, and its first line of text; columns count
+    /// characters.
+    matches: Vec<JsonMatch<'a>>,
+    /// Per-match fingerprints, consumed by `--compare` when this report is the baseline.
+    fingerprints: Vec<&'a str>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    blame: &'a HashMap<usize, BlameInfo>,
+    /// Matches dropped by `--max-violations-per-file`, set on `linty::file-overflow` notes.
+    #[serde(skip_serializing_if = "is_zero")]
+    discarded: usize,
+    /// Set for findings in files `--pre-commit-untracked` added, which aren't staged.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    untracked: bool,
+    /// Set for findings of `report_once` rules, whose one match is the file's first.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    report_once: bool,
+    /// Edits from the rule's `fix` template, one per match it could be computed for.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    suggestions: Vec<JsonSuggestion<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonMatch<'a> {
+    /// The positions are left out for path rules' matches, which have none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_column: Option<usize>,
+    text: &'a str,
+    /// The violation's message filled in with this match's capture groups, when the
+    /// rule's message has placeholders.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<&'a str>,
+}
+
+/// A suggested replacement of the text from `line`:`column` to `end_line`:`end_column`
+/// (exclusive), positioned like the match it replaces.
+#[derive(Serialize)]
+struct JsonSuggestion<'a> {
+    line: usize,
+    column: usize,
+    end_line: usize,
+    end_column: usize,
+    replacement: &'a str,
+}
+
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
+/// What produced a report, so archived reports are self-describing.
+#[derive(Serialize)]
+pub struct ReportMeta {
+    linty_version: &'static str,
+    /// FNV-1a hash of the effective config, to tell whether two reports used the same rules.
+    config_hash: String,
+    /// When the run started, in RFC 3339.
+    timestamp: String,
+    #[serde(flatten)]
+    git: GitState,
+}
+
+/// The checked-out commit and branch and whether the work tree has changes, all null
+/// outside a git work tree.
+#[derive(Serialize, Default)]
+struct GitState {
+    commit: Option<String>,
+    branch: Option<String>,
+    dirty: Option<bool>,
+}
+
+impl ReportMeta {
+    /// Gathers the report's metadata, with the git state of `repo`. This runs git, so it's
+    /// only done when a structured report is written.
+    pub fn new(config: &Config, started: SystemTime, repo: Option<&Repo>) -> anyhow::Result<Self> {
+        // Rule sources are absolute paths, which would make the hash machine-specific, and
+        // going through `Value` sorts the keys of the config's maps.
+        let mut config = config.clone();
+        for rule in &mut config.rules {
+            rule.source = None;
+        }
+        let config = serde_json::to_value(&config)?;
+        Ok(ReportMeta {
+            linty_version: env!("CARGO_PKG_VERSION"),
+            config_hash: format!(
+                "{:016x}",
+                crate::compare::fnv1a(&serde_json::to_vec(&config)?)
+            ),
+            timestamp: humantime::format_rfc3339_seconds(started).to_string(),
+            git: repo.and_then(git_state).unwrap_or_default(),
+        })
+    }
+
+    /// The checked-out commit, if the run is in a git work tree with one.
+    pub fn commit(&self) -> Option<&str> {
+        self.git.commit.as_deref()
+    }
+
+    /// The checked-out branch, unless HEAD is detached.
+    pub fn branch(&self) -> Option<&str> {
+        self.git.branch.as_deref()
+    }
+}
+
+/// Reads the git state with a single `git status` call.
+fn git_state(repo: &Repo) -> Option<GitState> {
+    let stdout = match repo.output(&["status", "--porcelain=v2", "--branch"]) {
+        Ok(stdout) => String::from_utf8(stdout).ok()?,
+        Err(err) => {
+            eprintln!("Warning: Leaving the git state out of the report: {err}");
+            return None;
+        }
+    };
+
+    let mut commit = None;
+    let mut branch = None;
+    let mut dirty = false;
+    for line in stdout.lines() {
+        if let Some(oid) = line.strip_prefix("# branch.oid ") {
+            commit = (oid != "(initial)").then(|| oid.to_owned());
+        } else if let Some(head) = line.strip_prefix("# branch.head ") {
+            branch = (head != "(detached)").then(|| head.to_owned());
+        } else if !line.starts_with('#') {
+            dirty = true;
+        }
+    }
+    Some(GitState {
+        commit,
+        branch,
+        dirty: Some(dirty),
+    })
+}
+
+#[derive(Serialize)]
+struct JsonSummary<'a> {
+    warnings: usize,
+    errors: usize,
+    /// Matches hidden by suppression comments, per rule id.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    suppressed: &'a BTreeMap<String, usize>,
+    /// Matches hidden by `--baseline`, per rule id.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    baselined: Option<&'a BTreeMap<String, usize>>,
+    /// Baselined matches that no longer occur, per rule id.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    baseline_resolved: Option<&'a BTreeMap<String, usize>>,
+    #[serde(flatten)]
+    stats: &'a RunStats,
+}
+
+/// What the run covered and how long it took, as the text summary line reports it.
+#[derive(Serialize)]
+pub struct RunStats {
+    pub files_walked: usize,
+    pub files_scanned: usize,
+    /// Files no rule checked because they look binary, are too large, aren't UTF-8, or
+    /// match the config's `excludes`.
+    pub files_skipped: usize,
+    pub rules: usize,
+    /// From the start of the run to the end of the scan.
+    pub elapsed_ms: u64,
+    /// The part of the scan spent walking and reading files.
+    pub walk_ms: u64,
+    /// The part of the scan spent running regexes.
+    pub match_ms: u64,
+    /// About how long the files replayed from the cache would have taken to scan, with
+    /// `--cache`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_saved_ms: Option<u64>,
+    /// Milliseconds each rule spent matching, with `--stats`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub rule_durations_ms: BTreeMap<String, f64>,
+}
+
+/// What a report says about the run besides its violations.
+pub struct RunContext<'a> {
+    /// The configured rules, for each violation's description and url.
+    pub rules: &'a [RuleConfig],
+    pub meta: Option<&'a ReportMeta>,
+    pub suppressed: &'a BTreeMap<String, usize>,
+    /// What `--baseline` hid, when one was applied.
+    pub baseline: Option<&'a Applied>,
+    pub stats: &'a RunStats,
+    pub comparison: Option<&'a Comparison>,
+    pub outcome: Option<&'a Outcome>,
+    pub repository: Option<&'a RepositoryInfo>,
+}
+
+pub fn write_report(
+    writer: impl std::io::Write,
+    violations: &[Violation],
+    messages: &HashMap<&str, &str>,
+    context: RunContext,
+) -> anyhow::Result<()> {
+    let RunContext {
+        rules,
+        meta,
+        suppressed,
+        baseline,
+        stats,
+        comparison,
+        outcome,
+        repository,
+    } = context;
+    // The outcome's counts include violations dropped by --max-violations-per-file.
+    let (errors, warnings) = match outcome {
+        Some(outcome) => (outcome.errors, outcome.warnings),
+        None => {
+            let errors = violations
+                .iter()
+                .filter(|violation| matches!(violation.severity, Severity::Error))
+                .count();
+            (errors, violations.len() - errors)
+        }
+    };
+
+    let report = JsonReport {
+        schema_version: SCHEMA_VERSION,
+        meta,
+        violations: violations
+            .iter()
+            .map(|violation| {
+                let rule = rules.iter().find(|rule| rule.id == violation.rule_id);
+                JsonViolation {
+                    rule_id: &violation.rule_id,
+                    message: messages
+                        .get(violation.rule_id.as_str())
+                        .copied()
+                        .unwrap_or_default(),
+                    description: rule.and_then(|rule| rule.description.as_deref()),
+                    url: rule.and_then(|rule| rule.url.as_deref()),
+                    severity: violation.severity,
+                    file: crate::display_path(&violation.path),
+                    lines: violation.lines(),
+                    matches: violation
+                        .matches
+                        .iter()
+                        .map(|m| {
+                            let position = |value: usize| m.has_position().then_some(value);
+                            JsonMatch {
+                                line: position(m.line),
+                                column: position(m.column),
+                                end_line: position(m.end_line),
+                                end_column: position(m.end_column),
+                                text: &m.text,
+                                message: m.message.as_deref(),
+                            }
+                        })
+                        .collect(),
+                    fingerprints: violation
+                        .matches
+                        .iter()
+                        .map(|m| m.fingerprint.as_str())
+                        .collect(),
+                    blame: &violation.blame,
+                    discarded: violation.discarded,
+                    untracked: violation.untracked,
+                    report_once: violation.report_once,
+                    suggestions: violation
+                        .matches
+                        .iter()
+                        .filter_map(|m| {
+                            Some(JsonSuggestion {
+                                line: m.line,
+                                column: m.column,
+                                end_line: m.end_line,
+                                end_column: m.end_column,
+                                replacement: m.replacement.as_deref()?,
+                            })
+                        })
+                        .collect(),
+                }
+            })
+            .collect(),
+        summary: JsonSummary {
+            warnings,
+            errors,
+            suppressed,
+            baselined: baseline.map(|applied| &applied.known),
+            baseline_resolved: baseline.map(|applied| &applied.resolved),
+            stats,
+        },
+        comparison,
+        outcome,
+        repository,
+    };
+
+    serde_json::to_writer_pretty(writer, &report)?;
+    Ok(())
+}
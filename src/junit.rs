@@ -0,0 +1,108 @@
+use crate::xml::escape;
+use crate::{severity_name, Rule, Violation};
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Writes a JUnit XML report for CI test tabs: a test suite per rule, with a failing test
+/// case per file it matched, or a single passing one when it matched nothing.
+pub fn write_report(
+    mut writer: impl Write,
+    rules: &[Rule],
+    violations: &[Violation],
+    messages: &HashMap<&str, &str>,
+) -> anyhow::Result<()> {
+    let mut by_rule: Vec<(&str, Vec<&Violation>)> = Vec::new();
+    let mut indices: HashMap<&str, usize> = HashMap::new();
+    // Notes like linty::file-overflow aren't configured rules, but still get a suite.
+    let rule_ids = rules.iter().map(|rule| rule.id.as_str()).chain(
+        violations
+            .iter()
+            .map(|violation| violation.rule_id.as_str()),
+    );
+    for id in rule_ids {
+        if !indices.contains_key(id) {
+            indices.insert(id, by_rule.len());
+            by_rule.push((id, Vec::new()));
+        }
+    }
+    for violation in violations {
+        by_rule[indices[violation.rule_id.as_str()]]
+            .1
+            .push(violation);
+    }
+
+    let tests: usize = by_rule
+        .iter()
+        .map(|(_, violations)| violations.len().max(1))
+        .sum();
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<testsuites name="linty" tests="{tests}" failures="{}">"#,
+        violations.len()
+    )?;
+    for (id, violations) in &by_rule {
+        let id = escape(id);
+        writeln!(
+            writer,
+            r#"  <testsuite name="{id}" tests="{}" failures="{}">"#,
+            violations.len().max(1),
+            violations.len()
+        )?;
+        if violations.is_empty() {
+            writeln!(writer, r#"    <testcase name="{id}" classname="{id}"/>"#)?;
+        }
+        for violation in violations {
+            let message = messages
+                .get(violation.rule_id.as_str())
+                .copied()
+                .filter(|message| !message.is_empty())
+                .unwrap_or(&violation.rule_id);
+            let spans = violation.line_spans();
+            let failure = if spans.is_empty() {
+                message.to_owned()
+            } else {
+                let spans: Vec<String> = spans
+                    .into_iter()
+                    .map(|(start, end)| crate::line_ranges::format_span(start, end))
+                    .collect();
+                format!("{message} (lines: {})", spans.join(", "))
+            };
+            writeln!(
+                writer,
+                r#"    <testcase name="{}" classname="{id}">"#,
+                escape(&violation.file)
+            )?;
+            writeln!(
+                writer,
+                r#"      <failure message="{}" type="{}">{}</failure>"#,
+                escape(&failure),
+                severity_name(violation.severity),
+                escape(&match_lines(violation))
+            )?;
+            writeln!(writer, "    </testcase>")?;
+        }
+        writeln!(writer, "  </testsuite>")?;
+    }
+    writeln!(writer, "</testsuites>")?;
+    Ok(())
+}
+
+/// The failure's body: each match as file:line:col with the matched text, or with its
+/// own message when the rule's message has placeholders, or just the file for a path
+/// rule's match.
+fn match_lines(violation: &Violation) -> String {
+    let lines: Vec<String> = violation
+        .matches
+        .iter()
+        .map(|m| {
+            if m.has_position() {
+                let text = m.message.as_deref().unwrap_or(&m.text);
+                format!("{}:{}:{}: {text}", violation.file, m.line, m.column)
+            } else {
+                violation.file.clone()
+            }
+        })
+        .collect();
+    lines.join("\n")
+}
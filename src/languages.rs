@@ -0,0 +1,102 @@
+use std::collections::{BTreeMap, HashMap};
+
+/// File extensions for the names rules can list in `languages`.
+const BUILTIN_LANGUAGES: [(&str, &[&str]); 20] = [
+    ("c", &["c", "h"]),
+    ("cpp", &["cc", "cpp", "cxx", "hh", "hpp", "hxx"]),
+    ("csharp", &["cs"]),
+    ("css", &["css", "scss"]),
+    ("go", &["go"]),
+    ("html", &["html", "htm"]),
+    ("java", &["java"]),
+    ("javascript", &["js", "jsx", "mjs", "cjs"]),
+    ("json", &["json"]),
+    ("kotlin", &["kt", "kts"]),
+    ("markdown", &["md", "markdown"]),
+    ("php", &["php"]),
+    ("python", &["py", "pyi"]),
+    ("ruby", &["rb"]),
+    ("rust", &["rs"]),
+    ("shell", &["sh", "bash", "zsh"]),
+    ("swift", &["swift"]),
+    ("toml", &["toml"]),
+    ("typescript", &["ts", "tsx", "mts", "cts"]),
+    ("yaml", &["yaml", "yml"]),
+];
+
+/// The language names rules can use, from the built-in table plus the config's
+/// `languages` section, whose entries replace built-in ones with the same name.
+pub struct LanguageTable {
+    extensions: BTreeMap<String, Vec<String>>,
+}
+
+impl LanguageTable {
+    pub fn new(configured: Option<&HashMap<String, Vec<String>>>) -> LanguageTable {
+        let mut extensions: BTreeMap<String, Vec<String>> = BUILTIN_LANGUAGES
+            .iter()
+            .map(|(name, extensions)| {
+                (
+                    name.to_string(),
+                    extensions
+                        .iter()
+                        .map(|extension| extension.to_string())
+                        .collect(),
+                )
+            })
+            .collect();
+        for (name, configured_extensions) in configured.into_iter().flatten() {
+            extensions.insert(name.to_owned(), configured_extensions.clone());
+        }
+        LanguageTable { extensions }
+    }
+
+    /// Include globs for a rule's `languages`.
+    pub fn globs(&self, rule_id: &str, languages: &[String]) -> anyhow::Result<Vec<String>> {
+        let mut globs = Vec::new();
+        for language in languages {
+            let Some(extensions) = self.extensions.get(language) else {
+                let suggestion = match self.closest(language) {
+                    Some(name) => format!("; did you mean {name}?"),
+                    None => String::from("; see linty list-languages"),
+                };
+                anyhow::bail!("Rule {rule_id} has unknown language {language:?}{suggestion}");
+            };
+            globs.extend(
+                extensions
+                    .iter()
+                    .map(|extension| format!("**/*.{extension}")),
+            );
+        }
+        Ok(globs)
+    }
+
+    /// Each language and its extensions, by name, for `linty list-languages`.
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &Vec<String>)> {
+        self.extensions.iter()
+    }
+
+    /// The known name nearest to `language`, if it's close enough to be a typo.
+    fn closest(&self, language: &str) -> Option<&str> {
+        self.extensions
+            .keys()
+            .map(|name| (edit_distance(language, name), name))
+            .filter(|(distance, _)| *distance <= 2)
+            .min()
+            .map(|(_, name)| name.as_str())
+    }
+}
+
+/// Levenshtein distance between two names.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
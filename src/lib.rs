@@ -0,0 +1,1840 @@
+//! Linty's config model, rule compilation, and scanning, for embedding in other tools.
+//! The `linty` binary adds argument parsing, reporting, prompts, and exit codes on top.
+
+pub mod blame;
+pub mod cache;
+pub mod cancel;
+pub mod chunked;
+pub mod compare;
+pub mod coverage;
+pub mod fix;
+pub mod git;
+pub mod gitattributes;
+pub mod ignore_explain;
+pub mod languages;
+pub mod log;
+mod message;
+pub mod metric;
+pub mod normalize;
+pub mod path_globs;
+pub mod prefilter;
+pub mod progress;
+pub mod region;
+pub mod rule_kind;
+pub mod scan;
+mod string_list;
+pub mod suppress;
+mod unknown_fields;
+
+use blame::BlameInfo;
+use clap::ValueEnum;
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use path_globs::PathGlobs;
+use regex::{Regex, RegexBuilder};
+use scan::Match;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Why the library couldn't load a config or check files.
+#[derive(Debug)]
+pub enum Error {
+    /// The config file couldn't be read.
+    Io(std::io::Error),
+    /// The config isn't valid in its format, or doesn't have the shape of a config.
+    /// `rule` names the first rule that doesn't parse on its own, if one doesn't.
+    Parse {
+        format: ConfigFormat,
+        message: String,
+        rule: Option<String>,
+    },
+    /// The config parses but doesn't hold together, like a rule extending a rule that
+    /// doesn't exist.
+    InvalidConfig(String),
+    /// Rules that don't compile, with a message naming the rule and field of each.
+    InvalidRules(Vec<String>),
+    /// Walking or reading the files to check failed.
+    Scan(anyhow::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "Failed to read config: {err}"),
+            Error::Parse {
+                format,
+                message,
+                rule,
+            } => {
+                write!(f, "Failed to parse config as {}: {message}", format.name())?;
+                if let Some(rule) = rule {
+                    write!(f, "\nThe problem is in {rule}")?;
+                }
+                Ok(())
+            }
+            Error::InvalidConfig(message) => write!(f, "{message}"),
+            Error::InvalidRules(problems) => write!(f, "{}", problems.join("\n")),
+            Error::Scan(err) => write!(f, "{err:#}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Scan(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Checks files against a config's rules, with the walk settings the `linty` binary uses
+/// by default: ignore files are honored, and hidden, generated, and binary files are
+/// skipped.
+pub struct Linter {
+    rules: Vec<Rule>,
+    overrides: Vec<Override>,
+    options: scan::WalkOptions,
+}
+
+impl Linter {
+    /// Compiles `config`'s enabled rules, after resolving the rules that extend others.
+    /// Fails with every rule that doesn't compile rather than just the first.
+    pub fn from_config(mut config: Config) -> Result<Linter, Error> {
+        resolve_rule_inheritance(&mut config)
+            .map_err(|err| Error::InvalidConfig(err.to_string()))?;
+        let (rules, problems) = build_rules(&config, false);
+        if !problems.is_empty() {
+            return Err(Error::InvalidRules(problems));
+        }
+        let overrides = generate_overrides_from_config(&config)
+            .map_err(|err| Error::InvalidConfig(err.to_string()))?;
+        let excludes =
+            PathGlobs::new(config.excludes.as_deref().unwrap_or_default()).map_err(|errors| {
+                Error::InvalidConfig(format!("Invalid exclude: {}", errors.join("; ")))
+            })?;
+        let options = scan::WalkOptions {
+            roots: Vec::new(),
+            ignore_sources: ignore_explain::IgnoreSources {
+                vcs: true,
+                dot: true,
+                parent: true,
+            },
+            hidden: false,
+            follow_symlinks: false,
+            include_generated: false,
+            explain_skip: false,
+            excludes: Arc::new(excludes),
+            max_violations_per_file: config.max_violations_per_file,
+            record_coverage: false,
+            record_rule_times: false,
+            rule_timeout: None,
+            threads: std::thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            inline_config: true,
+            report_unused_suppressions: false,
+            staged: Default::default(),
+            repo: None,
+            invalid_utf8: InvalidUtf8::Skip,
+            skip_binary: true,
+            max_filesize: None,
+            glob_base: PathBuf::new(),
+            only: None,
+            cache: None,
+            progress: None,
+        };
+        Ok(Linter {
+            rules,
+            overrides,
+            options,
+        })
+    }
+
+    /// The compiled rules, in config order, followed by the registered ones.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Adds `kind` as a rule of its own, with the kind's name as its id and the kind's
+    /// severity. It's checked against every file the walk reaches, as a rule without
+    /// globs is, and its findings are reported like any rule's matches. Suppression
+    /// comments name it by its id.
+    #[cfg(feature = "rule-kinds")]
+    pub fn register_rule_kind(&mut self, kind: Box<dyn rule_kind::RuleKind>) {
+        self.rules.push(Rule {
+            id: kind.name().to_owned(),
+            regex: Regex::new(r"[^\s\S]").expect("a regex that matches nothing compiles"),
+            regex_flags: prefilter::RegexFlags::default(),
+            severity: kind.severity(),
+            includes: PathGlobs::empty(),
+            excludes: PathGlobs::empty(),
+            directory: GlobSet::empty(),
+            unicode_normalize: None,
+            scan_window: None,
+            region: None,
+            allow: Vec::new(),
+            word_boundary: false,
+            message: None,
+            fix: None,
+            max_age_days: None,
+            escalate_after_days: None,
+            max_match_length: scan::DEFAULT_MAX_MATCH_LENGTH,
+            binary: false,
+            kind,
+            registered: true,
+            // Nothing says a registered kind looks at one line at a time.
+            line_local: false,
+            report_once: false,
+        });
+    }
+
+    /// Walks `path`, a file or a directory, and returns the violations in it, sorted by
+    /// path. A relative `path` is matched against rules' globs like the binary's walk
+    /// from the current directory, so `src` and `./src` are the same.
+    /// Files that can't be read are left out rather than failing the whole walk.
+    pub fn check_path(&self, path: &Path) -> Result<Vec<Violation>, Error> {
+        let options = scan::WalkOptions {
+            roots: vec![walk_path(path)],
+            ..self.options.clone()
+        };
+        let current_dir = std::env::current_dir().map_err(Error::Io)?;
+        scan::scan(&options, &self.rules, &self.overrides, &current_dir)
+            .map(|result| result.violations)
+            .map_err(Error::Scan)
+    }
+
+    /// Checks `contents` as if it were the file at `path`, which rules' globs match
+    /// against; nothing is read from disk.
+    pub fn check_str(&self, contents: &str, path: &Path) -> Result<Vec<Violation>, Error> {
+        let current_dir = std::env::current_dir().map_err(Error::Io)?;
+        scan::scan_stdin(
+            contents.as_bytes(),
+            &walk_path(path),
+            &self.options,
+            &self.rules,
+            &self.overrides,
+            &current_dir,
+        )
+        .map(|result| result.violations)
+        .map_err(Error::Scan)
+    }
+}
+
+/// A relative path as the walk from the current directory sees it, with a `./` prefix.
+fn walk_path(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_owned();
+    }
+    Path::new("./").join(path.strip_prefix("./").unwrap_or(path))
+}
+
+impl ConfigFormat {
+    /// The format implied by a config file's extension, defaulting to JSON.
+    pub fn from_path(path: &Path) -> ConfigFormat {
+        match path.extension().and_then(std::ffi::OsStr::to_str) {
+            Some("toml") => ConfigFormat::Toml,
+            Some("yaml" | "yml") => ConfigFormat::Yaml,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "JSON",
+            ConfigFormat::Toml => "TOML",
+            ConfigFormat::Yaml => "YAML",
+        }
+    }
+}
+
+/// Reads and parses the config file at `path`. `format` overrides the format implied by
+/// the file extension. Each rule's `source` is the path.
+pub fn read_config(path: &Path, format: Option<ConfigFormat>) -> Result<Config, Error> {
+    let contents = std::fs::read_to_string(path).map_err(Error::Io)?;
+    parse_config(
+        &contents,
+        format.unwrap_or_else(|| ConfigFormat::from_path(path)),
+        &path.display().to_string(),
+    )
+}
+
+/// Parses a config in `format`, setting each rule's `source` to `source`, which says
+/// where the config came from for `print-config` and `dump-rules`. A field linty doesn't
+/// know, which is usually a typo like `severty`, fails the parse, as does a rule id used
+/// twice.
+pub fn parse_config(contents: &str, format: ConfigFormat, source: &str) -> Result<Config, Error> {
+    let (config, unknown) = parse_config_lenient(contents, format, source)?;
+    if !unknown.is_empty() {
+        return Err(Error::Parse {
+            format,
+            message: unknown.join("\n"),
+            rule: None,
+        });
+    }
+    Ok(config)
+}
+
+/// Parses a config like [`parse_config`], but leaves out the fields linty doesn't know,
+/// such as ones added by a newer version, returning a description of each instead.
+pub fn parse_config_lenient(
+    contents: &str,
+    format: ConfigFormat,
+    source: &str,
+) -> Result<(Config, Vec<String>), Error> {
+    let mut ignored = Vec::new();
+    let mut record = |path: serde_ignored::Path| ignored.push(unknown_fields::segments(&path));
+    let parsed: Result<Config, String> = match format {
+        ConfigFormat::Json => {
+            let mut deserializer = serde_json::Deserializer::from_str(contents);
+            serde_ignored::deserialize(&mut deserializer, &mut record)
+                .and_then(|config| deserializer.end().map(|()| config))
+                .map_err(|err| err.to_string())
+        }
+        ConfigFormat::Toml => {
+            serde_ignored::deserialize(toml::Deserializer::new(contents), &mut record)
+                .map_err(|err| err.to_string())
+        }
+        ConfigFormat::Yaml => {
+            serde_ignored::deserialize(serde_yaml::Deserializer::from_str(contents), &mut record)
+                .map_err(|err| err.to_string())
+        }
+    };
+    let mut config = parsed.map_err(|message| Error::Parse {
+        format,
+        message,
+        rule: find_rule_error(contents, format),
+    })?;
+
+    let mut first_index: HashMap<&str, usize> = HashMap::new();
+    for (index, rule) in config.rules.iter().enumerate() {
+        if let Some(first) = first_index.insert(&rule.id, index) {
+            return Err(Error::InvalidConfig(format!(
+                "Rule {} is defined more than once, as rules[{first}] and rules[{index}]",
+                rule.id
+            )));
+        }
+    }
+
+    let rule_ids: Vec<String> = config.rules.iter().map(|rule| rule.id.clone()).collect();
+    let unknown = ignored
+        .into_iter()
+        .filter_map(|segments| unknown_fields::describe(segments, &rule_ids))
+        .collect();
+    for (index, rule) in config.rules.iter_mut().enumerate() {
+        rule.source = Some(source.to_owned());
+        rule.position = Some(index + 1);
+    }
+    Ok((config, unknown))
+}
+
+/// What to do with a file that isn't valid UTF-8, like an image or a Latin-1 source file.
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InvalidUtf8 {
+    /// Leave the file out, mentioning it with --verbose
+    Skip,
+    /// Check the file with each invalid sequence replaced by U+FFFD
+    Lossy,
+    /// Report the file as an error
+    Error,
+}
+
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RuleConfig {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    pub message: Option<String>,
+    pub regex: Option<String>,
+    /// Whether `regex` is a regular expression or a literal string to find (default: regex).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_type: Option<MatchType>,
+    /// Whether files the rule applies to must not match (`forbidden`, the default) or must
+    /// match at least once (`required`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<RuleMode>,
+    /// Whether the regex is matched against file contents (`content`, the default) or
+    /// against each file's path (`path`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<RuleTarget>,
+    /// Whether the rule matches its regex (`pattern`, the default) or checks a built-in
+    /// measurement against `limit`, like `max_line_length`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<RuleKind>,
+    /// The most characters per line, or lines per file, a `max_line_length` or
+    /// `max_file_lines` rule allows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// A regex for the lines a `max_line_length` or `max_file_lines` rule leaves out, like
+    /// ones holding a URL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_pattern: Option<String>,
+    pub severity: Option<Severity>,
+    #[serde(
+        default,
+        deserialize_with = "string_list::deserialize_optional",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub includes: Option<Vec<String>>,
+    #[serde(
+        default,
+        deserialize_with = "string_list::deserialize_optional",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub excludes: Option<Vec<String>>,
+    /// Language names, like `typescript`, that add include globs for their extensions.
+    #[serde(
+        default,
+        deserialize_with = "string_list::deserialize_optional",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub languages: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(
+        default,
+        deserialize_with = "string_list::deserialize_optional",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_commit: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_commit_only: Option<bool>,
+    /// Marks a rule as intentionally replacing a rule with the same id from another source.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r#override: Option<bool>,
+    /// Normalize file contents to this form before matching, so composed and decomposed
+    /// spellings of the same text both match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unicode_normalize: Option<normalize::NormalForm>,
+    /// Match case-insensitively unless the regex contains an uppercase letter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smart_case: Option<bool>,
+    /// Match case-insensitively, as with `(?i)`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_insensitive: Option<bool>,
+    /// Let `^` and `$` match at the start and end of every line, as with `(?m)`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multi_line: Option<bool>,
+    /// Let `.` match newlines, as with `(?s)`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dot_matches_new_line: Option<bool>,
+    /// Treat `\r\n` as a line ending, as with `(?R)`: `$` under `multi_line` matches before
+    /// the `\r` of a CRLF line, and `.` doesn't match a `\r`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crlf: Option<bool>,
+    /// Only report matches that are whole words: a match that starts or ends with a word
+    /// character mustn't have another word character next to it there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word_boundary: Option<bool>,
+    /// How many bytes the compiled regex may take, passed to `RegexBuilder::size_limit`,
+    /// for a large pattern that fails to compile otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_limit: Option<usize>,
+    /// How many bytes of cache the regex's lazy DFA may take per thread, passed to
+    /// `RegexBuilder::dfa_size_limit`. A pattern that outgrows it falls back to a slower
+    /// engine, which raising it can avoid.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dfa_size_limit: Option<usize>,
+    /// Only match within the first or last bytes of each file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scan_window: Option<ScanWindowConfig>,
+    /// Replacement for each match, with `$1` or `${name}` capture references, reported as a
+    /// suggestion in JSON output and applied by `--fix`.
+    #[serde(
+        default,
+        alias = "replacement",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub fix: Option<String>,
+    /// Only report matches on lines whose last change, per `git blame`, is at least this many
+    /// days old.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u64>,
+    /// Report matches on lines at least this many days old as errors.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escalate_after_days: Option<u64>,
+    /// Matches longer than this many bytes are cut short in reports and get no fix
+    /// suggestion (default: 10 KiB).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_match_length: Option<usize>,
+    /// Lines to list per file in text output before summarizing the rest, instead of
+    /// `--max-listed-lines` (0 for no limit). Structured formats still get every match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_reported: Option<usize>,
+    /// Report only the first match in each file, for rules where which files are affected
+    /// matters more than every occurrence, and stop matching the file there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub report_once: Option<bool>,
+    /// Also check files that look binary, which are otherwise skipped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary: Option<bool>,
+    /// Only match inside, or only outside, regions delimited by marker lines.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<region::RegionConfig>,
+    /// Regexes for the known-good text around a match, like `http://localhost` for a rule
+    /// against `http://`; a match that one of them overlaps on its lines isn't reported.
+    #[serde(
+        default,
+        deserialize_with = "string_list::deserialize_optional",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub allow: Option<Vec<String>>,
+    /// A longer explanation of the rule than its message, shown at the warning prompt, in
+    /// `rules -v`, and in JSON reports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Text the rule must and mustn't match, checked by `linty test`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub examples: Option<RuleExamples>,
+    /// Where to read more about the rule, printed after its message in text reports and
+    /// linked from Gerrit comments and SARIF rules.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecation_message: Option<String>,
+    /// Where the rule was defined, for `print-config` and `dump-rules`; never read from a config.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// The rule's 1-based position in the config that defined it, for errors; never read
+    /// from a config.
+    #[serde(skip)]
+    pub position: Option<usize>,
+    /// The directory the rule is limited to, relative to the config's directory like
+    /// `backend`. Nested configs set it on the rules they add, and `print-config` prints
+    /// it, so a saved config keeps their scope.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub directory: Option<String>,
+}
+
+impl RuleConfig {
+    /// The rule's regex as it's compiled, with a literal `match_type` escaped.
+    pub fn pattern(&self) -> Option<Cow<'_, str>> {
+        let regex = self.regex.as_deref()?;
+        Some(match self.match_type.unwrap_or_default() {
+            MatchType::Regex => Cow::Borrowed(regex),
+            MatchType::Literal => Cow::Owned(regex::escape(regex)),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchType {
+    #[default]
+    Regex,
+    Literal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleMode {
+    #[default]
+    Forbidden,
+    Required,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleTarget {
+    #[default]
+    Content,
+    Path,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleKind {
+    #[default]
+    Pattern,
+    MaxLineLength,
+    MaxFileLines,
+}
+
+impl RuleKind {
+    /// The kind as it's spelled in a config.
+    pub fn name(self) -> &'static str {
+        match self {
+            RuleKind::Pattern => "pattern",
+            RuleKind::MaxLineLength => "max_line_length",
+            RuleKind::MaxFileLines => "max_file_lines",
+        }
+    }
+}
+
+/// A rule's `examples`, each checked as the whole contents of a file, or as a path for a
+/// path rule.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RuleExamples {
+    #[serde(
+        default,
+        deserialize_with = "string_list::deserialize",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub matches: Vec<String>,
+    #[serde(
+        default,
+        deserialize_with = "string_list::deserialize",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub no_matches: Vec<String>,
+}
+
+/// A rule's `scan_window`; exactly one of the two must be set.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct ScanWindowConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head_bytes: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tail_bytes: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Profile {
+    pub error_on_warning: Option<bool>,
+    pub no_confirm: Option<bool>,
+    pub ignored: Option<bool>,
+    pub hidden: Option<bool>,
+    #[serde(default, deserialize_with = "string_list::deserialize_optional")]
+    pub enable: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "string_list::deserialize_optional")]
+    pub disable: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "string_list::deserialize_optional")]
+    pub only_tags: Option<Vec<String>>,
+    pub severities: Option<HashMap<String, Severity>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleToggle {
+    Off,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(untagged)]
+pub enum RuleOverride {
+    Toggle(RuleToggle),
+    Settings { severity: Severity },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OverrideConfig {
+    #[serde(deserialize_with = "string_list::deserialize")]
+    pub paths: Vec<String>,
+    pub rules: HashMap<String, RuleOverride>,
+}
+
+/// How the text and markdown reports print one severity.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SeverityDisplay {
+    pub label: Option<String>,
+    pub color: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Config {
+    /// Other configs whose rules this one builds on; currently only `builtin:defaults`.
+    #[serde(
+        default,
+        deserialize_with = "string_list::deserialize_optional",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub extends: Option<Vec<String>>,
+    /// Directories to walk instead of the current directory, like the parts of a monorepo
+    /// that are linted.
+    #[serde(
+        default,
+        alias = "root",
+        deserialize_with = "string_list::deserialize_optional",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub roots: Option<Vec<String>>,
+    /// Walk hidden files, as `--hidden` does; `--no-hidden` overrides it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_hidden: Option<bool>,
+    /// Walk files that ignore files leave out, as `--no-ignore` does; `--ignore` overrides
+    /// it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_ignored: Option<bool>,
+    /// Globs for files no rule checks, like build output and vendored dependencies.
+    #[serde(
+        default,
+        alias = "global_excludes",
+        deserialize_with = "string_list::deserialize_optional",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub excludes: Option<Vec<String>>,
+    pub rules: Vec<RuleConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profiles: Option<HashMap<String, Profile>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<Vec<OverrideConfig>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<HashMap<Severity, SeverityDisplay>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_violations_per_file: Option<usize>,
+    /// Extra or replacement language names for rules' `languages`, mapped to extensions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub languages: Option<HashMap<String, Vec<String>>>,
+    /// Check the rules `.editorconfig` files describe, like max_line_length.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editorconfig: Option<bool>,
+}
+
+pub struct Rule {
+    pub id: String,
+    pub regex: Regex,
+    /// The flags `regex` was built with.
+    pub regex_flags: prefilter::RegexFlags,
+    pub severity: Severity,
+    /// The rule's languages' globs followed by its `includes`.
+    pub includes: PathGlobs,
+    pub excludes: PathGlobs,
+    /// Matches the files below the directory of the nested config that defined the rule,
+    /// or everything when empty.
+    pub directory: GlobSet,
+    pub unicode_normalize: Option<normalize::NormalForm>,
+    pub scan_window: Option<scan::ScanWindow>,
+    pub region: Option<region::Region>,
+    /// Matches these overlap on the matched lines aren't reported.
+    pub allow: Vec<Regex>,
+    /// Only report matches that aren't part of a longer word.
+    pub word_boundary: bool,
+    /// The rule's message, when it has capture placeholders to fill in for each match.
+    pub message: Option<String>,
+    pub fix: Option<String>,
+    pub max_age_days: Option<u64>,
+    pub escalate_after_days: Option<u64>,
+    pub max_match_length: usize,
+    pub binary: bool,
+    /// What the rule checks files for.
+    pub kind: Box<dyn rule_kind::RuleKind>,
+    /// Registered with [`Linter::register_rule_kind`] rather than built from a config, so
+    /// its kind doesn't search with `regex`.
+    pub registered: bool,
+    /// Whether every match stays within one line, so large files can be read in chunks.
+    pub line_local: bool,
+    /// Keep only the first match in each file.
+    pub report_once: bool,
+}
+
+pub struct Override {
+    pub paths: PathGlobs,
+    pub rules: HashMap<String, RuleOverride>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub rule_id: String,
+    pub severity: Severity,
+    /// The path relative to the current directory, as reported.
+    pub file: String,
+    pub path: PathBuf,
+    pub matches: Vec<Match>,
+    pub blame: HashMap<usize, BlameInfo>,
+    /// Matches dropped for this file by `--max-violations-per-file`; only set on
+    /// `linty::file-overflow` notes.
+    pub discarded: usize,
+    /// Found by `--pre-commit-untracked` in a file that isn't staged.
+    pub untracked: bool,
+    /// From a `report_once` rule, so the one match is the file's first of many, perhaps.
+    pub report_once: bool,
+}
+
+impl Violation {
+    /// The lines the matches start on, each once, leaving out path rules' matches, which
+    /// have none.
+    pub fn lines(&self) -> Vec<usize> {
+        let mut lines: Vec<usize> = self
+            .matches
+            .iter()
+            .filter(|m| m.has_position())
+            .map(|m| m.line)
+            .collect();
+        lines.sort_unstable();
+        lines.dedup();
+        lines
+    }
+
+    /// The first and last line of each match, which differ for a match spanning lines,
+    /// leaving out path rules' matches.
+    pub fn line_spans(&self) -> Vec<(usize, usize)> {
+        self.matches
+            .iter()
+            .filter(|m| m.has_position())
+            .map(|m| (m.line, m.last_line()))
+            .collect()
+    }
+}
+
+/// Formats a walked path for reports, without the walker's leading `./` and with `/`
+/// separators, so reports, baselines, and `--compare` runs read the same on Windows.
+pub fn display_path(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let path = path.strip_prefix("./").unwrap_or(&path);
+    if std::path::MAIN_SEPARATOR == '/' {
+        return path.to_owned();
+    }
+    path.replace(std::path::MAIN_SEPARATOR, "/")
+}
+
+/// The longest path Windows takes without the `\\?\` prefix.
+const MAX_PLAIN_PATH: usize = 260;
+
+/// Canonicalizes `path`, dropping the `\\?\` prefix Windows adds when the path means the
+/// same without it, so it can be compared with and stripped from the paths the current
+/// directory and git give, which don't have it.
+pub fn canonicalize(path: &Path) -> std::io::Result<PathBuf> {
+    let canonical = path.canonicalize()?;
+    let Some(std::path::Component::Prefix(prefix)) = canonical.components().next() else {
+        return Ok(canonical);
+    };
+    let Some(text) = canonical
+        .to_str()
+        .filter(|text| text.len() < MAX_PLAIN_PATH)
+    else {
+        return Ok(canonical);
+    };
+    Ok(match prefix.kind() {
+        std::path::Prefix::VerbatimDisk(_) => PathBuf::from(&text[r"\\?\".len()..]),
+        std::path::Prefix::VerbatimUNC(..) => {
+            PathBuf::from(format!(r"\\{}", &text[r"\\?\UNC\".len()..]))
+        }
+        _ => canonical,
+    })
+}
+
+/// Resolves `.` and `..` components without touching the file system, so a path like
+/// `src/../../lib` becomes `../lib`.
+pub fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(std::path::Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Names the first rule that doesn't deserialize on its own, and why, since the parsers'
+/// errors only give a position in the file, or no position at all for TOML arrays.
+fn find_rule_error(contents: &str, format: ConfigFormat) -> Option<String> {
+    let value: serde_json::Value = match format {
+        ConfigFormat::Json => serde_json::from_str(contents).ok()?,
+        ConfigFormat::Toml => toml::from_str(contents).ok()?,
+        ConfigFormat::Yaml => serde_yaml::from_str(contents).ok()?,
+    };
+    let rules = value.get("rules")?.as_array()?;
+    rules.iter().enumerate().find_map(|(index, rule)| {
+        let err = serde_json::from_value::<RuleConfig>(rule.clone()).err()?;
+        Some(match rule.get("id").and_then(serde_json::Value::as_str) {
+            Some(id) => format!("rules[{index}] (id {id}): {err}"),
+            None => format!("rules[{index}]: {err}"),
+        })
+    })
+}
+
+pub fn resolve_rule_inheritance(config: &mut Config) -> anyhow::Result<()> {
+    let index_by_id: HashMap<String, usize> = config
+        .rules
+        .iter()
+        .enumerate()
+        .map(|(index, rule)| (rule.id.to_owned(), index))
+        .collect();
+    let mut resolved = vec![false; config.rules.len()];
+
+    for index in 0..config.rules.len() {
+        resolve_rule(
+            &mut config.rules,
+            index,
+            &index_by_id,
+            &mut resolved,
+            &mut Vec::new(),
+        )?;
+    }
+    Ok(())
+}
+
+fn resolve_rule(
+    rules: &mut [RuleConfig],
+    index: usize,
+    index_by_id: &HashMap<String, usize>,
+    resolved: &mut [bool],
+    chain: &mut Vec<usize>,
+) -> anyhow::Result<()> {
+    if resolved[index] {
+        return Ok(());
+    }
+
+    if chain.contains(&index) {
+        let cycle = chain
+            .iter()
+            .chain(std::iter::once(&index))
+            .map(|&rule_index| rules[rule_index].id.as_str())
+            .collect::<Vec<&str>>()
+            .join(" -> ");
+        anyhow::bail!("Rule inheritance cycle: {cycle}");
+    }
+
+    if let Some(parent_id) = rules[index].extends.clone() {
+        let Some(&parent_index) = index_by_id.get(&parent_id) else {
+            anyhow::bail!("Rule {} extends unknown rule {parent_id}", rules[index].id);
+        };
+
+        chain.push(index);
+        resolve_rule(rules, parent_index, index_by_id, resolved, chain)?;
+        chain.pop();
+
+        let parent = rules[parent_index].clone();
+        inherit_fields(&mut rules[index], parent);
+    }
+
+    resolved[index] = true;
+    Ok(())
+}
+
+/// Fills in the matching and reporting fields `rule` leaves unset from `parent`'s; the
+/// id, `enabled`, hook, and deprecation settings stay the rule's own.
+pub fn inherit_fields(rule: &mut RuleConfig, parent: RuleConfig) {
+    rule.message = rule.message.take().or(parent.message);
+    rule.regex = rule.regex.take().or(parent.regex);
+    rule.match_type = rule.match_type.or(parent.match_type);
+    rule.mode = rule.mode.or(parent.mode);
+    rule.target = rule.target.or(parent.target);
+    rule.kind = rule.kind.or(parent.kind);
+    rule.limit = rule.limit.or(parent.limit);
+    rule.ignore_pattern = rule.ignore_pattern.take().or(parent.ignore_pattern);
+    rule.severity = rule.severity.or(parent.severity);
+    rule.includes = rule.includes.take().or(parent.includes);
+    rule.languages = rule.languages.take().or(parent.languages);
+    rule.excludes = rule.excludes.take().or(parent.excludes);
+    rule.tags = rule.tags.take().or(parent.tags);
+    rule.unicode_normalize = rule.unicode_normalize.or(parent.unicode_normalize);
+    rule.smart_case = rule.smart_case.or(parent.smart_case);
+    rule.case_insensitive = rule.case_insensitive.or(parent.case_insensitive);
+    rule.multi_line = rule.multi_line.or(parent.multi_line);
+    rule.dot_matches_new_line = rule.dot_matches_new_line.or(parent.dot_matches_new_line);
+    rule.crlf = rule.crlf.or(parent.crlf);
+    rule.word_boundary = rule.word_boundary.or(parent.word_boundary);
+    rule.size_limit = rule.size_limit.or(parent.size_limit);
+    rule.dfa_size_limit = rule.dfa_size_limit.or(parent.dfa_size_limit);
+    rule.scan_window = rule.scan_window.or(parent.scan_window);
+    rule.region = rule.region.take().or(parent.region);
+    rule.allow = rule.allow.take().or(parent.allow);
+    rule.fix = rule.fix.take().or(parent.fix);
+    rule.max_age_days = rule.max_age_days.or(parent.max_age_days);
+    rule.escalate_after_days = rule.escalate_after_days.or(parent.escalate_after_days);
+    rule.max_match_length = rule.max_match_length.or(parent.max_match_length);
+    rule.max_reported = rule.max_reported.or(parent.max_reported);
+    rule.report_once = rule.report_once.or(parent.report_once);
+    rule.binary = rule.binary.or(parent.binary);
+    rule.description = rule.description.take().or(parent.description);
+    rule.examples = rule.examples.take().or(parent.examples);
+    rule.url = rule.url.take().or(parent.url);
+}
+
+pub fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+pub fn generate_overrides_from_config(config: &Config) -> anyhow::Result<Vec<Override>> {
+    let mut overrides: Vec<Override> = Vec::new();
+
+    for (index, override_config) in config.overrides.iter().flatten().enumerate() {
+        for rule_id in override_config.rules.keys() {
+            if !config.rules.iter().any(|rule| &rule.id == rule_id) {
+                anyhow::bail!("Override overrides[{index}] references unknown rule {rule_id}");
+            }
+        }
+
+        let paths = PathGlobs::new(&override_config.paths).map_err(|errors| {
+            anyhow::anyhow!(
+                "Override overrides[{index}] has an invalid glob in paths: {}",
+                errors.join("; ")
+            )
+        })?;
+
+        overrides.push(Override {
+            paths,
+            rules: override_config.rules.clone(),
+        });
+    }
+    Ok(overrides)
+}
+
+/// Compiles the enabled rules, dropping those that don't run in the current mode:
+/// rules with `pre_commit: false` are skipped in hook runs, and rules with
+/// `pre_commit_only: true` only run in hook runs. Fails with every problem found, one per
+/// line, rather than just the first.
+pub fn generate_rules_from_config(config: &Config, hook_mode: bool) -> anyhow::Result<Vec<Rule>> {
+    let (rules, problems) = build_rules(config, hook_mode);
+    if !problems.is_empty() {
+        anyhow::bail!("{}", problems.join("\n"));
+    }
+    Ok(rules)
+}
+
+/// Compiles the rules that run in the current mode, like [`generate_rules_from_config`],
+/// along with a message naming the rule and field of each problem that kept a rule from
+/// compiling.
+pub fn build_rules(config: &Config, hook_mode: bool) -> (Vec<Rule>, Vec<String>) {
+    let mut rules: Vec<Rule> = Vec::new();
+    let mut problems: Vec<String> = Vec::new();
+    let language_table = languages::LanguageTable::new(config.languages.as_ref());
+
+    for rule_config in &config.rules {
+        let problem_count = problems.len();
+        if let Some(rule) = build_rule(rule_config, hook_mode, &language_table, &mut problems) {
+            rules.push(rule);
+        }
+        // Rules merged from several configs can't be told apart by id alone.
+        if let Some(source) = &rule_config.source {
+            for problem in &mut problems[problem_count..] {
+                *problem = format!("{source}: {problem}");
+            }
+        }
+    }
+    (rules, problems)
+}
+
+/// Compiles one rule, adding each problem that keeps it from compiling to `problems`, or
+/// returns `None` when it has problems or doesn't run in the current mode.
+fn build_rule(
+    rule_config: &RuleConfig,
+    hook_mode: bool,
+    language_table: &languages::LanguageTable,
+    problems: &mut Vec<String>,
+) -> Option<Rule> {
+    let id = &rule_config.id;
+    let name = rule_name(rule_config);
+    let pre_commit = rule_config.pre_commit.unwrap_or(true);
+    let pre_commit_only = rule_config.pre_commit_only.unwrap_or(false);
+    if !pre_commit && pre_commit_only {
+        problems.push(format!(
+            "Rule {name} sets both pre_commit: false and pre_commit_only: true, so it would never run"
+        ));
+        return None;
+    }
+
+    if !rule_config.enabled.unwrap_or(true)
+        || (hook_mode && !pre_commit)
+        || (!hook_mode && pre_commit_only)
+    {
+        return None;
+    }
+
+    let problem_count = problems.len();
+    // The languages' globs come first, so `!` includes can take back what they match.
+    let mut include_patterns = Vec::new();
+    if let Some(rule_languages) = &rule_config.languages {
+        match language_table.globs(&name, rule_languages) {
+            Ok(globs) => include_patterns = globs,
+            Err(err) => problems.push(err.to_string()),
+        }
+    }
+    include_patterns.extend(rule_config.includes.iter().flatten().cloned());
+    let includes = path_globs(problems, &name, "includes", &include_patterns);
+    let excludes = path_globs(
+        problems,
+        &name,
+        "excludes",
+        rule_config.excludes.as_deref().unwrap_or_default(),
+    );
+
+    let severity = rule_config.severity;
+    if severity.is_none() {
+        problems.push(format!("Rule {name} is missing a severity"));
+    }
+
+    let scan_window = match rule_config.scan_window {
+        None => None,
+        Some(ScanWindowConfig {
+            head_bytes: Some(bytes),
+            tail_bytes: None,
+        }) => Some(scan::ScanWindow::Head(bytes)),
+        Some(ScanWindowConfig {
+            head_bytes: None,
+            tail_bytes: Some(bytes),
+        }) => Some(scan::ScanWindow::Tail(bytes)),
+        Some(_) => {
+            problems.push(format!(
+                "Rule {name} must set exactly one of head_bytes and tail_bytes in scan_window"
+            ));
+            None
+        }
+    };
+
+    let region = match rule_config.region.as_ref().map(region::Region::new) {
+        None => None,
+        Some(Ok(region)) => Some(region),
+        Some(Err(err)) => {
+            problems.push(format!("Rule {name}: invalid regex in region: {err}"));
+            None
+        }
+    };
+
+    let multi_line = rule_config.multi_line.unwrap_or(false);
+    let dot_matches_new_line = rule_config.dot_matches_new_line.unwrap_or(false);
+    let crlf = rule_config.crlf.unwrap_or(false);
+    let word_boundary = rule_config.word_boundary.unwrap_or(false);
+    let required = rule_config.mode == Some(RuleMode::Required);
+    let matches_path = rule_config.target == Some(RuleTarget::Path);
+    if matches_path {
+        for (field, set) in [
+            ("mode: required", required),
+            ("fix", rule_config.fix.is_some()),
+            ("scan_window", rule_config.scan_window.is_some()),
+            ("region", rule_config.region.is_some()),
+            ("allow", rule_config.allow.is_some()),
+            ("word_boundary", word_boundary),
+            ("unicode_normalize", rule_config.unicode_normalize.is_some()),
+            ("max_age_days", rule_config.max_age_days.is_some()),
+            (
+                "escalate_after_days",
+                rule_config.escalate_after_days.is_some(),
+            ),
+        ] {
+            if set {
+                problems.push(format!(
+                    "Rule {name} sets {field}, which only applies to content rules, but its target is path"
+                ));
+            }
+        }
+    }
+    if required && rule_config.fix.is_some() {
+        problems.push(format!(
+            "Rule {name} is required, so it has no matches for its fix to replace"
+        ));
+    }
+    if rule_config.match_type == Some(MatchType::Literal) {
+        for (field, set) in [
+            ("multi_line", multi_line),
+            ("dot_matches_new_line", dot_matches_new_line),
+            ("crlf", crlf),
+        ] {
+            if set {
+                problems.push(format!(
+                    "Rule {name} sets {field}, which only applies to regex rules, but its match_type is literal"
+                ));
+            }
+        }
+        if rule_config
+            .fix
+            .as_deref()
+            .is_some_and(has_capture_reference)
+        {
+            problems.push(format!(
+                "Rule {name} has a fix that refers to a capture group, but its match_type is literal, so only $0 is available"
+            ));
+        }
+    }
+    let kind = rule_config.kind.unwrap_or_default();
+    let metric = match (kind, rule_config.limit) {
+        (RuleKind::Pattern, _) => {
+            for (field, set) in [
+                ("limit", rule_config.limit.is_some()),
+                ("ignore_pattern", rule_config.ignore_pattern.is_some()),
+            ] {
+                if set {
+                    problems.push(format!(
+                        "Rule {name} sets {field}, which only applies to max_line_length and max_file_lines rules, but its kind is pattern"
+                    ));
+                }
+            }
+            None
+        }
+        (_, None) => {
+            problems.push(format!(
+                "Rule {name} is missing a limit, which its kind {} needs",
+                kind.name()
+            ));
+            None
+        }
+        (_, Some(limit)) => {
+            for (field, set) in [
+                ("regex", rule_config.regex.is_some()),
+                (
+                    "match_type: literal",
+                    rule_config.match_type == Some(MatchType::Literal),
+                ),
+                ("mode: required", required),
+                ("target: path", matches_path),
+                ("fix", rule_config.fix.is_some()),
+                ("scan_window", rule_config.scan_window.is_some()),
+                ("region", rule_config.region.is_some()),
+                ("allow", rule_config.allow.is_some()),
+                ("unicode_normalize", rule_config.unicode_normalize.is_some()),
+                ("smart_case", rule_config.smart_case.unwrap_or(false)),
+                (
+                    "case_insensitive",
+                    rule_config.case_insensitive.unwrap_or(false),
+                ),
+                ("multi_line", multi_line),
+                ("dot_matches_new_line", dot_matches_new_line),
+                ("crlf", crlf),
+                ("word_boundary", word_boundary),
+            ] {
+                if set {
+                    problems.push(format!(
+                        "Rule {name} sets {field}, which only applies to pattern rules, but its kind is {}",
+                        kind.name()
+                    ));
+                }
+            }
+            let ignore = match rule_config.ignore_pattern.as_deref().map(Regex::new) {
+                None => None,
+                Some(Ok(ignore)) => Some(ignore),
+                Some(Err(err)) => {
+                    problems.push(format!(
+                        "Rule {name}: invalid regex in ignore_pattern {}",
+                        describe_regex_error(
+                            rule_config.ignore_pattern.as_deref().unwrap_or_default(),
+                            &err
+                        )
+                    ));
+                    None
+                }
+            };
+            Some(metric::Metric {
+                kind,
+                limit,
+                ignore,
+            })
+        }
+    };
+    let pattern = match &metric {
+        Some(metric) => Some(Cow::Owned(metric.pattern())),
+        None => rule_config.pattern(),
+    };
+    let regex_flags = prefilter::RegexFlags {
+        case_insensitive: rule_config.case_insensitive.unwrap_or(false)
+            || (rule_config.smart_case.unwrap_or(false)
+                && pattern
+                    .as_deref()
+                    .is_some_and(|pattern| !has_uppercase_literal(pattern))),
+        multi_line,
+        dot_matches_new_line,
+        crlf,
+    };
+    let compiled = match pattern {
+        None if kind != RuleKind::Pattern => None,
+        None => {
+            problems.push(format!("Rule {name} is missing a regex"));
+            None
+        }
+        Some(regex) => {
+            let regex = regex.as_ref();
+            let mut regex_builder = RegexBuilder::new(regex);
+            regex_builder
+                .case_insensitive(regex_flags.case_insensitive)
+                .multi_line(regex_flags.multi_line)
+                .dot_matches_new_line(regex_flags.dot_matches_new_line)
+                .crlf(regex_flags.crlf);
+            if let Some(size_limit) = rule_config.size_limit {
+                regex_builder.size_limit(size_limit);
+            }
+            if let Some(dfa_size_limit) = rule_config.dfa_size_limit {
+                regex_builder.dfa_size_limit(dfa_size_limit);
+            }
+            match regex_builder.build() {
+                Ok(compiled) => Some(compiled),
+                Err(err) => {
+                    problems.push(format!(
+                        "Rule {name}: invalid regex {}",
+                        describe_regex_error(regex, &err)
+                    ));
+                    None
+                }
+            }
+        }
+    };
+
+    let mut allow = Vec::new();
+    for pattern in rule_config.allow.iter().flatten() {
+        match Regex::new(pattern) {
+            Ok(compiled) => allow.push(compiled),
+            Err(err) => problems.push(format!(
+                "Rule {name}: invalid regex in allow {}",
+                describe_regex_error(pattern, &err)
+            )),
+        }
+    }
+
+    let mut directory_globs = GlobSetBuilder::new();
+    if let Some(directory) = &rule_config.directory {
+        add_globs(
+            &mut directory_globs,
+            problems,
+            &name,
+            "directory",
+            &[format!("{}/**", directory.trim_end_matches('/'))],
+        );
+    }
+
+    let directory = match directory_globs.build() {
+        Ok(directory) => directory,
+        Err(err) => {
+            problems.push(format!("Rule {name}: {err}"));
+            return None;
+        }
+    };
+    let (Some(regex), Some(severity), Some(includes), Some(excludes)) =
+        (compiled, severity, includes, excludes)
+    else {
+        return None;
+    };
+    if problems.len() > problem_count {
+        return None;
+    }
+    let report_once = rule_config.report_once.unwrap_or(false);
+    let message = rule_config
+        .message
+        .clone()
+        .filter(|message| metric.is_none() && message::has_placeholders(message));
+    let rule_kind: Box<dyn rule_kind::RuleKind> = match metric {
+        Some(metric) => Box::new(metric),
+        None if matches_path => Box::new(rule_kind::PathPattern(regex.clone())),
+        None if required => Box::new(rule_kind::Required),
+        None => Box::new(rule_kind::Pattern { report_once }),
+    };
+
+    Some(Rule {
+        id: id.to_owned(),
+        // Counting a file's lines, or telling a pattern is missing from it, takes all of
+        // it at once.
+        line_local: kind != RuleKind::MaxFileLines
+            && !required
+            && chunked::is_line_local(regex.as_str(), multi_line, dot_matches_new_line),
+        regex,
+        regex_flags,
+        severity,
+        includes,
+        excludes,
+        directory,
+        unicode_normalize: rule_config.unicode_normalize,
+        scan_window,
+        region,
+        allow,
+        word_boundary,
+        message,
+        fix: rule_config.fix.clone(),
+        max_age_days: rule_config.max_age_days,
+        escalate_after_days: rule_config.escalate_after_days,
+        max_match_length: rule_config
+            .max_match_length
+            .unwrap_or(scan::DEFAULT_MAX_MATCH_LENGTH),
+        binary: rule_config.binary.unwrap_or(false),
+        kind: rule_kind,
+        registered: false,
+        report_once,
+    })
+}
+
+/// How errors name a rule: quoted, with its position in the config that defined it when
+/// it came from one, like `'NoPrintln' (#3)`.
+fn rule_name(rule_config: &RuleConfig) -> String {
+    match rule_config.position {
+        Some(position) => format!("'{}' (#{position})", rule_config.id),
+        None => format!("'{}'", rule_config.id),
+    }
+}
+
+/// Adds `globs`, from the rule's `field`, to `builder`, noting the ones that don't parse.
+fn add_globs(
+    builder: &mut GlobSetBuilder,
+    problems: &mut Vec<String>,
+    name: &str,
+    field: &str,
+    globs: &[String],
+) {
+    for glob in globs {
+        match path_glob(glob) {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(err) => problems.push(format!("Rule {name}: invalid glob in {field}: {err}")),
+        }
+    }
+}
+
+/// Compiles the globs of the rule's `field`, noting the ones that don't parse.
+fn path_globs(
+    problems: &mut Vec<String>,
+    name: &str,
+    field: &str,
+    globs: &[String],
+) -> Option<PathGlobs> {
+    match PathGlobs::new(globs) {
+        Ok(globs) => Some(globs),
+        Err(errors) => {
+            for err in errors {
+                problems.push(format!("Rule {name}: invalid glob in {field}: {err}"));
+            }
+            None
+        }
+    }
+}
+
+/// Compiles a config glob, which matches paths relative to the config's directory, like
+/// `src/main.rs`. A glob without a `/` matches in any directory, so `*.rs` is `**/*.rs`;
+/// one with a `/` is anchored there, with a leading `./` or `/` allowed. `*` and `?`
+/// don't match `/`; `**` does.
+pub fn path_glob(glob: &str) -> Result<Glob, globset::Error> {
+    let anchored = glob
+        .strip_prefix("./")
+        .or_else(|| glob.strip_prefix('/'))
+        .unwrap_or(glob);
+    let pattern = if glob.contains('/') {
+        Cow::Borrowed(anchored)
+    } else {
+        Cow::Owned(format!("**/{glob}"))
+    };
+    GlobBuilder::new(&pattern).literal_separator(true).build()
+}
+
+/// Where in `pattern` it fails to parse and why, like `at column 4: unclosed group`, or the
+/// compiler's own message for problems other than syntax, like exceeding the size limit.
+fn describe_regex_error(pattern: &str, err: &regex::Error) -> String {
+    let (span, kind) = match regex_syntax::Parser::new().parse(pattern) {
+        Err(regex_syntax::Error::Parse(err)) => (*err.span(), err.kind().to_string()),
+        Err(regex_syntax::Error::Translate(err)) => (*err.span(), err.kind().to_string()),
+        _ => return format!("{pattern:?}: {err}"),
+    };
+    format!("{pattern:?} at column {}: {kind}", span.start.column)
+}
+
+/// Whether `pattern` has an uppercase letter outside of an escape such as `\S`, which is
+/// what `smart_case` checks, like ripgrep's `--smart-case`.
+fn has_uppercase_literal(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+    while let Some(character) = chars.next() {
+        if character == '\\' {
+            chars.next();
+        } else if character.is_uppercase() {
+            return true;
+        }
+    }
+    false
+}
+
+/// Whether a `fix` template refers to a capture group other than the whole match, `$0`.
+/// `$$` is an escaped dollar sign.
+fn has_capture_reference(template: &str) -> bool {
+    let mut rest = template;
+    while let Some(index) = rest.find('$') {
+        rest = &rest[index + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            rest = after;
+            continue;
+        }
+        let name = match rest.strip_prefix('{') {
+            Some(braced) => braced.split('}').next().unwrap_or_default(),
+            None => {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                &rest[..end]
+            }
+        };
+        if !name.is_empty() && name != "0" {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linter(config: &str) -> Linter {
+        let config = parse_config(config, ConfigFormat::Json, "test config").unwrap();
+        Linter::from_config(config).unwrap()
+    }
+
+    fn rule_ids(violations: &[Violation]) -> Vec<&str> {
+        violations.iter().map(|v| v.rule_id.as_str()).collect()
+    }
+
+    #[test]
+    fn includes_and_excludes_filter_files() {
+        let linter = linter(
+            r#"{"rules":[{"id":"NoTodo","message":"No todos","regex":"TODO","severity":"error",
+                "includes":["src/**"],"excludes":["src/generated/**"]}]}"#,
+        );
+        let checked = |path: &str| linter.check_str("TODO\n", Path::new(path)).unwrap();
+        assert_eq!(rule_ids(&checked("src/main.rs")), ["NoTodo"]);
+        assert_eq!(rule_ids(&checked("./src/lib/mod.rs")), ["NoTodo"]);
+        assert!(checked("docs/notes.md").is_empty());
+        assert!(checked("src/generated/api.rs").is_empty());
+    }
+
+    #[test]
+    fn global_excludes_apply_to_every_rule() {
+        let linter = linter(
+            r#"{"excludes":["vendor/**"],"rules":[
+                {"id":"A","message":"a","regex":"TODO","severity":"error"},
+                {"id":"B","message":"b","regex":"TODO","severity":"warning"}]}"#,
+        );
+        assert!(linter
+            .check_str("TODO\n", Path::new("vendor/lib.rs"))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            rule_ids(&linter.check_str("TODO\n", Path::new("lib.rs")).unwrap()),
+            ["A", "B"]
+        );
+    }
+
+    #[test]
+    fn matches_report_one_based_lines_and_columns() {
+        let linter = linter(
+            r#"{"rules":[{"id":"NoTodo","message":"No todos","regex":"TODO","severity":"error"}]}"#,
+        );
+        let violations = linter
+            .check_str("fine\n  TODO\nfine\r\nx TODO TODO\n", Path::new("a.txt"))
+            .unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].lines(), [2, 4]);
+        let columns: Vec<usize> = violations[0].matches.iter().map(|m| m.column).collect();
+        assert_eq!(columns, [3, 3, 8]);
+        assert_eq!(violations[0].line_spans(), [(2, 2), (4, 4), (4, 4)]);
+    }
+
+    #[test]
+    fn case_insensitive_rules_match_any_case() {
+        let rule = |flag: &str| {
+            linter(&format!(
+                r#"{{"rules":[{{"id":"NoTodo","message":"No todos","regex":"TODO","severity":"error"{flag}}}]}}"#
+            ))
+        };
+        let lines = |linter: &Linter| {
+            let violations = linter
+                .check_str(
+                    "ToDo
+todo
+TODO
+",
+                    Path::new("a.txt"),
+                )
+                .unwrap();
+            violations.first().map(Violation::lines).unwrap_or_default()
+        };
+        assert_eq!(lines(&rule(r#","case_insensitive":true"#)), [1, 2, 3]);
+        assert_eq!(lines(&rule(r#","case_insensitive":false"#)), [3]);
+        assert_eq!(lines(&rule("")), [3]);
+    }
+
+    #[test]
+    fn regex_flags_round_trip_and_stay_out_of_output_when_unset() {
+        let config = parse_config(
+            r#"{"rules":[
+                {"id":"Set","message":"m","regex":"x","severity":"error","case_insensitive":true,"multi_line":false,"dot_matches_new_line":true},
+                {"id":"Unset","message":"m","regex":"x","severity":"error"}]}"#,
+            ConfigFormat::Json,
+            "test config",
+        )
+        .unwrap();
+        let json = serde_json::to_string(&config).unwrap();
+        let toml = toml::to_string(&config).unwrap();
+        for (written, format) in [(json, ConfigFormat::Json), (toml, ConfigFormat::Toml)] {
+            let reread = parse_config(&written, format, "test config").unwrap();
+            let set = &reread.rules[0];
+            assert_eq!(set.case_insensitive, Some(true), "{written}");
+            assert_eq!(set.multi_line, Some(false), "{written}");
+            assert_eq!(set.dot_matches_new_line, Some(true), "{written}");
+            let unset = &reread.rules[1];
+            assert_eq!(unset.case_insensitive, None, "{written}");
+            assert_eq!(unset.multi_line, None, "{written}");
+            assert_eq!(unset.dot_matches_new_line, None, "{written}");
+            assert_eq!(written.matches("case_insensitive").count(), 1, "{written}");
+        }
+    }
+
+    /// A rule setting every field a config can, whether or not they make sense together.
+    const EVERY_FIELD: &str = r#"{"rules":[{
+        "id":"Every","extends":"Base","message":"m","regex":"x","match_type":"literal",
+        "mode":"required","target":"path","kind":"max_line_length","limit":80,
+        "ignore_pattern":"https?://","severity":"warning","includes":["src/**"],
+        "excludes":["src/gen/**"],"languages":["rust"],"enabled":false,"tags":["style"],
+        "pre_commit":false,"pre_commit_only":true,"override":true,"unicode_normalize":"nfc",
+        "smart_case":true,"case_insensitive":true,"multi_line":true,
+        "dot_matches_new_line":true,"crlf":true,"word_boundary":true,"size_limit":1000000,
+        "dfa_size_limit":2000000,"scan_window":{"head_bytes":100,"tail_bytes":200},
+        "fix":"y","max_age_days":30,"escalate_after_days":7,"max_match_length":500,
+        "max_reported":5,"report_once":true,"binary":true,
+        "region":{"start":"BEGIN","end":"END","mode":"outside"},"allow":["ok"],
+        "description":"d","examples":{"matches":["x"],"no_matches":["y"]},
+        "url":"https://example.com","deprecated":true,"deprecation_message":"gone",
+        "directory":"sub"
+    }]}"#;
+
+    #[test]
+    fn every_rule_field_round_trips_in_every_format() {
+        let config = parse_config(EVERY_FIELD, ConfigFormat::Json, "test config").unwrap();
+        let expected = serde_json::to_value(&config).unwrap();
+        let written: serde_json::Value = serde_json::from_str(EVERY_FIELD).unwrap();
+        // Each field is read, and only `source` is added.
+        let rule = expected["rules"][0].as_object().unwrap();
+        assert_eq!(
+            rule.len(),
+            written["rules"][0].as_object().unwrap().len() + 1
+        );
+        assert_eq!(rule["source"], "test config");
+
+        for (format, contents) in [
+            (ConfigFormat::Json, serde_json::to_string(&config).unwrap()),
+            (ConfigFormat::Toml, toml::to_string(&config).unwrap()),
+            (ConfigFormat::Yaml, serde_yaml::to_string(&config).unwrap()),
+        ] {
+            let reread = parse_config(&contents, format, "test config").unwrap();
+            assert_eq!(
+                serde_json::to_value(&reread).unwrap(),
+                expected,
+                "{contents}"
+            );
+        }
+    }
+
+    #[test]
+    fn multi_line_matches_span_their_lines() {
+        let linter = linter(
+            r#"{"rules":[{"id":"Block","message":"No blocks","regex":"begin[\\s\\S]*?end","severity":"error"}]}"#,
+        );
+        let violations = linter
+            .check_str("x\nbegin\n\nend\nbegin end\n", Path::new("a.txt"))
+            .unwrap();
+        assert_eq!(violations[0].lines(), [2, 5]);
+        assert_eq!(violations[0].line_spans(), [(2, 4), (5, 5)]);
+    }
+
+    #[test]
+    fn violations_carry_their_rules_severity_and_overrides() {
+        let linter = linter(
+            r#"{"rules":[
+                {"id":"Hard","message":"hard","regex":"TODO","severity":"error"},
+                {"id":"Soft","message":"soft","regex":"TODO","severity":"warning"}],
+              "overrides":[{"paths":["tests/**"],"rules":{"Hard":{"severity":"warning"},"Soft":"off"}}]}"#,
+        );
+        let severities = |path: &str| -> Vec<(String, Severity)> {
+            linter
+                .check_str("TODO\n", Path::new(path))
+                .unwrap()
+                .into_iter()
+                .map(|v| (v.rule_id, v.severity))
+                .collect()
+        };
+        assert_eq!(
+            severities("src/lib.rs"),
+            [
+                (String::from("Hard"), Severity::Error),
+                (String::from("Soft"), Severity::Warning)
+            ]
+        );
+        assert_eq!(
+            severities("tests/it.rs"),
+            [(String::from("Hard"), Severity::Warning)]
+        );
+    }
+
+    #[test]
+    fn rules_that_dont_compile_are_all_reported() {
+        let config = parse_config(
+            r#"{"rules":[
+                {"id":"A","message":"a","regex":"(","severity":"error"},
+                {"id":"B","message":"b","regex":"[","severity":"error"}]}"#,
+            ConfigFormat::Json,
+            "test config",
+        )
+        .unwrap();
+        match Linter::from_config(config) {
+            Err(Error::InvalidRules(problems)) => assert_eq!(problems.len(), 2, "{problems:?}"),
+            Err(err) => panic!("unexpected error: {err}"),
+            Ok(_) => panic!("invalid rules compiled"),
+        }
+    }
+
+    #[test]
+    fn display_path_drops_the_walk_prefix() {
+        assert_eq!(display_path(Path::new("./src/main.rs")), "src/main.rs");
+        assert_eq!(display_path(Path::new("../lib/x.rs")), "../lib/x.rs");
+        assert_eq!(display_path(Path::new("main.rs")), "main.rs");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn display_path_uses_forward_slashes() {
+        assert_eq!(display_path(Path::new(r".\src\main.rs")), "src/main.rs");
+        assert_eq!(display_path(Path::new(r"..\lib\x.rs")), "../lib/x.rs");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn canonicalize_drops_the_verbatim_prefix() {
+        let current_dir = std::env::current_dir().unwrap();
+        let canonical = canonicalize(&current_dir).unwrap();
+        assert!(
+            !canonical.to_string_lossy().starts_with(r"\\?\"),
+            "{canonical:?}"
+        );
+        assert_eq!(canonicalize(&canonical).unwrap(), canonical);
+    }
+
+    #[test]
+    fn canonicalize_gives_a_path_the_current_directory_strips() {
+        let current_dir = canonicalize(&std::env::current_dir().unwrap()).unwrap();
+        let manifest = canonicalize(Path::new("Cargo.toml")).unwrap();
+        assert_eq!(
+            manifest.strip_prefix(&current_dir).unwrap(),
+            Path::new("Cargo.toml")
+        );
+    }
+
+    #[test]
+    fn normalize_lexically_resolves_dots() {
+        assert_eq!(
+            normalize_lexically(Path::new("src/../../lib")),
+            Path::new("../lib")
+        );
+        assert_eq!(normalize_lexically(Path::new("./a/./b/..")), Path::new("a"));
+        assert_eq!(
+            normalize_lexically(Path::new("../../a")),
+            Path::new("../../a")
+        );
+    }
+
+    #[test]
+    fn glob_paths_are_relative_to_the_configs_directory() {
+        let options = |glob_base: &str| scan::WalkOptions {
+            glob_base: PathBuf::from(glob_base),
+            ..linter(r#"{"rules":[]}"#).options
+        };
+        let path = Path::new("./main.rs");
+        assert_eq!(options("").glob_path(path), Path::new("main.rs"));
+        assert_eq!(options("src").glob_path(path), Path::new("src/main.rs"));
+        assert_eq!(
+            options("src").glob_path(Path::new("../lib/x.rs")),
+            Path::new("lib/x.rs")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn globs_match_paths_with_backslashes() {
+        let linter = linter(
+            r#"{"rules":[{"id":"Src","message":"m","regex":"TODO","severity":"error","includes":["src/**/*.rs"]}]}"#,
+        );
+        let violations = linter
+            .check_str("TODO\n", Path::new(r"src\deep\main.rs"))
+            .unwrap();
+        assert_eq!(rule_ids(&violations), ["Src"]);
+        assert_eq!(violations[0].file, "src/deep/main.rs");
+    }
+
+    const CRLF_RULE: &str = r#"{"rules":[{"id":"Semi","message":"m","regex":";$","severity":"error","multi_line":true,"crlf":true}]}"#;
+
+    #[test]
+    fn crlf_rule_finds_the_lines_lf_content_gives() {
+        let linter = linter(CRLF_RULE);
+        let lf = linter
+            .check_str("a;\nb\nc;\n", Path::new("lf.txt"))
+            .unwrap();
+        let crlf = linter
+            .check_str("a;\r\nb\r\nc;\r\n", Path::new("crlf.txt"))
+            .unwrap();
+        assert_eq!(lf[0].lines(), [1, 3]);
+        assert_eq!(crlf[0].lines(), [1, 3]);
+        let columns = |violations: &[Violation]| -> Vec<(usize, usize)> {
+            violations[0]
+                .matches
+                .iter()
+                .map(|m| (m.column, m.end_column))
+                .collect()
+        };
+        assert_eq!(columns(&crlf), columns(&lf));
+    }
+
+    #[test]
+    fn rule_without_crlf_misses_crlf_line_ends() {
+        let linter = linter(&CRLF_RULE.replace(r#","crlf":true"#, ""));
+        assert!(linter
+            .check_str("a;\r\nb\r\n", Path::new("crlf.txt"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn literal_rules_match_without_escaping_where_regex_rules_do() {
+        let linter = linter(
+            r#"{"rules":[
+                {"id":"Literal","message":"m","regex":"console.log(","match_type":"literal","severity":"error"},
+                {"id":"Regex","message":"m","regex":"console\\.log\\(","severity":"error"}]}"#,
+        );
+        let violations = linter
+            .check_str(
+                "consoleXlog(\n  console.log(1); console.log(2)\nconsole.log\n",
+                Path::new("a.js"),
+            )
+            .unwrap();
+        assert_eq!(rule_ids(&violations), ["Literal", "Regex"]);
+        let positions = |violation: &Violation| -> Vec<(usize, usize, usize, usize)> {
+            violation
+                .matches
+                .iter()
+                .map(|m| (m.line, m.column, m.end_line, m.end_column))
+                .collect()
+        };
+        // The dot isn't a wildcard, and the parenthesis needs no escaping.
+        assert_eq!(positions(&violations[0]), [(2, 3, 2, 15), (2, 19, 2, 31)]);
+        assert_eq!(positions(&violations[0]), positions(&violations[1]));
+        assert_eq!(violations[0].matches[0].text, "console.log(");
+    }
+
+    #[test]
+    fn crlf_is_invalid_for_literal_rules() {
+        let config = parse_config(
+            r#"{"rules":[{"id":"Lit","message":"m","regex":";","match_type":"literal","severity":"error","crlf":true}]}"#,
+            ConfigFormat::Json,
+            "test config",
+        )
+        .unwrap();
+        match Linter::from_config(config) {
+            Err(Error::InvalidRules(problems)) => {
+                assert!(problems[0].contains("sets crlf"), "{problems:?}")
+            }
+            _ => panic!("a literal rule with crlf compiled"),
+        }
+    }
+}
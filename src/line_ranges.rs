@@ -0,0 +1,116 @@
+/// Renders matches' line spans, their first and last lines, for the text report: runs of
+/// matched lines as ranges like `10-24, 31, 40-45`, and each match spanning lines in
+/// brackets like `[50-54]`, so it doesn't read as matches on each of its lines. A line
+/// matched more than once is listed once. At most `max_listed` matches are listed before
+/// the rest are summarized as "… and N more", and the list wraps so no output line
+/// exceeds `width` columns. `indent` is the width of the text already printed before the
+/// list on the first line, and continuation lines are indented by it.
+pub fn format_line_list(
+    spans: &[(usize, usize)],
+    max_listed: usize,
+    width: usize,
+    indent: usize,
+) -> String {
+    let mut sorted = spans.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    let total = sorted.len();
+    let listed = match max_listed {
+        0 => total,
+        limit => limit.min(total),
+    };
+
+    // Runs of single lines, and spans, as (first line, last line, whether it's a span).
+    let mut ranges: Vec<(usize, usize, bool)> = Vec::new();
+    for &(start, end) in &sorted[..listed] {
+        let span = start != end;
+        match ranges.last_mut() {
+            Some((_, run_end, false)) if !span && start == *run_end + 1 => *run_end = start,
+            _ => ranges.push((start, end, span)),
+        }
+    }
+    let mut items: Vec<String> = ranges
+        .into_iter()
+        .map(|(start, end, span)| {
+            if span {
+                format!("[{}]", format_span(start, end))
+            } else {
+                format_span(start, end)
+            }
+        })
+        .collect();
+    if listed < total {
+        items.push(format!("… and {} more", total - listed));
+    }
+
+    let continuation_indent = indent.min(width / 2);
+    let mut output = String::new();
+    let mut column = indent;
+    for (index, item) in items.iter().enumerate() {
+        let separator = if index + 1 < items.len() { "," } else { "" };
+        let item_width = item.chars().count() + separator.len();
+        if index > 0 {
+            if column + 1 + item_width > width {
+                output.push('\n');
+                output.push_str(&" ".repeat(continuation_indent));
+                column = continuation_indent;
+            } else {
+                output.push(' ');
+                column += 1;
+            }
+        }
+        output.push_str(item);
+        output.push_str(separator);
+        column += item_width;
+    }
+    output
+}
+
+/// A line span like `10-14`, or `10` when it's one line.
+pub fn format_span(start: usize, end: usize) -> String {
+    if start == end {
+        start.to_string()
+    } else {
+        format!("{start}-{end}")
+    }
+}
+
+/// The width to wrap text output to: the terminal's width, or 80 columns when stdout
+/// isn't a terminal.
+pub fn output_width() -> usize {
+    terminal_size::terminal_size()
+        .map(|(terminal_size::Width(width), _)| usize::from(width))
+        .unwrap_or(80)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_of_lines_and_spans_read_differently() {
+        assert_eq!(format_line_list(&[(1, 1), (2, 2)], 0, 80, 0), "1-2");
+        assert_eq!(format_line_list(&[(1, 2)], 0, 80, 0), "[1-2]");
+        assert_eq!(
+            format_line_list(&[(1, 1), (2, 2), (3, 5), (6, 6)], 0, 80, 0),
+            "1-2, [3-5], 6"
+        );
+    }
+
+    #[test]
+    fn lines_matched_twice_are_listed_once() {
+        assert_eq!(
+            format_line_list(&[(1, 1), (3, 3), (3, 3)], 0, 80, 0),
+            "1, 3"
+        );
+        assert_eq!(format_line_list(&[(3, 3), (3, 3)], 1, 80, 0), "3");
+    }
+
+    #[test]
+    fn limit_counts_listed_matches() {
+        assert_eq!(
+            format_line_list(&[(1, 1), (2, 2), (4, 6), (9, 9)], 3, 80, 0),
+            "1-2, [4-6], … and 1 more"
+        );
+    }
+}
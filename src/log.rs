@@ -0,0 +1,34 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How many `-v` flags a diagnostic needs before it's printed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// `-v`: the config in use, and the files skipped and why.
+    Info = 1,
+    /// `-vv`: each rule's compiled regex, and how long each file took to match.
+    Debug = 2,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(0);
+
+/// Sets the verbosity from the number of `-v` flags.
+pub fn set_level(verbose: u8) {
+    LEVEL.store(verbose, Ordering::Relaxed);
+}
+
+pub fn enabled(level: Level) -> bool {
+    LEVEL.load(Ordering::Relaxed) >= level as u8
+}
+
+/// Prints a diagnostic to stderr when the verbosity is at least `level`. Diagnostics never
+/// go to stdout, so they can't end up in a JSON or SARIF report.
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {
+        if $crate::log::enabled($level) {
+            eprintln!($($arg)+);
+        }
+    };
+}
+
+pub use log;
@@ -1,416 +1,4289 @@
-use clap::{CommandFactory, Parser};
+mod adhoc;
+mod badge;
+mod baseline;
+mod changed_lines;
+mod checkstyle;
+mod collapse;
+mod config_hygiene;
+mod config_trust;
+mod desktop_notify;
+mod display;
+mod editorconfig;
+mod explain;
+mod gerrit;
+mod github_annotations;
+mod global_config;
+mod hooks;
+mod json_report;
+mod junit;
+mod line_ranges;
+mod metrics;
+mod nested;
+mod outcome;
+mod post_results;
+mod pr_comment;
+mod pretty;
+mod project_type;
+mod rule_examples;
+mod sarif;
+mod spinner;
+mod watch;
+mod xml;
+
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
 use clap_complete::{generate, Shell};
+use compare::Comparison;
+use config_trust::ConfigTrust;
 use core::result::Result::Ok;
-use globset::{Glob, GlobSet, GlobSetBuilder};
-use ignore::WalkBuilder;
-use regex::{Regex, RegexBuilder};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use display::DisplayTable;
+use globset::GlobSet;
+use linty::blame::{self, BlameCache};
+use linty::cache::{self, ResultCache};
+use linty::git::{GitError, Repo};
+use linty::path_globs::PathGlobs;
+use linty::prefilter::RegexFlags;
+use linty::rule_kind;
+use linty::{
+    build_rules, cancel, chunked, compare, display_path, fix, generate_overrides_from_config,
+    generate_rules_from_config, ignore_explain, inherit_fields, languages, log,
+    normalize_lexically, region, resolve_rule_inheritance, scan, severity_name, suppress, Config,
+    ConfigFormat, InvalidUtf8, MatchType, OverrideConfig, Profile, Rule, RuleConfig, RuleKind,
+    RuleMode, RuleOverride, RuleTarget, RuleToggle, Severity, SeverityDisplay, Violation,
+};
+use outcome::{Outcome, Reason};
+use regex::RegexBuilder;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::ffi::OsString;
 use std::fs::File;
-use std::io::{BufReader, Read, Write};
-use std::path::Path;
-use std::process::{exit, Command};
+use std::io::{IsTerminal, Read, Write};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
 const DEFAULT_CONFIG_PATH_STR: &str = ".lintyconfig.json";
+const DEFAULT_CONFIG_PATH_STRS: [&str; 4] = [
+    ".lintyconfig.json",
+    ".lintyconfig.toml",
+    ".lintyconfig.yaml",
+    ".lintyconfig.yml",
+];
+const STDIN_CONFIG_PATH: &str = "-";
+const BUILTIN_DEFAULTS: &str = "builtin:defaults";
+const BUILTIN_DEFAULTS_CONFIG: &str = include_str!("defaults.lintyconfig.json");
+
+/// Whether configs may have fields linty doesn't know, from --lenient-config; set once
+/// before any config is read.
+static LENIENT_CONFIG: AtomicBool = AtomicBool::new(false);
 
 #[derive(Parser, Debug)]
 enum Subcommand {
-    /// Initialize an empty .lintyconfig
-    Init,
+    /// Write an example .lintyconfig.json or .lintyconfig.toml to start from
+    Init {
+        #[arg(long, value_enum, default_value_t = InitFormat::Json)]
+        format: InitFormat,
+        /// Overwrite an existing config file
+        #[arg(long)]
+        force: bool,
+    },
     /// Generate shell completions
     Completions { shell: Shell },
+    /// Write a git hook that runs linty
+    InstallHooks {
+        #[arg(long, value_enum, default_value_t = hooks::Hook::PreCommit)]
+        hook: hooks::Hook,
+        /// Replace a hook linty didn't write, keeping it with a .linty-backup suffix
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove the git hooks install-hooks wrote
+    UninstallHooks {
+        /// Only remove this hook (default: all of them)
+        #[arg(long, value_enum)]
+        hook: Option<hooks::Hook>,
+    },
+    /// List the rules that would run, after applying any profile; -v adds their messages
+    #[command(visible_alias = "rules")]
+    ListRules {
+        #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+        format: ListFormat,
+    },
+    /// Print every rule with inherited fields resolved
+    DumpRules,
+    /// Print the language names rules can use in languages, and their extensions
+    ListLanguages,
+    /// Summarize the loaded config and which rules are active in each mode
+    Doctor,
+    /// Check the config and list rules that replace rules with the same id from other sources
+    Validate,
+    /// Check each rule's examples: the text it must match and the text it mustn't
+    Test,
+    /// Show which rules check a file and why, as a full run would see it
+    Explain {
+        path: String,
+        /// Also run the rules that apply and count their matches
+        #[arg(long)]
+        run: bool,
+    },
+    /// Print the effective config after extends, inheritance, profiles, and --severity, with defaults filled in
+    PrintConfig {
+        #[arg(long, value_enum, default_value_t = ConfigFormat::Json)]
+        format: ConfigFormat,
+    },
+    /// Search files for a regex with linty's file walker; needs no config
+    Grep(GrepArgs),
+    /// Scan and record every current violation in a baseline file for --baseline
+    Baseline {
+        /// Where to write the baseline
+        #[arg(long, default_value = baseline::DEFAULT_BASELINE_PATH)]
+        output: String,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct GrepArgs {
+    /// Regex to search for
+    pattern: String,
+
+    /// Files or directories to search (default: the current directory)
+    paths: Vec<PathBuf>,
+
+    /// Match case-insensitively
+    #[arg(short, long)]
+    ignore_case: bool,
+
+    /// Treat the pattern as a literal string instead of a regex
+    #[arg(short = 'F', long)]
+    fixed_strings: bool,
+
+    /// Exit with status 1 if anything matched
+    #[arg(long)]
+    fail_on_match: bool,
+}
+
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+enum Format {
+    /// Human-readable report listing the matched lines of each file
+    #[value(alias = "text")]
+    Short,
+    /// Human-readable report showing each match in context
+    Pretty,
+    /// A single JSON document on stdout
+    Json,
+    /// Markdown chunks sized for PR comments, written under --output
+    PrComment,
+    /// A SARIF 2.1.0 log on stdout, for code scanning uploads
+    Sarif,
+    /// JUnit XML on stdout, with a test suite per rule, for CI test tabs
+    Junit,
+    /// Checkstyle XML on stdout, with an error per match, for existing lint tooling
+    Checkstyle,
+    /// GitHub Actions workflow commands that annotate each matched line
+    Github,
+    /// A Gerrit set-review body with a robot comment per match
+    Gerrit,
+}
+
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+enum GroupBy {
+    /// A section per rule, listing the files it matched
+    Rule,
+    /// A section per file, listing its matches in line order
+    File,
+}
+
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+enum ListFormat {
+    /// One line per rule
+    Text,
+    /// A JSON array of rules, for editor tooling
+    Json,
 }
 
+/// The config formats linty looks for in the current directory, for `linty init`.
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+enum InitFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl InitFormat {
+    fn path(self) -> &'static str {
+        match self {
+            InitFormat::Json => DEFAULT_CONFIG_PATH_STRS[0],
+            InitFormat::Toml => DEFAULT_CONFIG_PATH_STRS[1],
+            InitFormat::Yaml => DEFAULT_CONFIG_PATH_STRS[2],
+        }
+    }
+}
+
+/// How the config's `includes`, `excludes`, and override `paths` match, for `--help`.
+const GLOBS_HELP: &str = "\
+Globs:
+  Globs match paths relative to the config's directory, like src/main.rs, whichever
+  directory linty runs from and however files are given. A glob without a / matches in
+  any directory (*.rs is **/*.rs); one with a / is anchored at the config's directory,
+  with or without a leading ./ (src/*.rs matches src/main.rs but not lib/src/main.rs).
+  * and ? don't cross a /, while ** matches any number of directories.
+  In a list of globs, the last one that matches a path decides, and one starting with !
+  takes the path back out, as in .gitignore: the excludes gen/** and then !gen/keep.rs
+  exclude everything in gen but gen/keep.rs.";
+
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = None,
+    after_help = format!("{GLOBS_HELP}\n\n{}", outcome::EXIT_CODES_HELP)
+)]
 struct Args {
     /// Treat warnings as errors
     #[arg(long)]
     error_on_warning: bool,
 
-    /// Optional path to .lintyconfig.json file
-    #[arg(short, long)]
-    config_path: Option<String>,
+    /// Fail when more than this many warning matches are left, letting a few through
+    #[arg(long, value_name = "N")]
+    max_warnings: Option<usize>,
+
+    /// Optional path to .lintyconfig.json file, or - to read the config from stdin; repeat
+    /// to add the rules of more configs
+    #[arg(short, long, global = true)]
+    config_path: Vec<String>,
+
+    /// Format of the config (default: from the file extension, or json for stdin)
+    #[arg(long, global = true, value_enum)]
+    config_format: Option<ConfigFormat>,
+
+    /// Warn about config fields linty doesn't know, like ones from a newer version, rather
+    /// than failing
+    #[arg(long, global = true)]
+    lenient_config: bool,
+
+    /// Let the config reference files outside the repository, and use a config found in
+    /// the current directory even when it's a symlink pointing outside the repository
+    #[arg(long, global = true)]
+    trusted_config: bool,
+
+    /// Let the config reference remote URLs
+    #[arg(long, global = true)]
+    allow_remote_config: bool,
+
+    /// Name of a profile from the config to apply on top of the base config
+    #[arg(long, global = true, env = "LINTY_PROFILE")]
+    profile: Option<String>,
+
+    /// Only run rules carrying at least one of these tags (comma-separated, repeatable)
+    #[arg(long, global = true, value_delimiter = ',')]
+    tags: Vec<String>,
+
+    /// Don't run rules carrying any of these tags (comma-separated, repeatable)
+    #[arg(long, global = true, value_delimiter = ',')]
+    exclude_tags: Vec<String>,
+
+    /// Only run the rules with these ids (comma-separated, repeatable)
+    #[arg(long, global = true, value_delimiter = ',', value_name = "RULE_ID")]
+    only: Vec<String>,
+
+    /// Ad-hoc rule like 'id=tmp;severity=error;regex=unwrap\(\);includes=src/**/*.rs' (repeatable)
+    #[arg(long, global = true)]
+    rule: Vec<String>,
+
+    /// Regex for an ad-hoc rule, as a friendlier alternative to --rule
+    #[arg(long, global = true)]
+    rule_regex: Option<String>,
+
+    /// Id for the --rule-regex rule
+    #[arg(long, global = true, requires = "rule_regex")]
+    rule_id: Option<String>,
+
+    /// Severity for the --rule-regex rule (default: error)
+    #[arg(long, global = true, value_enum, requires = "rule_regex")]
+    rule_severity: Option<Severity>,
+
+    /// Message for the --rule-regex rule
+    #[arg(long, global = true, requires = "rule_regex")]
+    rule_message: Option<String>,
+
+    /// Include glob for the --rule-regex rule (repeatable)
+    #[arg(long, global = true, requires = "rule_regex")]
+    rule_includes: Vec<String>,
+
+    /// Override a rule's severity for this run, as RULE_ID=warning or RULE_ID=error, or
+    /// all=error for every rule (repeatable)
+    #[arg(
+        long,
+        global = true,
+        value_name = "RULE_ID=SEVERITY",
+        value_parser = parse_severity_override
+    )]
+    severity: Vec<(String, Severity)>,
+
+    /// Treat enabled deprecated rules, and references to them, as config errors
+    #[arg(long, global = true)]
+    forbid_deprecated: bool,
+
+    /// Stop scanning after this long (e.g. 5s, 500ms) and report partial results
+    #[arg(long, global = true, value_parser = humantime::parse_duration)]
+    timeout: Option<std::time::Duration>,
+
+    /// Exit successfully when --timeout is hit instead of with status 124
+    #[arg(long, global = true, requires = "timeout")]
+    timeout_ok: bool,
+
+    /// Stop matching a rule against a file after this long (e.g. 2s, 500ms), warning about
+    /// it and leaving the file unchecked by that rule
+    #[arg(long, global = true, value_parser = humantime::parse_duration)]
+    rule_timeout: Option<std::time::Duration>,
+
+    /// Number of threads to scan files on (default: the number of logical CPUs)
+    #[arg(short, long, global = true)]
+    jobs: Option<NonZeroUsize>,
+
+    /// Fail if a rule replaces a same-id rule from another source without "override": true
+    #[arg(long, global = true)]
+    strict_merge: bool,
+
+    /// Fail on config problems like regexes that match the empty string, instead of warning
+    #[arg(long, global = true)]
+    strict_config: bool,
+
+    /// Print a machine-readable line to stderr on exit explaining the exit status
+    #[arg(long, global = true)]
+    explain_exit_code: bool,
+
+    /// Don't load a config file; only run ad-hoc rules
+    #[arg(long, global = true)]
+    no_config: bool,
+
+    /// Don't add the rules from the user's global config, like ~/.config/linty/config.json
+    #[arg(long, global = true)]
+    no_global_config: bool,
+
+    /// Run with the built-in default rules when no config file is found
+    #[arg(long, global = true)]
+    use_defaults: bool,
+
+    /// Print more details about the run: the config file and skipped files, and with -vv
+    /// each rule's regex and each file's match time
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Print nothing but a line per failing violation, and never prompt
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// After the summary, list the rules that spent the longest matching
+    #[arg(long, conflicts_with = "quiet")]
+    stats: bool,
 
     /// Print warnings and continue without confirmation
-    #[arg(long)]
+    #[arg(long, overrides_with = "confirm")]
     no_confirm: bool,
 
-    /// Include files listed in .gitignore and .ignore files
-    #[arg(long)]
+    /// Confirm warnings even when stdout isn't a terminal, reading the answers from stdin
+    #[arg(long, overrides_with = "no_confirm")]
+    confirm: bool,
+
+    /// Don't respect any ignore files (.gitignore, .ignore, global and parent ignores)
+    #[arg(long, global = true, overrides_with = "ignore")]
+    no_ignore: bool,
+
+    /// Respect ignore files even if the config sets include_ignored
+    #[arg(long, global = true, overrides_with = "no_ignore")]
+    ignore: bool,
+
+    /// Don't respect .gitignore, .git/info/exclude, or the global gitignore
+    #[arg(long, global = true)]
+    no_ignore_vcs: bool,
+
+    /// Don't respect .ignore files
+    #[arg(long, global = true)]
+    no_ignore_dot: bool,
+
+    /// Don't respect ignore files in directories above the current one
+    #[arg(long, global = true)]
+    no_ignore_parent: bool,
+
+    /// Deprecated alias for --no-ignore
+    #[arg(long, global = true, hide = true)]
     ignored: bool,
 
     /// Include hidden files
-    #[arg(long)]
+    #[arg(long, global = true, overrides_with = "no_hidden")]
     hidden: bool,
 
-    /// Limit to files staged for commit
-    #[arg(long, group = "input")]
-    pre_commit: bool,
+    /// Skip hidden files even if the config sets include_hidden
+    #[arg(long, global = true, overrides_with = "hidden")]
+    no_hidden: bool,
 
-    /// Relative paths to files to lint (default: all files in current directory recursively)
-    #[arg(group = "input")]
-    files: Vec<String>,
+    /// Walk into symlinked directories; a symlink loop is warned about and skipped
+    #[arg(long, global = true)]
+    follow_symlinks: bool,
 
-    #[command(subcommand)]
-    command: Option<Subcommand>,
-}
+    /// Annotate violations with the author and date of each offending line from git blame
+    #[arg(long)]
+    blame: bool,
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
-#[serde(rename_all = "snake_case")]
-enum Severity {
-    Warning,
-    Error,
-}
+    /// Output format (default: github under GitHub Actions, short otherwise)
+    #[arg(long, value_enum, default_value_t = Format::Short)]
+    format: Format,
 
-#[derive(Serialize, Deserialize, Debug)]
-struct RuleConfig {
-    id: String,
-    message: String,
-    regex: String,
-    severity: Severity,
-    includes: Option<Vec<String>>,
-    excludes: Option<Vec<String>>,
-}
+    /// Show each match's source lines, underlined; short for --format pretty
+    #[arg(long, conflicts_with = "format")]
+    show_source: bool,
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Config {
-    rules: Vec<RuleConfig>,
-}
+    /// How the short and pretty reports are organized
+    #[arg(long, value_enum, default_value_t = GroupBy::Rule)]
+    group_by: GroupBy,
 
-struct Rule {
-    id: String,
-    regex: Regex,
-    severity: Severity,
-    includes: GlobSet,
-    excludes: GlobSet,
-}
+    /// Lines of source to show before and after each match with --format pretty
+    #[arg(long, value_name = "LINES", default_value_t = 2)]
+    context: usize,
 
-#[derive(Debug)]
-struct Violation {
-    rule_id: String,
-    severity: Severity,
-    file: OsString,
-    lines: Vec<usize>,
-}
+    /// When to color the short and pretty reports; structured formats are never colored
+    #[arg(long, global = true, value_enum, default_value_t = display::ColorChoice::Auto)]
+    color: display::ColorChoice,
 
-fn main() -> anyhow::Result<()> {
-    let args = Args::parse();
+    /// Maximum characters per chunk for --format pr-comment
+    #[arg(long, default_value_t = 65000)]
+    max_chars: usize,
 
-    match args.command {
-        Some(Subcommand::Completions { shell }) => {
-            generate(shell, &mut Args::command(), "linty", &mut std::io::stdout());
-            return Ok(());
-        }
-        Some(Subcommand::Init) => return init_config(),
-        None => {}
-    }
+    /// File to write the --format report to, leaving the terminal the text report, or - for
+    /// stdout (a directory for pr-comment)
+    #[arg(long, value_name = "PATH")]
+    output: Option<String>,
 
-    let config_path_str = args
-        .config_path
-        .as_ref()
-        .map(|s| s.as_str())
-        .unwrap_or(DEFAULT_CONFIG_PATH_STR);
+    /// Baseline file from `linty baseline`; violations it lists aren't reported or counted
+    #[arg(long)]
+    baseline: Option<String>,
 
-    let config = read_config(config_path_str);
+    /// Previous --format json report to compare against
+    #[arg(long)]
+    compare: Option<String>,
 
-    let rules = generate_rules_from_config(&config)?;
+    /// With --compare, only fail on violations that aren't in the previous report
+    #[arg(long, requires = "compare")]
+    fail_on_new: bool,
 
-    let current_dir = std::env::current_dir()?;
+    /// Rewrite files with each match's fix, then scan again and report what's left
+    #[arg(long, conflicts_with_all = ["no_suggestions", "pre_commit"])]
+    fix: bool,
 
-    let mut specified_paths: Vec<OsString> = Vec::new();
+    /// Print the changes --fix would make as a diff, without changing any files
+    #[arg(long, conflicts_with_all = ["fix", "no_suggestions", "pre_commit"])]
+    fix_dry_run: bool,
 
-    if args.pre_commit {
-        println!("Checking staged files...");
-        let git_output = Command::new("git")
-            .args(&["diff", "--staged", "--name-only"])
-            .output()?;
+    /// Robot run id for --format gerrit (default: the time the run started)
+    #[arg(long)]
+    robot_run_id: Option<String>,
 
-        if git_output.status.success() {
-            let stdout = String::from_utf8(git_output.stdout)?;
-            let staged_paths: Vec<&str> = stdout.lines().collect();
+    /// Leave fix suggestions out of structured output, such as for rules that match secrets
+    #[arg(long)]
+    no_suggestions: bool,
 
-            if staged_paths.is_empty() {
-                eprintln!("No staged files found!");
-                exit(1);
-            }
+    /// Write the files each rule examined, and the bytes read from each, as JSON to this path
+    #[arg(long)]
+    coverage_json: Option<String>,
 
-            for path in staged_paths {
-                specified_paths.push(Path::new(path).canonicalize()?.as_os_str().to_owned());
-            }
-        } else {
-            eprintln!(
-                "Error running git: {}",
-                String::from_utf8_lossy(&git_output.stderr)
-            );
-            exit(1);
-        }
-    } else {
-        for file in args.files {
-            specified_paths.push(
-                current_dir
-                    .join(Path::new(&file))
-                    .canonicalize()?
-                    .as_os_str()
-                    .to_owned(),
-            );
-        }
-    }
+    /// Write Prometheus text-format metrics about the run to this path
+    #[arg(long)]
+    metrics: Option<String>,
 
-    let mut violations: Vec<Violation> = Vec::new();
+    /// Send a desktop notification with the summary when the run finishes
+    #[arg(long)]
+    notify: bool,
 
-    for result in WalkBuilder::new("./")
-        .git_ignore(!args.ignored)
-        .ignore(!args.ignored)
-        .hidden(!args.hidden)
-        .build()
-    {
-        match result {
-            Err(err) => eprintln!("Error: {err}"),
-            Result::Ok(entry) => {
-                if entry.metadata()?.is_dir() {
-                    continue;
-                }
+    /// After the run, keep re-linting files as they change until Ctrl-C
+    #[arg(long, conflicts_with_all = ["pre_commit", "stdin", "timeout"])]
+    watch: bool,
 
-                let mut file_contents = String::new();
-                for rule in &rules {
-                    if (!rule.includes.is_empty() && !rule.includes.is_match(entry.path()))
-                        || rule.excludes.is_match(entry.path())
-                        || (!specified_paths.is_empty()
-                            && !specified_paths
-                                .contains(&entry.path().canonicalize()?.as_os_str().to_owned()))
-                    {
-                        continue;
-                    }
+    /// Write a shields.io endpoint badge for the run to this path
+    #[arg(long)]
+    badge: Option<String>,
 
-                    if file_contents.is_empty() {
-                        let file = File::open(entry.path());
-
-                        match file {
-                            std::io::Result::Ok(mut file) => {
-                                if let Err(err) = file.read_to_string(&mut file_contents) {
-                                    eprintln!(
-                                        "Error: Failed to read {}\nReason: {}",
-                                        entry.path().to_str().unwrap(),
-                                        err
-                                    );
-                                    continue;
-                                };
-                            }
-                            Err(err) => {
-                                eprintln!(
-                                    "Error: Failed to open {}\nReason: {}",
-                                    entry.path().to_str().unwrap(),
-                                    err
-                                );
-                                continue;
-                            }
-                        }
-                    }
+    /// Message for --badge, with {errors}, {warnings}, and {total} placeholders
+    #[arg(long, requires = "badge", default_value = badge::DEFAULT_MESSAGE)]
+    badge_message: String,
 
-                    let mut lines = Vec::new();
-                    for regex_match in rule.regex.find_iter(&file_contents) {
-                        let offending_line = file_contents[..regex_match.start()]
-                            .chars()
-                            .filter(|&c| c == '\n')
-                            .count()
-                            + 1;
-                        lines.push(offending_line);
-                    }
-                    if !lines.is_empty() {
-                        violations.push(Violation {
-                            rule_id: rule.id.to_owned(),
-                            severity: rule.severity,
-                            file: entry.file_name().to_owned(),
-                            lines,
-                        })
-                    }
-                }
-            }
-        }
-    }
+    /// POST the JSON report to this URL
+    #[arg(long)]
+    post_results: Option<String>,
 
-    let (warnings, errors): (Vec<Violation>, Vec<Violation>) =
-        violations
-            .into_iter()
-            .partition(|violation| match &violation.severity {
-                Severity::Warning => true,
-                Severity::Error => false,
-            });
+    /// Extra header for --post-results, as 'Name: value' (repeatable)
+    #[arg(long, requires = "post_results")]
+    post_header: Vec<String>,
 
-    let mut warnings_by_id: HashMap<String, Vec<Violation>> = HashMap::new();
-    for warning in warnings {
-        warnings_by_id
-            .entry(warning.rule_id.to_owned())
-            .or_insert(Vec::new())
-            .push(warning);
-    }
+    /// Fail the run if --post-results can't deliver the report
+    #[arg(long, requires = "post_results")]
+    post_required: bool,
 
-    let mut errors_by_id: HashMap<String, Vec<Violation>> = HashMap::new();
-    for error in errors {
-        errors_by_id
-            .entry(error.rule_id.to_owned())
-            .or_insert(Vec::new())
-            .push(error);
-    }
+    /// Include files marked linguist-generated or linguist-vendored in .gitattributes
+    #[arg(long, global = true)]
+    include_generated: bool,
 
-    for rule_id in warnings_by_id.keys() {
-        let message = &config
-            .rules
-            .iter()
-            .find(|rule| &rule.id == rule_id)
-            .unwrap()
-            .message;
-        println!("Found warning {rule_id}: {message}");
+    /// What to do with files that aren't valid UTF-8
+    #[arg(long, value_enum, default_value_t = InvalidUtf8::Skip)]
+    invalid_utf8: InvalidUtf8,
 
-        for violation in warnings_by_id.get(rule_id).unwrap() {
-            println!(
-                "Warning present in file: {}, lines: {}",
-                violation.file.to_str().unwrap(),
-                violation
-                    .lines
-                    .iter()
-                    .map(|line| line.to_string())
-                    .collect::<Vec<String>>()
-                    .join(", ")
-            );
-        }
+    /// Check files with a NUL byte near the start too, instead of skipping them as binary
+    #[arg(long)]
+    no_skip_binary: bool,
 
-        if args.no_confirm {
-            continue;
+    /// Skip files larger than this, like 500K or 1G (0 for no limit)
+    #[arg(long, value_name = "SIZE", default_value = "10M", value_parser = parse_file_size)]
+    max_filesize: u64,
+
+    /// Replay the results for files unchanged since the last --cache run instead of
+    /// scanning them again
+    #[arg(long, conflicts_with_all = ["stdin", "explain_skip", "coverage_json"])]
+    cache: bool,
+
+    /// Where --cache keeps its results
+    #[arg(long, requires = "cache", default_value = cache::DEFAULT_CACHE_PATH)]
+    cache_path: String,
+
+    /// In text output, list only a few of the files where a rule has the same single
+    /// finding on line 1, when there are many
+    #[arg(long)]
+    collapse_similar: bool,
+
+    /// Ignore suppression comments like linty-disable-next-line in the scanned files
+    #[arg(long)]
+    no_inline_config: bool,
+
+    /// Warn about suppression comments that don't suppress any match
+    #[arg(long, conflicts_with = "no_inline_config")]
+    report_unused_suppressions: bool,
+
+    /// In the short report, also list each match as file:line:col with the matched text
+    #[arg(long)]
+    show_matches: bool,
+
+    /// Maximum line numbers to list per file in text output before summarizing (0 for no
+    /// limit); a rule's max_reported takes precedence
+    #[arg(long, alias = "max-matches-per-file", default_value_t = 50)]
+    max_listed_lines: usize,
+
+    /// Print only how many files and matches each rule found, and the totals, for scripts
+    #[arg(
+        long,
+        conflicts_with_all = ["format", "show_source", "quiet", "fix", "fix_dry_run", "watch"]
+    )]
+    count: bool,
+
+    /// Print only the path of each file with a match, once each, for scripts like xargs
+    #[arg(
+        long,
+        conflicts_with_all = ["count", "format", "show_source", "quiet", "fix", "fix_dry_run", "watch"]
+    )]
+    files_with_matches: bool,
+
+    /// Print why rules were skipped or changed for each file
+    #[arg(long, global = true)]
+    explain_skip: bool,
+
+    /// Stop recording matches for a file after this many, across all rules; overrides the
+    /// config's max_violations_per_file
+    #[arg(long)]
+    max_violations_per_file: Option<usize>,
+
+    /// Limit to files staged for commit
+    #[arg(long, group = "input")]
+    pre_commit: bool,
+
+    /// With --pre-commit, also lint untracked files that aren't ignored
+    #[arg(long, requires = "pre_commit")]
+    pre_commit_untracked: bool,
+
+    /// Limit to files changed in the working tree since it forked from this git ref
+    #[arg(long, group = "input", value_name = "REF")]
+    diff_base: Option<String>,
+
+    /// Limit to files changed since this git ref (default: HEAD), staged or not, and
+    /// untracked files that aren't ignored
+    #[arg(
+        long,
+        group = "input",
+        value_name = "REF",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "HEAD"
+    )]
+    changed: Option<String>,
+
+    /// Only report matches on lines the staged diff (--pre-commit) or the diff from
+    /// --diff-base adds
+    #[arg(long)]
+    changed_lines_only: bool,
+
+    /// Lint exactly the files listed in this file (- for stdin), one per line, without walking
+    #[arg(long, group = "input")]
+    files_from: Option<String>,
+
+    /// With --files-from, entries are separated by NUL instead of newlines, and with
+    /// --files-with-matches, the paths printed are
+    #[arg(long)]
+    null: bool,
+
+    /// Lint what's piped to stdin as a single file instead of walking, for editors and
+    /// formatters
+    #[arg(long, group = "input", conflicts_with_all = ["fix", "fix_dry_run"])]
+    stdin: bool,
+
+    /// With --stdin, the path the content is linted and reported as, which rules'
+    /// includes and excludes match against (default: stdin)
+    #[arg(long, requires = "stdin", value_name = "PATH")]
+    stdin_filename: Option<String>,
+
+    /// Directory to walk instead of the current directory (repeatable); overrides the
+    /// config's roots
+    #[arg(long, conflicts_with_all = ["files_from", "pre_commit", "diff_base", "changed", "files", "stdin"])]
+    root: Vec<String>,
+
+    /// Relative paths to files or directories to lint (default: all files in current directory
+    /// recursively)
+    #[arg(group = "input")]
+    files: Vec<String>,
+
+    /// Fail when a path given on the command line doesn't exist, instead of skipping it
+    #[arg(long)]
+    strict_paths: bool,
+
+    #[command(subcommand)]
+    command: Option<Subcommand>,
+}
+
+/// The config file a run uses, and how it was chosen.
+struct ConfigLocation {
+    path: String,
+    selected_by: &'static str,
+    /// The current directory relative to the config's, when it was found in a parent
+    /// directory; empty otherwise.
+    glob_base: PathBuf,
+}
+
+/// Resolves the configured roots against the current directory, failing on any that
+/// isn't a directory. Walked paths keep the `./` prefix of a full walk, so rules' globs
+/// see the same paths whichever roots are active.
+fn walk_roots(configured: &[String], verbose: bool) -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    for root in configured {
+        if !Path::new(root).is_dir() {
+            eprintln!("Root {root} is not a directory");
+            outcome::fail(Reason::ConfigError);
         }
+        roots.push((Path::new("./").join(root), root.as_str()));
+    }
+    collapse_nested(roots, verbose)
+}
 
-        loop {
-            print!("Ignore warning? y/n ");
-            std::io::stdout().flush()?;
-            let mut input = String::new();
-            std::io::stdin().read_line(&mut input)?;
-            match input.trim() {
-                "y" => break,
-                "n" => {
-                    eprintln!("Failing due to warnings");
-                    exit(1);
+/// Drops requested paths that repeat another or lie inside another requested directory,
+/// so overlapping arguments like `src src/parser` don't scan or report a file twice.
+/// Each path comes with the argument it was given as, to name when `verbose` is set.
+fn collapse_nested(requested: Vec<(PathBuf, &str)>, verbose: bool) -> Vec<PathBuf> {
+    let mut kept = Vec::new();
+    for (index, (path, argument)) in requested.iter().enumerate() {
+        // Between identical paths, the first one given is kept.
+        let covering = requested
+            .iter()
+            .enumerate()
+            .find(|(other_index, (other, _))| {
+                path.starts_with(other) && (other != path || other_index < &index)
+            });
+        match covering {
+            Some((_, (_, covering_argument))) => {
+                if verbose {
+                    eprintln!(
+                        "Skipping argument {argument}: already covered by {covering_argument}"
+                    );
                 }
-                _ => continue,
             }
+            None => kept.push(path.clone()),
         }
     }
+    kept
+}
 
-    for rule_id in errors_by_id.keys() {
-        let message = &config
-            .rules
-            .iter()
-            .find(|rule| &rule.id == rule_id)
-            .unwrap()
-            .message;
-        println!("Found error {rule_id}: {message}");
+/// Finds the repository `mode`, a flag like `--pre-commit`, runs git in, failing with a
+/// one-line message when git isn't installed or the current directory isn't in a
+/// repository.
+fn git_repo(mode: &str) -> Repo {
+    match Repo::discover() {
+        Ok(repo) => repo,
+        Err(err) => git_failure(mode, err),
+    }
+}
 
-        for violation in errors_by_id.get(rule_id).unwrap() {
-            println!(
-                "Error present in file: {}, lines: {}",
-                violation.file.to_str().unwrap(),
-                violation
-                    .lines
-                    .iter()
-                    .map(|line| line.to_string())
-                    .collect::<Vec<String>>()
-                    .join(", ")
-            );
+/// Fails with an I/O error, since git being missing or failing is the environment's
+/// problem rather than the config's.
+fn git_failure(mode: &str, err: GitError) -> ! {
+    match err {
+        GitError::NotInstalled => {
+            eprintln!("{mode} needs git, which isn't installed or isn't on PATH");
         }
+        GitError::NotARepository => {
+            eprintln!("{mode} needs a git repository, and the current directory isn't in one");
+        }
+        err => eprintln!("Error: {err}"),
     }
+    outcome::fail(Reason::IoError);
+}
 
-    if !&errors_by_id.is_empty() || (args.error_on_warning && !&warnings_by_id.is_empty()) {
-        eprintln!("Failing due to errors");
-        exit(1);
-    }
+/// Runs a git command that lists paths, as walk roots.
+fn git_paths(repo: &Repo, mode: &str, git_args: &[&str]) -> Vec<PathBuf> {
+    repo.paths(git_args)
+        .unwrap_or_else(|err| git_failure(mode, err))
+}
 
-    Ok(())
+/// The `git diff` arguments comparing the working tree with where HEAD forked from
+/// `diff_base`, so changes made on `diff_base` since then aren't linted.
+fn diff_base_args(diff_base: &str) -> [&str; 3] {
+    ["--merge-base", diff_base, "--"]
 }
 
-fn read_config(config_path: &str) -> Config {
-    let path = Path::new(config_path);
-    let Ok(file) = File::open(path) else {
-        eprintln!("Failed to find config file; do you need to create a .lintyconfig.json file?");
-        eprintln!("Try running `linty init`!");
-        exit(1);
+/// Fails with a config error unless `diff_base` names a commit that HEAD shares history
+/// with, which `git diff` would otherwise reject with a message about its flags.
+fn check_diff_base(repo: &Repo, diff_base: &str) {
+    let succeeds = |git_args: &[&str]| {
+        repo.succeeds(git_args)
+            .unwrap_or_else(|err| git_failure("--diff-base", err))
     };
+    if !succeeds(&[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("{diff_base}^{{commit}}"),
+    ]) {
+        eprintln!("--diff-base {diff_base} isn't a commit, branch, or tag in this repository");
+        outcome::fail(Reason::ConfigError);
+    }
+    if !succeeds(&["merge-base", diff_base, "HEAD"]) {
+        eprintln!(
+            "HEAD has no history in common with --diff-base {diff_base}; a shallow clone may need more of it, as with git fetch --unshallow"
+        );
+        outcome::fail(Reason::ConfigError);
+    }
+}
 
-    let mut reader = BufReader::new(file);
-
-    match path.extension().and_then(std::ffi::OsStr::to_str) {
-        Some("toml") => {
-            let mut buf = String::new();
-            reader
-                .read_to_string(&mut buf)
-                .expect("Unexpected failure while reading config file");
-            match toml::from_str(buf.as_str()) {
-                Ok(config) => config,
-                Err(err) => {
-                    eprintln!("Failed to parse config file at {config_path} as TOML: {err}");
-                    exit(1);
-                }
-            }
-        }
-        _ => match serde_json::from_reader(reader) {
-            Ok(config) => config,
-            Err(err) => {
-                eprintln!("Failed to parse config file at {config_path} as JSON: {err}");
-                exit(1);
-            }
-        },
+/// The working-tree files changed since `changed`, a git ref: those that differ from it,
+/// staged or not, and untracked files that aren't ignored, each listed once, from the
+/// whole repository. Fails with a config error when `changed` isn't a commit. The
+/// untracked files are also added to `untracked`.
+fn changed_paths(repo: &Repo, changed: &str, untracked: &mut HashSet<PathBuf>) -> Vec<PathBuf> {
+    let is_commit = repo
+        .succeeds(&[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{changed}^{{commit}}"),
+        ])
+        .unwrap_or_else(|err| git_failure("--changed", err));
+    if !is_commit {
+        eprintln!("--changed={changed} isn't a commit, branch, or tag in this repository");
+        outcome::fail(Reason::ConfigError);
     }
+    let mut paths = git_paths(
+        repo,
+        "--changed",
+        &["diff", "--name-only", "--diff-filter=d", changed, "--"],
+    );
+    paths.extend(git_paths(
+        repo,
+        "--changed",
+        &["diff", "--staged", "--name-only", "--diff-filter=d"],
+    ));
+    let others = git_paths(
+        repo,
+        "--changed",
+        &["ls-files", "--others", "--exclude-standard"],
+    );
+    untracked.extend(others.iter().cloned());
+    paths.extend(others);
+    let mut seen = HashSet::new();
+    // A staged file can since have been deleted from the working tree.
+    paths.retain(|path| path.exists() && seen.insert(path.clone()));
+    paths
 }
 
-fn generate_rules_from_config(config: &Config) -> anyhow::Result<Vec<Rule>> {
-    let mut rules: Vec<Rule> = Vec::new();
+/// Prints what the scan couldn't check, like files that couldn't be read.
+fn print_problems(result: &scan::ScanResult) {
+    for problem in &result.problems {
+        eprintln!("{problem}");
+    }
+}
 
-    for rule_config in &config.rules {
-        let mut include_globs = GlobSetBuilder::new();
-        let mut exclude_globs = GlobSetBuilder::new();
+/// The walked path `--stdin` content is scanned as: `--stdin-filename` relative to the
+/// current directory and prefixed with `./`, like a walked file, or `./stdin` without one.
+fn stdin_path(filename: Option<&str>, current_dir: &Path) -> PathBuf {
+    let filename = Path::new(filename.unwrap_or("stdin"));
+    let relative = filename
+        .strip_prefix(current_dir)
+        .or_else(|_| filename.strip_prefix("./"))
+        .unwrap_or(filename);
+    Path::new("./").join(relative)
+}
 
-        for include in rule_config.includes.as_ref().unwrap_or(&Vec::new()) {
-            include_globs.add(Glob::new(&include)?);
+/// Reads the `--files-from` list, to be used as the walk roots so only those files are
+/// visited. Relative entries are prefixed with `./` so globs match them as they would in
+/// a full walk. Entries that don't exist are skipped with a warning, or fail the run with
+/// `strict_paths`, and counted in `missing_paths`.
+fn read_files_from(
+    files_from: &str,
+    null: bool,
+    config_paths: &[String],
+    strict_paths: bool,
+    missing_paths: &mut usize,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let mut list = String::new();
+    if files_from == "-" {
+        if config_paths.iter().any(|path| path == STDIN_CONFIG_PATH) {
+            eprintln!("Can't read both the config and --files-from from stdin");
+            outcome::fail(Reason::ConfigError);
         }
+        std::io::stdin().read_to_string(&mut list)?;
+    } else {
+        let Ok(mut file) = File::open(files_from) else {
+            eprintln!("Failed to open --files-from list {files_from}");
+            outcome::fail(Reason::ConfigError);
+        };
+        file.read_to_string(&mut list)?;
+    }
 
-        for exclude in rule_config.excludes.as_ref().unwrap_or(&Vec::new()) {
-            exclude_globs.add(Glob::new(&exclude)?);
+    let separator = if null { '\0' } else { '\n' };
+    let mut roots = Vec::new();
+    let mut listed = 0;
+    for entry in list.split(separator) {
+        let entry = entry.strip_suffix('\r').unwrap_or(entry);
+        if entry.is_empty() {
+            continue;
+        }
+        listed += 1;
+        let path = Path::new(entry);
+        if let Err(err) = path.canonicalize() {
+            let reason = if err.kind() == std::io::ErrorKind::NotFound {
+                "not found".to_owned()
+            } else {
+                err.to_string()
+            };
+            if strict_paths {
+                eprintln!("Error: {entry} from --files-from: {reason}");
+            } else {
+                eprintln!("Warning: Skipping {entry} from --files-from: {reason}");
+            }
+            *missing_paths += 1;
+            continue;
         }
+        roots.push(if path.is_relative() {
+            Path::new("./").join(path)
+        } else {
+            path.to_path_buf()
+        });
+    }
 
-        let regex = RegexBuilder::new(&rule_config.regex);
+    if strict_paths && *missing_paths > 0 {
+        outcome::fail(Reason::ConfigError);
+    }
+    if listed == 0 {
+        eprintln!("No files listed in --files-from!");
+        outcome::fail(Reason::ConfigError);
+    }
+    if roots.is_empty() {
+        eprintln!("None of the files listed in --files-from exist");
+        outcome::fail(Reason::ConfigError);
+    }
+    Ok(roots)
+}
 
-        rules.push(Rule {
-            id: rule_config.id.to_owned(),
-            regex: regex.build()?,
-            severity: rule_config.severity,
-            includes: include_globs.build()?,
-            excludes: exclude_globs.build()?,
-        });
+/// The path a walk of the current directory gives `file`, with a `./` prefix. A file
+/// elsewhere below the config's directory, which `glob_base` leads from to the current
+/// directory, is reached through `../` as a staged file is, so globs anchored at the
+/// config's directory match it; a file outside both keeps its absolute path. Fails when
+/// `file` doesn't exist.
+fn walk_path(
+    file: &str,
+    current_dir: &Path,
+    canonical_dir: &Path,
+    glob_base: &Path,
+) -> std::io::Result<PathBuf> {
+    let logical = normalize_lexically(&current_dir.join(Path::new(file)));
+    let path = linty::canonicalize(&logical)?;
+    if let Ok(relative) = logical
+        .strip_prefix(current_dir)
+        .or_else(|_| path.strip_prefix(canonical_dir))
+    {
+        return Ok(Path::new("./").join(relative));
+    }
+    let depth = glob_base.components().count();
+    let up: PathBuf = std::iter::repeat_n("..", depth).collect();
+    for (dir, target) in [(current_dir, &logical), (canonical_dir, &path)] {
+        let config_dir = dir.ancestors().nth(depth);
+        if let Some(relative) =
+            config_dir.and_then(|config_dir| target.strip_prefix(config_dir).ok())
+        {
+            return Ok(up.join(relative));
+        }
     }
-    Ok(rules)
+    Ok(logical)
 }
 
-fn init_config() -> anyhow::Result<()> {
-    let default_config = Config {
-        rules: vec![RuleConfig {
-            id: String::from("WarnOnTodos"),
-            message: String::from("Are you sure you meant to leave a TODO?"),
-            regex: String::from("(TODO|todo)"),
-            severity: Severity::Warning,
-            includes: None,
-            excludes: None,
-        }],
-    };
+/// Builds the walk settings shared by the main run and `linty grep` from the flags and,
+/// for the main run, the config.
+fn walk_options(
+    args: &Args,
+    roots: Vec<PathBuf>,
+    config: Option<&Config>,
+) -> anyhow::Result<scan::WalkOptions> {
+    let excludes = PathGlobs::new(
+        config
+            .and_then(|config| config.excludes.as_deref())
+            .unwrap_or_default(),
+    )
+    .map_err(|errors| anyhow::anyhow!("Invalid exclude: {}", errors.join("; ")))?;
+    Ok(scan::WalkOptions {
+        roots,
+        ignore_sources: ignore_explain::IgnoreSources {
+            vcs: !args.no_ignore && !args.no_ignore_vcs,
+            dot: !args.no_ignore && !args.no_ignore_dot,
+            parent: !args.no_ignore && !args.no_ignore_parent,
+        },
+        hidden: args.hidden,
+        follow_symlinks: args.follow_symlinks,
+        include_generated: args.include_generated,
+        explain_skip: args.explain_skip,
+        excludes: Arc::new(excludes),
+        // The scripting modes list every file and count every match.
+        max_violations_per_file: args
+            .max_violations_per_file
+            .or(config.and_then(|config| config.max_violations_per_file))
+            .filter(|_| !args.count && !args.files_with_matches),
+        record_coverage: args.coverage_json.is_some(),
+        // Timeouts are reported with the slowest rules.
+        record_rule_times: args.stats || args.rule_timeout.is_some(),
+        rule_timeout: args.rule_timeout,
+        threads: args.jobs.map_or_else(
+            || std::thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            NonZeroUsize::get,
+        ),
+        inline_config: !args.no_inline_config,
+        report_unused_suppressions: args.report_unused_suppressions,
+        staged: HashSet::new(),
+        repo: None,
+        invalid_utf8: args.invalid_utf8,
+        skip_binary: !args.no_skip_binary,
+        max_filesize: (args.max_filesize > 0).then_some(args.max_filesize),
+        glob_base: PathBuf::new(),
+        only: None,
+        cache: None,
+        progress: None,
+    })
+}
 
-    let Ok(file) = File::create(DEFAULT_CONFIG_PATH_STR) else {
-        eprintln!(
-            "Failed to create config file at {}",
-            DEFAULT_CONFIG_PATH_STR
-        );
-        exit(1);
+/// Hashes what decides a file's results besides its contents: the effective config and
+/// the settings that change how rules match, so changing any of them starts a `--cache`
+/// over.
+fn cache_key(
+    config: &Config,
+    args: &Args,
+    walk_options: &scan::WalkOptions,
+) -> anyhow::Result<String> {
+    let inputs = serde_json::json!({
+        "linty_version": env!("CARGO_PKG_VERSION"),
+        "config": config,
+        "hook_mode": args.pre_commit,
+        "no_suggestions": args.no_suggestions,
+        "max_violations_per_file": walk_options.max_violations_per_file,
+        "inline_config": walk_options.inline_config,
+        "report_unused_suppressions": walk_options.report_unused_suppressions,
+        "invalid_utf8": format!("{:?}", walk_options.invalid_utf8),
+        "skip_binary": walk_options.skip_binary,
+        "max_filesize": walk_options.max_filesize,
+        "glob_base": walk_options.glob_base,
+        "severity": args.severity,
+    });
+    Ok(format!(
+        "{:016x}",
+        compare::fnv1a(&serde_json::to_vec(&inputs)?)
+    ))
+}
+
+fn main() {
+    let outcome = match run() {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            eprintln!("Error: {err:?}");
+            // Errors that aren't I/O failures come from the config or the arguments.
+            let reason = if err.chain().any(|cause| cause.is::<std::io::Error>()) {
+                Reason::IoError
+            } else {
+                Reason::ConfigError
+            };
+            Outcome::new(reason, reason.exit_code())
+        }
     };
-    serde_json::to_writer_pretty(file, &default_config)?;
+    outcome.exit()
+}
 
-    println!("Initialized example config at {}", DEFAULT_CONFIG_PATH_STR);
-    Ok(())
+fn run() -> anyhow::Result<Outcome> {
+    let started = SystemTime::now();
+    let run_start = Instant::now();
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    // An explicit --format, even --format short, opts out of annotations under Actions.
+    if args.show_source {
+        args.format = Format::Pretty;
+    } else if matches.value_source("format") == Some(ValueSource::DefaultValue)
+        && github_annotations::in_github_actions()
+        && !args.count
+        && !args.files_with_matches
+    {
+        args.format = Format::Github;
+    }
+    outcome::set_explain(args.explain_exit_code);
+    log::set_level(args.verbose);
+    LENIENT_CONFIG.store(args.lenient_config, Ordering::Relaxed);
+
+    if args.ignored {
+        eprintln!("Warning: --ignored is deprecated; use --no-ignore instead");
+        args.no_ignore = true;
+    }
+    if args
+        .config_path
+        .iter()
+        .filter(|path| *path == STDIN_CONFIG_PATH)
+        .count()
+        > 1
+    {
+        eprintln!("Can't read more than one config from stdin");
+        outcome::fail(Reason::ConfigError);
+    }
+    if args.stdin {
+        if args
+            .config_path
+            .iter()
+            .any(|path| path == STDIN_CONFIG_PATH)
+        {
+            eprintln!("Can't read both the config and --stdin from stdin");
+            outcome::fail(Reason::ConfigError);
+        }
+        // Stdin is taken by the content, so there's nothing to answer a prompt with.
+        args.no_confirm = true;
+    }
+    if args.null && args.files_from.is_none() && !args.files_with_matches {
+        eprintln!("--null needs --files-from or --files-with-matches");
+        outcome::fail(Reason::ConfigError);
+    }
+    // Watching would wait on the prompts before it could start, and with stdout going to
+    // a hook or CI log there's usually nobody to see them. The scripting modes print no
+    // report to prompt about.
+    args.no_confirm |= args.watch
+        || args.count
+        || args.files_with_matches
+        || (!args.confirm && !std::io::stdout().is_terminal());
+    outcome::install_interrupt_handler()?;
+    if let Some(timeout) = args.timeout {
+        cancel::start_timer(timeout);
+    }
+
+    match &args.command {
+        Some(Subcommand::Completions { shell }) => {
+            generate(
+                *shell,
+                &mut Args::command(),
+                "linty",
+                &mut std::io::stdout(),
+            );
+            return Ok(Outcome::clean());
+        }
+        Some(Subcommand::Init { format, force }) => {
+            return init_config(*format, *force).map(|()| Outcome::clean())
+        }
+        Some(Subcommand::InstallHooks { hook, force }) => {
+            return hooks::install(*hook, *force).map(|()| Outcome::clean())
+        }
+        Some(Subcommand::UninstallHooks { hook }) => {
+            return hooks::uninstall(*hook).map(|()| Outcome::clean())
+        }
+        Some(Subcommand::Grep(grep_args)) => return grep(&args, grep_args),
+        _ => {}
+    }
+
+    let mut config_location = locate_config(args.config_path.first().map(String::as_str));
+    if args.use_defaults
+        && config_location.path != STDIN_CONFIG_PATH
+        && !Path::new(&config_location.path).exists()
+    {
+        config_location = ConfigLocation {
+            path: BUILTIN_DEFAULTS.to_owned(),
+            selected_by: "no config file found, so using --use-defaults",
+            glob_base: PathBuf::new(),
+        };
+    }
+    let trust = ConfigTrust::new(args.trusted_config, args.allow_remote_config);
+    if args.config_path.is_empty() && !args.no_config && config_location.path != BUILTIN_DEFAULTS {
+        if let Err(message) = trust.check_config_file(&config_location.path) {
+            eprintln!("{message}");
+            outcome::fail(Reason::ConfigError);
+        }
+    }
+    log::log!(
+        log::Level::Info,
+        "Using config {} ({})",
+        config_path_display(&config_location.path),
+        config_location.selected_by
+    );
+
+    let mut config = if args.no_config {
+        Config::default()
+    } else if config_location.path == BUILTIN_DEFAULTS {
+        eprintln!("No config file found; using built-in default rules");
+        builtin_defaults()
+    } else {
+        read_config(&config_location.path, args.config_format)
+    };
+    let mut merges = apply_extends(&mut config, &trust, &config_location.path)?;
+    if !args.no_config {
+        add_config_files(
+            &mut config,
+            args.config_path.get(1..).unwrap_or_default(),
+            args.config_format,
+            &trust,
+            &mut merges,
+        )?;
+    }
+    if args.no_global_config || args.no_config {
+        log::log!(
+            log::Level::Info,
+            "Not using a global config ({})",
+            if args.no_config {
+                "--no-config"
+            } else {
+                "--no-global-config"
+            }
+        );
+    } else {
+        global_config::add_rules(&mut config, &trust, &config_location.glob_base)?;
+    }
+    if config.editorconfig.unwrap_or(false) {
+        editorconfig::add_rules(&mut config.rules)?;
+    }
+    let mut adhoc_rules = Vec::new();
+    for (index, spec) in args.rule.iter().enumerate() {
+        adhoc_rules.push(adhoc::parse_rule(spec, index)?);
+    }
+    if let Some(regex) = &args.rule_regex {
+        adhoc_rules.push(adhoc::rule_from_flags(
+            regex,
+            args.rule_id.as_deref(),
+            args.rule_severity,
+            args.rule_message.as_deref(),
+            &args.rule_includes,
+        )?);
+    }
+    merge_rules(&mut config.rules, adhoc_rules, &mut merges);
+    if args.strict_merge {
+        check_strict_merge(&merges);
+    }
+    resolve_rule_inheritance(&mut config)?;
+    if !args.no_config {
+        nested::add_configs(
+            &mut config,
+            &trust,
+            &config_location.path,
+            &config_location.glob_base,
+            args.verbose > 0,
+        )?;
+        // Rules from nested configs can extend any rule.
+        resolve_rule_inheritance(&mut config)?;
+    }
+
+    let profile = args.profile.as_ref().and_then(|profile_name| {
+        config
+            .profiles
+            .as_ref()
+            .and_then(|profiles| profiles.get(profile_name))
+    });
+    apply_walk_settings(&config, profile, &mut args);
+    if let Some(profile_name) = args.profile.clone() {
+        apply_profile(&mut config, &mut args, &profile_name);
+    }
+    apply_tag_filters(&mut config, &args.tags, &args.exclude_tags);
+    apply_only(&mut config, &args.only);
+    apply_severity_overrides(&mut config, &args.severity);
+    check_deprecated(&config, args.profile.as_deref(), args.forbid_deprecated);
+    config_hygiene::check(&config, args.strict_config);
+    let display = display::DisplayTable::new(config.display.as_ref(), args.color)?;
+
+    // Handled before the rules are built, so it still works when a rule names an unknown
+    // language.
+    if let Some(Subcommand::ListLanguages) = args.command {
+        for (name, extensions) in languages::LanguageTable::new(config.languages.as_ref()).entries()
+        {
+            println!("{name}: {}", extensions.join(", "));
+        }
+        return Ok(Outcome::clean());
+    }
+    // Also handled before the rules are built, so it can report every broken rule rather
+    // than failing on the first.
+    if let Some(Subcommand::Validate) = args.command {
+        validate(&config, &merges);
+        return Ok(Outcome::clean());
+    }
+    if let Some(Subcommand::Test) = args.command {
+        return Ok(rule_examples::run(&config));
+    }
+    let mut rules = generate_rules_from_config(&config, args.pre_commit)?;
+    for rule in &rules {
+        log::log!(
+            log::Level::Debug,
+            "Rule {} compiled to {}",
+            rule.id,
+            rule.regex
+        );
+    }
+    if args.no_suggestions {
+        for rule in &mut rules {
+            rule.fix = None;
+        }
+    }
+
+    match args.command {
+        Some(Subcommand::Doctor) => {
+            doctor(&config, &config_location)?;
+            return Ok(Outcome::clean());
+        }
+        Some(Subcommand::ListRules { format }) => {
+            list_rules(&config, format, args.verbose > 0)?;
+            return Ok(Outcome::clean());
+        }
+        Some(Subcommand::DumpRules) => {
+            serde_json::to_writer_pretty(std::io::stdout(), &config.rules)?;
+            println!();
+            return Ok(Outcome::clean());
+        }
+        Some(Subcommand::PrintConfig { format }) => {
+            generate_overrides_from_config(&config)?;
+            print_config(&config, format)?;
+            return Ok(Outcome::clean());
+        }
+        _ => {}
+    }
+    let overrides = generate_overrides_from_config(&config)?;
+
+    let current_dir = std::env::current_dir()?;
+
+    if let Some(Subcommand::Explain { path, run }) = &args.command {
+        let walk_options = scan::WalkOptions {
+            glob_base: config_location.glob_base.clone(),
+            ..walk_options(&args, vec![PathBuf::from("./")], Some(&config))?
+        };
+        return explain::run(path, *run, &rules, &overrides, &walk_options, &current_dir);
+    }
+
+    let mut roots = vec![PathBuf::from("./")];
+    let mut untracked_paths: HashSet<PathBuf> = HashSet::new();
+    let mut staged_paths: HashSet<PathBuf> = HashSet::new();
+    let mut staged_repo = None;
+    // Given or staged paths that aren't there to lint, counted with the skipped files.
+    let mut missing_paths = 0;
+    // Whether the whole tree is walked, rather than a list of files.
+    let mut full_walk = false;
+
+    if args.changed_lines_only && !args.pre_commit && args.diff_base.is_none() {
+        eprintln!(
+            "--changed-lines-only needs --pre-commit or --diff-base to say which diff to use"
+        );
+        outcome::fail(Reason::ConfigError);
+    }
+    let mut piped = None;
+    if args.stdin {
+        let mut contents = Vec::new();
+        std::io::stdin().read_to_end(&mut contents)?;
+        piped = Some((
+            stdin_path(args.stdin_filename.as_deref(), &current_dir),
+            contents,
+        ));
+    } else if let Some(files_from) = &args.files_from {
+        roots = read_files_from(
+            files_from,
+            args.null,
+            &args.config_path,
+            args.strict_paths,
+            &mut missing_paths,
+        )?;
+    } else if args.pre_commit {
+        // Structured formats keep stdout for the report itself.
+        if !args.quiet {
+            if text_report(&args) {
+                println!("Checking staged files...");
+            } else {
+                eprintln!("Checking staged files...");
+            }
+        }
+        // The staged files are walked directly rather than filtered out of a full walk, so
+        // hidden and ignored settings can't drop a file git says is being committed.
+        // Staged deletions have nothing left to check.
+        // Git lists them from the whole repository, relative to its root, which the walked
+        // paths are made relative to the current directory from.
+        let repo = git_repo("--pre-commit");
+        roots = git_paths(
+            &repo,
+            "--pre-commit",
+            &["diff", "--staged", "--name-only", "--diff-filter=d"],
+        );
+        roots.retain(|path| {
+            let exists = path.exists();
+            if !exists {
+                eprintln!(
+                    "Warning: Skipping {}: it's staged but was deleted from the working tree",
+                    path.display()
+                );
+                missing_paths += 1;
+            }
+            exists
+        });
+        staged_paths.extend(roots.iter().cloned());
+        staged_repo = Some(repo.clone());
+        if args.pre_commit_untracked {
+            let untracked = git_paths(
+                &repo,
+                "--pre-commit-untracked",
+                &["ls-files", "--others", "--exclude-standard"],
+            );
+            untracked_paths.extend(untracked.iter().cloned());
+            roots.extend(untracked);
+        }
+
+        if roots.is_empty() {
+            eprintln!("No staged files found!");
+            outcome::fail(Reason::ConfigError);
+        }
+    } else if let Some(diff_base) = &args.diff_base {
+        if !args.quiet {
+            if text_report(&args) {
+                println!("Checking files changed since {diff_base}...");
+            } else {
+                eprintln!("Checking files changed since {diff_base}...");
+            }
+        }
+        let repo = git_repo("--diff-base");
+        check_diff_base(&repo, diff_base);
+        let mut diff_args = vec!["diff", "--name-only", "--diff-filter=d"];
+        diff_args.extend(diff_base_args(diff_base));
+        roots = git_paths(&repo, "--diff-base", &diff_args);
+        if roots.is_empty() {
+            eprintln!("No files changed since {diff_base}");
+            return Ok(Outcome::clean());
+        }
+    } else if let Some(changed) = &args.changed {
+        if !args.quiet {
+            if text_report(&args) {
+                println!("Checking files changed since {changed}...");
+            } else {
+                eprintln!("Checking files changed since {changed}...");
+            }
+        }
+        roots = changed_paths(&git_repo("--changed"), changed, &mut untracked_paths);
+        if roots.is_empty() {
+            eprintln!("No files changed since {changed}");
+            return Ok(Outcome::clean());
+        }
+    } else if !args.files.is_empty() {
+        // The given paths are walked directly, replacing any configured roots, rather
+        // than filtered out of a walk of the whole tree. Walked paths keep the `./`
+        // prefix a full walk would give them. A symlink is walked under its own path, so
+        // its target's violations are reported where the link is.
+        let canonical_dir = linty::canonicalize(&current_dir)?;
+        let mut requested = Vec::new();
+        for file in &args.files {
+            let walked = match walk_path(
+                file,
+                &current_dir,
+                &canonical_dir,
+                &config_location.glob_base,
+            ) {
+                Ok(walked) => walked,
+                Err(err) => {
+                    let reason = if err.kind() == std::io::ErrorKind::NotFound {
+                        "not found".to_owned()
+                    } else {
+                        err.to_string()
+                    };
+                    if args.strict_paths {
+                        eprintln!("Error: {file}: {reason}");
+                    } else {
+                        eprintln!("Warning: Skipping {file}: {reason}");
+                    }
+                    missing_paths += 1;
+                    continue;
+                }
+            };
+            requested.push((walked, file.as_str()));
+        }
+        if args.strict_paths && missing_paths > 0 {
+            outcome::fail(Reason::ConfigError);
+        }
+        roots = collapse_nested(requested, args.verbose > 0);
+        if roots.is_empty() {
+            eprintln!("None of the given paths exist");
+            outcome::fail(Reason::ConfigError);
+        }
+    } else {
+        full_walk = true;
+        let configured_roots = if args.root.is_empty() {
+            config.roots.clone().unwrap_or_default()
+        } else {
+            args.root.clone()
+        };
+        // The config's roots are relative to its directory, which command-line roots
+        // aren't.
+        let configured_roots = if args.root.is_empty() {
+            let up = config_location
+                .glob_base
+                .components()
+                .fold(PathBuf::new(), |up, _| up.join(".."));
+            configured_roots
+                .iter()
+                .map(|root| up.join(root).to_string_lossy().into_owned())
+                .collect()
+        } else {
+            configured_roots
+        };
+        if !configured_roots.is_empty() {
+            roots = walk_roots(&configured_roots, args.verbose > 0);
+        }
+        log::log!(
+            log::Level::Info,
+            "Walking {} ({})",
+            roots
+                .iter()
+                .map(|root| root.display().to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+            if !args.root.is_empty() {
+                "--root"
+            } else if configured_roots.is_empty() {
+                "the current directory"
+            } else {
+                "the config's roots"
+            }
+        );
+    }
+
+    let mut walk_options = scan::WalkOptions {
+        staged: staged_paths,
+        repo: staged_repo,
+        glob_base: config_location.glob_base.clone(),
+        ..walk_options(&args, roots, Some(&config))?
+    };
+    if args.cache {
+        let key = cache_key(&config, &args, &walk_options)?;
+        let (cache, warning) = ResultCache::load(Path::new(&args.cache_path), key);
+        if let Some(warning) = warning {
+            eprintln!("{warning}");
+        }
+        walk_options.cache = Some(Arc::new(cache));
+    }
+    // Progress is only shown while a text report is coming to a terminal, and not with
+    // -v, whose lines would break it up.
+    let show_progress = matches!(args.format, Format::Short | Format::Pretty)
+        && !args.quiet
+        && args.verbose == 0
+        && std::io::stderr().is_terminal();
+    let scan = || {
+        let result = match &piped {
+            Some((path, contents)) => scan::scan_stdin(
+                contents,
+                path,
+                &walk_options,
+                &rules,
+                &overrides,
+                &current_dir,
+            ),
+            None if show_progress => {
+                let spinner = spinner::Spinner::start();
+                let options = scan::WalkOptions {
+                    progress: Some(spinner.progress()),
+                    ..walk_options.clone()
+                };
+                scan::scan(&options, &rules, &overrides, &current_dir)
+            }
+            None => scan::scan(&walk_options, &rules, &overrides, &current_dir),
+        }?;
+        print_problems(&result);
+        anyhow::Ok(result)
+    };
+    let mut scan_result = scan()?;
+    if (args.fix || args.fix_dry_run) && cancel::cancelled().is_none() {
+        let fixable = reported_violations(&args, &rules, scan_result.violations.clone())?;
+        let fixed = fix_files(&fixable, args.fix_dry_run, args.format)?;
+        // The rescan reports whatever the fixes didn't cover.
+        if args.fix && fixed > 0 {
+            scan_result = scan()?;
+        }
+    }
+    if let Some(cache) = &walk_options.cache {
+        let updates = std::mem::take(&mut scan_result.cache_updates);
+        let written = cache
+            .render(updates)
+            .and_then(|contents| write_file_atomically(cache.path(), contents.as_bytes()));
+        if let Err(err) = written {
+            eprintln!(
+                "Warning: Failed to write the cache {}: {err}",
+                cache.path().display()
+            );
+        }
+    }
+    // Files replayed from the cache never had the globs matched against them.
+    if args.verbose > 0 && full_walk && scan_result.cached == 0 && cancel::cancelled().is_none() {
+        print_unmatched_globs(&config, &rules, &overrides, &walk_options.excludes);
+    }
+    let scan::ScanResult {
+        mut violations,
+        files_walked,
+        files_scanned,
+        duration: scan_duration,
+        match_duration,
+        bytes_read,
+        dropped,
+        suppressed,
+        binary_skipped,
+        large_skipped,
+        encoding_skipped,
+        excluded,
+        cached,
+        cache_updates: _,
+        cache_saved,
+        rule_durations,
+        timed_out,
+        coverage,
+        problems: _,
+    } = scan_result;
+    let stats = json_report::RunStats {
+        files_walked,
+        files_scanned,
+        files_skipped: binary_skipped + large_skipped + encoding_skipped + excluded + missing_paths,
+        rules: rules.len(),
+        elapsed_ms: run_start.elapsed().as_millis() as u64,
+        walk_ms: scan_duration.saturating_sub(match_duration).as_millis() as u64,
+        match_ms: match_duration.as_millis() as u64,
+        cache_saved_ms: args.cache.then_some(cache_saved.as_millis() as u64),
+        rule_durations_ms: rule_durations
+            .iter()
+            .map(|(rule_id, duration)| (rule_id.clone(), duration.as_secs_f64() * 1000.0))
+            .collect(),
+    };
+    let cancellation = cancel::cancelled();
+    let report_start = Instant::now();
+    if let Some(cancellation) = cancellation {
+        eprintln!("{}", cancellation.banner());
+    }
+
+    for violation in &mut violations {
+        violation.untracked = untracked_paths.contains(&violation.path);
+    }
+    if args.changed_lines_only {
+        let repo = git_repo("--changed-lines-only");
+        violations = changed_lines::ChangedLines::from_git(&repo, &changed_lines_diff_args(&args))
+            .retain(violations);
+    }
+
+    // Age limits report lines git can't blame regardless, but `--blame` needs git.
+    let mut blame_cache = if args.blame {
+        BlameCache::new(git_repo("--blame"))
+    } else {
+        BlameCache::default()
+    };
+    let mut violations =
+        blame::apply_age_limits(violations, &rules, &mut blame_cache, args.verbose > 0);
+    if let Some(Subcommand::Baseline { output }) = &args.command {
+        if cancellation.is_some() {
+            anyhow::bail!("Not writing a baseline from an incomplete scan");
+        }
+        write_file_atomically(Path::new(output), baseline::render(&violations)?.as_bytes())?;
+        eprintln!("Wrote the baseline to {output}");
+        return Ok(Outcome::clean());
+    }
+    let mut baseline_applied = None;
+    if let Some(baseline_path) = &args.baseline {
+        let (remaining, applied) = baseline::apply(Path::new(baseline_path), violations)?;
+        violations = remaining;
+        baseline_applied = Some(applied);
+    }
+    if args.blame {
+        for violation in &mut violations {
+            violation.blame = blame_cache.blame_lines(&violation.path, &violation.lines());
+        }
+    }
+
+    if let (Some(coverage_path), Some(coverage)) = (&args.coverage_json, coverage) {
+        write_file_atomically(Path::new(coverage_path), coverage.into_json()?.as_bytes())?;
+    }
+
+    if let Some(metrics_path) = &args.metrics {
+        let metrics = metrics::render(
+            &rules,
+            &violations,
+            &suppressed,
+            files_scanned,
+            bytes_read,
+            scan_duration,
+        );
+        write_file_atomically(Path::new(metrics_path), metrics.as_bytes())?;
+    }
+
+    let comparison = match &args.compare {
+        Some(previous_path) => Some(compare::compare(Path::new(previous_path), &violations)?),
+        None => None,
+    };
+    let outcome = compute_outcome(
+        &violations,
+        &dropped,
+        comparison.as_ref(),
+        &args,
+        cancellation,
+    );
+    let budget_line = budget_line(&violations, &dropped, comparison.as_ref(), &args)
+        .filter(|_| cancellation.is_none());
+    // Warnings over the budget fail the run without errors, so the budget says why instead.
+    let failure_line = |outcome: &Outcome, fallback: &str| {
+        if let Some(line) = &budget_line {
+            eprintln!("{line}");
+        }
+        if budget_line.is_none() || outcome.reason == Reason::Violations {
+            eprintln!("{fallback}");
+        }
+    };
+
+    // Written before reporting, since the interactive prompts can end the run early.
+    if let Some(badge_path) = &args.badge {
+        let badge = badge::render(&outcome, &args.badge_message)?;
+        write_file_atomically(Path::new(badge_path), badge.as_bytes())?;
+    }
+
+    let mut notifier = desktop_notify::Notifier::default();
+    let structured_report =
+        matches!(args.format, Format::Json | Format::Sarif) || args.post_results.is_some();
+    let report_repo = structured_report.then(post_results::report_repo).flatten();
+    let report_meta = if structured_report {
+        Some(json_report::ReportMeta::new(
+            &config,
+            started,
+            report_repo.as_ref(),
+        )?)
+    } else {
+        None
+    };
+    // A nested config replacing a rule adds a second rule with its id; the first keeps
+    // the message.
+    let mut messages: HashMap<&str, &str> = config
+        .rules
+        .iter()
+        .rev()
+        .map(|rule| {
+            (
+                rule.id.as_str(),
+                rule.message.as_deref().unwrap_or_default(),
+            )
+        })
+        .collect();
+    messages.insert(
+        scan::OVERFLOW_RULE_ID,
+        "Further matches in this file were discarded by --max-violations-per-file",
+    );
+    messages.insert(
+        scan::OVERSIZED_MATCH_RULE_ID,
+        "A match in this file was longer than its rule's max_match_length, so it was cut short and has no fix suggestion",
+    );
+    messages.insert(
+        suppress::UNUSED_SUPPRESSION_RULE_ID,
+        "This suppression comment didn't suppress any match",
+    );
+    messages.insert(
+        region::UNBALANCED_REGION_RULE_ID,
+        "Region markers in this file don't pair up, so rules with a region skipped it",
+    );
+
+    // Re-runs go through the same filters as the first, apart from the whole-run reports.
+    let relint = |changed: &HashSet<PathBuf>| -> anyhow::Result<Option<Vec<Violation>>> {
+        let options = scan::WalkOptions {
+            only: Some(changed.clone()),
+            ..walk_options.clone()
+        };
+        let scan_result = scan::scan(&options, &rules, &overrides, &current_dir)?;
+        print_problems(&scan_result);
+        if scan_result.files_walked == 0 {
+            return Ok(None);
+        }
+        let mut violations = scan_result.violations;
+        if args.changed_lines_only {
+            if let Some(diff_base) = &args.diff_base {
+                let repo = git_repo("--changed-lines-only");
+                violations =
+                    changed_lines::ChangedLines::from_git(&repo, &diff_base_args(diff_base))
+                        .retain(violations);
+            }
+        }
+        let mut violations = blame::apply_age_limits(
+            violations,
+            &rules,
+            &mut BlameCache::default(),
+            args.verbose > 0,
+        );
+        if let Some(baseline_path) = &args.baseline {
+            violations = baseline::apply(Path::new(baseline_path), violations)?.0;
+        }
+        Ok(Some(violations))
+    };
+    // Watching carries on from whichever report the first run ends with.
+    let watched = args.watch.then(|| violations.clone());
+    let finish = |outcome: Outcome, notifier: desktop_notify::Notifier| match watched {
+        Some(violations) => watch::Watch {
+            roots: &walk_options.roots,
+            display: &display,
+            messages: &messages,
+            notifier: args.notify.then_some(notifier),
+        }
+        .run(violations, outcome, relint, |violations| {
+            compute_outcome(violations, &[], None, &args, None)
+        }),
+        None => Ok(outcome),
+    };
+
+    if let Some(url) = &args.post_results {
+        let headers = args
+            .post_header
+            .iter()
+            .map(|header| post_results::parse_header(header))
+            .collect::<anyhow::Result<Vec<(String, String)>>>()?;
+        let repository = post_results::RepositoryInfo::gather(report_repo.as_ref());
+        let mut body = Vec::new();
+        json_report::write_report(
+            &mut body,
+            &violations,
+            &messages,
+            json_report::RunContext {
+                rules: &config.rules,
+                meta: report_meta.as_ref(),
+                suppressed: &suppressed,
+                baseline: baseline_applied.as_ref(),
+                stats: &stats,
+                comparison: comparison.as_ref(),
+                outcome: Some(&outcome),
+                repository: Some(&repository),
+            },
+        )?;
+        if let Err(err) = post_results::post(url, &headers, &body) {
+            eprintln!("{err}");
+            if args.post_required {
+                outcome::fail(Reason::IoError);
+            }
+        }
+    }
+
+    // A report written to a file leaves the terminal the text report, as if no format had
+    // been picked.
+    let report_file = args
+        .output
+        .as_deref()
+        .filter(|output| *output != "-" && args.format != Format::PrComment);
+    if !matches!(args.format, Format::Short | Format::Pretty) {
+        let mut report: Vec<u8> = Vec::new();
+        match args.format {
+            Format::Json => {
+                json_report::write_report(
+                    &mut report,
+                    &violations,
+                    &messages,
+                    json_report::RunContext {
+                        rules: &config.rules,
+                        meta: report_meta.as_ref(),
+                        suppressed: &suppressed,
+                        baseline: baseline_applied.as_ref(),
+                        stats: &stats,
+                        comparison: comparison.as_ref(),
+                        outcome: Some(&outcome),
+                        repository: None,
+                    },
+                )?;
+                writeln!(report)?;
+            }
+            Format::PrComment => {
+                let Some(output_dir) = &args.output else {
+                    eprintln!("--format pr-comment requires --output <dir>");
+                    outcome::fail(Reason::ConfigError);
+                };
+                let count = pr_comment::write_chunks(
+                    &violations,
+                    &messages,
+                    &display,
+                    args.max_chars,
+                    Path::new(output_dir),
+                )?;
+                eprintln!("Wrote {count} comment chunk(s) to {output_dir}");
+            }
+            Format::Sarif => {
+                let repository = post_results::RepositoryInfo::gather(report_repo.as_ref());
+                sarif::write_log(
+                    &mut report,
+                    &config.rules,
+                    &violations,
+                    &messages,
+                    report_meta.as_ref(),
+                    repository.repo.as_deref(),
+                )?;
+                writeln!(report)?;
+            }
+            Format::Junit => {
+                junit::write_report(&mut report, &rules, &violations, &messages)?;
+            }
+            Format::Checkstyle => {
+                checkstyle::write_report(&mut report, &violations, &messages)?;
+            }
+            Format::Github => {
+                github_annotations::write_annotations(&mut report, &violations, &messages)?
+            }
+            Format::Gerrit => {
+                let urls: HashMap<&str, &str> = config
+                    .rules
+                    .iter()
+                    .filter_map(|rule| Some((rule.id.as_str(), rule.url.as_deref()?)))
+                    .collect();
+                let run_id = args
+                    .robot_run_id
+                    .clone()
+                    .unwrap_or_else(|| humantime::format_rfc3339_seconds(started).to_string());
+                gerrit::write_review(&mut report, &violations, &messages, &urls, &run_id)?;
+                writeln!(report)?;
+            }
+            Format::Short | Format::Pretty => unreachable!(),
+        }
+        if let Some(report_file) = report_file {
+            write_report_file(Path::new(report_file), &report);
+        } else {
+            std::io::stdout().write_all(&report)?;
+        }
+    }
+    if !matches!(args.format, Format::Short | Format::Pretty) && report_file.is_none() {
+        if let Some(comparison) = &comparison {
+            eprintln!("{}", comparison_summary(comparison));
+        }
+        if let Some(applied) = &baseline_applied {
+            eprintln!("{}", baseline_summary(applied));
+        }
+
+        if let Some(cancellation) = cancellation {
+            eprintln!("{}", cancellation.banner());
+        } else if outcome.exit_code != 0 {
+            failure_line(&outcome, "Failing due to errors");
+        }
+        if args.notify {
+            notifier.notify(
+                outcome.errors,
+                outcome.warnings,
+                &count_summary(&display, &outcome),
+            );
+        }
+        return finish(outcome, notifier);
+    }
+
+    // The scripting modes print nothing on stdout but their counts or paths.
+    if args.count || args.files_with_matches {
+        if let Some(cancellation) = cancellation {
+            eprintln!("{}", cancellation.banner());
+        }
+        let mut stdout = std::io::stdout().lock();
+        if args.files_with_matches {
+            write_files_with_matches(&mut stdout, &violations, args.null)?;
+        } else {
+            write_counts(&mut stdout, violations, &config.rules)?;
+        }
+        if cancellation.is_none() && outcome.exit_code != 0 {
+            failure_line(&outcome, "Failing due to errors");
+        }
+        if args.notify {
+            notifier.notify(
+                outcome.errors,
+                outcome.warnings,
+                &count_summary(&display, &outcome),
+            );
+        }
+        return finish(outcome, notifier);
+    }
+
+    // Quiet runs skip the report and the prompts, and only say what failed.
+    if args.quiet {
+        if let Some(cancellation) = cancellation {
+            eprintln!("{}", cancellation.banner());
+        }
+        if outcome.exit_code != 0 {
+            for violation in &violations {
+                if outcome.failed_rules.contains(&violation.rule_id) {
+                    print_terse_violation(&display, violation, &messages);
+                }
+            }
+            if let Some(line) = &budget_line {
+                eprintln!("{line}");
+            }
+        }
+        if args.notify {
+            notifier.notify(
+                outcome.errors,
+                outcome.warnings,
+                &count_summary(&display, &outcome),
+            );
+        }
+        return finish(outcome, notifier);
+    }
+
+    let mut active_counts: HashMap<String, usize> = HashMap::new();
+    for violation in &violations {
+        *active_counts.entry(violation.rule_id.clone()).or_default() += violation.matches.len();
+    }
+    let (warnings, errors): (Vec<Violation>, Vec<Violation>) =
+        violations
+            .into_iter()
+            .partition(|violation| match &violation.severity {
+                Severity::Warning => true,
+                Severity::Error => false,
+            });
+
+    let warnings_by_id = group_by_rule(warnings, &config.rules);
+    let errors_by_id = group_by_rule(errors, &config.rules);
+
+    // The outcome is decided before reporting so a run that fails anyway doesn't prompt
+    // about warnings.
+    let failing = matches!(outcome.reason, Reason::Violations | Reason::WarningBudget);
+
+    let piped_text = piped
+        .as_ref()
+        .map(|(_, contents)| String::from_utf8_lossy(contents));
+    let source = match (&piped_text, &walk_options.repo) {
+        (Some(text), _) => pretty::Source::Piped(text),
+        (None, Some(repo)) => pretty::Source::Staged(repo),
+        (None, None) => pretty::Source::Disk,
+    };
+    let report_rule = |out: &mut dyn Write,
+                       severity: Severity,
+                       rule_id: &str,
+                       violations: &[Violation]|
+     -> std::io::Result<()> {
+        let rule = config.rules.iter().find(|rule| rule.id == rule_id);
+        writeln!(
+            out,
+            "Found {} {}: {}{}",
+            display.paint(severity, display.label(severity)),
+            display.bold(rule_id),
+            messages.get(rule_id).copied().unwrap_or_default(),
+            rule.map(deprecation_label).unwrap_or_default()
+        )?;
+        if let Some(url) = rule.and_then(|rule| rule.url.as_deref()) {
+            writeln!(out, "see: {url}")?;
+        }
+        let max_listed = rule
+            .and_then(|rule| rule.max_reported)
+            .unwrap_or(args.max_listed_lines);
+        report_violations(
+            out, &display, violations, &messages, max_listed, source, &args,
+        )
+    };
+    match args.group_by {
+        GroupBy::Rule => {
+            // Errors go to stderr so they stand out from the warnings in a CI log.
+            for (rule_id, violations) in &errors_by_id {
+                report_rule(&mut std::io::stderr(), Severity::Error, rule_id, violations)?;
+            }
+            for (rule_id, violations) in &warnings_by_id {
+                report_rule(
+                    &mut std::io::stdout(),
+                    Severity::Warning,
+                    rule_id,
+                    violations,
+                )?;
+            }
+        }
+        // A file's errors and warnings are listed together, so they share stdout.
+        GroupBy::File => report_files(
+            &mut std::io::stdout(),
+            &display,
+            errors_by_id
+                .iter()
+                .chain(&warnings_by_id)
+                .flat_map(|(_, violations)| violations),
+            &messages,
+            source,
+            &args,
+        )?,
+    }
+
+    if let Some(comparison) = &comparison {
+        println!("{}", comparison_summary(comparison));
+    }
+    if let Some(applied) = &baseline_applied {
+        println!("{}", baseline_summary(applied));
+    }
+    print_suppressed(&suppressed, &active_counts);
+    if binary_skipped > 0 {
+        println!(
+            "Skipped {} binary file(s); pass --no-skip-binary to check them",
+            format_count(binary_skipped)
+        );
+    }
+    if large_skipped > 0 {
+        println!(
+            "Skipped {} file(s) larger than --max-filesize; -v lists them",
+            format_count(large_skipped)
+        );
+    }
+    if timed_out > 0 {
+        println!(
+            "Stopped matching {} rule(s) against a file after --rule-timeout; the slowest rules are below",
+            format_count(timed_out)
+        );
+    }
+    if cached > 0 {
+        println!(
+            "Reused the results for {} unchanged file(s) from the cache, saving about {:.1}s",
+            format_count(cached),
+            cache_saved.as_secs_f64()
+        );
+    }
+
+    let skipped = if stats.files_skipped > 0 {
+        format!(" and skipped {}", format_count(stats.files_skipped))
+    } else {
+        String::new()
+    };
+    println!(
+        "{} from {} rule(s) (walked {} files{skipped} in {:.1}s; matched in {:.1}s; reported in {:.1}s; {:.1}s in all)",
+        count_summary(&display, &outcome),
+        format_count(stats.rules),
+        format_count(files_walked),
+        scan_duration.saturating_sub(match_duration).as_secs_f64(),
+        match_duration.as_secs_f64(),
+        report_start.elapsed().as_secs_f64(),
+        run_start.elapsed().as_secs_f64()
+    );
+    if args.stats || timed_out > 0 {
+        print_rule_times(&rule_durations);
+    }
+
+    // Prompts come last, once the report is fully written, so nothing else writes to the
+    // terminal while waiting for an answer.
+    let pending: Vec<(&str, &[Violation])> = if args.no_confirm || cancellation.is_some() {
+        Vec::new()
+    } else {
+        warnings_by_id
+            .iter()
+            .map(|(rule_id, violations)| (rule_id.as_str(), violations.as_slice()))
+            .collect()
+    };
+    let mut unreviewed_warnings = 0;
+    if failing {
+        unreviewed_warnings = pending.len();
+    } else if let Some(rejected) = confirm_warnings(
+        &pending,
+        &config.rules,
+        &messages,
+        &display,
+        &outcome,
+        source,
+        &args,
+    )? {
+        return Ok(rejected);
+    }
+
+    if let Some(cancellation) = cancellation {
+        eprintln!("{}", cancellation.banner());
+    } else if failing {
+        if unreviewed_warnings > 0 {
+            failure_line(
+                &outcome,
+                &format!(
+                    "Failing due to errors ({unreviewed_warnings} warning rule(s) not individually reviewed)"
+                ),
+            );
+        } else {
+            failure_line(&outcome, "Failing due to errors");
+        }
+    }
+    if args.notify {
+        notifier.notify(
+            outcome.errors,
+            outcome.warnings,
+            &count_summary(&display, &outcome),
+        );
+    }
+
+    finish(outcome, notifier)
+}
+
+/// Prints, for `-v` after a full walk, the config's globs that matched none of the files
+/// they were checked against, which usually means they're stale or mistyped. Includes a
+/// rule's languages added aren't reported.
+fn print_unmatched_globs(
+    config: &Config,
+    rules: &[Rule],
+    overrides: &[linty::Override],
+    excludes: &PathGlobs,
+) {
+    for pattern in excludes.unmatched() {
+        log::log!(
+            log::Level::Info,
+            "Global excludes pattern {pattern} matched no file"
+        );
+    }
+    for rule in rules {
+        let own_includes = config
+            .rules
+            .iter()
+            .find(|rule_config| rule_config.id == rule.id)
+            .and_then(|rule_config| rule_config.includes.as_deref())
+            .unwrap_or_default();
+        for pattern in rule.includes.unmatched() {
+            if own_includes.iter().any(|own| own == pattern) {
+                log::log!(
+                    log::Level::Info,
+                    "Rule {}'s includes pattern {pattern} matched no file",
+                    rule.id
+                );
+            }
+        }
+        for pattern in rule.excludes.unmatched() {
+            log::log!(
+                log::Level::Info,
+                "Rule {}'s excludes pattern {pattern} matched no file",
+                rule.id
+            );
+        }
+    }
+    for (index, rule_override) in overrides.iter().enumerate() {
+        for pattern in rule_override.paths.unmatched() {
+            log::log!(
+                log::Level::Info,
+                "Override overrides[{index}]'s paths pattern {pattern} matched no file"
+            );
+        }
+    }
+}
+
+/// What each answer to the warning prompt does, shown for an empty or unknown answer.
+const PROMPT_HELP: &str = "  y - ignore this rule's warnings
+  n - fail on this rule, after reviewing the rest
+  a - ignore this rule and every remaining one
+  v - view the matches in context
+  q - fail now, without reviewing the rest";
+
+/// Asks about each warning rule in `pending` in turn, with the rule's description from
+/// `rules` when it has one, returning the rejected outcome if any is refused or stdin
+/// runs out before every rule is reviewed. Viewing a rule's matches shows them as
+/// `--format pretty` would, with the context read from the same place as the report's.
+fn confirm_warnings(
+    pending: &[(&str, &[Violation])],
+    rules: &[RuleConfig],
+    messages: &HashMap<&str, &str>,
+    display: &DisplayTable,
+    outcome: &Outcome,
+    source: pretty::Source,
+    args: &Args,
+) -> anyhow::Result<Option<Outcome>> {
+    let mut refused: Vec<String> = Vec::new();
+    let rejected = |refused: Vec<String>| Outcome {
+        exit_code: Reason::Rejected.exit_code(),
+        reason: Reason::Rejected,
+        failed_rules: refused,
+        ..outcome.clone()
+    };
+    std::io::stderr().flush()?;
+    'rules: for (index, (rule_id, violations)) in pending.iter().enumerate() {
+        let message = messages.get(rule_id).copied().unwrap_or_default();
+        println!(
+            "[{index} of {} warning rules reviewed] {}: {}",
+            pending.len(),
+            display.bold(rule_id),
+            display.paint(Severity::Warning, message)
+        );
+        let description = rules
+            .iter()
+            .find(|rule| rule.id == *rule_id)
+            .and_then(|rule| rule.description.as_deref());
+        if let Some(description) = description {
+            println!("{description}");
+        }
+        loop {
+            print!("Ignore warning? [y,n,a,v,q,?] ");
+            std::io::stdout().flush()?;
+            let mut input = String::new();
+            if std::io::stdin().read_line(&mut input)? == 0 {
+                eprintln!("Failing due to warnings (no answer on stdin)");
+                refused.push((*rule_id).to_owned());
+                return Ok(Some(rejected(refused)));
+            }
+            match input.trim() {
+                "y" => continue 'rules,
+                "n" => {
+                    refused.push((*rule_id).to_owned());
+                    continue 'rules;
+                }
+                "a" => break 'rules,
+                "v" => {
+                    for violation in violations.iter() {
+                        pretty::print_violation(
+                            &mut std::io::stdout(),
+                            display,
+                            violation,
+                            message,
+                            args.max_listed_lines,
+                            args.context,
+                            context_source(violation, source),
+                        )?;
+                    }
+                }
+                "q" => {
+                    eprintln!("Failing due to warnings");
+                    refused.push((*rule_id).to_owned());
+                    return Ok(Some(rejected(refused)));
+                }
+                _ => println!("{PROMPT_HELP}"),
+            }
+        }
+    }
+    if refused.is_empty() {
+        return Ok(None);
+    }
+    eprintln!("Failing due to warnings");
+    Ok(Some(rejected(refused)))
+}
+
+/// Decides how a finished scan exits, the same way for every output format.
+fn compute_outcome(
+    violations: &[Violation],
+    dropped: &[(String, Severity)],
+    comparison: Option<&Comparison>,
+    args: &Args,
+    cancellation: Option<cancel::Cancellation>,
+) -> Outcome {
+    // Overflow notes aren't violations themselves, but the ones they stand in for count.
+    let counted: Vec<(&str, Severity)> = violations
+        .iter()
+        .filter(|violation| violation.rule_id != scan::OVERFLOW_RULE_ID)
+        .map(|violation| (violation.rule_id.as_str(), violation.severity))
+        .chain(
+            dropped
+                .iter()
+                .map(|(id, severity)| (id.as_str(), *severity)),
+        )
+        .collect();
+    let errors = counted
+        .iter()
+        .filter(|(_, severity)| matches!(severity, Severity::Error))
+        .count();
+    let warnings = counted.len() - errors;
+
+    let failed_on_errors = match (comparison, args.fail_on_new) {
+        (Some(comparison), true) => comparison.new_errors > 0,
+        _ => errors > 0,
+    };
+    let over_budget = warning_budget(args)
+        .is_some_and(|budget| budgeted_warnings(violations, dropped, comparison, args) > budget);
+    let failing = failed_on_errors || over_budget;
+
+    let mut failed_rules: Vec<String> = Vec::new();
+    if failing {
+        failed_rules = counted
+            .iter()
+            .filter(|(_, severity)| over_budget || matches!(severity, Severity::Error))
+            .map(|(id, _)| id.to_string())
+            .collect();
+        failed_rules.sort();
+        failed_rules.dedup();
+    }
+
+    let (reason, exit_code) = match cancellation {
+        Some(cancellation) => cancelled_outcome(cancellation, args.timeout_ok),
+        None if failing && failed_on_errors => (Reason::Violations, Reason::Violations.exit_code()),
+        None if failing => (Reason::WarningBudget, Reason::WarningBudget.exit_code()),
+        None => (Reason::Clean, Reason::Clean.exit_code()),
+    };
+    Outcome {
+        exit_code,
+        reason,
+        errors,
+        warnings,
+        failed_rules,
+    }
+}
+
+/// One line on stderr for a violation in a `--quiet` run, like
+/// `src/main.rs:12: error NoTodo: No todos`, at the violation's first line.
+fn print_terse_violation(
+    display: &DisplayTable,
+    violation: &Violation,
+    messages: &HashMap<&str, &str>,
+) {
+    let location = match violation.lines().first() {
+        Some(line) => format!("{}:{line}", violation.file),
+        None => violation.file.clone(),
+    };
+    let rule_message = messages
+        .get(violation.rule_id.as_str())
+        .copied()
+        .unwrap_or_default();
+    eprintln!(
+        "{}: {} {}: {}",
+        display.dim(&location),
+        display.paint(violation.severity, display.label(violation.severity)),
+        display.bold(&violation.rule_id),
+        violation
+            .matches
+            .first()
+            .map_or(rule_message, |m| m.message(rule_message))
+    );
+}
+
+/// Lists the rules that spent the longest matching, for `--stats` and after a rule goes
+/// over `--rule-timeout`.
+fn print_rule_times(rule_durations: &HashMap<String, Duration>) {
+    let mut slowest: Vec<(&String, &Duration)> = rule_durations.iter().collect();
+    slowest.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    if slowest.is_empty() {
+        return;
+    }
+    println!("Slowest rules by time spent matching:");
+    for (rule_id, duration) in slowest.into_iter().take(SLOWEST_RULES_LISTED) {
+        println!("  {rule_id}: {:.1}ms", duration.as_secs_f64() * 1000.0);
+    }
+}
+
+/// How many rules `--stats` lists.
+const SLOWEST_RULES_LISTED: usize = 10;
+
+/// The counts part of the summary line, like `3 error(s) and 7 warning(s)`.
+fn count_summary(display: &DisplayTable, outcome: &Outcome) -> String {
+    format!(
+        "{} {}(s) and {} {}(s)",
+        outcome.errors,
+        display.label(Severity::Error),
+        outcome.warnings,
+        display.label(Severity::Warning)
+    )
+}
+
+/// How a cancelled run exits. `--timeout-ok` lets a timed-out run pass.
+fn cancelled_outcome(cancellation: cancel::Cancellation, timeout_ok: bool) -> (Reason, i32) {
+    match cancellation {
+        cancel::Cancellation::Interrupted => (Reason::Interrupted, cancellation.exit_code()),
+        cancel::Cancellation::TimedOut if timeout_ok => (Reason::TimedOut, 0),
+        cancel::Cancellation::TimedOut => (Reason::TimedOut, cancellation.exit_code()),
+    }
+}
+
+/// Whether stdout gets the short or pretty report, rather than a structured format or a
+/// scripting mode's output that progress lines would get mixed into.
+fn text_report(args: &Args) -> bool {
+    matches!(args.format, Format::Short | Format::Pretty) && !args.count && !args.files_with_matches
+}
+
+/// Prints, for `--files-with-matches`, each file with a violation once, sorted, ended by a
+/// newline or with `null` a NUL.
+fn write_files_with_matches(
+    out: &mut dyn Write,
+    violations: &[Violation],
+    null: bool,
+) -> std::io::Result<()> {
+    let paths: BTreeSet<&Path> = violations
+        .iter()
+        .map(|violation| violation.path.as_path())
+        .collect();
+    for path in paths {
+        write!(
+            out,
+            "{}{}",
+            display_path(path),
+            if null { '\0' } else { '\n' }
+        )?;
+    }
+    Ok(())
+}
+
+/// Prints, for `--count`, how many files and matches each rule with a violation found, in
+/// the report's order, then the totals.
+fn write_counts(
+    out: &mut dyn Write,
+    violations: Vec<Violation>,
+    rules: &[RuleConfig],
+) -> std::io::Result<()> {
+    let mut all_paths = BTreeSet::new();
+    let mut all_matches = 0;
+    for (rule_id, violations) in group_by_rule(violations, rules) {
+        let paths: BTreeSet<PathBuf> = violations
+            .iter()
+            .map(|violation| violation.path.clone())
+            .collect();
+        let matches: usize = violations
+            .iter()
+            .map(|violation| violation.matches.len())
+            .sum();
+        writeln!(
+            out,
+            "{rule_id}: {} file(s), {matches} match(es)",
+            paths.len()
+        )?;
+        all_paths.extend(paths);
+        all_matches += matches;
+    }
+    writeln!(
+        out,
+        "Total: {} file(s), {all_matches} match(es)",
+        all_paths.len()
+    )
+}
+
+/// Formats a count with thousands separators, like `12,431`.
+fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// Prints how many matches of each rule suppression comments hid, flagging rules with more
+/// suppressed matches than active ones, which likely need tuning.
+fn print_suppressed(suppressed: &BTreeMap<String, usize>, active: &HashMap<String, usize>) {
+    for (rule_id, &count) in suppressed {
+        let active = active.get(rule_id).copied().unwrap_or_default();
+        let tuning = if count > active {
+            "; more are suppressed than reported, so the rule may need tuning"
+        } else {
+            ""
+        };
+        println!("Suppressed {count} match(es) of {rule_id} inline, {active} reported{tuning}");
+    }
+}
+
+fn baseline_summary(applied: &baseline::Applied) -> String {
+    let by_rule = |counts: &BTreeMap<String, usize>| {
+        counts
+            .iter()
+            .map(|(rule_id, count)| format!("{rule_id}: {count}"))
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+    let mut summary = format!(
+        "{} known match(es) hidden by the baseline",
+        applied.known_total()
+    );
+    if !applied.known.is_empty() {
+        summary.push_str(&format!(" ({})", by_rule(&applied.known)));
+    }
+    if !applied.resolved.is_empty() {
+        summary.push_str(&format!(
+            "; {} resolved ({}), consider regenerating the baseline with linty baseline",
+            applied.resolved_total(),
+            by_rule(&applied.resolved)
+        ));
+    }
+    summary
+}
+
+fn comparison_summary(comparison: &Comparison) -> String {
+    format!(
+        "{} new violation(s), {} fixed, {} unchanged since the previous report",
+        comparison.new, comparison.fixed, comparison.unchanged
+    )
+}
+
+/// How many warnings the run may have before it fails: none with `--error-on-warning`,
+/// and `--max-warnings` otherwise.
+fn warning_budget(args: &Args) -> Option<usize> {
+    if args.error_on_warning {
+        Some(0)
+    } else {
+        args.max_warnings
+    }
+}
+
+/// The warnings checked against the budget: every match of a warning rule left after
+/// suppressions and the baseline, or with `--fail-on-new` the new warning violations.
+/// A violation `max_violations_per_file` dropped counts as one match.
+fn budgeted_warnings(
+    violations: &[Violation],
+    dropped: &[(String, Severity)],
+    comparison: Option<&Comparison>,
+    args: &Args,
+) -> usize {
+    if let (Some(comparison), true) = (comparison, args.fail_on_new) {
+        return comparison.new_warnings;
+    }
+    let matches: usize = violations
+        .iter()
+        .filter(|violation| {
+            violation.rule_id != scan::OVERFLOW_RULE_ID
+                && matches!(violation.severity, Severity::Warning)
+        })
+        .map(|violation| violation.matches.len())
+        .sum();
+    matches
+        + dropped
+            .iter()
+            .filter(|(_, severity)| matches!(severity, Severity::Warning))
+            .count()
+}
+
+/// The line saying a `--max-warnings` budget was exceeded, like
+/// `warnings: 14 (budget 10), failing`.
+fn budget_line(
+    violations: &[Violation],
+    dropped: &[(String, Severity)],
+    comparison: Option<&Comparison>,
+    args: &Args,
+) -> Option<String> {
+    let budget = args.max_warnings.filter(|_| !args.error_on_warning)?;
+    let warnings = budgeted_warnings(violations, dropped, comparison, args);
+    (warnings > budget).then(|| format!("warnings: {warnings} (budget {budget}), failing"))
+}
+
+/// Applies the fixes of `violations`' matches to their files, or with `dry_run` prints
+/// them as a diff, and returns how many files changed. Prints a line per file either way.
+/// The `git diff` arguments `--changed-lines-only` reads the changed lines from.
+fn changed_lines_diff_args(args: &Args) -> Vec<&str> {
+    match &args.diff_base {
+        Some(diff_base) => diff_base_args(diff_base).to_vec(),
+        None => vec!["--staged", "--"],
+    }
+}
+
+/// The violations the report keeps after `--changed-lines-only`, age limits, and
+/// `--baseline`, so `--fix` only rewrites matches the run goes on to report.
+fn reported_violations(
+    args: &Args,
+    rules: &[Rule],
+    mut violations: Vec<Violation>,
+) -> anyhow::Result<Vec<Violation>> {
+    if args.changed_lines_only {
+        let repo = git_repo("--changed-lines-only");
+        violations = changed_lines::ChangedLines::from_git(&repo, &changed_lines_diff_args(args))
+            .retain(violations);
+    }
+    let mut violations =
+        blame::apply_age_limits(violations, rules, &mut BlameCache::default(), false);
+    if let Some(baseline_path) = &args.baseline {
+        violations = baseline::apply(Path::new(baseline_path), violations)?.0;
+    }
+    Ok(violations)
+}
+
+fn fix_files(violations: &[Violation], dry_run: bool, format: Format) -> anyhow::Result<usize> {
+    let mut edits: BTreeMap<&Path, (&str, Vec<fix::Edit>)> = BTreeMap::new();
+    for violation in violations {
+        for m in &violation.matches {
+            let Some(replacement) = &m.replacement else {
+                continue;
+            };
+            edits
+                .entry(&violation.path)
+                .or_insert_with(|| (&violation.file, Vec::new()))
+                .1
+                .push(fix::Edit {
+                    line: m.line,
+                    column: m.column,
+                    end_line: m.end_line,
+                    end_column: m.end_column,
+                    replacement,
+                });
+        }
+    }
+
+    let mut changed = 0;
+    for (path, (file, file_edits)) in edits {
+        let contents = std::fs::read_to_string(path)?;
+        let fixes = fix::Fixes::new(&contents, file_edits);
+        let mut skipped = String::new();
+        if fixes.skipped > 0 {
+            skipped.push_str(&format!(
+                " ({} overlapping fix(es) left for a later run)",
+                fixes.skipped
+            ));
+        }
+        if fixes.stale > 0 {
+            skipped.push_str(&format!(
+                " ({} fix(es) skipped, since the file changed after it was scanned)",
+                fixes.stale
+            ));
+        }
+        if dry_run {
+            // Structured formats keep stdout for the report itself.
+            if matches!(format, Format::Short | Format::Pretty) {
+                print!("{}", fixes.diff(file, &contents));
+            } else {
+                eprint!("{}", fixes.diff(file, &contents));
+            }
+            eprintln!("Would fix {} match(es) in {file}{skipped}", fixes.len());
+        } else {
+            write_file_atomically(path, fixes.apply(&contents).as_bytes())?;
+            eprintln!("Fixed {} match(es) in {file}{skipped}", fixes.len());
+        }
+        changed += 1;
+    }
+    Ok(changed)
+}
+
+/// Writes a report to `--output`'s `path` atomically, creating its directory if needed.
+/// Failing to is an environment failure rather than a finding, so it exits as one.
+fn write_report_file(path: &Path, report: &[u8]) {
+    let written = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            std::fs::create_dir_all(parent).map_err(anyhow::Error::from)
+        }
+        _ => Ok(()),
+    }
+    .and_then(|()| write_file_atomically(path, report));
+    if let Err(err) = written {
+        eprintln!("Failed to write the report to {}: {err}", path.display());
+        outcome::fail(Reason::IoError);
+    }
+    eprintln!("Wrote the report to {}", path.display());
+}
+
+/// Writes to a temporary file next to `path` and renames it into place, so readers
+/// never observe a partially-written file. An existing file's permissions are kept.
+fn write_file_atomically(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Invalid output path {}", path.display()))?;
+    let mut temp_name = OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    std::fs::write(&temp_path, contents)?;
+    let mut result = match std::fs::metadata(path) {
+        Ok(metadata) => std::fs::set_permissions(&temp_path, metadata.permissions()),
+        Err(_) => Ok(()),
+    };
+    if result.is_ok() {
+        result = std::fs::rename(&temp_path, path);
+    }
+    if let Err(err) = result {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(err.into());
+    }
+    Ok(())
+}
+
+/// Parses a `--max-filesize` like `4096`, `500K`, `10M`, or `1G`, where the suffixes are
+/// powers of 1024.
+fn parse_file_size(value: &str) -> Result<u64, String> {
+    let trimmed = value.trim();
+    let (digits, multiplier) = match trimmed.char_indices().last() {
+        Some((index, suffix)) if suffix.is_ascii_alphabetic() => {
+            let multiplier = match suffix.to_ascii_uppercase() {
+                'K' => 1 << 10,
+                'M' => 1 << 20,
+                'G' => 1 << 30,
+                _ => return Err(format!("unknown size suffix {suffix:?}; use K, M, or G")),
+            };
+            (&trimmed[..index], multiplier)
+        }
+        _ => (trimmed, 1),
+    };
+    let count: u64 = digits
+        .parse()
+        .map_err(|_| format!("{value:?} isn't a size like 500K or 10M"))?;
+    count
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("{value:?} is too large"))
+}
+
+/// Reports one rule's violations in text output, listing at most `max_listed` lines or
+/// matches per file. `source` is where the run read the files from.
+fn report_violations(
+    out: &mut dyn Write,
+    display: &DisplayTable,
+    violations: &[Violation],
+    messages: &HashMap<&str, &str>,
+    max_listed: usize,
+    source: pretty::Source,
+    args: &Args,
+) -> std::io::Result<()> {
+    let (shown, collapsed) = if args.collapse_similar {
+        collapse::collapse_similar(violations)
+    } else {
+        (violations.iter().collect(), 0)
+    };
+    for violation in shown {
+        report_violation(out, display, violation, messages, max_listed, source, args)?;
+    }
+    if collapsed > 0 {
+        writeln!(
+            out,
+            "… and {collapsed} more files with the same finding (run without --collapse-similar to list all)"
+        )?;
+    }
+    Ok(())
+}
+
+/// Where the lines shown around `violation`'s matches come from, given where the run read
+/// files from. Pre-commit runs check what's staged, so the context comes from there too,
+/// except for untracked files, which have nothing staged.
+fn context_source<'a>(violation: &Violation, source: pretty::Source<'a>) -> pretty::Source<'a> {
+    match source {
+        pretty::Source::Staged(_) if violation.untracked => pretty::Source::Disk,
+        source => source,
+    }
+}
+
+fn report_violation(
+    out: &mut dyn Write,
+    display: &DisplayTable,
+    violation: &Violation,
+    messages: &HashMap<&str, &str>,
+    max_listed: usize,
+    source: pretty::Source,
+    args: &Args,
+) -> std::io::Result<()> {
+    if args.format == Format::Pretty {
+        let message = messages
+            .get(violation.rule_id.as_str())
+            .copied()
+            .unwrap_or_default();
+        pretty::print_violation(
+            out,
+            display,
+            violation,
+            message,
+            max_listed,
+            args.context,
+            context_source(violation, source),
+        )
+    } else {
+        print_violation(out, display, violation, max_listed)?;
+        if args.show_matches {
+            print_matches(out, display, violation, max_listed)?;
+        }
+        Ok(())
+    }
+}
+
+/// Groups violations by rule, with the rules in config order and notes like
+/// `linty::file-overflow` after them by id, so the report reads in a stable order. Each
+/// rule's violations stay sorted by path.
+fn group_by_rule(
+    violations: Vec<Violation>,
+    rules: &[RuleConfig],
+) -> Vec<(String, Vec<Violation>)> {
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    for (position, rule) in rules.iter().enumerate() {
+        positions.entry(&rule.id).or_insert(position);
+    }
+    let mut by_id: BTreeMap<String, Vec<Violation>> = BTreeMap::new();
+    for violation in violations {
+        by_id
+            .entry(violation.rule_id.to_owned())
+            .or_default()
+            .push(violation);
+    }
+    let mut grouped: Vec<(String, Vec<Violation>)> = by_id.into_iter().collect();
+    grouped.sort_by_key(|(rule_id, _)| {
+        positions
+            .get(rule_id.as_str())
+            .copied()
+            .unwrap_or(usize::MAX)
+    });
+    grouped
+}
+
+/// Reports violations a file at a time for `--group-by file`, with the files sorted by
+/// path. `source` is where the run read the files from.
+fn report_files<'a>(
+    out: &mut dyn Write,
+    display: &DisplayTable,
+    violations: impl Iterator<Item = &'a Violation>,
+    messages: &HashMap<&str, &str>,
+    source: pretty::Source,
+    args: &Args,
+) -> std::io::Result<()> {
+    let mut by_file: BTreeMap<&str, Vec<&Violation>> = BTreeMap::new();
+    for violation in violations {
+        by_file.entry(&violation.file).or_default().push(violation);
+    }
+    for violations in by_file.values() {
+        if args.format == Format::Pretty {
+            let source = context_source(violations[0], source);
+            pretty::print_file(out, display, violations, messages, args.context, source)?;
+        } else {
+            print_file(out, display, violations, messages, args.max_listed_lines)?;
+        }
+    }
+    Ok(())
+}
+
+/// A file's matches across its violations, ordered by position and then rule, with the
+/// path rules' matches first.
+fn file_matches<'a>(violations: &[&'a Violation]) -> Vec<(&'a Violation, &'a scan::Match)> {
+    let mut matches: Vec<(&Violation, &scan::Match)> = violations
+        .iter()
+        .flat_map(|violation| violation.matches.iter().map(move |m| (*violation, m)))
+        .collect();
+    matches.sort_by(|(a, a_match), (b, b_match)| {
+        (a_match.line, a_match.column, &a.rule_id).cmp(&(b_match.line, b_match.column, &b.rule_id))
+    });
+    matches
+}
+
+/// Lists one file's matches for `--group-by file` as `line:col: severity rule: message`,
+/// at most `max_listed` of them (0 for no limit).
+fn print_file(
+    out: &mut dyn Write,
+    display: &DisplayTable,
+    violations: &[&Violation],
+    messages: &HashMap<&str, &str>,
+    max_listed: usize,
+) -> std::io::Result<()> {
+    let first = violations[0];
+    let untracked = if first.untracked { " (untracked)" } else { "" };
+    writeln!(out, "{}{untracked}", display.bold(&first.file))?;
+    let matches = file_matches(violations);
+    let shown = match max_listed {
+        0 => matches.len(),
+        limit => limit.min(matches.len()),
+    };
+    for (violation, m) in &matches[..shown] {
+        let rule_message = messages
+            .get(violation.rule_id.as_str())
+            .copied()
+            .unwrap_or_default();
+        let location = if m.has_position() {
+            format!("{}:{}: ", m.line, m.column)
+        } else {
+            String::new()
+        };
+        writeln!(
+            out,
+            "  {}{} {}: {}",
+            display.dim(&location),
+            display.paint(violation.severity, display.label(violation.severity)),
+            display.bold(&violation.rule_id),
+            m.message(rule_message)
+        )?;
+        if let Some(info) = violation.blame.get(&m.line) {
+            writeln!(out, "    {} on {}", info.author, info.date)?;
+        }
+    }
+    if shown < matches.len() {
+        writeln!(out, "  … and {} more", matches.len() - shown)?;
+    }
+    let discarded: usize = violations.iter().map(|violation| violation.discarded).sum();
+    if discarded > 0 {
+        writeln!(out, "  {discarded} more match(es) discarded")?;
+    }
+    Ok(())
+}
+
+/// Longest matched text `--show-matches` prints before cutting it short.
+const MAX_SNIPPET_CHARS: usize = 80;
+
+fn print_matches(
+    out: &mut dyn Write,
+    display: &DisplayTable,
+    violation: &Violation,
+    max_listed_lines: usize,
+) -> std::io::Result<()> {
+    let limit = if max_listed_lines == 0 {
+        usize::MAX
+    } else {
+        max_listed_lines
+    };
+    for m in violation.matches.iter().take(limit) {
+        let snippet = if m.text.chars().count() > MAX_SNIPPET_CHARS {
+            let cut: String = m.text.chars().take(MAX_SNIPPET_CHARS - 1).collect();
+            format!("{cut}…")
+        } else {
+            m.text.clone()
+        };
+        if m.has_position() {
+            let location = format!("{}:{}:{}", violation.file, m.line, m.column);
+            writeln!(out, "  {}: {snippet}", display.dim(&location))?;
+        } else {
+            writeln!(out, "  {snippet}")?;
+        }
+    }
+    if violation.matches.len() > limit {
+        writeln!(out, "  … and {} more", violation.matches.len() - limit)?;
+    }
+    Ok(())
+}
+
+fn print_violation(
+    out: &mut dyn Write,
+    display: &DisplayTable,
+    violation: &Violation,
+    max_listed_lines: usize,
+) -> std::io::Result<()> {
+    let title = display.title(violation.severity);
+    let untracked = if violation.untracked {
+        "untracked "
+    } else {
+        ""
+    };
+    let spans = violation.line_spans();
+    // Path rules match the file as a whole.
+    if spans.is_empty() {
+        return writeln!(
+            out,
+            "{} present in {untracked}file: {}",
+            display.paint(violation.severity, &title),
+            display.dim(&violation.file)
+        );
+    }
+    if violation.report_once {
+        return writeln!(
+            out,
+            "{} present in {untracked}file: {}, first occurrence at line {} (report_once)",
+            display.paint(violation.severity, &title),
+            display.dim(&violation.file),
+            spans[0].0
+        );
+    }
+    let rest = format!(" present in {untracked}file: {}, lines: ", violation.file);
+    let prefix_width = title.chars().count() + rest.chars().count();
+    writeln!(
+        out,
+        "{} present in {untracked}file: {}, lines: {}",
+        display.paint(violation.severity, &title),
+        display.dim(&violation.file),
+        line_ranges::format_line_list(
+            &spans,
+            max_listed_lines,
+            line_ranges::output_width(),
+            prefix_width,
+        )
+    )?;
+    // A message with placeholders reads differently for each match.
+    let shown = match max_listed_lines {
+        0 => violation.matches.len(),
+        limit => limit.min(violation.matches.len()),
+    };
+    for m in &violation.matches[..shown] {
+        if let Some(message) = &m.message {
+            writeln!(
+                out,
+                "  {}: {message}",
+                display.dim(&line_ranges::format_span(m.line, m.last_line()))
+            )?;
+        }
+    }
+    if violation.discarded > 0 {
+        writeln!(out, "  {} more match(es) discarded", violation.discarded)?;
+    }
+    print_blame(out, violation)
+}
+
+fn print_blame(out: &mut dyn Write, violation: &Violation) -> std::io::Result<()> {
+    for line in &violation.lines() {
+        if let Some(info) = violation.blame.get(line) {
+            match info.age_days {
+                Some(age_days) => writeln!(
+                    out,
+                    "  line {line}: {} on {} ({age_days} days old)",
+                    info.author, info.date
+                )?,
+                None => writeln!(out, "  line {line}: {} on {}", info.author, info.date)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs `linty grep`: a single rule built from the pattern, walked over the given paths
+/// like a normal run, printing each match as `path:line:col: text`.
+fn grep(args: &Args, grep_args: &GrepArgs) -> anyhow::Result<Outcome> {
+    let pattern = if grep_args.fixed_strings {
+        regex::escape(&grep_args.pattern)
+    } else {
+        grep_args.pattern.clone()
+    };
+    let regex = match RegexBuilder::new(&pattern)
+        .case_insensitive(grep_args.ignore_case)
+        .build()
+    {
+        Ok(regex) => regex,
+        Err(err) => {
+            eprintln!("Invalid pattern {:?}: {err}", grep_args.pattern);
+            outcome::fail(Reason::ConfigError);
+        }
+    };
+    let rule = Rule {
+        id: String::from("grep"),
+        regex,
+        regex_flags: RegexFlags {
+            case_insensitive: grep_args.ignore_case,
+            ..RegexFlags::default()
+        },
+        severity: Severity::Error,
+        includes: PathGlobs::empty(),
+        excludes: PathGlobs::empty(),
+        directory: GlobSet::empty(),
+        unicode_normalize: None,
+        scan_window: None,
+        region: None,
+        allow: Vec::new(),
+        word_boundary: false,
+        message: None,
+        fix: None,
+        max_age_days: None,
+        escalate_after_days: None,
+        max_match_length: scan::DEFAULT_MAX_MATCH_LENGTH,
+        binary: false,
+        kind: Box::new(rule_kind::Pattern { report_once: false }),
+        registered: false,
+        line_local: chunked::is_line_local(&pattern, false, false),
+        report_once: false,
+    };
+
+    let roots = if grep_args.paths.is_empty() {
+        vec![PathBuf::from("./")]
+    } else {
+        grep_args.paths.clone()
+    };
+    let result = scan::scan(
+        &walk_options(args, roots, None)?,
+        &[rule],
+        &[],
+        &std::env::current_dir()?,
+    )?;
+    print_problems(&result);
+
+    for violation in &result.violations {
+        let path = display_path(&violation.path);
+        for grep_match in &violation.matches {
+            println!(
+                "{path}:{}:{}: {}",
+                grep_match.line, grep_match.column, grep_match.text
+            );
+        }
+    }
+
+    if let Some(cancellation) = cancel::cancelled() {
+        eprintln!("{}", cancellation.banner());
+        let (reason, exit_code) = cancelled_outcome(cancellation, args.timeout_ok);
+        return Ok(Outcome::new(reason, exit_code));
+    }
+    if grep_args.fail_on_match && !result.violations.is_empty() {
+        return Ok(Outcome {
+            errors: result.violations.len(),
+            failed_rules: vec![String::from("grep")],
+            ..Outcome::new(Reason::Violations, Reason::Violations.exit_code())
+        });
+    }
+    Ok(Outcome::clean())
+}
+
+/// Picks the config file: `--config-path` if given, otherwise whichever default config
+/// exists in the current directory or, failing that, the nearest parent directory. Inside
+/// a repository, the outermost config up to its root wins instead, and the ones between
+/// it and the current directory apply as nested configs. Having more than one default
+/// config in a directory is an error, since silently preferring one would hide a stale
+/// file.
+fn locate_config(config_path: Option<&str>) -> ConfigLocation {
+    if let Some(config_path) = config_path {
+        return ConfigLocation {
+            path: config_path.to_owned(),
+            selected_by: "from --config-path",
+            glob_base: PathBuf::new(),
+        };
+    }
+
+    let current_dir = std::env::current_dir().unwrap_or_default();
+    let mut up = PathBuf::new();
+    let mut candidates = Vec::new();
+    for dir in current_dir.ancestors() {
+        let found: Vec<PathBuf> = DEFAULT_CONFIG_PATH_STRS
+            .into_iter()
+            .map(|name| up.join(name))
+            .filter(|path| path.is_file())
+            .collect();
+        if found.len() > 1 {
+            let names: Vec<String> = found
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect();
+            eprintln!(
+                "Found multiple config files in {}: {}",
+                dir.display(),
+                names.join(", ")
+            );
+            eprintln!("Remove all but one, or pick one with --config-path");
+            outcome::fail(Reason::ConfigError);
+        }
+        if let Some(path) = found.into_iter().next() {
+            candidates.push(ConfigLocation {
+                path: path.to_string_lossy().into_owned(),
+                selected_by: if up.as_os_str().is_empty() {
+                    "found in the current directory"
+                } else {
+                    "found in a parent directory"
+                },
+                glob_base: current_dir
+                    .strip_prefix(dir)
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default(),
+            });
+        }
+        // A config outside the repository belongs to some other project.
+        if up.join(".git").exists() {
+            if let Some(outermost) = candidates.pop() {
+                return outermost;
+            }
+            break;
+        }
+        up.push("..");
+    }
+    if !candidates.is_empty() {
+        return candidates.swap_remove(0);
+    }
+
+    ConfigLocation {
+        path: DEFAULT_CONFIG_PATH_STR.to_owned(),
+        selected_by: "found in the current directory",
+        glob_base: PathBuf::new(),
+    }
+}
+
+fn absolute_path(path: &str) -> PathBuf {
+    linty::canonicalize(Path::new(path)).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+/// Reads and parses the config at `config_path`, or from standard input when it's `-`.
+/// `format` overrides the format implied by the file extension, and defaults to JSON.
+fn read_config(config_path: &str, format: Option<ConfigFormat>) -> Config {
+    let mut contents = String::new();
+    let source = if config_path == STDIN_CONFIG_PATH {
+        if let Err(err) = std::io::stdin().read_to_string(&mut contents) {
+            eprintln!("Failed to read config from standard input: {err}");
+            outcome::fail(Reason::IoError);
+        }
+        String::from("config from standard input")
+    } else {
+        let Ok(mut file) = File::open(config_path) else {
+            eprintln!(
+                "Failed to find config file; do you need to create a .lintyconfig.json file?"
+            );
+            eprintln!(
+                "Try running `linty init`, or pass --use-defaults to run the built-in rules!"
+            );
+            outcome::fail(Reason::ConfigError);
+        };
+        file.read_to_string(&mut contents)
+            .expect("Unexpected failure while reading config file");
+        format!("config file at {config_path}")
+    };
+
+    let format = format.unwrap_or_else(|| ConfigFormat::from_path(Path::new(config_path)));
+    let display = config_path_display(config_path);
+    let parsed = if LENIENT_CONFIG.load(Ordering::Relaxed) {
+        linty::parse_config_lenient(&contents, format, &display)
+    } else {
+        linty::parse_config(&contents, format, &display).map(|config| (config, Vec::new()))
+    };
+    match parsed {
+        Ok((config, unknown)) => {
+            for field in unknown {
+                eprintln!("Warning: In {source}, ignoring {field}");
+            }
+            config
+        }
+        Err(linty::Error::Parse { message, rule, .. }) => {
+            eprintln!("Failed to parse {source} as {}: {message}", format.name());
+            if let Some(rule) = rule {
+                eprintln!("The problem is in {rule}");
+            }
+            if !LENIENT_CONFIG.load(Ordering::Relaxed) && message.starts_with("unknown field") {
+                eprintln!(
+                    "Pass --lenient-config to ignore fields this version of linty doesn't know"
+                );
+            }
+            outcome::fail(Reason::ConfigError);
+        }
+        Err(err) => {
+            eprintln!("Invalid {source}: {err}");
+            outcome::fail(Reason::ConfigError);
+        }
+    }
+}
+
+/// How to show a config path in messages: absolute, or "standard input" for `-`.
+fn config_path_display(path: &str) -> String {
+    if path == STDIN_CONFIG_PATH {
+        String::from("standard input")
+    } else {
+        absolute_path(path).display().to_string()
+    }
+}
+
+fn builtin_defaults() -> Config {
+    let mut config: Config =
+        serde_json::from_str(BUILTIN_DEFAULTS_CONFIG).expect("Built-in default config is valid");
+    for rule in &mut config.rules {
+        rule.source = Some(BUILTIN_DEFAULTS.to_owned());
+    }
+    config
+}
+
+/// Prints the config the scan runs with: extends and rule inheritance are already merged
+/// in, so they're dropped, and every optional rule field shows its effective value.
+fn print_config(config: &Config, format: ConfigFormat) -> anyhow::Result<()> {
+    let mut config = config.clone();
+    config.extends = None;
+    for rule in &mut config.rules {
+        rule.extends = None;
+        rule.includes.get_or_insert_with(Vec::new);
+        rule.excludes.get_or_insert_with(Vec::new);
+        rule.enabled.get_or_insert(true);
+        rule.tags.get_or_insert_with(Vec::new);
+        rule.pre_commit.get_or_insert(true);
+        rule.pre_commit_only.get_or_insert(false);
+        rule.match_type.get_or_insert_default();
+        rule.mode.get_or_insert_default();
+        rule.target.get_or_insert_default();
+        rule.kind.get_or_insert_default();
+        rule.smart_case.get_or_insert(false);
+        rule.case_insensitive.get_or_insert(false);
+        rule.multi_line.get_or_insert(false);
+        rule.dot_matches_new_line.get_or_insert(false);
+        rule.crlf.get_or_insert(false);
+        rule.word_boundary.get_or_insert(false);
+        rule.deprecated.get_or_insert(false);
+        rule.max_match_length
+            .get_or_insert(scan::DEFAULT_MAX_MATCH_LENGTH);
+        rule.binary.get_or_insert(false);
+        rule.report_once.get_or_insert(false);
+    }
+
+    match format {
+        ConfigFormat::Json => {
+            serde_json::to_writer_pretty(std::io::stdout(), &config)?;
+            println!();
+        }
+        ConfigFormat::Toml => print!("{}", toml::to_string_pretty(&config)?),
+        ConfigFormat::Yaml => print!("{}", serde_yaml::to_string(&config)?),
+    }
+    Ok(())
+}
+
+/// A rule id defined by more than one source, where the rule from `winner` replaced the
+/// one from `loser`.
+struct MergedRule {
+    id: String,
+    winner: String,
+    loser: String,
+    intentional: bool,
+}
+
+/// Adds `incoming` rules to `rules`, replacing any existing rule with the same id in place
+/// and recording the replacement in `merges`.
+fn merge_rules(
+    rules: &mut Vec<RuleConfig>,
+    incoming: Vec<RuleConfig>,
+    merges: &mut Vec<MergedRule>,
+) {
+    for rule in incoming {
+        let Some(existing) = rules.iter_mut().find(|existing| existing.id == rule.id) else {
+            rules.push(rule);
+            continue;
+        };
+        merges.push(MergedRule {
+            id: rule.id.clone(),
+            winner: rule.source.clone().unwrap_or_default(),
+            loser: existing.source.clone().unwrap_or_default(),
+            intentional: rule.r#override.unwrap_or(false),
+        });
+        *existing = rule;
+    }
+}
+
+/// Adds the rules of each config at `paths`, the ones given with `--config-path` after the
+/// first, in order. A rule with the id of a rule from an earlier config fails the run
+/// unless it sets `"override": true`, in which case it replaces that rule in place. Only
+/// the `rules` and `extends` of these configs are used.
+fn add_config_files(
+    config: &mut Config,
+    paths: &[String],
+    format: Option<ConfigFormat>,
+    trust: &ConfigTrust,
+    merges: &mut Vec<MergedRule>,
+) -> anyhow::Result<()> {
+    let mut conflicts = Vec::new();
+    for path in paths {
+        log::log!(
+            log::Level::Info,
+            "Adding the rules of config {} (from --config-path)",
+            config_path_display(path)
+        );
+        let mut added = read_config(path, format);
+        merges.extend(apply_extends(&mut added, trust, path)?);
+        for rule in &added.rules {
+            let existing = config.rules.iter().find(|existing| existing.id == rule.id);
+            if let (Some(existing), false) = (existing, rule.r#override.unwrap_or(false)) {
+                conflicts.push(format!(
+                    "Rule {} from {} is already defined in {}; set \"override\": true on it to replace that rule",
+                    rule.id,
+                    rule.source.as_deref().unwrap_or_default(),
+                    existing.source.as_deref().unwrap_or_default()
+                ));
+            }
+        }
+        merge_rules(&mut config.rules, added.rules, merges);
+    }
+    if !conflicts.is_empty() {
+        for conflict in &conflicts {
+            eprintln!("{conflict}");
+        }
+        eprintln!("Failing due to rule id conflicts between --config-path files");
+        outcome::fail(Reason::ConfigError);
+    }
+    Ok(())
+}
+
+/// Fails the run for `--strict-merge` if any rule replaced another without `"override": true`.
+fn check_strict_merge(merges: &[MergedRule]) {
+    let unintentional: Vec<&MergedRule> =
+        merges.iter().filter(|merge| !merge.intentional).collect();
+    if unintentional.is_empty() {
+        return;
+    }
+    for merge in unintentional {
+        eprintln!(
+            "Rule {} from {} replaces the rule from {}; set \"override\": true on it if that's intended",
+            merge.id, merge.winner, merge.loser
+        );
+    }
+    eprintln!("Failing due to rule id conflicts (--strict-merge)");
+    outcome::fail(Reason::ConfigError);
+}
+
+/// Prepends the rules of every config listed in `extends`, letting local rules with the
+/// same id replace inherited ones. Returns the replacements made.
+fn apply_extends(
+    config: &mut Config,
+    trust: &ConfigTrust,
+    config_path: &str,
+) -> anyhow::Result<Vec<MergedRule>> {
+    let mut inherited: Vec<RuleConfig> = Vec::new();
+    let mut merges = Vec::new();
+    extend_rules(
+        config.extends.as_deref().unwrap_or_default(),
+        config_path,
+        trust,
+        &mut vec![absolute_path(config_path)],
+        &mut HashSet::new(),
+        &mut inherited,
+        &mut merges,
+    )?;
+    merge_rules(
+        &mut inherited,
+        std::mem::take(&mut config.rules),
+        &mut merges,
+    );
+    config.rules = inherited;
+    Ok(merges)
+}
+
+/// Merges the rules of each config in `extends`, after the configs those extend in turn,
+/// into `rules`. Relative paths are resolved against the directory of the config at
+/// `config_path`, and `chain` holds the configs being extended, to catch cycles.
+///
+/// A config reached along more than one path is only merged the first time, so in a
+/// diamond a rule overridden on one side isn't reverted by the other side inheriting it.
+fn extend_rules(
+    extends: &[String],
+    config_path: &str,
+    trust: &ConfigTrust,
+    chain: &mut Vec<PathBuf>,
+    merged: &mut HashSet<PathBuf>,
+    rules: &mut Vec<RuleConfig>,
+    merges: &mut Vec<MergedRule>,
+) -> anyhow::Result<()> {
+    for extended in extends {
+        if extended == BUILTIN_DEFAULTS {
+            if merged.insert(PathBuf::from(BUILTIN_DEFAULTS)) {
+                merge_rules(rules, builtin_defaults().rules, merges);
+            }
+            continue;
+        }
+        if let Err(message) = trust.check_reference(config_path, extended) {
+            eprintln!("{message}");
+            outcome::fail(Reason::ConfigError);
+        }
+        if extended.contains("://") {
+            anyhow::bail!(
+                "Unsupported extends entry {extended}; remote configs can't be extended yet"
+            );
+        }
+
+        let path = if config_path == STDIN_CONFIG_PATH {
+            PathBuf::from(extended)
+        } else {
+            Path::new(config_path)
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(extended)
+        };
+        if !path.is_file() {
+            eprintln!(
+                "Failed to find config {} extended by {}",
+                path.display(),
+                config_path_display(config_path)
+            );
+            outcome::fail(Reason::ConfigError);
+        }
+        let key = absolute_path(&path.to_string_lossy());
+        if chain.contains(&key) {
+            let cycle = chain
+                .iter()
+                .chain(std::iter::once(&key))
+                .map(|path| path.display().to_string())
+                .collect::<Vec<String>>()
+                .join(" -> ");
+            anyhow::bail!("Config extends cycle: {cycle}");
+        }
+        if merged.contains(&key) {
+            continue;
+        }
+
+        let path = path.to_string_lossy();
+        let extended_config = read_config(&path, None);
+        chain.push(key.clone());
+        extend_rules(
+            extended_config.extends.as_deref().unwrap_or_default(),
+            &path,
+            trust,
+            chain,
+            merged,
+            rules,
+            merges,
+        )?;
+        chain.pop();
+        merge_rules(rules, extended_config.rules, merges);
+        merged.insert(key);
+    }
+    Ok(())
+}
+
+/// Checks the config the same way a run would, and lists rules that replaced others.
+/// Reports every problem that would keep the config's rules from compiling in either
+/// mode, failing the run if there are any, and lists the rules that replace others.
+fn validate(config: &Config, merges: &[MergedRule]) {
+    let mut problems: Vec<String> = Vec::new();
+    for (index, rule) in config.rules.iter().enumerate() {
+        if rule.id.trim().is_empty() {
+            problems.push(format!("rules[{index}] has an empty id"));
+        }
+    }
+    // Rules that run in both modes would otherwise report their problems twice.
+    for problem in build_rules(config, false)
+        .1
+        .into_iter()
+        .chain(build_rules(config, true).1)
+    {
+        if !problems.contains(&problem) {
+            problems.push(problem);
+        }
+    }
+    if let Err(err) = generate_overrides_from_config(config) {
+        problems.push(err.to_string());
+    }
+    if !problems.is_empty() {
+        for problem in &problems {
+            eprintln!("{problem}");
+        }
+        eprintln!("Found {} problem(s) in the config", problems.len());
+        outcome::fail(Reason::ConfigError);
+    }
+
+    for merge in merges {
+        println!(
+            "Rule {} from {} overrides the rule from {}{}",
+            merge.id,
+            merge.winner,
+            merge.loser,
+            if merge.intentional {
+                ""
+            } else {
+                " (not marked \"override\": true)"
+            }
+        );
+    }
+    println!("Config is valid: {} rule(s)", config.rules.len());
+}
+
+/// Settles whether the walk includes hidden files and the files ignore files leave out: a
+/// flag decides when one is passed, then the profile, then the config's `include_hidden`
+/// and `include_ignored`. `-v` prints what was settled and what decided it.
+fn apply_walk_settings(config: &Config, profile: Option<&Profile>, args: &mut Args) {
+    let profile_source = format!("profile {}", args.profile.as_deref().unwrap_or_default());
+    let (hidden, hidden_source) = if args.hidden {
+        (true, "--hidden".to_owned())
+    } else if args.no_hidden {
+        (false, "--no-hidden".to_owned())
+    } else if let Some(hidden) = profile.and_then(|profile| profile.hidden) {
+        (hidden, profile_source.clone())
+    } else if let Some(hidden) = config.include_hidden {
+        (hidden, "the config's include_hidden".to_owned())
+    } else {
+        (false, "the default".to_owned())
+    };
+    let (no_ignore, ignore_source) = if args.no_ignore {
+        (true, "--no-ignore".to_owned())
+    } else if args.ignore {
+        (false, "--ignore".to_owned())
+    } else if let Some(ignored) = profile.and_then(|profile| profile.ignored) {
+        (ignored, profile_source)
+    } else if let Some(ignored) = config.include_ignored {
+        (ignored, "the config's include_ignored".to_owned())
+    } else {
+        (false, "the default".to_owned())
+    };
+    args.hidden = hidden;
+    args.no_ignore = no_ignore;
+    log::log!(
+        log::Level::Info,
+        "{} hidden files ({hidden_source})",
+        if hidden { "Walking" } else { "Skipping" }
+    );
+    log::log!(
+        log::Level::Info,
+        "{} ignore files ({ignore_source})",
+        if no_ignore {
+            "Not respecting"
+        } else {
+            "Respecting"
+        }
+    );
+}
+
+fn apply_profile(config: &mut Config, args: &mut Args, profile_name: &str) {
+    let Some(profile) = config
+        .profiles
+        .as_mut()
+        .and_then(|profiles| profiles.remove(profile_name))
+    else {
+        let mut available: Vec<&String> = config
+            .profiles
+            .as_ref()
+            .map(|profiles| profiles.keys().collect())
+            .unwrap_or_default();
+        available.sort();
+        eprintln!("Unknown profile {profile_name}");
+        if available.is_empty() {
+            eprintln!("No profiles are defined in the config");
+        } else {
+            eprintln!(
+                "Available profiles: {}",
+                available
+                    .iter()
+                    .map(|name| name.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            );
+        }
+        outcome::fail(Reason::ConfigError);
+    };
+
+    args.error_on_warning |= profile.error_on_warning.unwrap_or(false);
+    args.no_confirm |= profile.no_confirm.unwrap_or(false);
+
+    let enable = profile.enable.unwrap_or_default();
+    let disable = profile.disable.unwrap_or_default();
+    let severities = profile.severities.unwrap_or_default();
+    for rule_id in enable.iter().chain(disable.iter()).chain(severities.keys()) {
+        if !config.rules.iter().any(|rule| &rule.id == rule_id) {
+            eprintln!("Profile {profile_name} references unknown rule {rule_id}");
+            outcome::fail(Reason::ConfigError);
+        }
+    }
+
+    for rule in &mut config.rules {
+        if enable.contains(&rule.id) {
+            rule.enabled = Some(true);
+        }
+        if disable.contains(&rule.id) {
+            rule.enabled = Some(false);
+        }
+        if let Some(severity) = severities.get(&rule.id) {
+            rule.severity = Some(*severity);
+        }
+        if let Some(only_tags) = &profile.only_tags {
+            let tags = rule.tags.as_deref().unwrap_or_default();
+            if !tags.iter().any(|tag| only_tags.contains(tag)) {
+                rule.enabled = Some(false);
+            }
+        }
+    }
+}
+
+/// Parses a `--severity` like `NoTodo=warning`.
+fn parse_severity_override(value: &str) -> Result<(String, Severity), String> {
+    let Some((rule_id, severity)) = value.split_once('=') else {
+        return Err(format!(
+            "{value:?} isn't RULE_ID=SEVERITY, like NoTodo=warning"
+        ));
+    };
+    let severity = Severity::from_str(severity.trim(), true)
+        .map_err(|_| format!("unknown severity {severity:?}; use warning or error"))?;
+    Ok((rule_id.trim().to_owned(), severity))
+}
+
+/// Applies `--severity` to the config's rules, in order, so a later flag wins, before the
+/// rules are built, so `print-config` shows the severities the scan runs with. The flag
+/// also takes precedence over severities the config's `overrides` set for some paths. A
+/// rule missing its severity is left to fail as it would without the flag.
+fn apply_severity_overrides(config: &mut Config, severities: &[(String, Severity)]) {
+    for (rule_id, _) in severities {
+        if rule_id != "all" && !config.rules.iter().any(|rule| &rule.id == rule_id) {
+            let mut known: Vec<&str> = config.rules.iter().map(|rule| rule.id.as_str()).collect();
+            known.sort_unstable();
+            known.dedup();
+            eprintln!("Unknown rule {rule_id} in --severity");
+            eprintln!("Valid rule ids: all, {}", known.join(", "));
+            outcome::fail(Reason::ConfigError);
+        }
+    }
+    for (rule_id, severity) in severities {
+        let applies = |id: &str| rule_id == "all" || rule_id == id;
+        for rule in config.rules.iter_mut().filter(|rule| applies(&rule.id)) {
+            if let Some(rule_severity) = &mut rule.severity {
+                *rule_severity = *severity;
+            }
+        }
+        for rule_override in config.overrides.iter_mut().flatten() {
+            for (id, setting) in &mut rule_override.rules {
+                if let RuleOverride::Settings {
+                    severity: override_severity,
+                } = setting
+                {
+                    if applies(id) {
+                        *override_severity = *severity;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Disables the rules `--tags` and `--exclude-tags` leave out, on top of the config and
+/// profile. A tag no rule carries is warned about, since it's likely a typo.
+fn apply_tag_filters(config: &mut Config, tags: &[String], exclude_tags: &[String]) {
+    for tag in tags.iter().chain(exclude_tags) {
+        let tagged = config
+            .rules
+            .iter()
+            .any(|rule| rule.tags.as_deref().unwrap_or_default().contains(tag));
+        if !tagged {
+            eprintln!("Warning: No rules are tagged {tag}");
+        }
+    }
+    for rule in &mut config.rules {
+        let rule_tags = rule.tags.as_deref().unwrap_or_default();
+        let selected = tags.is_empty() || rule_tags.iter().any(|tag| tags.contains(tag));
+        if !selected || rule_tags.iter().any(|tag| exclude_tags.contains(tag)) {
+            rule.enabled = Some(false);
+        }
+    }
+}
+
+/// Disables every rule `--only` doesn't name. An unknown id fails the run, as with
+/// `--severity`.
+fn apply_only(config: &mut Config, only: &[String]) {
+    if only.is_empty() {
+        return;
+    }
+    for rule_id in only {
+        if !config.rules.iter().any(|rule| &rule.id == rule_id) {
+            let mut known: Vec<&str> = config.rules.iter().map(|rule| rule.id.as_str()).collect();
+            known.sort_unstable();
+            known.dedup();
+            eprintln!("Unknown rule {rule_id} in --only");
+            eprintln!("Valid rule ids: {}", known.join(", "));
+            outcome::fail(Reason::ConfigError);
+        }
+    }
+    for rule in &mut config.rules {
+        if !only.contains(&rule.id) {
+            rule.enabled = Some(false);
+        }
+    }
+}
+
+fn deprecation_message(rule: &RuleConfig) -> Option<String> {
+    if !rule.deprecated.unwrap_or(false) {
+        return None;
+    }
+    Some(
+        rule.deprecation_message
+            .clone()
+            .unwrap_or_else(|| format!("Rule {} is deprecated", rule.id)),
+    )
+}
+
+fn deprecation_label(rule: &RuleConfig) -> String {
+    deprecation_message(rule)
+        .map(|message| format!(" [deprecated: {message}]"))
+        .unwrap_or_default()
+}
+
+/// Warns about overrides and the applied profile referring to deprecated rules. With
+/// `--forbid-deprecated`, those references and any enabled deprecated rule are errors.
+fn check_deprecated(config: &Config, profile_name: Option<&str>, forbid: bool) {
+    let mut references: Vec<(&str, String)> = Vec::new();
+    for (index, rule_override) in config.overrides.iter().flatten().enumerate() {
+        for id in rule_override.rules.keys() {
+            references.push((id, format!("overrides[{index}]")));
+        }
+    }
+    let profile = profile_name.and_then(|name| Some((name, config.profiles.as_ref()?.get(name)?)));
+    if let Some((name, profile)) = profile {
+        let ids = profile
+            .enable
+            .iter()
+            .chain(profile.disable.iter())
+            .flatten()
+            .map(String::as_str)
+            .chain(
+                profile
+                    .severities
+                    .iter()
+                    .flat_map(|severities| severities.keys().map(String::as_str)),
+            );
+        for id in ids {
+            references.push((id, format!("profile {name}")));
+        }
+    }
+
+    let mut forbidden = false;
+    for (id, referrer) in references {
+        let Some(message) = config
+            .rules
+            .iter()
+            .find(|rule| rule.id == id)
+            .and_then(deprecation_message)
+        else {
+            continue;
+        };
+        eprintln!("Warning: {referrer} refers to deprecated rule {id}: {message}");
+        forbidden |= forbid;
+    }
+    if forbid {
+        for rule in &config.rules {
+            if !rule.enabled.unwrap_or(true) {
+                continue;
+            }
+            if let Some(message) = deprecation_message(rule) {
+                eprintln!("Deprecated rule {} is enabled: {message}", rule.id);
+                forbidden = true;
+            }
+        }
+    }
+    if forbidden {
+        eprintln!("Failing due to deprecated rules (--forbid-deprecated)");
+        outcome::fail(Reason::ConfigError);
+    }
+}
+
+/// A rule as `linty list-rules --format json` describes it.
+#[derive(Serialize)]
+struct ListedRule<'a> {
+    id: &'a str,
+    severity: Option<Severity>,
+    regex: Option<&'a str>,
+    match_type: MatchType,
+    mode: RuleMode,
+    target: RuleTarget,
+    kind: RuleKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ignore_pattern: Option<&'a str>,
+    message: Option<&'a str>,
+    includes: &'a [String],
+    excludes: &'a [String],
+    languages: &'a [String],
+    allow: &'a [String],
+    tags: &'a [String],
+    enabled: bool,
+    pre_commit: bool,
+    pre_commit_only: bool,
+    deprecated: bool,
+    source: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    directory: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<&'a str>,
+}
+
+/// Lists every rule, with the disabled ones marked as such.
+fn list_rules(config: &Config, format: ListFormat, verbose: bool) -> anyhow::Result<()> {
+    if format == ListFormat::Json {
+        let listed: Vec<ListedRule> = config
+            .rules
+            .iter()
+            .map(|rule| ListedRule {
+                id: &rule.id,
+                severity: rule.severity,
+                regex: rule.regex.as_deref(),
+                match_type: rule.match_type.unwrap_or_default(),
+                mode: rule.mode.unwrap_or_default(),
+                target: rule.target.unwrap_or_default(),
+                kind: rule.kind.unwrap_or_default(),
+                limit: rule.limit,
+                ignore_pattern: rule.ignore_pattern.as_deref(),
+                message: rule.message.as_deref(),
+                includes: rule.includes.as_deref().unwrap_or_default(),
+                excludes: rule.excludes.as_deref().unwrap_or_default(),
+                languages: rule.languages.as_deref().unwrap_or_default(),
+                allow: rule.allow.as_deref().unwrap_or_default(),
+                tags: rule.tags.as_deref().unwrap_or_default(),
+                enabled: rule.enabled.unwrap_or(true),
+                pre_commit: rule.pre_commit.unwrap_or(true),
+                pre_commit_only: rule.pre_commit_only.unwrap_or(false),
+                deprecated: rule.deprecated.unwrap_or(false),
+                source: rule.source.as_deref(),
+                directory: rule.directory.as_deref(),
+                description: rule.description.as_deref(),
+                url: rule.url.as_deref(),
+            })
+            .collect();
+        serde_json::to_writer_pretty(std::io::stdout(), &listed)?;
+        println!();
+        return Ok(());
+    }
+
+    for rule in &config.rules {
+        let hook_behavior = if rule.pre_commit_only.unwrap_or(false) {
+            " [pre-commit only]"
+        } else if !rule.pre_commit.unwrap_or(true) {
+            " [skipped in pre-commit]"
+        } else {
+            ""
+        };
+        let mut filters = String::new();
+        for (field, values) in [
+            ("includes", &rule.includes),
+            ("languages", &rule.languages),
+            ("excludes", &rule.excludes),
+            ("tags", &rule.tags),
+        ] {
+            if let Some(values) = values.as_ref().filter(|values| !values.is_empty()) {
+                filters.push_str(&format!("; {field}: {}", values.join(", ")));
+            }
+        }
+        let literal = if rule.match_type == Some(MatchType::Literal) {
+            " [literal]"
+        } else {
+            ""
+        };
+        let required = if rule.mode == Some(RuleMode::Required) {
+            " [required]"
+        } else {
+            ""
+        };
+        let target = if rule.target == Some(RuleTarget::Path) {
+            " [path]"
+        } else {
+            ""
+        };
+        let directory = rule
+            .directory
+            .as_ref()
+            .map(|directory| format!(" [in {directory}]"))
+            .unwrap_or_default();
+        let disabled = if rule.enabled.unwrap_or(true) {
+            ""
+        } else {
+            " [disabled]"
+        };
+        let checks = match rule.kind.unwrap_or_default() {
+            RuleKind::Pattern => rule.regex.clone().unwrap_or_default(),
+            kind => format!(
+                "{} {}",
+                kind.name(),
+                rule.limit
+                    .map(|limit| limit.to_string())
+                    .unwrap_or_default()
+            ),
+        };
+        println!(
+            "{} ({}): {checks}{literal}{required}{target}{filters}{directory}{hook_behavior}{disabled}{}",
+            rule.id,
+            rule.severity.map(severity_name).unwrap_or_default(),
+            deprecation_label(rule)
+        );
+        if verbose {
+            if let Some(source) = &rule.source {
+                println!("    from {source}");
+            }
+            if let Some(message) = rule
+                .message
+                .as_deref()
+                .filter(|message| !message.is_empty())
+            {
+                println!("    {message}");
+            }
+            if let Some(description) = &rule.description {
+                println!("    {description}");
+            }
+            if let Some(url) = &rule.url {
+                println!("    see: {url}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn doctor(config: &Config, config_location: &ConfigLocation) -> anyhow::Result<()> {
+    let enabled = config
+        .rules
+        .iter()
+        .filter(|rule| rule.enabled.unwrap_or(true))
+        .count();
+    println!(
+        "Config: {} ({})",
+        config_path_display(&config_location.path),
+        config_location.selected_by
+    );
+    println!("Rules: {} defined, {enabled} enabled", config.rules.len());
+    println!(
+        "Active in normal runs: {}",
+        generate_rules_from_config(config, false)?.len()
+    );
+    println!(
+        "Active in pre-commit runs: {}",
+        generate_rules_from_config(config, true)?.len()
+    );
+    Ok(())
+}
+
+fn init_config(format: InitFormat, force: bool) -> anyhow::Result<()> {
+    let path = format.path();
+    for existing in DEFAULT_CONFIG_PATH_STRS {
+        if !Path::new(existing).exists() {
+            continue;
+        }
+        if existing != path {
+            // Linty refuses to pick between two config files, so writing a second one
+            // would break the next run.
+            eprintln!(
+                "A config already exists at {existing}; remove it before initializing {path}"
+            );
+            outcome::fail(Reason::ConfigError);
+        }
+        if !force {
+            eprintln!("A config already exists at {existing}; pass --force to overwrite it");
+            outcome::fail(Reason::ConfigError);
+        }
+    }
+
+    let file_names: Vec<String> = std::fs::read_dir(".")?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    let project_types = project_type::detect(&file_names);
+    let globs = |pick: fn(&project_type::ProjectType) -> &'static [&'static str]| {
+        let globs: Vec<String> = project_types
+            .iter()
+            .flat_map(|project_type| pick(project_type).iter().map(|glob| glob.to_string()))
+            .collect();
+        (!globs.is_empty()).then_some(globs)
+    };
+
+    let default_config = Config {
+        excludes: globs(|project_type| project_type.excludes),
+        rules: vec![
+            RuleConfig {
+                id: String::from("WarnOnTodos"),
+                message: Some(String::from("Are you sure you meant to leave a TODO?")),
+                regex: Some(String::from("(TODO|todo)")),
+                severity: Some(Severity::Warning),
+                includes: globs(|project_type| project_type.includes),
+                ..Default::default()
+            },
+            RuleConfig {
+                id: String::from("NoMergeConflictMarkers"),
+                message: Some(String::from("Resolve the merge conflict before committing")),
+                regex: Some(String::from("^(<{7}|={7}|>{7})( |$)")),
+                multi_line: Some(true),
+                severity: Some(Severity::Error),
+                // Markdown and reStructuredText use runs of = to underline headings.
+                excludes: Some(vec![String::from("**/*.md"), String::from("**/*.rst")]),
+                ..Default::default()
+            },
+            RuleConfig {
+                id: String::from("NoPrivateKeys"),
+                message: Some(String::from("Private keys don't belong in the repository")),
+                regex: Some(String::from("-----BEGIN [A-Z ]*PRIVATE KEY-----")),
+                severity: Some(Severity::Error),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    let contents = match format {
+        InitFormat::Json => serde_json::to_string_pretty(&default_config)? + "\n",
+        InitFormat::Toml => format!(
+            "# Each [[rules]] entry reports matches of its regex in the files its includes\n\
+             # globs match, minus those its excludes globs match. Warnings can be\n\
+             # acknowledged interactively; errors always fail the run.\n\n{}",
+            toml::to_string_pretty(&default_config)?
+        ),
+        InitFormat::Yaml => format!(
+            "# Each entry under rules reports matches of its regex in the files its includes\n\
+             # globs match, minus those its excludes globs match. Warnings can be\n\
+             # acknowledged interactively; errors always fail the run.\n\n{}",
+            serde_yaml::to_string(&default_config)?
+        ),
+    };
+    if std::fs::write(path, contents).is_err() {
+        eprintln!("Failed to create config file at {path}");
+        outcome::fail(Reason::IoError);
+    }
+
+    println!("Initialized example config at {path}");
+    if !project_types.is_empty() {
+        let names: Vec<&str> = project_types
+            .iter()
+            .map(|project_type| project_type.name)
+            .collect();
+        println!(
+            "Detected {} project; excluded its build and vendor directories",
+            names.join(" and ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linty::{parse_config, Linter};
+
+    #[test]
+    fn builtin_defaults_round_trip_through_the_config_machinery() {
+        let defaults = builtin_defaults();
+        assert!(defaults
+            .rules
+            .iter()
+            .all(|rule| rule.source.as_deref() == Some(BUILTIN_DEFAULTS)));
+        let expected = serde_json::to_value(&defaults).unwrap();
+
+        for (format, written) in [
+            (
+                ConfigFormat::Json,
+                serde_json::to_string(&defaults).unwrap(),
+            ),
+            (ConfigFormat::Toml, toml::to_string(&defaults).unwrap()),
+            (
+                ConfigFormat::Yaml,
+                serde_yaml::to_string(&defaults).unwrap(),
+            ),
+        ] {
+            // Parsing labels each rule with where it came from, as the defaults are.
+            let config = parse_config(&written, format, BUILTIN_DEFAULTS).unwrap();
+            assert_eq!(
+                serde_json::to_value(&config).unwrap(),
+                expected,
+                "{written}"
+            );
+
+            let linter = Linter::from_config(config).unwrap();
+            let ids: Vec<&str> = linter.rules().iter().map(|rule| rule.id.as_str()).collect();
+            assert_eq!(
+                ids,
+                ["MergeConflictMarker", "TrailingWhitespace", "WarnOnTodos"]
+            );
+            let violations = linter
+                .check_str("<<<<<<< HEAD\nkeep \n// TODO\n", Path::new("a.txt"))
+                .unwrap();
+            let found: Vec<(&str, Vec<usize>)> = violations
+                .iter()
+                .map(|violation| (violation.rule_id.as_str(), violation.lines()))
+                .collect();
+            assert_eq!(
+                found,
+                [
+                    ("MergeConflictMarker", vec![1]),
+                    ("TrailingWhitespace", vec![2]),
+                    ("WarnOnTodos", vec![3])
+                ]
+            );
+        }
+    }
+
+    /// A config directory with `src/main.rs` and `lib/x.rs`, a file outside it, and a
+    /// symlink to it, in a scratch directory.
+    fn path_tree(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("linty-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        for file in ["repo/src/main.rs", "repo/lib/x.rs", "other/y.rs"] {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.join("repo"), dir.join("link")).unwrap();
+        linty::canonicalize(&dir).unwrap()
+    }
+
+    #[test]
+    fn relative_and_absolute_arguments_give_the_same_walk_path() {
+        let dir = path_tree("walk-path");
+        let current_dir = dir.join("repo/src");
+        let walked = |file: &Path| {
+            let file = file.to_str().unwrap();
+            walk_path(file, &current_dir, &current_dir, Path::new("src")).unwrap()
+        };
+        for file in [
+            PathBuf::from("main.rs"),
+            PathBuf::from("./main.rs"),
+            PathBuf::from("../src/main.rs"),
+            dir.join("repo/src/main.rs"),
+            dir.join("repo/src/../src/main.rs"),
+        ] {
+            assert_eq!(walked(&file), Path::new("./main.rs"), "{}", file.display());
+        }
+        // Files elsewhere under the config's directory are reached from the current one.
+        for file in [PathBuf::from("../lib/x.rs"), dir.join("repo/lib/x.rs")] {
+            assert_eq!(
+                walked(&file),
+                Path::new("../lib/x.rs"),
+                "{}",
+                file.display()
+            );
+        }
+        // Anything else keeps its absolute path, however it was given.
+        for file in [PathBuf::from("../../other/y.rs"), dir.join("other/y.rs")] {
+            assert_eq!(walked(&file), dir.join("other/y.rs"), "{}", file.display());
+        }
+        let missing = walk_path("nope.rs", &current_dir, &current_dir, Path::new("src"));
+        assert_eq!(missing.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn arguments_through_a_symlinked_current_directory_stay_relative() {
+        let dir = path_tree("walk-link");
+        let current_dir = dir.join("link/src");
+        let canonical_dir = dir.join("repo/src");
+        for file in [
+            PathBuf::from("main.rs"),
+            dir.join("link/src/main.rs"),
+            dir.join("repo/src/main.rs"),
+        ] {
+            let walked = walk_path(
+                file.to_str().unwrap(),
+                &current_dir,
+                &canonical_dir,
+                Path::new("src"),
+            );
+            assert_eq!(
+                walked.unwrap(),
+                Path::new("./main.rs"),
+                "{}",
+                file.display()
+            );
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn nested_and_repeated_paths_collapse_into_the_first_covering_one() {
+        let requested = vec![
+            (PathBuf::from("./src/parser"), "src/parser"),
+            (PathBuf::from("./src"), "src"),
+            (PathBuf::from("./src"), "./src/"),
+            (PathBuf::from("./src/parser/mod.rs"), "src/parser/mod.rs"),
+            // Sharing a prefix isn't being inside.
+            (PathBuf::from("./srcs/a.rs"), "srcs/a.rs"),
+            (PathBuf::from("/abs/src/a.rs"), "/abs/src/a.rs"),
+        ];
+        assert_eq!(
+            collapse_nested(requested, false),
+            [
+                PathBuf::from("./src"),
+                PathBuf::from("./srcs/a.rs"),
+                PathBuf::from("/abs/src/a.rs")
+            ]
+        );
+    }
+
+    /// Whether the walk includes hidden and ignored files, as `apply_walk_settings`
+    /// settles it for `flags`, with the config's `include_hidden` and `include_ignored`
+    /// and the profile's `hidden` and `ignored` each set to `config` and `profile`.
+    fn walk_settings(config: Option<bool>, profile: Option<bool>, flags: &[&str]) -> (bool, bool) {
+        let setting =
+            |value: Option<bool>| value.map_or(String::from("null"), |value| value.to_string());
+        let config = parse_config(
+            &format!(
+                r#"{{"include_hidden":{},"include_ignored":{},"rules":[]}}"#,
+                setting(config),
+                setting(config)
+            ),
+            ConfigFormat::Json,
+            "test config",
+        )
+        .unwrap();
+        let profile = profile.map(|value| Profile {
+            hidden: Some(value),
+            ignored: Some(value),
+            ..Profile::default()
+        });
+        let mut args = Args::parse_from([&["linty"], flags].concat());
+        apply_walk_settings(&config, profile.as_ref(), &mut args);
+        (args.hidden, args.no_ignore)
+    }
+
+    #[test]
+    fn walk_settings_come_from_flags_then_the_profile_then_the_config() {
+        assert_eq!(walk_settings(None, None, &[]), (false, false));
+        // Config only.
+        assert_eq!(walk_settings(Some(true), None, &[]), (true, true));
+        assert_eq!(walk_settings(Some(false), None, &[]), (false, false));
+        // Flags only.
+        assert_eq!(
+            walk_settings(None, None, &["--hidden", "--no-ignore"]),
+            (true, true)
+        );
+        assert_eq!(walk_settings(None, None, &["--hidden"]), (true, false));
+        // Flags overriding the config, either way.
+        assert_eq!(
+            walk_settings(Some(true), None, &["--no-hidden", "--ignore"]),
+            (false, false)
+        );
+        assert_eq!(
+            walk_settings(Some(false), None, &["--hidden", "--no-ignore"]),
+            (true, true)
+        );
+        assert_eq!(
+            walk_settings(Some(true), None, &["--no-hidden"]),
+            (false, true)
+        );
+        // The profile sits between the two.
+        assert_eq!(walk_settings(Some(true), Some(false), &[]), (false, false));
+        assert_eq!(
+            walk_settings(Some(false), Some(false), &["--hidden"]),
+            (true, false)
+        );
+    }
 }
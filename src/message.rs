@@ -0,0 +1,79 @@
+use regex::Captures;
+
+/// A piece of a message template.
+enum Part<'a> {
+    Text(&'a str),
+    /// `{0}` or `{name}`.
+    Group(&'a str),
+}
+
+/// Whether `template` has a capture placeholder like `{0}` or `{name}`, which makes it a
+/// message to fill in per match. Other text in braces, like `{}`, isn't one.
+pub fn has_placeholders(template: &str) -> bool {
+    parse(template)
+        .iter()
+        .any(|part| matches!(part, Part::Group(_)))
+}
+
+/// Fills in `template`'s placeholders with a match's capture groups, by number or name.
+/// A group that doesn't exist or didn't participate in the match renders as nothing, as
+/// do all of them without `captures`. `{{` and `}}` are literal braces.
+pub fn render(template: &str, captures: Option<&Captures>) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    for part in parse(template) {
+        match part {
+            Part::Text(text) => rendered.push_str(text),
+            Part::Group(group) => {
+                let value = captures.and_then(|captures| match group.parse::<usize>() {
+                    Ok(index) => captures.get(index),
+                    Err(_) => captures.name(group),
+                });
+                rendered.push_str(value.map_or("", |value| value.as_str()));
+            }
+        }
+    }
+    rendered
+}
+
+fn parse(template: &str) -> Vec<Part<'_>> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(brace) = rest.find(['{', '}']) {
+        parts.push(Part::Text(&rest[..brace]));
+        let after = &rest[brace + 1..];
+        // A doubled brace is one literal brace.
+        if after.starts_with(&rest[brace..=brace]) {
+            parts.push(Part::Text(&rest[brace..=brace]));
+            rest = &after[1..];
+            continue;
+        }
+        let group = (&rest[brace..=brace] == "{")
+            .then(|| after.find('}').map(|end| &after[..end]))
+            .flatten()
+            .filter(|group| is_group(group));
+        match group {
+            Some(group) => {
+                parts.push(Part::Group(group));
+                rest = &after[group.len() + 1..];
+            }
+            None => {
+                parts.push(Part::Text(&rest[brace..=brace]));
+                rest = after;
+            }
+        }
+    }
+    parts.push(Part::Text(rest));
+    parts
+}
+
+/// Whether the text between braces names a group: all digits, or an identifier.
+fn is_group(group: &str) -> bool {
+    let mut chars = group.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_digit() => group.chars().all(|c| c.is_ascii_digit()),
+        Some(first) if first.is_alphabetic() || first == '_' => {
+            chars.all(|c| c.is_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,66 @@
+use crate::RuleKind;
+use regex::Regex;
+use std::ops::Range;
+
+/// What a `max_line_length` or `max_file_lines` rule measures, in place of a regex of its own.
+pub struct Metric {
+    pub kind: RuleKind,
+    pub limit: usize,
+    /// Lines this matches aren't measured, like ones holding a long URL, or counted.
+    pub ignore: Option<Regex>,
+}
+
+impl Metric {
+    /// The regex that finds what to measure: the lines over the limit for
+    /// `max_line_length`, so large files can still be read in chunks, and the start of the
+    /// file for `max_file_lines`, which counts the lines once.
+    pub fn pattern(&self) -> String {
+        match self.kind {
+            RuleKind::MaxLineLength => format!("(?m)^[^\r\n]{{{},}}", self.limit + 1),
+            _ => String::from(r"\A"),
+        }
+    }
+
+    /// The ranges of `contents` found by [`Metric::pattern`] that break the limit, each with
+    /// the measured value for its message. A file over `max_file_lines` is reported at the
+    /// first line past the limit.
+    pub fn measure(
+        &self,
+        contents: &str,
+        ranges: Vec<Range<usize>>,
+    ) -> Vec<(Range<usize>, String)> {
+        if self.kind == RuleKind::MaxLineLength {
+            return ranges
+                .into_iter()
+                .filter(|range| !self.ignores(&contents[range.clone()]))
+                .map(|range| {
+                    let length = contents[range.clone()].chars().count();
+                    let message = format!("Line is {length} characters, limit is {}", self.limit);
+                    (range, message)
+                })
+                .collect();
+        }
+        let mut offset = 0;
+        let mut counted = Vec::new();
+        for line in contents.split_inclusive('\n') {
+            let text = line.trim_end_matches(['\n', '\r']);
+            if !self.ignores(text) {
+                counted.push(offset..offset + text.len());
+            }
+            offset += line.len();
+        }
+        match counted.get(self.limit) {
+            Some(range) => vec![(
+                range.clone(),
+                format!("File is {} lines, limit is {}", counted.len(), self.limit),
+            )],
+            None => Vec::new(),
+        }
+    }
+
+    fn ignores(&self, line: &str) -> bool {
+        self.ignore
+            .as_ref()
+            .is_some_and(|ignore| ignore.is_match(line))
+    }
+}
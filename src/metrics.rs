@@ -0,0 +1,89 @@
+use crate::{severity_name, Rule, Violation};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Renders scan metrics in the Prometheus text exposition format.
+/// Every rule gets a gauge even when it found nothing, so dashboards see explicit zeros.
+pub fn render(
+    rules: &[Rule],
+    violations: &[Violation],
+    suppressed: &BTreeMap<String, usize>,
+    files_scanned: usize,
+    bytes_read: u64,
+    scan_duration: Duration,
+) -> String {
+    let mut counts: BTreeMap<(&str, &'static str), usize> = BTreeMap::new();
+    for rule in rules {
+        counts
+            .entry((rule.id.as_str(), severity_name(rule.severity)))
+            .or_default();
+    }
+    for violation in violations {
+        *counts
+            .entry((
+                violation.rule_id.as_str(),
+                severity_name(violation.severity),
+            ))
+            .or_default() += violation.matches.len();
+    }
+
+    let mut output = String::new();
+    output
+        .push_str("# HELP linty_violations_total Number of matches found per rule and severity.\n");
+    output.push_str("# TYPE linty_violations_total gauge\n");
+    for ((rule_id, severity), count) in &counts {
+        let _ = writeln!(
+            output,
+            "linty_violations_total{{rule=\"{}\",severity=\"{severity}\"}} {count}",
+            escape_label_value(rule_id),
+        );
+    }
+
+    output.push_str(
+        "# HELP linty_suppressed_total Number of matches hidden by suppression comments per rule.\n",
+    );
+    output.push_str("# TYPE linty_suppressed_total gauge\n");
+    for rule in rules {
+        let count = suppressed.get(&rule.id).copied().unwrap_or_default();
+        let _ = writeln!(
+            output,
+            "linty_suppressed_total{{rule=\"{}\"}} {count}",
+            escape_label_value(&rule.id),
+        );
+    }
+
+    output.push_str(
+        "# HELP linty_files_scanned_total Number of files whose contents were checked.\n",
+    );
+    output.push_str("# TYPE linty_files_scanned_total gauge\n");
+    let _ = writeln!(output, "linty_files_scanned_total {files_scanned}");
+
+    output.push_str("# HELP linty_bytes_read_total Bytes of file contents read for matching.\n");
+    output.push_str("# TYPE linty_bytes_read_total gauge\n");
+    let _ = writeln!(output, "linty_bytes_read_total {bytes_read}");
+
+    output.push_str("# HELP linty_scan_duration_seconds Wall-clock time spent scanning files.\n");
+    output.push_str("# TYPE linty_scan_duration_seconds gauge\n");
+    let _ = writeln!(
+        output,
+        "linty_scan_duration_seconds {}",
+        scan_duration.as_secs_f64()
+    );
+
+    output
+}
+
+/// Escapes a label value per the exposition format: backslash, double quote, and newline.
+fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,291 @@
+use crate::config_trust::ConfigTrust;
+use crate::outcome::{self, Reason};
+use crate::{
+    Config, OverrideConfig, RuleConfig, RuleOverride, RuleToggle, DEFAULT_CONFIG_PATH_STRS,
+};
+use ignore::WalkBuilder;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Rule fields a nested config can set on a rule it inherits without redefining it.
+const PATCH_FIELDS: [&str; 4] = ["id", "severity", "enabled", "source"];
+
+/// Layers the other configs in the main config's directory tree over `config`, parents
+/// before children, so each applies to the files below its directory:
+///
+/// - a rule with a new id is added, limited to the directory by its `directory` field,
+///   or to its own `directory` below it;
+/// - a rule that only sets `severity` or `enabled` on an inherited id becomes an override
+///   for the directory;
+/// - a rule that changes anything else about an inherited id replaces it in the
+///   directory, inheriting the fields it leaves unset.
+///
+/// Only the `rules` and `excludes` of a nested config are used. `config`'s rules should
+/// already have inherited from the rules they extend. `main_config` and any other config
+/// in its directory are skipped, and `glob_base` is the current directory relative to its
+/// directory. A main config from outside the current directory's tree, as `-c` can name,
+/// has no nested configs.
+pub fn add_configs(
+    config: &mut Config,
+    trust: &ConfigTrust,
+    main_config: &str,
+    glob_base: &Path,
+    verbose: bool,
+) -> anyhow::Result<()> {
+    let main_config = crate::absolute_path(main_config);
+    let current_dir = linty::canonicalize(&std::env::current_dir()?)?;
+    // The config from stdin or the built-in defaults stands where the current directory is.
+    let main_dir = main_config
+        .parent()
+        .filter(|_| main_config.is_file())
+        .map_or_else(|| current_dir.clone(), Path::to_path_buf);
+    if !current_dir.starts_with(&main_dir) {
+        if verbose {
+            eprintln!(
+                "Not looking for nested configs, since {} is outside the current directory",
+                crate::config_path_display(&main_config.to_string_lossy())
+            );
+        }
+        return Ok(());
+    }
+    for (dir, path) in nested_configs(&main_config, glob_base)? {
+        if linty::canonicalize(&current_dir.join(&dir)).is_ok_and(|dir| dir == main_dir) {
+            continue;
+        }
+        // Relative to the main config's directory, like `backend`, as its globs are.
+        let glob_dir = crate::normalize_lexically(&glob_base.join(&dir))
+            .to_string_lossy()
+            .replace(std::path::MAIN_SEPARATOR, "/");
+        if glob_dir.is_empty() {
+            continue;
+        }
+        let path = path.to_string_lossy().into_owned();
+        if let Err(message) = trust.check_config_file(&path) {
+            eprintln!("{message}");
+            outcome::fail(Reason::ConfigError);
+        }
+        let mut nested = crate::read_config(&path, None);
+        crate::apply_extends(&mut nested, trust, &path)?;
+
+        let mut changes: Vec<String> = Vec::new();
+        let mut rule_overrides = HashMap::new();
+        for mut rule in nested.rules {
+            scope_globs(&mut rule.includes, &glob_dir);
+            scope_globs(&mut rule.excludes, &glob_dir);
+            let Some(parent) = config
+                .rules
+                .iter()
+                .rev()
+                .find(|existing| existing.id == rule.id)
+                .cloned()
+            else {
+                rule.directory = Some(match &rule.directory {
+                    Some(directory) => format!("{glob_dir}/{directory}"),
+                    None => glob_dir.clone(),
+                });
+                changes.push(format!("adds {}", rule.id));
+                config.rules.push(rule);
+                continue;
+            };
+
+            // Turning a rule on that the parent turned off needs all of the rule, not an
+            // override.
+            let reenables = rule.enabled == Some(true) && !parent.enabled.unwrap_or(true);
+            if is_patch(&rule) && !reenables {
+                let change = if rule.enabled == Some(false) {
+                    changes.push(format!("turns off {}", rule.id));
+                    RuleOverride::Toggle(RuleToggle::Off)
+                } else if let Some(severity) = rule.severity {
+                    changes.push(format!(
+                        "sets {} to {}",
+                        rule.id,
+                        crate::severity_name(severity)
+                    ));
+                    RuleOverride::Settings { severity }
+                } else {
+                    continue;
+                };
+                rule_overrides.insert(rule.id, change);
+                continue;
+            }
+
+            // The replacement covers the directory, so every earlier definition of the id
+            // stops there.
+            for existing in config.rules.iter_mut().filter(|r| r.id == rule.id) {
+                existing
+                    .excludes
+                    .get_or_insert_with(Vec::new)
+                    .push(format!("{glob_dir}/**"));
+            }
+            rule.directory = Some(match &rule.directory {
+                Some(directory) => format!("{glob_dir}/{directory}"),
+                None => glob_dir.clone(),
+            });
+            rule.enabled = rule.enabled.or(parent.enabled);
+            rule.pre_commit = rule.pre_commit.or(parent.pre_commit);
+            rule.pre_commit_only = rule.pre_commit_only.or(parent.pre_commit_only);
+            rule.deprecated = rule.deprecated.or(parent.deprecated);
+            rule.deprecation_message = rule
+                .deprecation_message
+                .take()
+                .or(parent.deprecation_message.clone());
+            crate::inherit_fields(&mut rule, parent);
+            changes.push(format!("replaces {}", rule.id));
+            config.rules.push(rule);
+        }
+        if !rule_overrides.is_empty() {
+            config
+                .overrides
+                .get_or_insert_with(Vec::new)
+                .push(OverrideConfig {
+                    paths: vec![format!("{glob_dir}/**")],
+                    rules: rule_overrides,
+                });
+        }
+        scope_globs(&mut nested.excludes, &glob_dir);
+        config
+            .excludes
+            .get_or_insert_with(Vec::new)
+            .extend(nested.excludes.into_iter().flatten());
+
+        if verbose {
+            eprintln!(
+                "Using nested config {} for {glob_dir}: {}",
+                crate::config_path_display(&path),
+                if changes.is_empty() {
+                    String::from("no rule changes")
+                } else {
+                    changes.join(", ")
+                }
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Whether a nested rule only changes the severity or turns off the rule it inherits.
+fn is_patch(rule: &RuleConfig) -> bool {
+    serde_json::to_value(rule)
+        .ok()
+        .and_then(|value| {
+            value.as_object().map(|fields| {
+                fields
+                    .iter()
+                    .all(|(key, value)| value.is_null() || PATCH_FIELDS.contains(&key.as_str()))
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// Rewrites a nested config's globs to match at its directory, `glob_dir`: one with a `/`
+/// is anchored there, as the main config's are at its own, and one without matches in any
+/// directory below it.
+fn scope_globs(globs: &mut Option<Vec<String>>, glob_dir: &str) {
+    for glob in globs.iter_mut().flatten() {
+        let (negation, pattern) = match glob.strip_prefix('!') {
+            Some(pattern) => ("!", pattern),
+            None => ("", glob.as_str()),
+        };
+        let anchored = pattern
+            .strip_prefix("./")
+            .or_else(|| pattern.strip_prefix('/'))
+            .unwrap_or(pattern);
+        *glob = if pattern.contains('/') {
+            format!("{negation}{glob_dir}/{anchored}")
+        } else {
+            format!("{negation}{glob_dir}/**/{pattern}")
+        };
+    }
+}
+
+/// Configs between the main config's directory and the current directory, and in
+/// subdirectories of the current directory, as `(directory, path)` with directories
+/// relative to the current directory, parents before children. A directory with more than
+/// one config is an error, as it is for the main config.
+fn nested_configs(main_config: &Path, glob_base: &Path) -> anyhow::Result<Vec<(PathBuf, PathBuf)>> {
+    let mut dirs = Vec::new();
+    let mut up = PathBuf::new();
+    for _ in glob_base.components() {
+        up.push("..");
+        dirs.push(up.clone());
+    }
+    dirs.reverse();
+    dirs.push(PathBuf::new());
+
+    let mut configs = Vec::new();
+    for dir in dirs {
+        let found: Vec<PathBuf> = DEFAULT_CONFIG_PATH_STRS
+            .into_iter()
+            .map(|name| dir.join(name))
+            .filter(|path| {
+                path.is_file() && crate::absolute_path(&path.to_string_lossy()) != main_config
+            })
+            .collect();
+        check_single(&dir, &found);
+        configs.extend(found.into_iter().map(|path| (dir.clone(), path)));
+    }
+    configs.extend(walked_configs(main_config)?);
+    Ok(configs)
+}
+
+/// Fails the run when `dir` holds more than one of the configs in `found`.
+fn check_single(dir: &Path, found: &[PathBuf]) {
+    if found.len() <= 1 {
+        return;
+    }
+    let names: Vec<String> = found
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    eprintln!(
+        "Found multiple config files in {}: {}",
+        dir.display(),
+        names.join(", ")
+    );
+    eprintln!("Remove all but one");
+    outcome::fail(Reason::ConfigError);
+}
+
+/// Configs in subdirectories of the current directory, parents before children.
+fn walked_configs(main_config: &Path) -> anyhow::Result<Vec<(PathBuf, PathBuf)>> {
+    let mut by_dir: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+    // Configs are usually hidden files, but git's internals never hold one.
+    for entry in WalkBuilder::new("./")
+        .hidden(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build()
+    {
+        let entry = entry?;
+        let is_config = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| DEFAULT_CONFIG_PATH_STRS.contains(&name));
+        if !is_config || entry.depth() < 2 || !entry.path().is_file() {
+            continue;
+        }
+        if crate::absolute_path(&entry.path().to_string_lossy()) == main_config {
+            continue;
+        }
+        let path = entry.into_path();
+        let dir = path
+            .parent()
+            .and_then(|dir| dir.strip_prefix("./").ok())
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+        by_dir.entry(dir).or_default().push(path);
+    }
+
+    let mut configs = Vec::new();
+    for (dir, mut paths) in by_dir {
+        paths.sort();
+        check_single(&dir, &paths);
+        configs.extend(paths.into_iter().map(|path| (dir.clone(), path)));
+    }
+    configs.sort_by(|(a, _), (b, _)| {
+        a.components()
+            .count()
+            .cmp(&b.components().count())
+            .then(a.cmp(b))
+    });
+    Ok(configs)
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use unicode_normalization::char::canonical_combining_class;
+use unicode_normalization::UnicodeNormalization;
+
+/// A rule's `unicode_normalize` setting.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum NormalForm {
+    Nfc,
+    Nfd,
+}
+
+/// File contents normalized for matching, with a map back to byte offsets in the original
+/// so reported lines, columns, and snippets refer to the file as it is on disk.
+pub struct Normalized {
+    pub text: String,
+    /// Where each chunk (a starter character and the combining marks after it) begins, as
+    /// `(offset in text, offset in the original)`. Chunks are normalized independently, so
+    /// every chunk boundary is a boundary in both strings.
+    chunks: Vec<(usize, usize)>,
+    original_len: usize,
+}
+
+impl Normalized {
+    pub fn new(contents: &str, form: NormalForm) -> Normalized {
+        let mut normalized = Normalized {
+            text: String::with_capacity(contents.len()),
+            chunks: Vec::new(),
+            original_len: contents.len(),
+        };
+        let mut chunk_start = 0;
+        for (index, character) in contents.char_indices() {
+            if index > chunk_start && canonical_combining_class(character) == 0 {
+                normalized.push_chunk(&contents[chunk_start..index], chunk_start, form);
+                chunk_start = index;
+            }
+        }
+        if chunk_start < contents.len() {
+            normalized.push_chunk(&contents[chunk_start..], chunk_start, form);
+        }
+        normalized
+    }
+
+    fn push_chunk(&mut self, chunk: &str, original_start: usize, form: NormalForm) {
+        self.chunks.push((self.text.len(), original_start));
+        match form {
+            NormalForm::Nfc => self.text.extend(chunk.nfc()),
+            NormalForm::Nfd => self.text.extend(chunk.nfd()),
+        }
+    }
+
+    /// Maps a byte range of `text` to the range of the original covering the same chunks.
+    pub fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        let start_chunk = self
+            .chunks
+            .partition_point(|&(text_start, _)| text_start <= range.start);
+        let start = match start_chunk {
+            0 => 0,
+            chunk => self.chunks[chunk - 1].1,
+        };
+        let end_chunk = self
+            .chunks
+            .partition_point(|&(text_start, _)| text_start < range.end);
+        let end = self
+            .chunks
+            .get(end_chunk)
+            .map_or(self.original_len, |&(_, original_start)| original_start);
+        start..end.max(start)
+    }
+}
@@ -0,0 +1,146 @@
+use crate::cancel;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// Why a run ended the way it did.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    Clean,
+    /// Error-severity violations, or new ones under `--fail-on-new`.
+    Violations,
+    /// Only warnings, but more than `--max-warnings` allows, or any with
+    /// `--error-on-warning`.
+    WarningBudget,
+    /// A warning was rejected at the interactive prompt.
+    Rejected,
+    ConfigError,
+    IoError,
+    Interrupted,
+    TimedOut,
+}
+
+/// The exit statuses, as `--help` lists them.
+pub const EXIT_CODES_HELP: &str = "\
+Exit status:
+  0    No failing violations
+  1    Error-severity violations, warnings under --error-on-warning or over --max-warnings, or a warning rejected at the prompt
+  2    Usage or config problems, like an invalid regex, a missing config, or an unreadable given file
+  3    Environment failures, like git failing or an I/O error
+  124  --timeout was hit (0 with --timeout-ok)
+  130  Interrupted";
+
+impl Reason {
+    /// The exit status a run ending for this reason usually has; see `EXIT_CODES_HELP`.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Reason::Clean => 0,
+            Reason::Violations | Reason::WarningBudget | Reason::Rejected => 1,
+            Reason::ConfigError => 2,
+            Reason::IoError => 3,
+            Reason::Interrupted => crate::cancel::Cancellation::Interrupted.exit_code(),
+            Reason::TimedOut => crate::cancel::Cancellation::TimedOut.exit_code(),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Reason::Clean => "clean",
+            Reason::Violations => "violations",
+            Reason::WarningBudget => "warning_budget",
+            Reason::Rejected => "rejected",
+            Reason::ConfigError => "config_error",
+            Reason::IoError => "io_error",
+            Reason::Interrupted => "interrupted",
+            Reason::TimedOut => "timed_out",
+        }
+    }
+}
+
+/// The exit status of a run and the counts behind it, for `--explain-exit-code` and the
+/// JSON report's `outcome` object.
+#[derive(Serialize, Debug, Clone)]
+pub struct Outcome {
+    pub exit_code: i32,
+    pub reason: Reason,
+    pub errors: usize,
+    pub warnings: usize,
+    pub failed_rules: Vec<String>,
+}
+
+static EXPLAIN: AtomicBool = AtomicBool::new(false);
+static IDLE_OUTCOME: Mutex<Option<Outcome>> = Mutex::new(None);
+
+/// Makes every exit print its outcome line to stderr.
+pub fn set_explain(explain: bool) {
+    EXPLAIN.store(explain, Ordering::SeqCst);
+}
+
+impl Outcome {
+    pub fn clean() -> Outcome {
+        Outcome::new(Reason::Clean, 0)
+    }
+
+    /// An outcome that happened before any violations were counted.
+    pub fn new(reason: Reason, exit_code: i32) -> Outcome {
+        Outcome {
+            exit_code,
+            reason,
+            errors: 0,
+            warnings: 0,
+            failed_rules: Vec::new(),
+        }
+    }
+
+    /// A line like `linty: exit=1 reason=violations errors=3 warnings=7 failed_rules=A,B`.
+    pub fn line(&self) -> String {
+        format!(
+            "linty: exit={} reason={} errors={} warnings={} failed_rules={}",
+            self.exit_code,
+            self.reason.name(),
+            self.errors,
+            self.warnings,
+            self.failed_rules.join(",")
+        )
+    }
+
+    /// Ends the process with this outcome. Every exit path in linty goes through here.
+    pub fn exit(&self) -> ! {
+        if EXPLAIN.load(Ordering::SeqCst) {
+            eprintln!("{}", self.line());
+        }
+        std::process::exit(self.exit_code);
+    }
+}
+
+/// Exits with `reason`'s status, after whatever message the caller printed.
+pub fn fail(reason: Reason) -> ! {
+    Outcome::new(reason, reason.exit_code()).exit()
+}
+
+/// Sets how a Ctrl-C outside of a scan exits, for `--watch` to exit as its last scan did.
+pub fn set_idle_outcome(outcome: Outcome) {
+    if let Ok(mut idle) = IDLE_OUTCOME.lock() {
+        *idle = Some(outcome);
+    }
+}
+
+/// Installs a Ctrl-C handler that stops the scan so the partial results can be reported.
+/// A second Ctrl-C, or one outside of a scan, exits immediately.
+pub fn install_interrupt_handler() -> anyhow::Result<()> {
+    ctrlc::set_handler(|| {
+        if !cancel::scanning() || cancel::cancelled().is_some() {
+            let idle = IDLE_OUTCOME.lock().ok().and_then(|idle| idle.clone());
+            idle.unwrap_or_else(|| {
+                Outcome::new(
+                    Reason::Interrupted,
+                    cancel::Cancellation::Interrupted.exit_code(),
+                )
+            })
+            .exit();
+        }
+        cancel::cancel(cancel::Cancellation::Interrupted);
+    })?;
+    Ok(())
+}
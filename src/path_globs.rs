@@ -0,0 +1,160 @@
+use crate::path_glob;
+use globset::{GlobSet, GlobSetBuilder};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// An ordered list of config globs, like a rule's `includes`, where a pattern starting
+/// with `!` takes back what earlier patterns matched, as in `.gitignore`: the last pattern
+/// that matches a path decides. It also notes which patterns have matched a path, so
+/// patterns that never do can be reported.
+pub struct PathGlobs {
+    set: GlobSet,
+    /// The patterns as written, `!` included.
+    patterns: Vec<String>,
+    negated: Vec<bool>,
+    matched: Vec<AtomicBool>,
+}
+
+impl PathGlobs {
+    /// Compiles `patterns` with [`path_glob`], failing with why each one that doesn't
+    /// compile fails.
+    pub fn new(patterns: &[String]) -> Result<PathGlobs, Vec<String>> {
+        let mut builder = GlobSetBuilder::new();
+        let mut negated = Vec::new();
+        let mut errors = Vec::new();
+        for pattern in patterns {
+            // As in .gitignore, `\!` starts a glob with a literal `!`.
+            let (glob, negation) = match pattern.strip_prefix('!') {
+                Some(glob) => (glob, true),
+                None if pattern.starts_with("\\!") => (&pattern[1..], false),
+                None => (pattern.as_str(), false),
+            };
+            if glob.is_empty() {
+                errors.push(format!(
+                    "error parsing glob '{pattern}': a ! needs a glob after it"
+                ));
+                continue;
+            }
+            match path_glob(glob) {
+                Ok(glob) => {
+                    builder.add(glob);
+                    negated.push(negation);
+                }
+                Err(err) => errors.push(err.to_string()),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let set = builder.build().map_err(|err| vec![err.to_string()])?;
+        Ok(PathGlobs {
+            set,
+            patterns: patterns.to_vec(),
+            negated,
+            matched: patterns.iter().map(|_| AtomicBool::new(false)).collect(),
+        })
+    }
+
+    pub fn empty() -> PathGlobs {
+        PathGlobs {
+            set: GlobSet::empty(),
+            patterns: Vec::new(),
+            negated: Vec::new(),
+            matched: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// The index of the last pattern that matches `path`, which decides it.
+    pub fn last_match(&self, path: &Path) -> Option<usize> {
+        let matches = self.set.matches(path);
+        for &index in &matches {
+            // Checked first so threads matching the same pattern don't keep writing it.
+            if !self.matched[index].load(Ordering::Relaxed) {
+                self.matched[index].store(true, Ordering::Relaxed);
+            }
+        }
+        matches.into_iter().max()
+    }
+
+    /// Whether the last pattern that matches `path` is a plain one rather than a `!` one.
+    pub fn is_match(&self, path: &Path) -> bool {
+        self.last_match(path)
+            .is_some_and(|index| !self.negated[index])
+    }
+
+    pub fn is_negated(&self, index: usize) -> bool {
+        self.negated[index]
+    }
+
+    /// The pattern at `index`, as written.
+    pub fn pattern(&self, index: usize) -> &str {
+        &self.patterns[index]
+    }
+
+    /// The patterns no path has matched so far.
+    pub fn unmatched(&self) -> Vec<&str> {
+        self.patterns
+            .iter()
+            .zip(&self.matched)
+            .filter(|(_, matched)| !matched.load(Ordering::Relaxed))
+            .map(|(pattern, _)| pattern.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn globs(patterns: &[&str]) -> PathGlobs {
+        let patterns: Vec<String> = patterns.iter().map(|&pattern| pattern.to_owned()).collect();
+        PathGlobs::new(&patterns).unwrap()
+    }
+
+    #[test]
+    fn last_matching_pattern_decides() {
+        let later_negation = globs(&["generated/**", "!generated/handwritten.rs"]);
+        assert!(later_negation.is_match(Path::new("generated/a.rs")));
+        assert!(!later_negation.is_match(Path::new("generated/handwritten.rs")));
+        assert_eq!(
+            later_negation.last_match(Path::new("generated/handwritten.rs")),
+            Some(1)
+        );
+
+        // A negation before the pattern it would take back from has nothing to undo.
+        let earlier_negation = globs(&["!generated/handwritten.rs", "generated/**"]);
+        assert!(earlier_negation.is_match(Path::new("generated/handwritten.rs")));
+
+        let reincluded = globs(&["src/**", "!src/gen/**", "src/gen/keep.rs"]);
+        assert!(reincluded.is_match(Path::new("src/a.rs")));
+        assert!(!reincluded.is_match(Path::new("src/gen/b.rs")));
+        assert!(reincluded.is_match(Path::new("src/gen/keep.rs")));
+        assert_eq!(reincluded.last_match(Path::new("docs/a.md")), None);
+    }
+
+    #[test]
+    fn escaped_bang_is_a_literal_and_a_bare_one_is_an_error() {
+        let literal = globs(&["\\!important.txt"]);
+        assert!(literal.is_match(Path::new("!important.txt")));
+        assert!(!literal.is_negated(0));
+        assert_eq!(literal.pattern(0), "\\!important.txt");
+
+        let errors = PathGlobs::new(&[String::from("!")]).err().unwrap();
+        assert_eq!(
+            errors,
+            ["error parsing glob '!': a ! needs a glob after it"]
+        );
+    }
+
+    #[test]
+    fn unmatched_lists_patterns_no_path_has_matched() {
+        let globs = globs(&["src/**", "!src/gen/**", "never/**"]);
+        assert_eq!(globs.unmatched(), ["src/**", "!src/gen/**", "never/**"]);
+        globs.is_match(Path::new("src/gen/a.rs"));
+        assert_eq!(globs.unmatched(), ["never/**"]);
+    }
+}
@@ -0,0 +1,76 @@
+use linty::git::{GitError, Repo};
+use serde::Serialize;
+
+/// Where the report came from, gathered from git when available.
+#[derive(Serialize, Debug, Default)]
+pub struct RepositoryInfo {
+    pub repo: Option<String>,
+    pub branch: Option<String>,
+    pub commit: Option<String>,
+}
+
+impl RepositoryInfo {
+    /// Gathers repository metadata from `repo`, leaving fields empty when there's no
+    /// repository or git can't tell.
+    pub fn gather(repo: Option<&Repo>) -> RepositoryInfo {
+        let Some(repo) = repo else {
+            return RepositoryInfo::default();
+        };
+        RepositoryInfo {
+            repo: git_output(repo, &["config", "--get", "remote.origin.url"])
+                .or_else(|| Some(repo.root().to_string_lossy().into_owned())),
+            branch: git_output(repo, &["rev-parse", "--abbrev-ref", "HEAD"])
+                .filter(|branch| branch != "HEAD"),
+            commit: git_output(repo, &["rev-parse", "HEAD"]),
+        }
+    }
+}
+
+/// The repository a report describes, or `None` outside one or without git. Git failing
+/// some other way, such as on a repository owned by another user, is warned about, since
+/// the report is still written without it.
+pub fn report_repo() -> Option<Repo> {
+    match Repo::discover() {
+        Ok(repo) => Some(repo),
+        Err(GitError::NotARepository | GitError::NotInstalled) => None,
+        Err(err) => {
+            eprintln!("Warning: Leaving git details out of the report: {err}");
+            None
+        }
+    }
+}
+
+fn git_output(repo: &Repo, args: &[&str]) -> Option<String> {
+    let stdout = String::from_utf8(repo.output(args).ok()?).ok()?;
+    let trimmed = stdout.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_owned())
+}
+
+/// Parses a `--post-header` value of the form `Name: value`.
+pub fn parse_header(header: &str) -> anyhow::Result<(String, String)> {
+    match header.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_owned(), value.trim().to_owned()))
+        }
+        _ => anyhow::bail!("Invalid --post-header {header:?}; expected 'Name: value'"),
+    }
+}
+
+#[cfg(feature = "post-results")]
+pub fn post(url: &str, headers: &[(String, String)], body: &[u8]) -> anyhow::Result<()> {
+    let mut request = ureq::post(url).set("Content-Type", "application/json");
+    for (name, value) in headers {
+        request = request.set(name, value);
+    }
+    request
+        .send_bytes(body)
+        .map_err(|err| anyhow::anyhow!("Failed to post results to {url}: {err}"))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "post-results"))]
+pub fn post(url: &str, _headers: &[(String, String)], _body: &[u8]) -> anyhow::Result<()> {
+    anyhow::bail!(
+        "Failed to post results to {url}: linty was built without the post-results feature"
+    )
+}
@@ -0,0 +1,155 @@
+use crate::display::DisplayTable;
+use crate::{Severity, Violation};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// Longest marker line we expect to emit; reserved out of every chunk's budget.
+const MARKER_RESERVE: usize = "<!-- linty 9999/9999 -->\n".len();
+
+/// Renders violations as markdown grouped by file and writes them as `comment-N.md`
+/// chunks of at most `max_chars` characters into `output_dir`.
+/// Stale chunks from a previous run are removed so the bot never reposts them.
+pub fn write_chunks(
+    violations: &[Violation],
+    messages: &HashMap<&str, &str>,
+    display: &DisplayTable,
+    max_chars: usize,
+    output_dir: &Path,
+) -> anyhow::Result<usize> {
+    let chunks = render_chunks(violations, messages, display, max_chars);
+
+    fs::create_dir_all(output_dir)?;
+    for entry in fs::read_dir(output_dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with("comment-") && name.ends_with(".md") {
+            fs::remove_file(entry.path())?;
+        }
+    }
+
+    for (index, chunk) in chunks.iter().enumerate() {
+        fs::write(output_dir.join(format!("comment-{}.md", index + 1)), chunk)?;
+    }
+    Ok(chunks.len())
+}
+
+fn render_chunks(
+    violations: &[Violation],
+    messages: &HashMap<&str, &str>,
+    display: &DisplayTable,
+    max_chars: usize,
+) -> Vec<String> {
+    let budget = max_chars.saturating_sub(MARKER_RESERVE).max(1);
+
+    let mut by_file: BTreeMap<String, Vec<&Violation>> = BTreeMap::new();
+    for violation in violations {
+        by_file
+            .entry(crate::display_path(&violation.path))
+            .or_default()
+            .push(violation);
+    }
+
+    let error_count = violations
+        .iter()
+        .filter(|violation| matches!(violation.severity, Severity::Error))
+        .count();
+    let warning_count = violations.len() - error_count;
+
+    let mut sections = vec![if violations.is_empty() {
+        String::from("## linty found no violations\n\n")
+    } else {
+        format!(
+            "## linty found {error_count} {}(s) and {warning_count} {}(s)\n\n",
+            display.label(Severity::Error),
+            display.label(Severity::Warning)
+        )
+    }];
+
+    for (path, mut file_violations) in by_file {
+        file_violations.sort_by(|a, b| a.rule_id.cmp(&b.rule_id));
+        let mut section = format!("### `{path}`\n\n");
+        for violation in file_violations {
+            let lines = violation
+                .line_spans()
+                .into_iter()
+                .map(|(start, end)| crate::line_ranges::format_span(start, end))
+                .collect::<Vec<String>>()
+                .join(", ");
+            // A path rule's violation is about the whole file, so it has no lines.
+            let lines = if lines.is_empty() {
+                String::new()
+            } else {
+                format!(" (lines {lines})")
+            };
+            section.push_str(&format!(
+                "- **{}** ({}): {}{lines}\n",
+                violation.rule_id,
+                display.label(violation.severity),
+                messages.get(violation.rule_id.as_str()).unwrap_or(&""),
+            ));
+            // A message with placeholders reads differently for each match.
+            for m in &violation.matches {
+                if let Some(message) = &m.message {
+                    section.push_str(&format!(
+                        "  - line {}: {message}\n",
+                        crate::line_ranges::format_span(m.line, m.last_line())
+                    ));
+                }
+            }
+        }
+        section.push('\n');
+        sections.push(section);
+    }
+
+    let mut bodies: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for section in sections {
+        for piece in split_oversized(&section, budget) {
+            if !current.is_empty() && current.chars().count() + piece.chars().count() > budget {
+                bodies.push(std::mem::take(&mut current));
+            }
+            current.push_str(&piece);
+        }
+    }
+    if !current.is_empty() {
+        bodies.push(current);
+    }
+
+    let total = bodies.len();
+    bodies
+        .into_iter()
+        .enumerate()
+        .map(|(index, body)| format!("<!-- linty {}/{total} -->\n{body}", index + 1))
+        .collect()
+}
+
+/// Splits a section that can't fit in one chunk at line boundaries.
+/// Only a single line longer than the budget is ever cut mid-line.
+fn split_oversized(section: &str, budget: usize) -> Vec<String> {
+    if section.chars().count() <= budget {
+        return vec![section.to_owned()];
+    }
+
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    for line in section.split_inclusive('\n') {
+        let mut line: String = line.to_owned();
+        while line.chars().count() > budget {
+            let rest = line.split_off(line.char_indices().nth(budget).unwrap().0);
+            if !current.is_empty() {
+                pieces.push(std::mem::take(&mut current));
+            }
+            pieces.push(std::mem::replace(&mut line, rest));
+        }
+        if !current.is_empty() && current.chars().count() + line.chars().count() > budget {
+            pieces.push(std::mem::take(&mut current));
+        }
+        current.push_str(&line);
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
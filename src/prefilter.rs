@@ -0,0 +1,139 @@
+use crate::{log, Rule, Severity};
+use regex::{RegexSet, RegexSetBuilder};
+use regex_syntax::hir::{Hir, HirKind};
+
+/// The flags a rule's regex was built with besides its pattern, so the prefilter can build
+/// the pattern the same way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RegexFlags {
+    pub case_insensitive: bool,
+    pub multi_line: bool,
+    pub dot_matches_new_line: bool,
+    pub crlf: bool,
+}
+
+/// The fewest applicable rules of a set that make searching with the set worth it; for
+/// one rule, its own search is as quick.
+const MIN_SET_RULES: usize = 2;
+
+/// The cache a set's lazy DFA may grow to per thread. A set of dozens of patterns
+/// outgrows the regex crate's default at once and falls back to a much slower engine,
+/// which is slower than running the rules one by one.
+const SET_DFA_SIZE_LIMIT: usize = 32 * 1024 * 1024;
+
+/// The largest count of a repetition like `[^\n]{101,}` a pattern in a set may have. Each
+/// count multiplies the states the set's DFA needs for everything else in it, so such a
+/// pattern, like a `max_line_length` rule's, makes the set slower than the rules it saves.
+const MAX_SET_REPETITION: u32 = 16;
+
+/// A `RegexSet` over the content rules of a scan, one per combination of regex flags,
+/// that tells in one search of a file which rules can match it at all, so the rest never
+/// run their own regex over it. Rules whose matches depend on more than the text
+/// searched, because of a `scan_window`, `region`, or `unicode_normalize`, aren't in any
+/// set and always run, as do rules with a large counted repetition.
+#[derive(Default)]
+pub struct Prefilter<'a> {
+    pub(crate) sets: Vec<(RegexSet, Vec<&'a Rule>)>,
+}
+
+impl<'a> Prefilter<'a> {
+    pub fn new(rules: &'a [Rule]) -> Prefilter<'a> {
+        let mut groups: Vec<(RegexFlags, Vec<&'a Rule>)> = Vec::new();
+        for rule in rules.iter().filter(|rule| is_prefiltered(rule)) {
+            match groups
+                .iter_mut()
+                .find(|(flags, _)| *flags == rule.regex_flags)
+            {
+                Some((_, members)) => members.push(rule),
+                None => groups.push((rule.regex_flags, vec![rule])),
+            }
+        }
+        let mut sets = Vec::new();
+        for (flags, members) in groups {
+            if members.len() < MIN_SET_RULES {
+                continue;
+            }
+            let set = RegexSetBuilder::new(members.iter().map(|rule| rule.regex.as_str()))
+                .case_insensitive(flags.case_insensitive)
+                .multi_line(flags.multi_line)
+                .dot_matches_new_line(flags.dot_matches_new_line)
+                .crlf(flags.crlf)
+                .dfa_size_limit(SET_DFA_SIZE_LIMIT)
+                .build();
+            match set {
+                Ok(set) => sets.push((set, members)),
+                // Those rules just run on their own.
+                Err(err) => log::log!(
+                    log::Level::Debug,
+                    "Not prefiltering {} rule(s): {err}",
+                    members.len()
+                ),
+            }
+        }
+        Prefilter { sets }
+    }
+
+    /// Whether each of `applicable` can match `haystack`; only a rule a set searched
+    /// without a match for it is `false`. A set is only searched when enough of its rules
+    /// apply.
+    pub fn can_match(&self, applicable: &[(&Rule, Severity)], haystack: &str) -> Vec<bool> {
+        let mut can_match = vec![true; applicable.len()];
+        for (set, members) in &self.sets {
+            let positions: Vec<(usize, usize)> = applicable
+                .iter()
+                .enumerate()
+                .filter_map(|(index, (rule, _))| {
+                    members
+                        .iter()
+                        .position(|member| std::ptr::eq(*member, *rule))
+                        .map(|position| (index, position))
+                })
+                .collect();
+            if positions.len() < MIN_SET_RULES {
+                continue;
+            }
+            let matched = set.matches(haystack);
+            for (index, position) in positions {
+                can_match[index] = matched.matched(position);
+            }
+        }
+        can_match
+    }
+}
+
+/// Whether matching `rule`'s regex against all of the text a scan gives it is what the
+/// rule itself does, so a set search can stand in for it.
+fn is_prefiltered(rule: &Rule) -> bool {
+    rule.kind.reads_contents()
+        && !rule.registered
+        && rule.scan_window.is_none()
+        && rule.region.is_none()
+        && rule.unicode_normalize.is_none()
+        && !has_large_repetition(rule)
+}
+
+fn has_large_repetition(rule: &Rule) -> bool {
+    let flags = rule.regex_flags;
+    let parsed = regex_syntax::ParserBuilder::new()
+        .case_insensitive(flags.case_insensitive)
+        .multi_line(flags.multi_line)
+        .dot_matches_new_line(flags.dot_matches_new_line)
+        .crlf(flags.crlf)
+        .build()
+        .parse(rule.regex.as_str());
+    // A pattern the regex crate compiled always parses; if not, it's left to run alone.
+    parsed.map_or(true, |hir| repeats_over(&hir, MAX_SET_REPETITION))
+}
+
+fn repeats_over(hir: &Hir, limit: u32) -> bool {
+    match hir.kind() {
+        HirKind::Repetition(repetition) => {
+            repetition.max.unwrap_or(repetition.min) > limit || repeats_over(&repetition.sub, limit)
+        }
+        HirKind::Capture(capture) => repeats_over(&capture.sub, limit),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => {
+            hirs.iter().any(|hir| repeats_over(hir, limit))
+        }
+        HirKind::Empty | HirKind::Literal(_) | HirKind::Class(_) | HirKind::Look(_) => false,
+    }
+}
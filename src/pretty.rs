@@ -0,0 +1,308 @@
+use crate::display::DisplayTable;
+use crate::scan::Match;
+use crate::Violation;
+use linty::git::Repo;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+const TAB_WIDTH: usize = 4;
+
+/// Where the lines shown around a match come from.
+#[derive(Clone, Copy)]
+pub enum Source<'a> {
+    /// The file on disk.
+    Disk,
+    /// The git index of the repository, for pre-commit runs.
+    Staged(&'a Repo),
+    /// The content piped in with `--stdin`.
+    Piped(&'a str),
+}
+
+/// Prints each of a violation's matches in context for `--format pretty`: the spanned
+/// lines with a line-number gutter, underlined from the start column to the end column,
+/// with the rule message as the label and `context` lines before and after. At most
+/// `max_listed` matches are shown (0 for no limit). The lines are read from `source`.
+/// Falls back to the short format when the file can't be read anymore.
+pub fn print_violation(
+    out: &mut dyn std::io::Write,
+    display: &DisplayTable,
+    violation: &Violation,
+    message: &str,
+    max_listed: usize,
+    context: usize,
+    source: Source,
+) -> std::io::Result<()> {
+    // Path rules have nothing in the file to show.
+    if !violation.matches.iter().any(|m| m.has_position()) {
+        writeln!(
+            out,
+            "--> {}{}",
+            display.dim(&crate::display_path(&violation.path)),
+            if violation.untracked {
+                " (untracked)"
+            } else {
+                ""
+            }
+        )?;
+        writeln!(out, " | {}", display.paint(violation.severity, message))?;
+        return writeln!(out, " |");
+    }
+    let Ok(contents) = source.read(&violation.path) else {
+        return crate::print_violation(out, display, violation, max_listed);
+    };
+    let lines: Vec<&str> = contents.lines().collect();
+    let path = crate::display_path(&violation.path);
+    let note = match (violation.untracked, violation.report_once) {
+        (true, true) => " (untracked; first occurrence, report_once)",
+        (true, false) => " (untracked)",
+        (false, true) => " (first occurrence, report_once)",
+        (false, false) => "",
+    };
+    let width = crate::line_ranges::output_width();
+
+    let shown = match max_listed {
+        0 => violation.matches.len(),
+        limit => limit.min(violation.matches.len()),
+    };
+    for violation_match in &violation.matches[..shown] {
+        write!(
+            out,
+            "{}",
+            render_match(
+                &lines,
+                &format!(
+                    "{}{note}",
+                    display.dim(&format!(
+                        "{path}:{}:{}",
+                        violation_match.line, violation_match.column
+                    ))
+                ),
+                violation_match,
+                &display.paint(violation.severity, violation_match.message(message)),
+                |carets| display.paint(violation.severity, carets),
+                context,
+                width,
+            )
+        )?;
+    }
+    if shown < violation.matches.len() {
+        writeln!(
+            out,
+            "  ... and {} more match(es)",
+            violation.matches.len() - shown
+        )?;
+    }
+    if violation.discarded > 0 {
+        writeln!(out, "  {} more match(es) discarded", violation.discarded)?;
+    }
+    crate::print_blame(out, violation)
+}
+
+/// Prints all of one file's matches in context for `--group-by file`, in line order, each
+/// labeled with its severity, rule, and message. Falls back to the short listing when the
+/// file can't be read anymore.
+pub fn print_file(
+    out: &mut dyn std::io::Write,
+    display: &DisplayTable,
+    violations: &[&Violation],
+    messages: &HashMap<&str, &str>,
+    context: usize,
+    source: Source,
+) -> std::io::Result<()> {
+    let first = violations[0];
+    let contents = if violations
+        .iter()
+        .any(|violation| violation.matches.iter().any(|m| m.has_position()))
+    {
+        match source.read(&first.path) {
+            Ok(contents) => contents,
+            Err(_) => return crate::print_file(out, display, violations, messages, 0),
+        }
+    } else {
+        String::new()
+    };
+    let lines: Vec<&str> = contents.lines().collect();
+    let path = crate::display_path(&first.path);
+    let note = if first.untracked { " (untracked)" } else { "" };
+    let width = crate::line_ranges::output_width();
+
+    writeln!(out, "{}{note}", display.bold(&path))?;
+    for (violation, violation_match) in crate::file_matches(violations) {
+        let rule_message = messages
+            .get(violation.rule_id.as_str())
+            .copied()
+            .unwrap_or_default();
+        let label = display.paint(
+            violation.severity,
+            &format!(
+                "{} {}: {}",
+                display.label(violation.severity),
+                violation.rule_id,
+                violation_match.message(rule_message)
+            ),
+        );
+        // Path rules have nothing in the file to show.
+        if !violation_match.has_position() {
+            writeln!(out, " = {label}")?;
+            continue;
+        }
+        let location = format!("{path}:{}:{}", violation_match.line, violation_match.column);
+        write!(
+            out,
+            "{}",
+            render_match(
+                &lines,
+                &display.dim(&location),
+                violation_match,
+                &label,
+                |carets| display.paint(violation.severity, carets),
+                context,
+                width,
+            )
+        )?;
+    }
+    Ok(())
+}
+
+impl Source<'_> {
+    fn read(self, path: &std::path::Path) -> std::io::Result<String> {
+        match self {
+            Source::Disk => std::fs::read_to_string(path),
+            Source::Staged(repo) => crate::scan::staged_contents(repo, path)
+                .and_then(|bytes| String::from_utf8(bytes).map_err(std::io::Error::other)),
+            Source::Piped(text) => Ok(text.to_owned()),
+        }
+    }
+}
+
+/// Renders one match as a snippet, with up to `context` unmarked lines on either side.
+/// `lines` are the file's lines without terminators, and `location` follows the `-->`
+/// arrow.
+fn render_match(
+    lines: &[&str],
+    location: &str,
+    violation_match: &Match,
+    label: &str,
+    paint: impl Fn(&str) -> String,
+    context: usize,
+    width: usize,
+) -> String {
+    // A span that ends right after a newline ends at the end of the previous line.
+    let (last_line, end_column) =
+        if violation_match.end_column == 1 && violation_match.end_line > violation_match.line {
+            let last_line = violation_match.end_line - 1;
+            let length = lines
+                .get(last_line - 1)
+                .map_or(0, |line| line.chars().count());
+            (last_line, length + 1)
+        } else {
+            (violation_match.end_line, violation_match.end_column)
+        };
+
+    let first_shown = violation_match.line.saturating_sub(context).max(1);
+    let last_shown = (last_line + context).min(lines.len()).max(last_line);
+    let gutter = last_shown.to_string().len();
+    let pad = " ".repeat(gutter);
+    let available = width.saturating_sub(gutter + 3).max(20);
+    let context_line = |output: &mut String, line_number: usize| {
+        let (expanded, _) = expand_tabs(lines[line_number - 1]);
+        let (shown, _, _) = window(&expanded, 0, 0, available);
+        let _ = writeln!(output, "{line_number:>gutter$} | {shown}");
+    };
+
+    let mut output = String::new();
+    let _ = writeln!(output, "{pad}--> {location}");
+    let _ = writeln!(output, "{pad} |");
+    for line_number in first_shown..violation_match.line {
+        context_line(&mut output, line_number);
+    }
+    for line_number in violation_match.line..=last_line {
+        let line = lines.get(line_number - 1).copied().unwrap_or_default();
+        let (expanded, columns) = expand_tabs(line);
+        let start_char = if line_number == violation_match.line {
+            violation_match.column - 1
+        } else {
+            0
+        };
+        let end_char = if line_number == last_line {
+            end_column - 1
+        } else {
+            columns.len() - 1
+        };
+        let start = columns[start_char.min(columns.len() - 1)];
+        let mut end = columns[end_char.min(columns.len() - 1)];
+        // An empty match, or one starting at the end of its line, still gets a caret.
+        if line_number == violation_match.line && end <= start {
+            end = start + 1;
+        }
+
+        let (shown, start, end) = window(&expanded, start, end, available);
+        let _ = writeln!(output, "{line_number:>gutter$} | {shown}");
+        if end > start {
+            let carets = paint(&"^".repeat(end - start));
+            if line_number == last_line {
+                let _ = writeln!(output, "{pad} | {}{carets} {label}", " ".repeat(start));
+            } else {
+                let _ = writeln!(output, "{pad} | {}{carets}", " ".repeat(start));
+            }
+        } else if line_number == last_line {
+            let _ = writeln!(output, "{pad} | {label}");
+        }
+    }
+    for line_number in last_line + 1..=last_shown {
+        context_line(&mut output, line_number);
+    }
+    let _ = writeln!(output, "{pad} |");
+    output
+}
+
+/// Expands tabs to the next multiple of `TAB_WIDTH`, returning the expanded line and the
+/// display column of each character, plus one past the end.
+fn expand_tabs(line: &str) -> (String, Vec<usize>) {
+    let mut expanded = String::with_capacity(line.len());
+    let mut columns = Vec::with_capacity(line.len() + 1);
+    let mut column = 0;
+    for character in line.chars() {
+        columns.push(column);
+        if character == '\t' {
+            let spaces = TAB_WIDTH - column % TAB_WIDTH;
+            expanded.extend(std::iter::repeat_n(' ', spaces));
+            column += spaces;
+        } else {
+            expanded.push(character);
+            column += 1;
+        }
+    }
+    columns.push(column);
+    (expanded, columns)
+}
+
+/// Cuts a line longer than `available` columns down to a window around the span
+/// `start..end`, marking the cut ends with `...`, and shifts the span to match.
+fn window(line: &str, start: usize, end: usize, available: usize) -> (String, usize, usize) {
+    let chars: Vec<char> = line.chars().collect();
+    if chars.len() <= available {
+        return (line.to_owned(), start, end);
+    }
+
+    let keep = available - 6;
+    let from = start
+        .saturating_sub(keep / 4)
+        .min(chars.len().saturating_sub(keep));
+    let to = (from + keep).min(chars.len());
+    let mut shown = String::new();
+    let mut offset = 0;
+    if from > 0 {
+        shown.push_str("...");
+        offset = 3;
+    }
+    shown.extend(&chars[from..to]);
+    if to < chars.len() {
+        shown.push_str("...");
+    }
+    (
+        shown,
+        start.clamp(from, to) - from + offset,
+        end.clamp(from, to) - from + offset,
+    )
+}
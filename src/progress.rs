@@ -0,0 +1,36 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// How far a scan has got, updated by the walker threads as they finish each file so a
+/// progress display can read it while the scan runs.
+#[derive(Default)]
+pub struct Progress {
+    files: AtomicUsize,
+    current: Mutex<PathBuf>,
+}
+
+impl Progress {
+    /// Counts a finished file. The latest path is only recorded when no other thread is
+    /// recording or reading one, so the walkers never wait on each other.
+    pub fn record(&self, path: &Path) {
+        self.files.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut current) = self.current.try_lock() {
+            current.clear();
+            current.push(path);
+        }
+    }
+
+    /// The number of files finished so far.
+    pub fn files(&self) -> usize {
+        self.files.load(Ordering::Relaxed)
+    }
+
+    /// The file finished most recently, as walked.
+    pub fn current(&self) -> PathBuf {
+        self.current
+            .lock()
+            .map(|current| current.clone())
+            .unwrap_or_default()
+    }
+}
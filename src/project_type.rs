@@ -0,0 +1,96 @@
+/// A kind of project `linty init` recognizes from a marker file in the current directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProjectType {
+    pub name: &'static str,
+    marker: &'static str,
+    /// Build output and vendored dependencies, as global exclude globs.
+    pub excludes: &'static [&'static str],
+    /// Source files for the example rule's includes.
+    pub includes: &'static [&'static str],
+}
+
+const PROJECT_TYPES: [ProjectType; 4] = [
+    ProjectType {
+        name: "Rust",
+        marker: "Cargo.toml",
+        excludes: &["**/target/**"],
+        includes: &["**/*.rs"],
+    },
+    ProjectType {
+        name: "Node",
+        marker: "package.json",
+        excludes: &["**/node_modules/**", "**/dist/**"],
+        includes: &["**/*.js", "**/*.jsx", "**/*.ts", "**/*.tsx"],
+    },
+    ProjectType {
+        name: "Go",
+        marker: "go.mod",
+        excludes: &["**/vendor/**"],
+        includes: &["**/*.go"],
+    },
+    ProjectType {
+        name: "Python",
+        marker: "pyproject.toml",
+        excludes: &["**/.venv/**", "**/__pycache__/**"],
+        includes: &["**/*.py"],
+    },
+];
+
+/// The project types whose marker files appear among `file_names`, the entries of a
+/// directory listing.
+pub fn detect<S: AsRef<str>>(file_names: &[S]) -> Vec<ProjectType> {
+    PROJECT_TYPES
+        .into_iter()
+        .filter(|project_type| {
+            file_names
+                .iter()
+                .any(|file_name| file_name.as_ref() == project_type.marker)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(file_names: &[&str]) -> Vec<&'static str> {
+        detect(file_names)
+            .into_iter()
+            .map(|project_type| project_type.name)
+            .collect()
+    }
+
+    #[test]
+    fn each_marker_detects_its_project_type() {
+        assert_eq!(names(&["Cargo.toml", "src", "README.md"]), ["Rust"]);
+        assert_eq!(names(&["package.json", "index.js"]), ["Node"]);
+        assert_eq!(names(&["go.mod", "go.sum"]), ["Go"]);
+        assert_eq!(names(&["pyproject.toml"]), ["Python"]);
+    }
+
+    #[test]
+    fn several_markers_detect_every_type_in_a_fixed_order() {
+        assert_eq!(
+            names(&["pyproject.toml", "package.json", "Cargo.toml"]),
+            ["Rust", "Node", "Python"]
+        );
+        let detected = detect(&["package.json", "Cargo.toml"]);
+        assert_eq!(detected[0].excludes, ["**/target/**"]);
+        assert_eq!(detected[1].excludes, ["**/node_modules/**", "**/dist/**"]);
+    }
+
+    #[test]
+    fn only_exact_marker_names_count() {
+        assert!(names(&[]).is_empty());
+        assert!(names(&[
+            "cargo.toml",
+            "Cargo.toml.bak",
+            "sub/go.mod",
+            "package.json5"
+        ])
+        .is_empty());
+        // Owned names from a real listing work as well.
+        let listing: Vec<String> = vec![String::from("go.mod")];
+        assert_eq!(detect(&listing)[0].includes, ["**/*.go"]);
+    }
+}
@@ -0,0 +1,81 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Rule id of the warning recorded for a file whose region markers don't pair up.
+pub const UNBALANCED_REGION_RULE_ID: &str = "linty::unbalanced-region";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RegionMode {
+    Inside,
+    Outside,
+}
+
+/// A rule's `region`: lines matching `start` and `end` delimit regions, and the rule
+/// only sees the lines inside them, or only the lines outside them.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RegionConfig {
+    pub start: String,
+    pub end: String,
+    pub mode: RegionMode,
+}
+
+pub struct Region {
+    start: Regex,
+    end: Regex,
+    mode: RegionMode,
+}
+
+impl Region {
+    pub fn new(config: &RegionConfig) -> Result<Region, regex::Error> {
+        Ok(Region {
+            start: Regex::new(&config.start)?,
+            end: Regex::new(&config.end)?,
+            mode: config.mode,
+        })
+    }
+
+    /// The byte ranges of `contents` the rule sees. Marker lines belong to neither side.
+    /// When the markers are unbalanced, returns the range of the first marker line that
+    /// doesn't pair up instead.
+    pub fn partitions(&self, contents: &str) -> Result<Vec<Range<usize>>, Range<usize>> {
+        let mut partitions = Vec::new();
+        // The opening marker line, and where the line after it starts.
+        let mut opened: Option<(Range<usize>, usize)> = None;
+        let mut outside_start = 0;
+        let mut offset = 0;
+        for line in contents.split_inclusive('\n') {
+            let line_start = offset;
+            offset += line.len();
+            let text = line.trim_end_matches(['\r', '\n']);
+            let marker = line_start..line_start + text.len();
+            match &opened {
+                None if self.start.is_match(text) => {
+                    if self.mode == RegionMode::Outside {
+                        partitions.push(outside_start..line_start);
+                    }
+                    opened = Some((marker, offset));
+                }
+                None if self.end.is_match(text) => return Err(marker),
+                Some((_, inside_start)) if self.end.is_match(text) => {
+                    if self.mode == RegionMode::Inside {
+                        partitions.push(*inside_start..line_start);
+                    }
+                    outside_start = offset;
+                    opened = None;
+                }
+                Some(_) if self.start.is_match(text) => return Err(marker),
+                _ => {}
+            }
+        }
+        if let Some((start_marker, _)) = opened {
+            return Err(start_marker);
+        }
+        if self.mode == RegionMode::Outside {
+            partitions.push(outside_start..contents.len());
+        }
+        partitions.retain(|partition| !partition.is_empty());
+        Ok(partitions)
+    }
+}
@@ -0,0 +1,86 @@
+use crate::outcome::{self, Outcome, Reason};
+use linty::scan::{self, Match};
+use linty::{build_rules, Config, Rule};
+
+/// Checks each rule's `examples` for `linty test`: every `matches` entry must give the
+/// rule at least one match and every `no_matches` entry none, with a line per example
+/// saying what matched. Disabled and hook-only rules are checked too. Fails with a config
+/// error when a rule doesn't compile, and with status 1 when an example fails.
+pub fn run(config: &Config) -> Outcome {
+    let mut config = config.clone();
+    for rule in &mut config.rules {
+        rule.enabled = Some(true);
+        rule.pre_commit = None;
+        rule.pre_commit_only = None;
+    }
+    let (rules, problems) = build_rules(&config, false);
+    if !problems.is_empty() {
+        for problem in &problems {
+            eprintln!("{problem}");
+        }
+        eprintln!("Found {} problem(s) in the config", problems.len());
+        outcome::fail(Reason::ConfigError);
+    }
+
+    let mut passed = 0;
+    let mut failed = 0;
+    let mut untested = 0;
+    for rule_config in &config.rules {
+        let Some(rule) = rules.iter().find(|rule| rule.id == rule_config.id) else {
+            continue;
+        };
+        let examples = rule_config.examples.clone().unwrap_or_default();
+        if examples.matches.is_empty() && examples.no_matches.is_empty() {
+            println!("Rule {}: no examples, skipped", rule.id);
+            untested += 1;
+            continue;
+        }
+        println!("Rule {}:", rule.id);
+        for (field, texts, should_match) in [
+            ("matches", &examples.matches, true),
+            ("no_matches", &examples.no_matches, false),
+        ] {
+            for text in texts {
+                let found = scan::example_matches(rule, text);
+                let pass = found.is_empty() != should_match;
+                if pass {
+                    passed += 1;
+                } else {
+                    failed += 1;
+                }
+                println!(
+                    "  {} {field} {text:?}: {}",
+                    if pass { "pass" } else { "FAIL" },
+                    describe(rule, &found)
+                );
+            }
+        }
+    }
+    println!("{passed} example(s) passed and {failed} failed; {untested} rule(s) have no examples");
+    if failed > 0 {
+        return Outcome::new(Reason::Violations, Reason::Violations.exit_code());
+    }
+    Outcome::clean()
+}
+
+/// What the rule found in an example, for working out why one fails.
+fn describe(rule: &Rule, found: &[Match]) -> String {
+    let Some(first) = found.first() else {
+        return if !rule.kind.reads_contents() {
+            String::from("the regex doesn't match it as a path")
+        } else {
+            String::from("nothing matched")
+        };
+    };
+    let mut description = format!(
+        "matched {:?} at line {}, column {}",
+        first.text, first.line, first.column
+    );
+    if let Some(message) = &first.message {
+        description.push_str(&format!(" ({message})"));
+    }
+    if found.len() > 1 {
+        description.push_str(&format!(" and {} more", found.len() - 1));
+    }
+    description
+}
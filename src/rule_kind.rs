@@ -0,0 +1,359 @@
+use crate::metric::Metric;
+use crate::normalize::{NormalForm, Normalized};
+use crate::{scan, Rule, Severity};
+use regex::Regex;
+use std::borrow::Cow;
+use std::cell::{Cell, OnceCell, RefCell};
+use std::collections::HashMap;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+use std::time::Instant;
+
+/// What a rule checks a file for. The built-in kinds are [`Pattern`], [`Metric`],
+/// [`PathPattern`], and [`Required`]; others can be registered with a [`crate::Linter`]
+/// when the `rule-kinds` feature is on.
+pub trait RuleKind: Send + Sync {
+    /// The kind's name. A registered kind is a rule of its own, reported under its name.
+    fn name(&self) -> &str;
+
+    /// The severity a registered kind's findings are reported with. Built-in kinds take
+    /// their rule's instead.
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    /// Whether `check` looks at the contents. Only kinds that do have files read for
+    /// them, and skipped for being binary, too large, or not UTF-8.
+    fn reads_contents(&self) -> bool {
+        true
+    }
+
+    /// What's wrong with `file`.
+    fn check(&self, file: &FileContext) -> Vec<Finding>;
+}
+
+/// Something a rule kind reports about a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Finding {
+    /// Text at a byte range of the contents, with a message of its own or the rule's.
+    At {
+        range: Range<usize>,
+        message: Option<String>,
+    },
+    /// The file as a whole, with no position in it, as a path rule reports it.
+    File,
+    /// Something missing from the file, reported at its start, as a required rule
+    /// reports it.
+    Missing,
+}
+
+/// One file as a rule kind checks it. The contents are read the first time they're
+/// asked for, so a kind that only looks at the path never opens the file.
+pub struct FileContext<'a> {
+    path: &'a Path,
+    /// The file to read the contents from, when they weren't given.
+    disk_path: Option<&'a Path>,
+    contents: OnceCell<Result<Cow<'a, str>, (io::ErrorKind, String)>>,
+    line_index: OnceCell<LineIndex>,
+    metadata: OnceCell<Option<std::fs::Metadata>>,
+    /// How the scan searches the contents with the rule's regex, for the built-in kinds.
+    pub(crate) search: Option<Search<'a>>,
+}
+
+impl<'a> FileContext<'a> {
+    /// `contents` checked as the file at `path`, which isn't read from disk.
+    pub fn new(path: &'a Path, contents: &'a str) -> FileContext<'a> {
+        FileContext {
+            path,
+            disk_path: None,
+            contents: OnceCell::from(Ok(Cow::Borrowed(contents))),
+            line_index: OnceCell::new(),
+            metadata: OnceCell::new(),
+            search: None,
+        }
+    }
+
+    /// The file at `disk_path`, which globs see as `path`, read if its contents are asked
+    /// for.
+    pub(crate) fn on_disk(path: &'a Path, disk_path: &'a Path) -> FileContext<'a> {
+        FileContext {
+            disk_path: Some(disk_path),
+            contents: OnceCell::new(),
+            ..FileContext::new(path, "")
+        }
+    }
+
+    /// The path relative to the current directory, without a leading `./`, as globs
+    /// and path rules match it.
+    pub fn path(&self) -> &Path {
+        self.path
+    }
+
+    /// The contents, or the part of them the scan reads at a time when a large file is
+    /// read in chunks for rules that only look at one line at a time.
+    pub fn contents(&self) -> io::Result<&str> {
+        let contents = self.contents.get_or_init(|| {
+            let disk_path = self.disk_path.unwrap_or(self.path);
+            std::fs::read_to_string(disk_path)
+                .map(Cow::Owned)
+                .map_err(|err| (err.kind(), err.to_string()))
+        });
+        match contents {
+            Ok(contents) => Ok(contents),
+            Err((kind, message)) => Err(io::Error::new(*kind, message.clone())),
+        }
+    }
+
+    /// Where each line of the contents starts.
+    pub fn line_index(&self) -> io::Result<&LineIndex> {
+        let contents = self.contents()?;
+        Ok(self.line_index.get_or_init(|| LineIndex::new(contents)))
+    }
+
+    /// The file's metadata on disk, or `None` for contents that didn't come from a file,
+    /// like staged or piped ones.
+    pub fn metadata(&self) -> Option<&std::fs::Metadata> {
+        self.metadata
+            .get_or_init(|| std::fs::metadata(self.disk_path?).ok())
+            .as_ref()
+    }
+
+    /// The context with how the scan searches it with a rule's regex.
+    pub(crate) fn with_search(self, search: Search<'a>) -> FileContext<'a> {
+        FileContext {
+            search: Some(search),
+            ..self
+        }
+    }
+
+    /// Whether the rule's regex went over the rule timeout while searching.
+    pub(crate) fn timed_out(&self) -> bool {
+        self.search
+            .as_ref()
+            .is_some_and(|search| search.timed_out.get())
+    }
+}
+
+/// How the scan searches a file with a rule's regex.
+pub(crate) struct Search<'a> {
+    pub rule: &'a Rule,
+    /// The parts of the contents the rule looks at, after its scan window and region.
+    pub slices: Vec<Range<usize>>,
+    /// False when the prefilter found that the regex matches nowhere in the contents.
+    pub can_match: bool,
+    /// Normalized contents, shared between the rules that see all of them.
+    pub normalized: RefCell<&'a mut HashMap<NormalForm, Normalized>>,
+    pub deadline: Option<Instant>,
+    pub timed_out: Cell<bool>,
+}
+
+impl Search<'_> {
+    /// The ranges of `contents` the rule's regex matches, or only the first when
+    /// `first_only`.
+    fn find(&self, contents: &str, first_only: bool) -> Vec<Range<usize>> {
+        if !self.can_match {
+            return Vec::new();
+        }
+        let found = scan::find_ranges(
+            self.rule,
+            contents,
+            self.slices.clone(),
+            &mut self.normalized.borrow_mut(),
+            self.deadline,
+            first_only,
+        );
+        found.unwrap_or_else(|| {
+            self.timed_out.set(true);
+            Vec::new()
+        })
+    }
+}
+
+/// The ranges the scan's regex search finds in `file`, for a built-in kind, or none
+/// outside a scan.
+fn search(file: &FileContext, first_only: bool) -> Vec<Range<usize>> {
+    match (&file.search, file.contents()) {
+        (Some(search), Ok(contents)) => search.find(contents, first_only),
+        _ => Vec::new(),
+    }
+}
+
+/// Where each line of a file's contents starts.
+pub struct LineIndex {
+    starts: Vec<usize>,
+    len: usize,
+}
+
+impl LineIndex {
+    pub fn new(contents: &str) -> LineIndex {
+        LineIndex {
+            starts: std::iter::once(0)
+                .chain(contents.match_indices('\n').map(|(index, _)| index + 1))
+                .collect(),
+            len: contents.len(),
+        }
+    }
+
+    /// How many lines there are, counting text after the last newline as one, and none
+    /// for empty contents.
+    pub fn line_count(&self) -> usize {
+        match self.starts.last() {
+            Some(&last) if last == self.len => self.starts.len() - 1,
+            _ => self.starts.len(),
+        }
+    }
+
+    /// The 1-based line the byte `offset` is on.
+    pub fn line(&self, offset: usize) -> usize {
+        self.starts.partition_point(|&start| start <= offset)
+    }
+
+    /// The byte offset 1-based `line` starts at.
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.starts.get(line.checked_sub(1)?).copied()
+    }
+}
+
+/// Reports each match in the contents.
+pub struct Pattern {
+    /// Only the first match in a file is reported.
+    pub report_once: bool,
+}
+
+impl RuleKind for Pattern {
+    fn name(&self) -> &str {
+        "pattern"
+    }
+
+    fn check(&self, file: &FileContext) -> Vec<Finding> {
+        search(file, self.report_once)
+            .into_iter()
+            .map(|range| Finding::At {
+                range,
+                message: None,
+            })
+            .collect()
+    }
+}
+
+impl RuleKind for Metric {
+    fn name(&self) -> &str {
+        self.kind.name()
+    }
+
+    fn check(&self, file: &FileContext) -> Vec<Finding> {
+        let Ok(contents) = file.contents() else {
+            return Vec::new();
+        };
+        // Measuring can leave out the first match, so a report_once rule still needs all
+        // of them.
+        self.measure(contents, search(file, false))
+            .into_iter()
+            .map(|(range, message)| Finding::At {
+                range,
+                message: Some(message),
+            })
+            .collect()
+    }
+}
+
+/// Reports a file whose path the regex matches. Its contents are never read.
+pub struct PathPattern(pub Regex);
+
+impl RuleKind for PathPattern {
+    fn name(&self) -> &str {
+        "path"
+    }
+
+    fn reads_contents(&self) -> bool {
+        false
+    }
+
+    fn check(&self, file: &FileContext) -> Vec<Finding> {
+        if self.0.is_match(&file.path.to_string_lossy()) {
+            vec![Finding::File]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Reports a file the regex finds nothing in, once, and never what it finds.
+pub struct Required;
+
+impl Required {
+    pub const NAME: &'static str = "required";
+}
+
+impl RuleKind for Required {
+    fn name(&self) -> &str {
+        Required::NAME
+    }
+
+    fn check(&self, file: &FileContext) -> Vec<Finding> {
+        if file.contents().is_ok() && search(file, true).is_empty() {
+            vec![Finding::Missing]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_index_finds_lines() {
+        let index = LineIndex::new("ab\ncd\n\nef");
+        assert_eq!(index.line_count(), 4);
+        assert_eq!(index.line(0), 1);
+        assert_eq!(index.line(2), 1);
+        assert_eq!(index.line(3), 2);
+        assert_eq!(index.line(6), 3);
+        assert_eq!(index.line(8), 4);
+        assert_eq!(index.line_start(4), Some(7));
+        assert_eq!(index.line_start(0), None);
+        assert_eq!(index.line_start(5), None);
+    }
+
+    #[test]
+    fn given_contents_are_never_read_from_disk() {
+        let file = FileContext::new(Path::new("no/such/file.txt"), "TODO\n");
+        assert_eq!(file.contents().unwrap(), "TODO\n");
+        assert_eq!(file.line_index().unwrap().line_count(), 1);
+        assert!(file.metadata().is_none());
+    }
+
+    #[test]
+    fn disk_contents_are_read_when_asked_for() {
+        let file = FileContext::on_disk(Path::new("Cargo.toml"), Path::new("./Cargo.toml"));
+        assert!(file.metadata().is_some_and(|metadata| metadata.is_file()));
+        assert!(file.contents().unwrap().contains("[package]"));
+
+        let missing = FileContext::on_disk(Path::new("missing"), Path::new("./no/such/file"));
+        assert_eq!(
+            missing.contents().unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        assert!(missing.metadata().is_none());
+    }
+
+    #[test]
+    fn path_patterns_match_the_path_without_contents() {
+        let kind = PathPattern(Regex::new(r"\.orig$").unwrap());
+        assert!(!kind.reads_contents());
+        let file = FileContext::on_disk(Path::new("src/a.rs.orig"), Path::new("./no/such/file"));
+        assert_eq!(kind.check(&file), [Finding::File]);
+        let file = FileContext::on_disk(Path::new("src/a.rs"), Path::new("./no/such/file"));
+        assert!(kind.check(&file).is_empty());
+    }
+
+    #[test]
+    fn built_in_kinds_find_nothing_outside_a_scan() {
+        let file = FileContext::new(Path::new("a.txt"), "TODO\n");
+        assert!(Pattern { report_once: false }.check(&file).is_empty());
+        assert_eq!(Required.check(&file), [Finding::Missing]);
+    }
+}
@@ -0,0 +1,333 @@
+use crate::json_report::ReportMeta;
+use crate::{RuleConfig, Severity, Violation};
+use serde::Serialize;
+use std::collections::HashMap;
+
+const SARIF_VERSION: &str = "2.1.0";
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+#[derive(Serialize)]
+struct Log<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run<'a> {
+    tool: Tool<'a>,
+    /// The commit the results are for, when the run is in a git work tree.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    version_control_provenance: Vec<VersionControlDetails<'a>>,
+    results: Vec<SarifResult<'a>>,
+    /// Linty counts columns in characters rather than SARIF's default UTF-16 code units.
+    column_kind: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionControlDetails<'a> {
+    repository_uri: String,
+    revision_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    branch: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct Tool<'a> {
+    driver: Driver<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver<'a> {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<ReportingDescriptor<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportingDescriptor<'a> {
+    id: &'a str,
+    short_description: Message<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full_description: Option<Message<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_uri: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct Message<'a> {
+    text: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult<'a> {
+    rule_id: &'a str,
+    rule_index: usize,
+    level: &'static str,
+    message: Message<'a>,
+    locations: [Location; 1],
+    partial_fingerprints: HashMap<&'static str, &'a str>,
+    /// The rule's `fix` for the match, which code scanning offers as a suggestion.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix<'a> {
+    description: Message<'a>,
+    artifact_changes: [ArtifactChange<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange<'a> {
+    artifact_location: ArtifactLocation,
+    replacements: [Replacement<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement<'a> {
+    deleted_region: Region,
+    inserted_content: Message<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    /// Left out for a path rule's match, which is about the whole file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<Region>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: String,
+    /// Relative URIs resolve against the checkout, wherever the upload runs.
+    uri_base_id: &'static str,
+}
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+/// Writes a SARIF 2.1.0 log with one result per match, for code scanning uploads.
+/// `repository` is the remote or work tree the results are for, which `meta`'s commit and
+/// branch are recorded against.
+pub fn write_log(
+    writer: impl std::io::Write,
+    rule_configs: &[RuleConfig],
+    violations: &[Violation],
+    messages: &HashMap<&str, &str>,
+    meta: Option<&ReportMeta>,
+    repository: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut rules: Vec<ReportingDescriptor> = Vec::new();
+    let mut rule_indices: HashMap<&str, usize> = HashMap::new();
+    // Notes like linty::file-overflow aren't configured rules, but their results still
+    // need an entry to point at.
+    let rule_ids = rule_configs
+        .iter()
+        .map(|rule| {
+            (
+                rule.id.as_str(),
+                rule.description.as_deref(),
+                rule.url.as_deref(),
+            )
+        })
+        .chain(
+            violations
+                .iter()
+                .map(|violation| (violation.rule_id.as_str(), None, None)),
+        );
+    for (id, description, help_uri) in rule_ids {
+        if rule_indices.contains_key(id) {
+            continue;
+        }
+        rule_indices.insert(id, rules.len());
+        rules.push(ReportingDescriptor {
+            id,
+            short_description: Message {
+                text: message(messages, id),
+            },
+            full_description: description.map(|text| Message { text }),
+            help_uri,
+        });
+    }
+
+    let mut results = Vec::new();
+    for violation in violations {
+        let id = violation.rule_id.as_str();
+        let uri = relative_uri(&violation.file);
+        for m in &violation.matches {
+            let region = m.has_position().then_some(Region {
+                start_line: m.line,
+                start_column: m.column,
+                end_line: m.end_line,
+                end_column: m.end_column,
+            });
+            let fixes = match (region, m.replacement.as_deref()) {
+                (Some(region), Some(replacement)) => vec![Fix {
+                    description: Message {
+                        text: "Apply the rule's fix",
+                    },
+                    artifact_changes: [ArtifactChange {
+                        artifact_location: ArtifactLocation {
+                            uri: uri.clone(),
+                            uri_base_id: "%SRCROOT%",
+                        },
+                        replacements: [Replacement {
+                            deleted_region: region,
+                            inserted_content: Message { text: replacement },
+                        }],
+                    }],
+                }],
+                _ => Vec::new(),
+            };
+            results.push(SarifResult {
+                rule_id: id,
+                rule_index: rule_indices[id],
+                level: match violation.severity {
+                    Severity::Warning => "warning",
+                    Severity::Error => "error",
+                },
+                message: Message {
+                    text: m
+                        .message
+                        .as_deref()
+                        .filter(|text| !text.is_empty())
+                        .unwrap_or_else(|| message(messages, id)),
+                },
+                locations: [Location {
+                    physical_location: PhysicalLocation {
+                        artifact_location: ArtifactLocation {
+                            uri: uri.clone(),
+                            uri_base_id: "%SRCROOT%",
+                        },
+                        region,
+                    },
+                }],
+                partial_fingerprints: HashMap::from([(
+                    "lintyFingerprint/v1",
+                    m.fingerprint.as_str(),
+                )]),
+                fixes,
+            });
+        }
+    }
+
+    let log = Log {
+        schema: SARIF_SCHEMA,
+        version: SARIF_VERSION,
+        runs: [Run {
+            tool: Tool {
+                driver: Driver {
+                    name: "linty",
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: "https://github.com/rwblickhan/linty",
+                    rules,
+                },
+            },
+            version_control_provenance: meta
+                .and_then(ReportMeta::commit)
+                .zip(repository)
+                .map(|(revision_id, repository)| VersionControlDetails {
+                    repository_uri: repository_uri(repository),
+                    revision_id,
+                    branch: meta.and_then(ReportMeta::branch),
+                })
+                .into_iter()
+                .collect(),
+            results,
+            column_kind: "unicodeCodePoints",
+        }],
+    };
+    serde_json::to_writer_pretty(writer, &log)?;
+    Ok(())
+}
+
+/// The rule's message, or its id when it has none, since SARIF requires the text.
+fn message<'a>(messages: &HashMap<&str, &'a str>, id: &'a str) -> &'a str {
+    messages
+        .get(id)
+        .copied()
+        .filter(|text| !text.is_empty())
+        .unwrap_or(id)
+}
+
+/// A reported path as a relative URI reference: `/`-separated, with characters that
+/// aren't allowed in a URI path percent-encoded. `:` is encoded too, so the first
+/// segment can't be read as a scheme.
+fn relative_uri(path: &str) -> String {
+    let mut uri = String::new();
+    for byte in path.replace(std::path::MAIN_SEPARATOR, "/").bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~!$&'()*+,;=@".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    uri
+}
+
+/// A git remote or work tree as the absolute URI SARIF wants: URLs as they are, scp-like
+/// remotes like `git@github.com:owner/repo.git` as `ssh://` URLs, and paths as `file://`
+/// URLs.
+fn repository_uri(repository: &str) -> String {
+    if repository.contains("://") {
+        return repository.to_owned();
+    }
+    let path = repository.replace('\\', "/");
+    let is_drive =
+        path.as_bytes().get(1) == Some(&b':') && path.as_bytes()[0].is_ascii_alphabetic();
+    if path.starts_with('/') {
+        return format!("file://{}", relative_uri(&path));
+    }
+    if is_drive {
+        return format!("file:///{}", relative_uri(&path).replacen("%3A", ":", 1));
+    }
+    match path.split_once(':') {
+        Some((host, path)) => format!("ssh://{host}/{}", path.trim_start_matches('/')),
+        None => format!("file://{}", relative_uri(&path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repository_uris_are_absolute() {
+        assert_eq!(
+            repository_uri("https://github.com/owner/repo.git"),
+            "https://github.com/owner/repo.git"
+        );
+        assert_eq!(
+            repository_uri("git@github.com:owner/repo.git"),
+            "ssh://git@github.com/owner/repo.git"
+        );
+        assert_eq!(repository_uri("/work/my repo"), "file:///work/my%20repo");
+        assert_eq!(repository_uri("C:\\work\\repo"), "file:///C:/work/repo");
+    }
+}
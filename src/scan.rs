@@ -0,0 +1,2243 @@
+use crate::{
+    cache::{self, ResultCache},
+    cancel, chunked, compare,
+    coverage::Coverage,
+    fix,
+    git::Repo,
+    gitattributes, ignore_explain, log, message, normalize,
+    path_globs::PathGlobs,
+    prefilter::Prefilter,
+    progress::Progress,
+    region::UNBALANCED_REGION_RULE_ID,
+    rule_kind::{self, FileContext, Finding, RuleKind, Search},
+    severity_name,
+    suppress::{self, Suppressions, UNUSED_SUPPRESSION_RULE_ID},
+    InvalidUtf8, Override, Rule, RuleOverride, RuleToggle, Severity, Violation,
+};
+use ignore::{DirEntry, WalkBuilder, WalkState};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant, SystemTime};
+
+/// One place a rule's regex matched.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Match {
+    /// 1-based, or 0 for a path rule's match, which has no position; see `has_position`.
+    pub line: usize,
+    /// 1-based, counted in characters from the start of the line.
+    pub column: usize,
+    /// Where the match ends, exclusive, counted like `line` and `column`.
+    pub end_line: usize,
+    pub end_column: usize,
+    /// The first line of the matched text.
+    pub text: String,
+    pub fingerprint: String,
+    /// What the rule's `fix` template would replace the match with.
+    pub replacement: Option<String>,
+    /// The rule's message with this match's capture groups filled in, when the message
+    /// has placeholders; reports show it in place of the rule's message.
+    pub message: Option<String>,
+    /// Whether the match was longer than its rule's `max_match_length`, so the end
+    /// position stops short of it.
+    pub truncated: bool,
+}
+
+impl Match {
+    /// The message to report this match with: its own when the rule's message has
+    /// placeholders, or `rule_message` otherwise.
+    pub fn message<'a>(&'a self, rule_message: &'a str) -> &'a str {
+        self.message.as_deref().unwrap_or(rule_message)
+    }
+
+    /// Whether the match is at a place in the file, rather than being about its path.
+    pub fn has_position(&self) -> bool {
+        self.line > 0
+    }
+
+    /// The last line the matched text is on. A match that ends with a line break ends at
+    /// the start of the next line, which it doesn't otherwise touch.
+    pub fn last_line(&self) -> usize {
+        if self.end_column == 1 && self.end_line > self.line {
+            self.end_line - 1
+        } else {
+            self.end_line
+        }
+    }
+}
+
+/// Rule id of the note recorded for a file whose matches hit `--max-violations-per-file`.
+pub const OVERFLOW_RULE_ID: &str = "linty::file-overflow";
+
+/// Rule id of the note recorded for a file with a match longer than its rule's
+/// `max_match_length`.
+pub const OVERSIZED_MATCH_RULE_ID: &str = "linty::oversized-match";
+
+/// A rule's `max_match_length` when it doesn't set one.
+pub const DEFAULT_MAX_MATCH_LENGTH: usize = 10 * 1024;
+
+/// The part of a file a rule's regex sees, from the rule's `scan_window`.
+#[derive(Debug, Clone, Copy)]
+pub enum ScanWindow {
+    Head(usize),
+    Tail(usize),
+}
+
+impl ScanWindow {
+    /// The window's byte range in `contents`, shrunk to character boundaries.
+    fn range(self, contents: &str) -> Range<usize> {
+        match self {
+            ScanWindow::Head(bytes) => {
+                let mut end = bytes.min(contents.len());
+                while !contents.is_char_boundary(end) {
+                    end -= 1;
+                }
+                0..end
+            }
+            ScanWindow::Tail(bytes) => {
+                let mut start = contents.len().saturating_sub(bytes);
+                while !contents.is_char_boundary(start) {
+                    start += 1;
+                }
+                start..contents.len()
+            }
+        }
+    }
+}
+
+/// How the walk picks files, shared by the main run and `linty grep`.
+#[derive(Clone)]
+pub struct WalkOptions {
+    pub roots: Vec<PathBuf>,
+    pub ignore_sources: ignore_explain::IgnoreSources,
+    pub hidden: bool,
+    /// Descend into symlinked directories, reporting what's in them under the link's path.
+    pub follow_symlinks: bool,
+    pub include_generated: bool,
+    pub explain_skip: bool,
+    /// The config's global excludes; matching files aren't checked by any rule. Shared by
+    /// clones, so what they match adds up over the whole run.
+    pub excludes: Arc<PathGlobs>,
+    /// Stop recording matches for a file once this many have been recorded across rules.
+    pub max_violations_per_file: Option<usize>,
+    /// Record which files each rule examined, for `--coverage-json`.
+    pub record_coverage: bool,
+    /// Record how long each rule spent matching, for `--stats`.
+    pub record_rule_times: bool,
+    /// Stop matching a rule against a file once it has spent this long on it, for
+    /// `--rule-timeout`.
+    pub rule_timeout: Option<Duration>,
+    /// Walker threads to scan files on.
+    pub threads: usize,
+    /// Honor suppression comments like `linty-disable-next-line`.
+    pub inline_config: bool,
+    /// Warn about suppression comments that suppressed nothing.
+    pub report_unused_suppressions: bool,
+    /// Files checked as staged in the git index rather than as they are on disk, for
+    /// `--pre-commit`.
+    pub staged: HashSet<PathBuf>,
+    /// The git repository `staged` files are read from the index of.
+    pub repo: Option<Repo>,
+    /// What to do with files that aren't valid UTF-8.
+    pub invalid_utf8: InvalidUtf8,
+    /// Skip files that look binary for every rule without `binary: true`.
+    pub skip_binary: bool,
+    /// Skip files larger than this many bytes.
+    pub max_filesize: Option<u64>,
+    /// The current directory relative to the directory of a config found in a parent
+    /// directory, or empty. Globs match walked paths as a walk from the config's
+    /// directory would see them, wherever linty runs.
+    pub glob_base: PathBuf,
+    /// Only scan walked files at or under these absolute paths, for `--watch` re-runs.
+    /// The rest of the walk still decides which of them are ignored.
+    pub only: Option<HashSet<PathBuf>>,
+    /// A previous run's results, replayed for files that haven't changed since, for
+    /// `--cache`. Staged files are always scanned.
+    pub cache: Option<Arc<ResultCache>>,
+    /// Counts the files scanned as the walk goes, for a progress display.
+    pub progress: Option<Arc<Progress>>,
+}
+
+impl WalkOptions {
+    /// The path globs match for a walked path: relative to the config's directory, without
+    /// a `./` prefix, and with `/` separators. Named paths outside the config's directory
+    /// stay absolute.
+    pub fn glob_path<'a>(&self, path: &'a Path) -> Cow<'a, Path> {
+        if path.is_absolute() {
+            return Cow::Borrowed(path);
+        }
+        let relative = crate::normalize_lexically(&self.glob_base.join(path));
+        if std::path::MAIN_SEPARATOR == '/' {
+            return Cow::Owned(relative);
+        }
+        Cow::Owned(PathBuf::from(
+            relative
+                .to_string_lossy()
+                .replace(std::path::MAIN_SEPARATOR, "/"),
+        ))
+    }
+}
+
+pub struct ScanResult {
+    pub violations: Vec<Violation>,
+    /// Files the walk visited, including ones no rule applied to.
+    pub files_walked: usize,
+    pub files_scanned: usize,
+    pub duration: Duration,
+    /// The part of `duration` spent running regexes; the rest went to walking and reading.
+    pub match_duration: Duration,
+    /// Bytes of file contents read, which head windows can keep below the files' sizes.
+    pub bytes_read: u64,
+    /// Rule ids and severities of violations dropped entirely by `max_violations_per_file`,
+    /// still counted so the summary and exit status reflect every match.
+    pub dropped: Vec<(String, Severity)>,
+    /// Matches dropped by suppression comments, per rule id.
+    pub suppressed: BTreeMap<String, usize>,
+    /// Files no rule checked because they look binary.
+    pub binary_skipped: usize,
+    /// Files no rule checked because they're over `max_filesize`.
+    pub large_skipped: usize,
+    /// Files no rule checked because they aren't valid UTF-8.
+    pub encoding_skipped: usize,
+    /// Files left unread because the config's `excludes` match them.
+    pub excluded: usize,
+    /// Files whose results were replayed from `cache` rather than scanned; also counted
+    /// in `files_scanned` and the skip counts as they were when cached.
+    pub cached: usize,
+    /// Results of the files scanned that `cache` can replay next time, for the caller to
+    /// save with `ResultCache::render`.
+    pub cache_updates: Vec<(PathBuf, cache::Entry)>,
+    /// About how much of `duration` the cached files would have taken to scan, from what
+    /// checking them cost when they were cached.
+    pub cache_saved: Duration,
+    /// Time each rule spent matching, summed over files; only kept with `record_rule_times`.
+    pub rule_durations: HashMap<String, Duration>,
+    /// Rules left unfinished on a file for going over `rule_timeout`, counted per file.
+    pub timed_out: usize,
+    /// Set when `record_coverage` is.
+    pub coverage: Option<Coverage>,
+    /// Messages about entries the scan couldn't fully check, like a file that couldn't be
+    /// read, for the caller to print; the scan itself never prints them.
+    pub problems: Vec<String>,
+}
+
+/// Walks `options.roots` and runs every applicable rule against each file.
+/// Files are scanned on `options.threads` walker threads, and the violations are sorted
+/// by path afterwards so the output doesn't depend on scheduling.
+/// The walk stops early once the run is cancelled; see `cancel::cancelled`.
+pub fn scan(
+    options: &WalkOptions,
+    rules: &[Rule],
+    overrides: &[Override],
+    current_dir: &Path,
+) -> anyhow::Result<ScanResult> {
+    let scan_start = Instant::now();
+    let sources = options.ignore_sources;
+    cancel::set_scanning(true);
+
+    let prefilter = &prefilter(options, rules);
+    let (sender, receiver) = mpsc::channel::<anyhow::Result<EntryScan>>();
+    walk_builder(options)
+        .threads(options.threads)
+        .build_parallel()
+        .run(|| {
+            let sender = sender.clone();
+            // Each thread keeps its own cache of parsed .gitattributes files.
+            let mut generated_matcher = gitattributes::GeneratedMatcher::new(current_dir);
+            let mut linty_ignore =
+                ignore_explain::LintyIgnoreMatcher::new(current_dir, options.ignore_sources.parent);
+            Box::new(move |result| {
+                if cancel::cancelled().is_some() {
+                    return WalkState::Quit;
+                }
+                match result {
+                    Err(err) => {
+                        let problem = match symlink_loop(&err) {
+                            Some((child, ancestor)) => format!(
+                                "Warning: Skipping {}: it links back to {}",
+                                child.display(),
+                                ancestor.display()
+                            ),
+                            None => format!("Error: {err}"),
+                        };
+                        let failed = EntryScan {
+                            problems: vec![problem],
+                            ..Default::default()
+                        };
+                        if sender.send(Ok(failed)).is_err() {
+                            return WalkState::Quit;
+                        }
+                    }
+                    Ok(entry) => {
+                        let busy_start = Instant::now();
+                        let scanned = scan_entry(
+                            &entry,
+                            options,
+                            rules,
+                            prefilter,
+                            overrides,
+                            &mut generated_matcher,
+                            &mut linty_ignore,
+                        )
+                        .map(|scanned| EntryScan {
+                            busy_duration: busy_start.elapsed(),
+                            ..scanned
+                        });
+                        if let (Some(progress), Ok(scanned)) = (&options.progress, &scanned) {
+                            if scanned.walked {
+                                progress.record(&scanned.path);
+                            }
+                        }
+                        let failed = scanned.is_err();
+                        if sender.send(scanned).is_err() || failed {
+                            return WalkState::Quit;
+                        }
+                    }
+                }
+                WalkState::Continue
+            })
+        });
+    drop(sender);
+
+    let (result, visited) = merge_scans(receiver, options, rules, scan_start)?;
+    cancel::set_scanning(false);
+
+    if options.explain_skip && cancel::cancelled().is_none() {
+        for path in ignore_explain::skipped_paths(visited, &options.roots, !options.hidden) {
+            eprintln!(
+                "Skipping {}: {}",
+                path.display(),
+                ignore_explain::explain(&path, current_dir, sources)
+            );
+        }
+    }
+
+    Ok(result)
+}
+
+/// The walk of `options.roots`. Ignored directories are pruned rather than walked and
+/// filtered, and .lintyignore is honored even with --no-ignore since it only exists to
+/// exclude files from linting.
+fn walk_builder(options: &WalkOptions) -> WalkBuilder {
+    let sources = options.ignore_sources;
+    let mut walk_builder = WalkBuilder::new(&options.roots[0]);
+    for root in &options.roots[1..] {
+        walk_builder.add(root);
+    }
+    walk_builder
+        .add_custom_ignore_filename(ignore_explain::LINTY_IGNORE_FILENAME)
+        .git_ignore(sources.vcs)
+        .git_global(sources.vcs)
+        .git_exclude(sources.vcs)
+        .ignore(sources.dot)
+        .parents(sources.parent)
+        .hidden(!options.hidden)
+        .follow_links(options.follow_symlinks);
+    walk_builder
+}
+
+/// Where the walk of `options.roots` stops short of `path`, a walk path like
+/// `./src/main.rs`: at `path` itself or at the hidden or ignored directory it's in.
+/// `None` when the walk reaches it. Only the directories leading to `path` are walked.
+pub fn walk_stop(options: &WalkOptions, path: &Path) -> Option<PathBuf> {
+    let target = path.to_owned();
+    let reached: HashSet<PathBuf> = walk_builder(options)
+        .filter_entry(move |entry| target.starts_with(entry.path()))
+        .build()
+        .flatten()
+        .map(|entry| entry.path().to_owned())
+        .collect();
+    if reached.contains(path) {
+        return None;
+    }
+    let ancestors: Vec<&Path> = path.ancestors().collect();
+    ancestors
+        .into_iter()
+        .rev()
+        .skip_while(|ancestor| !reached.contains(*ancestor))
+        .find(|ancestor| !reached.contains(*ancestor))
+        .map(Path::to_owned)
+        .or_else(|| Some(path.to_owned()))
+}
+
+/// The link and the directory it leads back to, when the walk stopped at a symlink loop.
+fn symlink_loop(err: &ignore::Error) -> Option<(&Path, &Path)> {
+    match err {
+        ignore::Error::Loop { ancestor, child } => Some((child, ancestor)),
+        ignore::Error::WithPath { err, .. }
+        | ignore::Error::WithDepth { err, .. }
+        | ignore::Error::WithLineNumber { err, .. } => symlink_loop(err),
+        _ => None,
+    }
+}
+
+/// Scans `contents`, piped in with `--stdin`, as if it were the file at `path`, with the
+/// same rules, globs, and binary sniffing as a walked file. Empty input is never scanned.
+pub fn scan_stdin(
+    contents: &[u8],
+    path: &Path,
+    options: &WalkOptions,
+    rules: &[Rule],
+    overrides: &[Override],
+    current_dir: &Path,
+) -> anyhow::Result<ScanResult> {
+    let scan_start = Instant::now();
+    let scanned = if contents.is_empty() {
+        EntryScan {
+            path: path.to_owned(),
+            walked: true,
+            ..Default::default()
+        }
+    } else {
+        let target = ScanTarget {
+            path,
+            size: contents.len() as u64,
+            modified: None,
+            piped: Some(contents),
+            root: true,
+        };
+        let mut generated_matcher = gitattributes::GeneratedMatcher::new(current_dir);
+        let mut linty_ignore =
+            ignore_explain::LintyIgnoreMatcher::new(current_dir, options.ignore_sources.parent);
+        let prefilter = prefilter(options, rules);
+        let scanned = scan_file(
+            &target,
+            options,
+            rules,
+            &prefilter,
+            overrides,
+            &mut generated_matcher,
+            &mut linty_ignore,
+        )?;
+        EntryScan {
+            busy_duration: scan_start.elapsed(),
+            ..scanned
+        }
+    };
+    let (result, _) = merge_scans([Ok(scanned)], options, rules, scan_start)?;
+    Ok(result)
+}
+
+/// Adds up what each file's scan found, returning the paths visited for `explain_skip`.
+fn merge_scans(
+    scans: impl IntoIterator<Item = anyhow::Result<EntryScan>>,
+    options: &WalkOptions,
+    rules: &[Rule],
+    scan_start: Instant,
+) -> anyhow::Result<(ScanResult, HashSet<PathBuf>)> {
+    let mut violations: Vec<Violation> = Vec::new();
+    let mut files_walked = 0;
+    let mut files_scanned = 0;
+    let mut busy_duration = Duration::ZERO;
+    let mut match_time = Duration::ZERO;
+    let mut bytes_read = 0;
+    let mut dropped: Vec<(String, Severity)> = Vec::new();
+    let mut suppressed: BTreeMap<String, usize> = BTreeMap::new();
+    let mut binary_skipped = 0;
+    let mut large_skipped = 0;
+    let mut encoding_skipped = 0;
+    let mut excluded = 0;
+    let mut cached = 0;
+    let mut cache_updates = Vec::new();
+    let mut cache_saved = Duration::ZERO;
+    let mut rule_durations: HashMap<String, Duration> = HashMap::new();
+    let mut timed_out = 0;
+    let mut coverage = options.record_coverage.then(|| Coverage::new(rules));
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+    let mut problems = Vec::new();
+    for scanned in scans {
+        let scanned = scanned?;
+        problems.extend(scanned.problems);
+        files_walked += usize::from(scanned.walked);
+        files_scanned += usize::from(scanned.scanned);
+        binary_skipped += usize::from(scanned.binary_skipped);
+        large_skipped += usize::from(scanned.large_skipped);
+        encoding_skipped += usize::from(scanned.encoding_skipped);
+        excluded += usize::from(scanned.excluded);
+        cached += usize::from(scanned.cached);
+        cache_saved += scanned.cache_saved;
+        if let Some(entry) = scanned.cache_entry {
+            cache_updates.push((scanned.path.clone(), entry));
+        }
+        for (rule_id, duration) in scanned.rule_durations {
+            *rule_durations.entry(rule_id).or_default() += duration;
+        }
+        timed_out += scanned.timed_out;
+        busy_duration += scanned.busy_duration;
+        match_time += scanned.match_duration;
+        bytes_read += scanned.bytes_read;
+        dropped.extend(scanned.dropped);
+        for (rule_id, count) in scanned.suppressed {
+            *suppressed.entry(rule_id).or_default() += count;
+        }
+        violations.extend(scanned.violations);
+        if let Some(coverage) = &mut coverage {
+            for (rule_id, executed) in &scanned.coverage {
+                coverage.record_rule(rule_id, &scanned.path, *executed);
+            }
+            if scanned.scanned {
+                coverage.record_file(&scanned.path, scanned.bytes_read);
+            }
+        }
+        if options.explain_skip {
+            visited.insert(scanned.path);
+        }
+    }
+    // A stable sort keeps each file's violations together and in rule order.
+    violations.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let duration = scan_start.elapsed();
+    // The threads' matching time adds up across threads, so it's scaled to its share of
+    // their work, and so is the time the cache saved them.
+    let share_of_duration = |thread_time: Duration| {
+        if busy_duration.is_zero() {
+            Duration::ZERO
+        } else {
+            duration.mul_f64(thread_time.as_secs_f64() / busy_duration.as_secs_f64())
+        }
+    };
+    let match_duration = share_of_duration(match_time);
+    let cache_saved = share_of_duration(cache_saved);
+
+    Ok((
+        ScanResult {
+            violations,
+            files_walked,
+            files_scanned,
+            duration,
+            match_duration,
+            bytes_read,
+            dropped,
+            suppressed,
+            binary_skipped,
+            large_skipped,
+            encoding_skipped,
+            excluded,
+            cached,
+            cache_updates,
+            cache_saved,
+            rule_durations,
+            timed_out,
+            coverage,
+            problems,
+        },
+        visited,
+    ))
+}
+
+/// What scanning one walk entry found, sent from a walker thread to be merged.
+#[derive(Default)]
+struct EntryScan {
+    path: PathBuf,
+    /// Whether the entry is a file rather than a directory.
+    walked: bool,
+    /// Whether any rule applied and the file could be read.
+    scanned: bool,
+    /// Whether the file was left out for looking binary.
+    binary_skipped: bool,
+    /// Whether the file was left out for being over `max_filesize`.
+    large_skipped: bool,
+    /// Whether the file was left out for not being valid UTF-8.
+    encoding_skipped: bool,
+    /// Whether the file was left out for matching the config's `excludes`.
+    excluded: bool,
+    /// Whether the results were replayed from the cache.
+    cached: bool,
+    /// What to cache from a scan of the file, when it can be cached.
+    cache_entry: Option<cache::Entry>,
+    /// What checking the file cost when its replayed results were cached.
+    cache_saved: Duration,
+    bytes_read: u64,
+    busy_duration: Duration,
+    match_duration: Duration,
+    violations: Vec<Violation>,
+    dropped: Vec<(String, Severity)>,
+    suppressed: Vec<(String, usize)>,
+    /// Time each applicable rule spent matching, for `record_rule_times`.
+    rule_durations: Vec<(String, Duration)>,
+    /// Applicable rules that went over `rule_timeout`.
+    timed_out: usize,
+    /// The rules that applied and whether each one ran, for `record_coverage`.
+    coverage: Vec<(String, bool)>,
+    problems: Vec<String>,
+}
+
+/// A file to scan: one the walk found, or `--stdin` contents named by `path`.
+struct ScanTarget<'a> {
+    path: &'a Path,
+    size: u64,
+    /// Unknown for piped contents, which are never cached.
+    modified: Option<SystemTime>,
+    piped: Option<&'a [u8]>,
+    /// Taken as given, as a walk root is, rather than reached through the directories
+    /// above it.
+    root: bool,
+}
+
+/// The prefilter for a scan with `rules`. With `rule_timeout`, each rule runs on its own
+/// instead, so a slow pattern can't hold up a set search the timeout doesn't cover.
+fn prefilter<'a>(options: &WalkOptions, rules: &'a [Rule]) -> Prefilter<'a> {
+    if options.rule_timeout.is_some() {
+        return Prefilter::default();
+    }
+    Prefilter::new(rules)
+}
+
+fn scan_entry(
+    entry: &DirEntry,
+    options: &WalkOptions,
+    rules: &[Rule],
+    prefilter: &Prefilter,
+    overrides: &[Override],
+    generated_matcher: &mut gitattributes::GeneratedMatcher,
+    linty_ignore: &mut ignore_explain::LintyIgnoreMatcher,
+) -> anyhow::Result<EntryScan> {
+    // Symlinks the walk doesn't follow still lead to the file they link to, so that's
+    // what's checked, with its own size. A symlinked root directory is always walked.
+    let metadata = entry.metadata()?;
+    let metadata = if metadata.file_type().is_symlink() {
+        match std::fs::metadata(entry.path()) {
+            Ok(metadata) if metadata.is_dir() => {
+                let walked = entry.depth() == 0;
+                if !walked && (options.explain_skip || log::enabled(log::Level::Info)) {
+                    eprintln!(
+                        "Skipping {}: a symlink to a directory; pass --follow-symlinks to lint it",
+                        entry.path().display()
+                    );
+                }
+                return Ok(EntryScan {
+                    path: entry.path().to_owned(),
+                    ..Default::default()
+                });
+            }
+            Ok(metadata) => metadata,
+            Err(err) => {
+                return Ok(EntryScan {
+                    path: entry.path().to_owned(),
+                    problems: vec![format!(
+                        "Warning: Skipping {}: a symlink that can't be followed: {err}",
+                        entry.path().display()
+                    )],
+                    ..Default::default()
+                });
+            }
+        }
+    } else {
+        metadata
+    };
+    let unchanged = options
+        .only
+        .as_ref()
+        .is_some_and(|only| !is_changed(only, entry.path()));
+    let cache_file = options
+        .cache
+        .as_ref()
+        .is_some_and(|cache| cache.is_cache_file(entry.path()));
+    if metadata.is_dir() || unchanged || cache_file {
+        return Ok(EntryScan {
+            path: entry.path().to_owned(),
+            ..Default::default()
+        });
+    }
+    let target = ScanTarget {
+        path: entry.path(),
+        size: metadata.len(),
+        modified: metadata.modified().ok(),
+        piped: None,
+        root: entry.depth() == 0,
+    };
+    scan_file(
+        &target,
+        options,
+        rules,
+        prefilter,
+        overrides,
+        generated_matcher,
+        linty_ignore,
+    )
+}
+
+fn scan_file(
+    target: &ScanTarget,
+    options: &WalkOptions,
+    rules: &[Rule],
+    prefilter: &Prefilter,
+    overrides: &[Override],
+    generated_matcher: &mut gitattributes::GeneratedMatcher,
+    linty_ignore: &mut ignore_explain::LintyIgnoreMatcher,
+) -> anyhow::Result<EntryScan> {
+    let path = target.path;
+    let mut scanned = EntryScan {
+        path: path.to_owned(),
+        walked: true,
+        ..Default::default()
+    };
+
+    let glob_path = options.glob_path(path);
+    let linty_ignore = target.root.then_some(linty_ignore);
+    if let Some(skip) = file_skip(options, path, &glob_path, generated_matcher, linty_ignore) {
+        if options.explain_skip || log::enabled(log::Level::Info) {
+            eprintln!("Skipping {}: {}", path.display(), skip.reason());
+        }
+        scanned.excluded = matches!(skip, FileSkip::Excluded(_) | FileSkip::LintyIgnored(_));
+        return Ok(scanned);
+    }
+
+    // What's left depends only on the path, the rules, and the file's contents, so it's
+    // what the cache replays.
+    let cache = options
+        .cache
+        .as_deref()
+        .filter(|_| !options.staged.contains(path));
+    let stamp = cache
+        .and(target.modified)
+        .and_then(|modified| cache::Stamp::new(target.size, modified));
+    if let (Some(cache), Some(stamp)) = (cache, stamp) {
+        if let Some(entry) = cache.get(path, stamp) {
+            return Ok(replay(scanned, entry));
+        }
+    }
+    let check_start = Instant::now();
+    let mut scanned = check_rules(
+        target, options, rules, prefilter, overrides, &glob_path, scanned,
+    )?;
+    if let Some(stamp) = stamp.filter(|_| scanned.problems.is_empty()) {
+        scanned.cache_entry = Some(cache_entry(&scanned, stamp, check_start.elapsed()));
+    }
+    Ok(scanned)
+}
+
+/// Why a file the walk found is left out before any rule is looked at.
+#[derive(Debug, PartialEq, Eq)]
+pub enum FileSkip {
+    /// Marked as generated by this `.gitattributes` attribute, like `linguist-generated`.
+    Generated(&'static str),
+    /// Matched by this pattern of the config's global excludes.
+    Excluded(String),
+    /// Ignored by this `.lintyignore` file, for a file the walk didn't reach through it.
+    LintyIgnored(PathBuf),
+}
+
+impl FileSkip {
+    pub fn reason(&self) -> String {
+        match self {
+            FileSkip::Generated(attribute) => format!("marked {attribute} in .gitattributes"),
+            FileSkip::Excluded(pattern) => {
+                format!("matched by the config's global excludes pattern {pattern}")
+            }
+            FileSkip::LintyIgnored(ignore_file) => format!("ignored by {}", ignore_file.display()),
+        }
+    }
+}
+
+/// Whether the file at `path`, whose globs match `glob_path`, is left out whatever the
+/// rules say. The walk leaves out what `.lintyignore` files ignore below its roots, but
+/// takes the roots themselves, like staged and named files, as given, so `linty_ignore`
+/// checks those.
+pub fn file_skip(
+    options: &WalkOptions,
+    path: &Path,
+    glob_path: &Path,
+    generated_matcher: &mut gitattributes::GeneratedMatcher,
+    linty_ignore: Option<&mut ignore_explain::LintyIgnoreMatcher>,
+) -> Option<FileSkip> {
+    if !options.include_generated {
+        if let Some(attribute) = generated_matcher.generated_attribute(path) {
+            return Some(FileSkip::Generated(attribute));
+        }
+    }
+    let excludes = &options.excludes;
+    if let Some(index) = excludes
+        .last_match(glob_path)
+        .filter(|index| !excludes.is_negated(*index))
+    {
+        return Some(FileSkip::Excluded(excludes.pattern(index).to_owned()));
+    }
+    linty_ignore?.ignored_by(path).map(FileSkip::LintyIgnored)
+}
+
+/// Whether a rule checks a file, as far as the file's path decides, and why.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RuleDecision {
+    /// The rule checks the file.
+    Applies {
+        /// The rule's severity, or the one an override sets for the file.
+        severity: Severity,
+        /// The index of the include that decided the path, or `None` when the rule has
+        /// no includes and so applies to every file.
+        included_by: Option<usize>,
+        /// The index of the `!` exclude that took the path back after an earlier exclude
+        /// matched it.
+        reincluded_by: Option<usize>,
+        /// The index of the override that set `severity`, if one did.
+        severity_from: Option<usize>,
+    },
+    /// Outside the directory of the nested config that defined the rule.
+    OutsideDirectory,
+    /// The rule has includes and none of them match, or the one that decided the path is
+    /// the `!` include at this index.
+    NotIncluded(Option<usize>),
+    /// Decided by the exclude at this index.
+    Excluded(usize),
+    /// Turned off by the override at this index.
+    TurnedOff(usize),
+}
+
+impl RuleDecision {
+    /// Why `rule` does or doesn't check the file, like `matched by excludes pattern
+    /// tests/**`.
+    pub fn reason(&self, rule: &Rule) -> String {
+        match self {
+            RuleDecision::Applies {
+                included_by,
+                reincluded_by,
+                severity_from,
+                severity,
+            } => {
+                let mut reason = match included_by {
+                    Some(index) => {
+                        format!("matched includes pattern {}", rule.includes.pattern(*index))
+                    }
+                    None => String::from("no includes, so every file applies"),
+                };
+                if let Some(index) = reincluded_by {
+                    reason.push_str(&format!(
+                        "; excludes pattern {} takes it back",
+                        rule.excludes.pattern(*index)
+                    ));
+                }
+                if let Some(index) = severity_from {
+                    reason.push_str(&format!(
+                        "; override overrides[{index}] sets it to {}",
+                        severity_name(*severity)
+                    ));
+                }
+                reason
+            }
+            RuleDecision::OutsideDirectory => {
+                String::from("outside the directory of its nested config")
+            }
+            RuleDecision::NotIncluded(None) => String::from("not matched by includes"),
+            RuleDecision::NotIncluded(Some(index)) => format!(
+                "left out by includes pattern {}",
+                rule.includes.pattern(*index)
+            ),
+            RuleDecision::Excluded(index) => format!(
+                "matched by excludes pattern {}",
+                rule.excludes.pattern(*index)
+            ),
+            RuleDecision::TurnedOff(index) => format!("turned off by override overrides[{index}]"),
+        }
+    }
+}
+
+/// Whether `rule` checks the file whose globs match `glob_path`, after its directory,
+/// includes, and excludes, and then `overrides`, where the last one naming the rule wins.
+/// In includes and in excludes, the last pattern that matches decides, so a `!` pattern
+/// takes back what earlier ones matched; an exclude always beats an include.
+pub fn rule_decision(rule: &Rule, overrides: &[Override], glob_path: &Path) -> RuleDecision {
+    if !rule.directory.is_empty() && !rule.directory.is_match(glob_path) {
+        return RuleDecision::OutsideDirectory;
+    }
+    let included_by = if rule.includes.is_empty() {
+        None
+    } else {
+        match rule.includes.last_match(glob_path) {
+            Some(index) if !rule.includes.is_negated(index) => Some(index),
+            decided_by => return RuleDecision::NotIncluded(decided_by),
+        }
+    };
+    let reincluded_by = match rule.excludes.last_match(glob_path) {
+        Some(index) if rule.excludes.is_negated(index) => Some(index),
+        Some(index) => return RuleDecision::Excluded(index),
+        None => None,
+    };
+
+    let mut severity = rule.severity;
+    let mut severity_from = None;
+    let mut disabled_by = None;
+    for (index, rule_override) in overrides.iter().enumerate() {
+        if !rule_override.paths.is_match(glob_path) {
+            continue;
+        }
+        match rule_override.rules.get(&rule.id) {
+            Some(RuleOverride::Toggle(RuleToggle::Off)) => disabled_by = Some(index),
+            Some(RuleOverride::Settings {
+                severity: override_severity,
+            }) => {
+                severity = *override_severity;
+                severity_from = Some(index);
+                disabled_by = None;
+            }
+            None => {}
+        }
+    }
+    match disabled_by {
+        Some(index) => RuleDecision::TurnedOff(index),
+        None => RuleDecision::Applies {
+            severity,
+            included_by,
+            reincluded_by,
+            severity_from,
+        },
+    }
+}
+
+/// Adds a cached scan's results to `scanned`.
+fn replay(mut scanned: EntryScan, entry: &cache::Entry) -> EntryScan {
+    let file = crate::display_path(&scanned.path);
+    scanned.cached = true;
+    scanned.cache_saved = Duration::from_micros(entry.cost_micros);
+    scanned.scanned = entry.scanned;
+    scanned.binary_skipped = entry.binary_skipped;
+    scanned.large_skipped = entry.large_skipped;
+    scanned.encoding_skipped = entry.encoding_skipped;
+    scanned.violations = entry
+        .violations
+        .iter()
+        .map(|violation| Violation {
+            rule_id: violation.rule_id.clone(),
+            severity: violation.severity,
+            file: file.clone(),
+            path: scanned.path.clone(),
+            matches: violation.matches.clone(),
+            blame: HashMap::new(),
+            discarded: violation.discarded,
+            untracked: false,
+            report_once: violation.report_once,
+        })
+        .collect();
+    scanned.dropped = entry.dropped.clone();
+    scanned.suppressed = entry.suppressed.clone();
+    scanned
+}
+
+/// What the cache keeps of a file's scan, which took `cost` to check.
+fn cache_entry(scanned: &EntryScan, stamp: cache::Stamp, cost: Duration) -> cache::Entry {
+    cache::Entry {
+        stamp,
+        scanned: scanned.scanned,
+        binary_skipped: scanned.binary_skipped,
+        large_skipped: scanned.large_skipped,
+        encoding_skipped: scanned.encoding_skipped,
+        violations: scanned
+            .violations
+            .iter()
+            .map(|violation| cache::CachedViolation {
+                rule_id: violation.rule_id.clone(),
+                severity: violation.severity,
+                matches: violation.matches.clone(),
+                discarded: violation.discarded,
+                report_once: violation.report_once,
+            })
+            .collect(),
+        dropped: scanned.dropped.clone(),
+        suppressed: scanned.suppressed.clone(),
+        cost_micros: cost.as_micros() as u64,
+    }
+}
+
+/// Runs the rules that apply to a file that's past the walk-wide skips.
+fn check_rules(
+    target: &ScanTarget,
+    options: &WalkOptions,
+    rules: &[Rule],
+    prefilter: &Prefilter,
+    overrides: &[Override],
+    glob_path: &Path,
+    mut scanned: EntryScan,
+) -> anyhow::Result<EntryScan> {
+    let path = target.path;
+    let file = crate::display_path(path);
+
+    let mut applicable: Vec<(&Rule, Severity)> = Vec::new();
+    for rule in rules {
+        let decision = rule_decision(rule, overrides, glob_path);
+        let RuleDecision::Applies {
+            severity,
+            severity_from,
+            ..
+        } = decision
+        else {
+            if options.explain_skip {
+                eprintln!(
+                    "Skipping rule {} for {}: {}",
+                    rule.id,
+                    path.display(),
+                    decision.reason(rule)
+                );
+            }
+            continue;
+        };
+        if let (Some(index), true) = (severity_from, options.explain_skip) {
+            eprintln!(
+                "Override overrides[{index}] sets rule {} to {} for {}",
+                rule.id,
+                severity_name(severity),
+                path.display()
+            );
+        }
+        applicable.push((rule, severity));
+    }
+    // Path rules are settled by the path alone, so they never open the file, and the
+    // skips below for its size or contents don't apply to them.
+    let (path_rules, mut applicable): (Vec<_>, Vec<_>) = applicable
+        .into_iter()
+        .partition(|(rule, _)| !rule.kind.reads_contents());
+    let rule_path = glob_path.strip_prefix("./").unwrap_or(glob_path);
+    for (rule, severity) in path_rules {
+        if options.record_coverage {
+            scanned.coverage.push((rule.id.clone(), true));
+        }
+        let file_context = FileContext::on_disk(rule_path, path);
+        let findings = rule.kind.check(&file_context);
+        let matches = to_matches(rule, rule_path, "", findings);
+        if !matches.is_empty() {
+            scanned.violations.push(Violation {
+                rule_id: rule.id.to_owned(),
+                severity,
+                file: file.clone(),
+                path: path.to_owned(),
+                matches,
+                blame: HashMap::new(),
+                discarded: 0,
+                untracked: false,
+                report_once: rule.report_once,
+            });
+        }
+    }
+    if applicable.is_empty() {
+        return Ok(scanned);
+    }
+
+    let file_size = target.size;
+    if let Some(max_filesize) = options.max_filesize.filter(|&max| file_size > max) {
+        if options.explain_skip || log::enabled(log::Level::Info) {
+            eprintln!(
+                "Skipping {}: {file_size} bytes is over --max-filesize {max_filesize}",
+                path.display()
+            );
+        }
+        scanned.large_skipped = true;
+        return Ok(scanned);
+    }
+
+    // Staged and piped contents are needed up front to tell whether they look binary.
+    let staged_bytes = match target.piped {
+        Some(bytes) => Some(Ok(bytes.to_vec())),
+        None => options
+            .repo
+            .as_ref()
+            .filter(|_| options.staged.contains(path))
+            .map(|repo| staged_contents(repo, path)),
+    };
+    if options.skip_binary && applicable.iter().any(|(rule, _)| !rule.binary) {
+        let binary = match &staged_bytes {
+            Some(Ok(bytes)) => looks_binary(bytes),
+            Some(Err(_)) => false,
+            None => file_looks_binary(path),
+        };
+        if binary {
+            applicable.retain(|(rule, _)| rule.binary);
+            if applicable.is_empty() {
+                if options.explain_skip || log::enabled(log::Level::Info) {
+                    eprintln!("Skipping {}: looks binary", path.display());
+                }
+                scanned.binary_skipped = true;
+                return Ok(scanned);
+            }
+        }
+    }
+
+    // When every applicable rule only looks at the start of the file, there's
+    // no need to read past the largest of their windows.
+    let read_limit = applicable
+        .iter()
+        .map(|(rule, _)| match rule.scan_window {
+            Some(ScanWindow::Head(head_bytes)) => Some(head_bytes),
+            _ => None,
+        })
+        .try_fold(0, |limit, head_bytes| {
+            head_bytes.map(|bytes| limit.max(bytes))
+        });
+    // Large files are read in chunks when every rule only ever looks at one line at a time.
+    // Staged and piped contents are already in memory in one piece.
+    let chunked =
+        staged_bytes.is_none() && read_limit.is_none() && file_size > chunked::CHUNK_BYTES && {
+            let whole_file_rule = applicable.iter().find(|(rule, _)| {
+                !rule.line_local || rule.scan_window.is_some() || rule.region.is_some()
+            });
+            if let Some((rule, _)) = whole_file_rule {
+                scanned.problems.push(format!(
+                    "Warning: Reading {} ({} MiB) whole, since rule {} checks all of it at once",
+                    path.display(),
+                    file_size / (1024 * 1024),
+                    rule.id
+                ));
+            }
+            whole_file_rule.is_none()
+        };
+    let found = if let Some(bytes) = staged_bytes {
+        bytes
+            .and_then(|bytes| read_contents(bytes.as_slice(), read_limit, options.invalid_utf8))
+            .map(|contents| {
+                scan_contents(
+                    rule_path,
+                    &contents,
+                    &applicable,
+                    prefilter,
+                    options.inline_config,
+                    options.rule_timeout,
+                )
+            })
+            .map_err(|err| {
+                let action = match target.piped {
+                    Some(_) => "read",
+                    None => "read the staged contents of",
+                };
+                (err, action)
+            })
+    } else {
+        match File::open(path) {
+            Ok(file) => if chunked {
+                scan_chunks(rule_path, file, &applicable, prefilter, options)
+            } else {
+                read_contents(file, read_limit, options.invalid_utf8).map(|contents| {
+                    scan_contents(
+                        rule_path,
+                        &contents,
+                        &applicable,
+                        prefilter,
+                        options.inline_config,
+                        options.rule_timeout,
+                    )
+                })
+            }
+            .map_err(|err| (err, "read")),
+            Err(err) => Err((err, "open")),
+        }
+    };
+    let found = match found {
+        Ok(found) => Some(found),
+        Err((err, _))
+            if err.kind() == ErrorKind::InvalidData
+                && options.invalid_utf8 == InvalidUtf8::Skip =>
+        {
+            if options.explain_skip || log::enabled(log::Level::Info) {
+                eprintln!("Skipping {}: not valid UTF-8", path.display());
+            }
+            scanned.encoding_skipped = true;
+            None
+        }
+        Err((err, action)) => {
+            scanned.problems.push(format!(
+                "Error: Failed to {action} {}\nReason: {}",
+                path.to_str().unwrap(),
+                err
+            ));
+            None
+        }
+    };
+    if options.record_coverage {
+        scanned.coverage.extend(
+            applicable
+                .iter()
+                .map(|(rule, _)| (rule.id.clone(), found.is_some())),
+        );
+    }
+    let Some(mut found) = found else {
+        return Ok(scanned);
+    };
+    scanned.scanned = true;
+    scanned.bytes_read = found.bytes_read;
+    scanned.match_duration = found.match_duration;
+    log::log!(
+        log::Level::Debug,
+        "Matched {file} against {} rule(s) in {:.2}ms",
+        applicable.len(),
+        found.match_duration.as_secs_f64() * 1000.0
+    );
+    if options.record_rule_times {
+        scanned.rule_durations = applicable
+            .iter()
+            .zip(&found.rule_durations)
+            .map(|((rule, _), duration)| (rule.id.clone(), *duration))
+            .collect();
+    }
+
+    // A rule that gave up partway has no results for the file, not an empty one.
+    for &index in &found.timed_out {
+        scanned.problems.push(format!(
+            "Warning: Skipping rule {} for {}: matching took over --rule-timeout {}ms",
+            applicable[index].0.id,
+            crate::display_path(path),
+            options.rule_timeout.unwrap_or_default().as_millis()
+        ));
+    }
+    scanned.timed_out = found.timed_out.len();
+
+    let mut recorded = 0;
+    let mut overflow: Option<Violation> = None;
+    for (index, ((rule, severity), mut matches)) in
+        applicable.into_iter().zip(found.matches).enumerate()
+    {
+        if found.timed_out.contains(&index) {
+            continue;
+        }
+        // Suppressing a report_once rule's first match suppresses the file's finding.
+        if rule.report_once {
+            matches.truncate(1);
+        }
+        let before = matches.len();
+        matches.retain(|m| !found.suppressions.suppresses(&rule.id, m.line));
+        if matches.len() < before {
+            scanned
+                .suppressed
+                .push((rule.id.to_owned(), before - matches.len()));
+        }
+        if let Some(cap) = options.max_violations_per_file {
+            let keep = cap.saturating_sub(recorded).min(matches.len());
+            if keep < matches.len() {
+                // The note points at the first discarded match and takes the
+                // highest severity among the discarded ones.
+                let note = overflow.get_or_insert_with(|| Violation {
+                    rule_id: OVERFLOW_RULE_ID.to_owned(),
+                    severity,
+                    file: file.clone(),
+                    path: path.to_owned(),
+                    matches: vec![matches[keep].clone()],
+                    blame: HashMap::new(),
+                    discarded: 0,
+                    untracked: false,
+                    report_once: false,
+                });
+                note.discarded += matches.len() - keep;
+                if severity == Severity::Error {
+                    note.severity = Severity::Error;
+                }
+                if keep == 0 {
+                    scanned.dropped.push((rule.id.to_owned(), severity));
+                }
+                matches.truncate(keep);
+            }
+            recorded += matches.len();
+        }
+
+        if !matches.is_empty() {
+            scanned.violations.push(Violation {
+                rule_id: rule.id.to_owned(),
+                severity,
+                file: file.clone(),
+                path: path.to_owned(),
+                matches,
+                blame: HashMap::new(),
+                discarded: 0,
+                untracked: false,
+                report_once: rule.report_once,
+            })
+        }
+    }
+    scanned.violations.extend(overflow);
+    let oversized: Vec<Match> = scanned
+        .violations
+        .iter()
+        .filter(|violation| violation.rule_id != OVERFLOW_RULE_ID)
+        .flat_map(|violation| violation.matches.iter().filter(|m| m.truncated).cloned())
+        .collect();
+    let unused = if options.report_unused_suppressions {
+        found.suppressions.unused()
+    } else {
+        Vec::new()
+    };
+    for (rule_id, matches) in [
+        (UNBALANCED_REGION_RULE_ID, found.unbalanced),
+        (OVERSIZED_MATCH_RULE_ID, oversized),
+        (UNUSED_SUPPRESSION_RULE_ID, unused),
+    ] {
+        if !matches.is_empty() {
+            scanned.violations.push(Violation {
+                rule_id: rule_id.to_owned(),
+                severity: Severity::Warning,
+                file: file.clone(),
+                path: path.to_owned(),
+                matches,
+                blame: HashMap::new(),
+                discarded: 0,
+                untracked: false,
+                report_once: false,
+            });
+        }
+    }
+    Ok(scanned)
+}
+
+/// The matches of each applicable rule in one file, in the same order as the rules.
+struct FileMatches {
+    matches: Vec<Vec<Match>>,
+    /// Region markers that don't pair up, one per line.
+    unbalanced: Vec<Match>,
+    suppressions: Suppressions,
+    bytes_read: u64,
+    match_duration: Duration,
+    /// Time each rule spent matching, in the same order as the rules.
+    rule_durations: Vec<Duration>,
+    /// Indexes of the rules that went over the rule timeout, whose matches are left empty.
+    timed_out: Vec<usize>,
+}
+
+fn scan_contents(
+    path: &Path,
+    contents: &str,
+    applicable: &[(&Rule, Severity)],
+    prefilter: &Prefilter,
+    inline_config: bool,
+    rule_timeout: Option<Duration>,
+) -> FileMatches {
+    let mut found = FileMatches {
+        matches: Vec::with_capacity(applicable.len()),
+        unbalanced: Vec::new(),
+        suppressions: Suppressions::default(),
+        bytes_read: contents.len() as u64,
+        match_duration: Duration::ZERO,
+        rule_durations: Vec::with_capacity(applicable.len()),
+        timed_out: Vec::new(),
+    };
+    let prefilter_start = Instant::now();
+    let can_match = prefilter.can_match(applicable, contents);
+    found.match_duration += prefilter_start.elapsed();
+    let mut normalized: HashMap<normalize::NormalForm, normalize::Normalized> = HashMap::new();
+    for (index, (rule, _)) in applicable.iter().enumerate() {
+        let match_start = Instant::now();
+        let deadline = rule_timeout.map(|timeout| match_start + timeout);
+        let file = FileContext::new(path, contents).with_search(Search {
+            rule,
+            slices: rule_slices(rule, contents, &mut found.unbalanced),
+            can_match: can_match[index],
+            normalized: RefCell::new(&mut normalized),
+            deadline,
+            timed_out: Cell::new(false),
+        });
+        let findings = rule.kind.check(&file);
+        if file.timed_out() {
+            found.timed_out.push(index);
+            found.matches.push(Vec::new());
+        } else {
+            found
+                .matches
+                .push(to_matches(rule, path, contents, findings));
+        }
+        let rule_duration = match_start.elapsed();
+        found.rule_durations.push(rule_duration);
+        found.match_duration += rule_duration;
+    }
+    if inline_config {
+        for (directive, range) in suppress::parse(contents) {
+            found.suppressions.add(directive, match_at(contents, range));
+        }
+    }
+    found
+}
+
+/// The slices of `contents` `rule` looks at: its scan window, split by its region. When
+/// the region's markers don't pair up, there are none, and the first unpaired marker is
+/// added to `unbalanced` unless its line already is.
+fn rule_slices(rule: &Rule, contents: &str, unbalanced: &mut Vec<Match>) -> Vec<Range<usize>> {
+    let window = rule
+        .scan_window
+        .map_or(0..contents.len(), |window| window.range(contents));
+    let Some(region) = &rule.region else {
+        return vec![window];
+    };
+    match region.partitions(contents) {
+        Ok(partitions) => partitions
+            .into_iter()
+            .map(|partition| partition.start.max(window.start)..partition.end.min(window.end))
+            .filter(|slice| slice.start < slice.end)
+            .collect(),
+        Err(marker) => {
+            let marker = match_at(contents, marker);
+            if !unbalanced.iter().any(|noted| noted.line == marker.line) {
+                unbalanced.push(marker);
+            }
+            Vec::new()
+        }
+    }
+}
+
+/// Like `scan_contents` for line-local rules without windows or regions, reading the file
+/// a chunk at a time so the whole file never has to be in memory.
+fn scan_chunks(
+    path: &Path,
+    file: File,
+    applicable: &[(&Rule, Severity)],
+    prefilter: &Prefilter,
+    options: &WalkOptions,
+) -> std::io::Result<FileMatches> {
+    let inline_config = options.inline_config;
+    let mut found = FileMatches {
+        matches: vec![Vec::new(); applicable.len()],
+        unbalanced: Vec::new(),
+        suppressions: Suppressions::default(),
+        bytes_read: 0,
+        match_duration: Duration::ZERO,
+        rule_durations: vec![Duration::ZERO; applicable.len()],
+        timed_out: Vec::new(),
+    };
+    let mut chunks = chunked::Chunks::new(file, options.invalid_utf8)?;
+    while let Some(chunk) = chunks.next_chunk()? {
+        if cancel::cancelled().is_some() {
+            break;
+        }
+        found.bytes_read += chunk.core.len() as u64;
+        let match_start = Instant::now();
+        let can_match = prefilter.can_match(applicable, &chunk.text);
+        let mut normalized: HashMap<normalize::NormalForm, normalize::Normalized> = HashMap::new();
+        let last = chunk.core.end == chunk.text.len();
+        // Positions in the chunk, relative to its first line, moved to the whole file.
+        let in_file = |chunk_match: Match| Match {
+            line: chunk_match.line + chunk.first_line - 1,
+            end_line: chunk_match.end_line + chunk.first_line - 1,
+            ..chunk_match
+        };
+        for (index, (((rule, _), matches), rule_duration)) in applicable
+            .iter()
+            .zip(&mut found.matches)
+            .zip(&mut found.rule_durations)
+            .enumerate()
+        {
+            if !can_match[index]
+                || (rule.report_once && !matches.is_empty())
+                || found.timed_out.contains(&index)
+            {
+                continue;
+            }
+            let rule_start = Instant::now();
+            // The timeout is for the whole file, so each chunk gets what's left of it.
+            let deadline = options
+                .rule_timeout
+                .map(|timeout| rule_start + timeout.saturating_sub(*rule_duration));
+            let whole = 0..chunk.text.len();
+            let file = FileContext::new(path, &chunk.text).with_search(Search {
+                rule,
+                slices: vec![whole],
+                can_match: true,
+                normalized: RefCell::new(&mut normalized),
+                deadline,
+                timed_out: Cell::new(false),
+            });
+            let mut findings = rule.kind.check(&file);
+            if file.timed_out() {
+                matches.clear();
+                found.timed_out.push(index);
+                *rule_duration += rule_start.elapsed();
+                continue;
+            }
+            // Matches in the context lines belong to the neighboring chunks.
+            findings.retain(|finding| match finding {
+                Finding::At { range, .. } => {
+                    chunk.core.contains(&range.start) || (last && range.start == chunk.core.end)
+                }
+                _ => true,
+            });
+            matches.extend(
+                to_matches(rule, path, &chunk.text, findings)
+                    .into_iter()
+                    .map(in_file),
+            );
+            *rule_duration += rule_start.elapsed();
+        }
+        found.match_duration += match_start.elapsed();
+        if inline_config {
+            let core = &chunk.text[chunk.core.clone()];
+            for (directive, range) in suppress::parse(core) {
+                let range = range.start + chunk.core.start..range.end + chunk.core.start;
+                found
+                    .suppressions
+                    .add(directive, in_file(match_at(&chunk.text, range)));
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// Runs `rule`'s regex over each of `slices` of `contents`, normalized first if the rule
+/// asks for it, and returns the match ranges in `contents` with overlaps collapsed and
+/// the ones its `allow` patterns exempt left out, or only the first when `first_only`.
+/// Returns `None` if matching is still going at `deadline`.
+pub(crate) fn find_ranges(
+    rule: &Rule,
+    contents: &str,
+    slices: Vec<Range<usize>>,
+    normalized: &mut HashMap<normalize::NormalForm, normalize::Normalized>,
+    deadline: Option<Instant>,
+    first_only: bool,
+) -> Option<Vec<Range<usize>>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for slice in slices {
+        let text = &contents[slice.clone()];
+        // Normalized contents are shared between rules that see all of `contents`; other
+        // rules only need their slice normalized.
+        let mut slice_normalized = None;
+        let normalized = match rule.unicode_normalize {
+            Some(form) if slice.len() == contents.len() => Some(
+                &*normalized
+                    .entry(form)
+                    .or_insert_with(|| normalize::Normalized::new(contents, form)),
+            ),
+            Some(form) => Some(&*slice_normalized.insert(normalize::Normalized::new(text, form))),
+            None => None,
+        };
+        let haystack = normalized.map_or(text, |normalized| normalized.text.as_str());
+        let mut regex_matches = MatchIter::new(rule, haystack, deadline);
+        let mut found = regex_matches.by_ref().map(|range| {
+            let range = match normalized {
+                Some(normalized) => normalized.original_range(range),
+                None => range,
+            };
+            range.start + slice.start..range.end + slice.start
+        });
+        if first_only {
+            if let Some(first) = found.find(|range| is_kept(rule, contents, range)) {
+                return Some(vec![first]);
+            }
+        } else {
+            ranges.extend(found);
+        }
+        if regex_matches.timed_out {
+            return None;
+        }
+    }
+    let mut ranges = dedup_overlapping(ranges);
+    if !rule.allow.is_empty() || rule.word_boundary {
+        ranges.retain(|range| is_kept(rule, contents, range));
+    }
+    Some(ranges)
+}
+
+/// How much of a haystack one search covers, at the least, when a line-local rule is
+/// matched against a deadline.
+const DEADLINE_WINDOW_BYTES: usize = 64 * 1024;
+
+/// The ranges of a rule's matches in a haystack, as `Regex::find_iter` finds them, until
+/// the run is cancelled or `deadline` passes. The deadline is checked between matches,
+/// and a line-local rule is searched a window at a time so one long search that finds
+/// nothing can't run far past it. A window is searched up to the rule's
+/// `max_match_length` beyond its end, so a match starting in it is found as a whole
+/// search would find it unless it's longer than that.
+struct MatchIter<'a> {
+    regex: &'a Regex,
+    haystack: &'a str,
+    deadline: Option<Instant>,
+    /// The window size and how far past it to search, for a windowed search.
+    window: Option<(usize, usize)>,
+    /// Where the next search starts.
+    position: usize,
+    /// Where the last match ended; an empty match there is skipped, as `find_iter` does.
+    last_end: Option<usize>,
+    /// Set once the iterator stops for the deadline.
+    timed_out: bool,
+}
+
+impl<'a> MatchIter<'a> {
+    fn new(rule: &'a Rule, haystack: &'a str, deadline: Option<Instant>) -> MatchIter<'a> {
+        let overlap = rule.max_match_length;
+        MatchIter {
+            regex: &rule.regex,
+            haystack,
+            deadline,
+            window: (deadline.is_some() && rule.line_local).then(|| {
+                (
+                    DEADLINE_WINDOW_BYTES.max(overlap.saturating_mul(4)),
+                    overlap,
+                )
+            }),
+            position: 0,
+            last_end: None,
+            timed_out: false,
+        }
+    }
+
+    /// Whether to stop, because the run is cancelled or the deadline has passed.
+    fn stopped(&mut self) -> bool {
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.timed_out = true;
+        }
+        self.timed_out || cancel::cancelled().is_some()
+    }
+
+    /// The first match at or after `position`, searching a window at a time.
+    fn find_windowed(&mut self, window: usize, overlap: usize) -> Option<Range<usize>> {
+        let haystack = self.haystack;
+        let len = haystack.len();
+        loop {
+            let window_end = floor_char_boundary(haystack, self.position.saturating_add(window));
+            let search_end = floor_char_boundary(haystack, window_end.saturating_add(overlap));
+            // A character before the window keeps `\b` and `(?m)^` right at its start.
+            let context = haystack[..self.position]
+                .chars()
+                .next_back()
+                .map_or(self.position, |before| self.position - before.len_utf8());
+            let found = self
+                .regex
+                .find_at(&haystack[context..search_end], self.position - context)
+                .map(|found| found.start() + context..found.end() + context);
+            match found {
+                Some(range) if range.start < window_end || window_end == len => {
+                    // A match reaching the end of the search might go on past it, so it's
+                    // looked for again from its start.
+                    if range.end < search_end || search_end == len || range.start == self.position {
+                        return Some(range);
+                    }
+                    self.position = range.start;
+                }
+                _ if window_end == len => return None,
+                _ => self.position = window_end,
+            }
+            if self.stopped() {
+                return None;
+            }
+        }
+    }
+}
+
+impl Iterator for MatchIter<'_> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Range<usize>> {
+        loop {
+            if self.position > self.haystack.len() || self.stopped() {
+                return None;
+            }
+            let range = match self.window {
+                Some((window, overlap)) => self.find_windowed(window, overlap),
+                None => self
+                    .regex
+                    .find_at(self.haystack, self.position)
+                    .map(|found| found.range()),
+            }?;
+            if range.is_empty() && self.last_end == Some(range.end) {
+                self.position = range.end
+                    + self.haystack[range.end..]
+                        .chars()
+                        .next()
+                        .map_or(1, char::len_utf8);
+                continue;
+            }
+            self.position = range.end;
+            self.last_end = Some(range.end);
+            return Some(range);
+        }
+    }
+}
+
+/// `index`, or the last character boundary of `text` before it, capped at its length.
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Whether a match isn't exempted by the rule's `allow` patterns or, with
+/// `word_boundary`, by being part of a longer word.
+fn is_kept(rule: &Rule, contents: &str, range: &Range<usize>) -> bool {
+    let inside_word = rule.word_boundary && is_inside_word(contents, range);
+    !inside_word && !is_allowed(&rule.allow, contents, range)
+}
+
+/// Whether `range` starts or ends with a word character that continues a word outside
+/// it, like `todo` in `mastodon`. An edge that's a non-word character, like the `@` of
+/// `@deprecated`, is a boundary already, whatever is next to it.
+fn is_inside_word(contents: &str, range: &Range<usize>) -> bool {
+    let is_word = |character: Option<char>| {
+        character.is_some_and(|character| character.is_alphanumeric() || character == '_')
+    };
+    let matched = &contents[range.clone()];
+    (is_word(matched.chars().next()) && is_word(contents[..range.start].chars().next_back()))
+        || (is_word(matched.chars().next_back()) && is_word(contents[range.end..].chars().next()))
+}
+
+/// Whether a match of any of `allow` on the lines of `range` overlaps it. An empty
+/// `range` counts as overlapped when it's inside the allowed text.
+fn is_allowed(allow: &[Regex], contents: &str, range: &Range<usize>) -> bool {
+    let line_start = contents[..range.start]
+        .rfind('\n')
+        .map_or(0, |index| index + 1);
+    let line_end = contents[range.end..]
+        .find('\n')
+        .map_or(contents.len(), |index| range.end + index);
+    let lines = &contents[line_start..line_end];
+    allow.iter().any(|pattern| {
+        pattern.find_iter(lines).any(|allowed| {
+            let allowed = allowed.start() + line_start..allowed.end() + line_start;
+            range.start < allowed.end && allowed.start < range.end.max(range.start + 1)
+        })
+    })
+}
+
+/// What `rule` finds in `text` checked as a file's whole contents, or as the path of a
+/// file for a path rule, ignoring suppression comments; for `linty test`. A required
+/// rule gives what its pattern matches, rather than whether it's missing.
+pub fn example_matches(rule: &Rule, text: &str) -> Vec<Match> {
+    if !rule.kind.reads_contents() {
+        return rule
+            .regex
+            .find(text)
+            .map(|found| match_at(text, found.range()))
+            .into_iter()
+            .collect();
+    }
+    let pattern = rule_kind::Pattern {
+        report_once: rule.report_once,
+    };
+    let kind: &dyn RuleKind = if !rule.registered && rule.kind.name() == rule_kind::Required::NAME {
+        &pattern
+    } else {
+        rule.kind.as_ref()
+    };
+    let mut normalized = HashMap::new();
+    let file = FileContext::new(Path::new(""), text).with_search(Search {
+        rule,
+        slices: rule_slices(rule, text, &mut Vec::new()),
+        can_match: true,
+        normalized: RefCell::new(&mut normalized),
+        deadline: None,
+        timed_out: Cell::new(false),
+    });
+    to_matches(rule, Path::new(""), text, kind.check(&file))
+}
+
+/// Turns a rule kind's findings in `contents`, the file at `path`, into `Match`es,
+/// cutting matches longer than the rule's `max_match_length` short so they don't have to
+/// be copied whole, and filling in the rule's message for each without one of its own
+/// when it has placeholders.
+fn to_matches(rule: &Rule, path: &Path, contents: &str, findings: Vec<Finding>) -> Vec<Match> {
+    findings
+        .into_iter()
+        .map(|finding| {
+            let (range, message) = match finding {
+                Finding::At { range, message } => (range, message),
+                Finding::File => return path_match(&path.to_string_lossy()),
+                Finding::Missing => return missing_match(),
+            };
+            let message = message.or_else(|| {
+                let template = rule.message.as_deref()?;
+                // Matches found in Unicode-normalized text don't line up with the raw
+                // contents, so their groups render as nothing.
+                let captures = rule
+                    .regex
+                    .captures_at(contents, range.start)
+                    .filter(|captures| captures.get(0).is_some_and(|m| m.range() == range));
+                Some(message::render(template, captures.as_ref()))
+            });
+            if range.len() > rule.max_match_length {
+                let mut end = range.start + rule.max_match_length;
+                while !contents.is_char_boundary(end) {
+                    end -= 1;
+                }
+                return Match {
+                    truncated: true,
+                    message,
+                    ..match_at(contents, range.start..end)
+                };
+            }
+            Match {
+                replacement: rule.fix.as_deref().and_then(|template| {
+                    fix::replacement(&rule.regex, template, contents, range.clone())
+                }),
+                message,
+                ..match_at(contents, range)
+            }
+        })
+        .collect()
+}
+
+/// Reads a file's contents, or only its first `limit` bytes, dropping a character the
+/// limit cut in half. Contents that aren't UTF-8 are decoded as `invalid_utf8` says.
+fn read_contents(
+    mut file: impl Read,
+    limit: Option<usize>,
+    invalid_utf8: InvalidUtf8,
+) -> std::io::Result<String> {
+    let mut bytes = Vec::new();
+    match limit {
+        Some(limit) => {
+            file.take(limit as u64).read_to_end(&mut bytes)?;
+            if let Err(err) = std::str::from_utf8(&bytes) {
+                if err.error_len().is_none() {
+                    bytes.truncate(err.valid_up_to());
+                }
+            }
+        }
+        None => {
+            file.read_to_end(&mut bytes)?;
+        }
+    }
+    decode(bytes, invalid_utf8)
+}
+
+/// Decodes file contents as UTF-8. With `InvalidUtf8::Lossy`, invalid sequences become
+/// U+FFFD, which keeps line numbers since a line break is never part of one; otherwise
+/// they're an `InvalidData` error.
+pub fn decode(bytes: Vec<u8>, invalid_utf8: InvalidUtf8) -> std::io::Result<String> {
+    match String::from_utf8(bytes) {
+        Ok(contents) => Ok(contents),
+        Err(err) if invalid_utf8 == InvalidUtf8::Lossy => {
+            Ok(String::from_utf8_lossy(err.as_bytes()).into_owned())
+        }
+        Err(err) => Err(std::io::Error::new(ErrorKind::InvalidData, err)),
+    }
+}
+
+/// How much of the start of a file is checked for a NUL byte to tell whether it's binary.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+/// Whether the start of `contents` has a NUL byte, which text files practically never do.
+fn looks_binary(contents: &[u8]) -> bool {
+    contents[..contents.len().min(BINARY_SNIFF_BYTES)].contains(&0)
+}
+
+/// Reads the start of the file at `path` to tell whether it looks binary. Files that
+/// can't be read are left for the full read to report.
+pub fn file_looks_binary(path: &Path) -> bool {
+    let mut head = Vec::with_capacity(BINARY_SNIFF_BYTES);
+    File::open(path)
+        .and_then(|file| file.take(BINARY_SNIFF_BYTES as u64).read_to_end(&mut head))
+        .is_ok_and(|_| looks_binary(&head))
+}
+
+/// The contents `path` has in the git index of `repo`, which is what a commit would record
+/// however the file has changed on disk since it was staged.
+pub fn staged_contents(repo: &Repo, path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut object = OsString::from(":");
+    if std::path::MAIN_SEPARATOR == '/' {
+        object.push(repo.root_path(path));
+    } else {
+        // Git takes `/` in a path on Windows too, and a walked path can mix in `\`.
+        object.push(
+            repo.root_path(path)
+                .to_string_lossy()
+                .replace(std::path::MAIN_SEPARATOR, "/"),
+        );
+    }
+    repo.output(&[OsString::from("cat-file"), OsString::from("blob"), object])
+        .map_err(std::io::Error::other)
+}
+
+/// Collapses matches whose byte ranges overlap into the longest of them, so one piece of
+/// text is reported once per rule however many ways the rule's patterns match it.
+fn dedup_overlapping(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_by_key(|range| (range.start, range.end));
+    let mut deduped: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match deduped.last_mut() {
+            Some(last) if range.start < last.end => {
+                if range.len() > last.len() {
+                    *last = range;
+                }
+            }
+            _ => deduped.push(range),
+        }
+    }
+    deduped
+}
+
+/// A path rule's match, which has no position in the file.
+fn path_match(path: &str) -> Match {
+    Match {
+        line: 0,
+        column: 0,
+        end_line: 0,
+        end_column: 0,
+        text: path.to_owned(),
+        fingerprint: String::from("path"),
+        replacement: None,
+        message: None,
+        truncated: false,
+    }
+}
+
+/// Where a required rule's pattern is reported missing: the start of the file.
+fn missing_match() -> Match {
+    Match {
+        line: 1,
+        column: 1,
+        end_line: 1,
+        end_column: 1,
+        text: String::new(),
+        // Nothing in the file identifies an absence, but there's only one per rule.
+        fingerprint: String::from("missing"),
+        replacement: None,
+        message: None,
+        truncated: false,
+    }
+}
+
+fn match_at(contents: &str, range: Range<usize>) -> Match {
+    let before = &contents[..range.start];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    let matched = &contents[range.clone()];
+    let end_line_start = match matched.rfind('\n') {
+        Some(index) => range.start + index + 1,
+        None => line_start,
+    };
+    Match {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+        end_line: before.matches('\n').count() + matched.matches('\n').count() + 1,
+        end_column: contents[end_line_start..range.end].chars().count() + 1,
+        text: matched.lines().next().unwrap_or_default().to_owned(),
+        fingerprint: compare::fingerprint(contents, range.start),
+        replacement: None,
+        message: None,
+        truncated: false,
+    }
+}
+
+/// Whether `path` is at or under one of the `changed` absolute paths.
+pub fn is_changed(changed: &HashSet<PathBuf>, path: &Path) -> bool {
+    absolute(path)
+        .ancestors()
+        .any(|ancestor| changed.contains(ancestor))
+}
+
+/// A walked path made absolute and lexically normalized, as `WalkOptions::only` holds them.
+pub fn absolute(path: &Path) -> PathBuf {
+    crate::normalize_lexically(&std::path::absolute(path).unwrap_or_else(|_| path.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_config, ConfigFormat, Linter};
+
+    /// Rules in several flag groups, so the prefilter builds more than one set, among
+    /// them patterns a set could get wrong: anchors, alternations, classes, and a rule
+    /// that matches nowhere.
+    const RULES: &str = r#"{"rules":[
+        {"id":"Todo","message":"m","regex":"TODO","severity":"error"},
+        {"id":"Fixme","message":"m","regex":"FIXME\\(\\w+\\)","severity":"warning"},
+        {"id":"Println","message":"m","regex":"println!|eprintln!","severity":"error","includes":["*.rs"]},
+        {"id":"Trailing","message":"m","regex":"[ \\t]+$","severity":"warning","multi_line":true},
+        {"id":"LineStart","message":"m","regex":"^use ","severity":"warning","multi_line":true},
+        {"id":"Literal","message":"m","regex":"a.b","match_type":"literal","severity":"error"},
+        {"id":"Never","message":"m","regex":"never-in-any-file","severity":"error"},
+        {"id":"Hack","message":"m","regex":"hack","severity":"error","case_insensitive":true},
+        {"id":"Xxx","message":"m","regex":"x{3}","severity":"error","case_insensitive":true},
+        {"id":"Spanning","message":"m","regex":"begin.*?end","severity":"error","dot_matches_new_line":true},
+        {"id":"Crlf","message":"m","regex":"semi;$","severity":"error","multi_line":true,"crlf":true},
+        {"id":"Docs","message":"m","regex":"TODO|XXX","severity":"warning","excludes":["*.rs"]}
+    ]}"#;
+
+    const FILES: [(&str, &str); 6] = [
+        (
+            "src/main.rs",
+            "use std::io;\nfn main() {\n    println!(\"TODO\"); \n}\n",
+        ),
+        ("src/lib.rs", "// FIXME(ann): a.b and a-b\n// Hack\n"),
+        ("src/clean.rs", "fn clean() {}\n"),
+        ("docs/notes.md", "XXX begin\nsome\nend TODO\t\n"),
+        ("docs/win.txt", "semi;\r\nsemi; \r\nuse crlf\r\n"),
+        ("empty.txt", ""),
+    ];
+
+    fn fixture(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("linty-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        for (path, contents) in FILES {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+
+    /// A violation's rule, file, and match lines and columns.
+    type Found = (String, String, Vec<(usize, usize)>);
+
+    /// Each violation as `Found`, in a stable order.
+    fn outcome(violations: Vec<Violation>) -> Vec<Found> {
+        let mut outcome: Vec<_> = violations
+            .into_iter()
+            .map(|violation| {
+                let positions = violation
+                    .matches
+                    .iter()
+                    .map(|m| (m.line, m.column))
+                    .collect();
+                (violation.rule_id, violation.file, positions)
+            })
+            .collect();
+        outcome.sort();
+        outcome
+    }
+
+    #[test]
+    fn prefiltered_scan_finds_what_each_rule_finds_alone() {
+        let dir = fixture("prefilter");
+        let config = parse_config(RULES, ConfigFormat::Json, "test config").unwrap();
+        let linter = Linter::from_config(config).unwrap();
+        assert!(
+            Prefilter::new(&linter.rules).sets.len() >= 2,
+            "the rules should make several sets"
+        );
+        let options = WalkOptions {
+            roots: vec![dir.clone()],
+            threads: 1,
+            ..linter.options.clone()
+        };
+        // A rule timeout runs each rule on its own, without the prefilter.
+        let unfiltered = WalkOptions {
+            rule_timeout: Some(Duration::from_secs(600)),
+            ..options.clone()
+        };
+        let filtered = scan(&options, &linter.rules, &linter.overrides, &dir).unwrap();
+        let alone = scan(&unfiltered, &linter.rules, &linter.overrides, &dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let filtered = outcome(filtered.violations);
+        assert_eq!(filtered, outcome(alone.violations));
+        let found: HashSet<&str> = filtered.iter().map(|(id, _, _)| id.as_str()).collect();
+        assert_eq!(
+            found.len(),
+            11,
+            "every rule but Never should match: {found:?}"
+        );
+    }
+
+    /// `dedup_overlapping` on ranges given as start and end pairs.
+    fn dedup(ranges: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        let ranges = ranges.iter().map(|&(start, end)| start..end).collect();
+        dedup_overlapping(ranges)
+            .into_iter()
+            .map(|range| (range.start, range.end))
+            .collect()
+    }
+
+    #[test]
+    fn overlapping_ranges_collapse_into_the_longest() {
+        // Given out of order, as matches from several slices can be.
+        assert_eq!(dedup(&[(6, 10), (0, 4), (0, 5)]), [(0, 5), (6, 10)]);
+        assert_eq!(dedup(&[(0, 4), (0, 4)]), [(0, 4)]);
+        // A range inside a longer one goes, wherever it starts.
+        assert_eq!(dedup(&[(0, 10), (2, 4), (3, 9)]), [(0, 10)]);
+        // A later, longer range that overlaps the end replaces the earlier one.
+        assert_eq!(dedup(&[(0, 4), (3, 8)]), [(3, 8)]);
+        // Touching isn't overlapping.
+        assert_eq!(dedup(&[(0, 4), (4, 8)]), [(0, 4), (4, 8)]);
+        assert!(dedup(&[]).is_empty());
+    }
+
+    #[test]
+    fn nested_alternations_report_each_piece_of_text_once() {
+        let config = parse_config(
+            r#"{"rules":[{"id":"Todo","message":"m","regex":"((TODO)|(TODO:)|(TO(DO|DO:)))","severity":"error"}]}"#,
+            ConfigFormat::Json,
+            "test config",
+        )
+        .unwrap();
+        let linter = Linter::from_config(config).unwrap();
+        let violations = linter
+            .check_str("TODO: one\nTODO:TODO\n", Path::new("a.txt"))
+            .unwrap();
+        assert_eq!(
+            outcome(violations),
+            [(
+                String::from("Todo"),
+                String::from("a.txt"),
+                vec![(1, 1), (2, 1), (2, 6)]
+            )]
+        );
+    }
+
+    #[test]
+    fn required_rules_flag_only_checked_files_missing_the_pattern() {
+        let dir = std::env::temp_dir().join(format!("linty-required-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let bin = dir.join("src/bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        let files: [(&str, &[u8]); 7] = [
+            (
+                "with.rs",
+                b"// SPDX-License-Identifier: MIT\nfn main() {}\n",
+            ),
+            ("without.rs", b"fn main() {}\n"),
+            ("gen_excluded.rs", b"fn main() {}\n"),
+            ("binary.rs", b"fn main() {}\0\n"),
+            ("latin1.rs", b"// caf\xe9\n"),
+            ("large.rs", &[b'x'; 200]),
+            ("notes.txt", b"not a source file\n"),
+        ];
+        for (name, contents) in files {
+            std::fs::write(bin.join(name), contents).unwrap();
+        }
+        let config = parse_config(
+            r#"{"rules":[{"id":"License","message":"Missing license header","regex":"SPDX-License-Identifier","mode":"required","severity":"error","includes":["**/bin/*.rs"],"excludes":["**/bin/gen_*.rs"]}]}"#,
+            ConfigFormat::Json,
+            "test config",
+        )
+        .unwrap();
+        let linter = Linter::from_config(config).unwrap();
+        let options = WalkOptions {
+            roots: vec![dir.clone()],
+            threads: 1,
+            max_filesize: Some(100),
+            ..linter.options.clone()
+        };
+        let result = scan(&options, &linter.rules, &linter.overrides, &dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let missing: Vec<(&str, Vec<usize>)> = result
+            .violations
+            .iter()
+            .map(|violation| {
+                let name = violation.path.file_name().unwrap().to_str().unwrap();
+                (name, violation.lines())
+            })
+            .collect();
+        assert_eq!(missing, [("without.rs", vec![1])]);
+        assert_eq!(result.violations[0].matches[0].text, "");
+        assert_eq!(
+            (
+                result.binary_skipped,
+                result.large_skipped,
+                result.encoding_skipped
+            ),
+            (1, 1, 1)
+        );
+    }
+
+    /// Where the single `rule`, given as JSON, matches `contents`, as lines and columns.
+    fn positions(rule: &str, contents: &str) -> Vec<(usize, usize)> {
+        let config = parse_config(
+            &format!(r#"{{"rules":[{rule}]}}"#),
+            ConfigFormat::Json,
+            "test config",
+        )
+        .unwrap();
+        let linter = Linter::from_config(config).unwrap();
+        let violations = linter.check_str(contents, Path::new("a.txt")).unwrap();
+        outcome(violations)
+            .into_iter()
+            .flat_map(|(_, _, positions)| positions)
+            .collect()
+    }
+
+    #[test]
+    fn allow_exempts_only_the_match_it_overlaps_on_a_line() {
+        let rule = r#"{"id":"Todo","message":"m","regex":"TODO","allow":["TODO\\(#\\d+\\)"],"severity":"error"}"#;
+        assert_eq!(positions(rule, "TODO(#12) and TODO\n"), [(1, 15)]);
+        assert_eq!(positions(rule, "TODO and TODO(#12)\n"), [(1, 1)]);
+        assert_eq!(positions(rule, "TODO(#1) TODO(#2)\nTODO\n"), [(2, 1)]);
+
+        let allow = [Regex::new(r"TODO\(#\d+\)").unwrap()];
+        let contents = "TODO(#12) and TODO\n";
+        assert!(is_allowed(&allow, contents, &(0..4)));
+        assert!(!is_allowed(&allow, contents, &(14..18)));
+    }
+
+    #[test]
+    fn first_only_search_stops_at_the_first_kept_match() {
+        let config = parse_config(
+            r#"{"rules":[{"id":"Todo","message":"m","regex":"TODO","allow":["TODO\\(ok\\)"],"report_once":true,"severity":"error"}]}"#,
+            ConfigFormat::Json,
+            "test config",
+        )
+        .unwrap();
+        let linter = Linter::from_config(config).unwrap();
+        let contents = "TODO(ok)\nTODO\nTODO\n";
+        let find = |first_only| {
+            let whole = std::iter::once(0..contents.len()).collect();
+            find_ranges(
+                &linter.rules[0],
+                contents,
+                whole,
+                &mut HashMap::new(),
+                None,
+                first_only,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|range| (range.start, range.end))
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(find(true), [(9, 13)]);
+        assert_eq!(find(false), [(9, 13), (14, 18)]);
+
+        let violations = linter.check_str(contents, Path::new("a.txt")).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].report_once);
+        assert_eq!(violations[0].lines(), [2]);
+    }
+
+    #[test]
+    fn word_boundary_matches_only_whole_words() {
+        let rule =
+            r#"{"id":"Todo","message":"m","regex":"todo","word_boundary":true,"severity":"error"}"#;
+        assert_eq!(
+            positions(
+                rule,
+                "todo at the start\nmastodon todos_completed (todo)\nat the end todo"
+            ),
+            [(1, 1), (2, 27), (3, 12)]
+        );
+
+        let literal = r#"{"id":"Todo","message":"m","regex":"todo","match_type":"literal","case_insensitive":true,"word_boundary":true,"severity":"error"}"#;
+        assert_eq!(positions(literal, "ToDo.\nTODOS\n"), [(1, 1)]);
+
+        // Only an edge that's a word character needs a non-word neighbor.
+        let at = r#"{"id":"Deprecated","message":"m","regex":"@deprecated","word_boundary":true,"severity":"error"}"#;
+        assert_eq!(positions(at, "x@deprecated\n@deprecatedFoo\n"), [(1, 2)]);
+
+        // Matches are checked after they're found, so a shorter alternative isn't tried.
+        let alternation = r#"{"id":"Todo","message":"m","regex":"todo|to","word_boundary":true,"severity":"error"}"#;
+        assert!(positions(alternation, "todos\n").is_empty());
+    }
+
+    #[test]
+    fn negations_decide_within_each_list_and_excludes_beat_includes() {
+        let config = parse_config(
+            r#"{"rules":[{"id":"Gen","message":"m","regex":"x","severity":"error",
+                "includes":["src/**","!src/vendor/**","src/vendor/ours/**"],
+                "excludes":["src/generated/**","!src/generated/handwritten.rs","src/vendor/ours/gen.rs"]}]}"#,
+            ConfigFormat::Json,
+            "test config",
+        )
+        .unwrap();
+        let linter = Linter::from_config(config).unwrap();
+        let rule = &linter.rules[0];
+        let decide = |path: &str| rule_decision(rule, &[], Path::new(path));
+        let applies = |included_by, reincluded_by| RuleDecision::Applies {
+            severity: Severity::Error,
+            included_by,
+            reincluded_by,
+            severity_from: None,
+        };
+        assert_eq!(decide("src/a.rs"), applies(Some(0), None));
+        assert_eq!(
+            decide("src/vendor/theirs.rs"),
+            RuleDecision::NotIncluded(Some(1))
+        );
+        assert_eq!(decide("src/vendor/ours/a.rs"), applies(Some(2), None));
+        assert_eq!(decide("src/generated/a.rs"), RuleDecision::Excluded(0));
+        assert_eq!(
+            decide("src/generated/handwritten.rs"),
+            applies(Some(0), Some(1))
+        );
+        // Re-included by the includes, but an exclude still wins.
+        assert_eq!(decide("src/vendor/ours/gen.rs"), RuleDecision::Excluded(2));
+        assert_eq!(decide("docs/a.md"), RuleDecision::NotIncluded(None));
+    }
+}
@@ -0,0 +1,97 @@
+use linty::progress::Progress;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// How often the line is redrawn; the walkers only bump a counter, so this is all the
+/// display costs.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a scan runs before the line appears, so quick runs don't flash it.
+const SHOW_AFTER: Duration = Duration::from_millis(300);
+
+const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// A one-line progress display on stderr while a scan runs: a spinner, the files scanned
+/// so far, the elapsed time, and the latest file. The line is cleared when it stops, so
+/// the report starts on a clean line.
+pub struct Spinner {
+    progress: Arc<Progress>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Spinner {
+    pub fn start() -> Spinner {
+        let progress = Arc::new(Progress::default());
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let progress = Arc::clone(&progress);
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || draw(&progress, &stop))
+        };
+        Spinner {
+            progress,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// The counter for the scan to update.
+    pub fn progress(&self) -> Arc<Progress> {
+        Arc::clone(&self.progress)
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Redraws the line until `stop` is set, then clears it if it was ever drawn.
+fn draw(progress: &Progress, stop: &AtomicBool) {
+    let start = Instant::now();
+    let mut drawn = false;
+    let mut frame = 0;
+    while !stop.load(Ordering::Relaxed) {
+        std::thread::park_timeout(REDRAW_INTERVAL);
+        if stop.load(Ordering::Relaxed) || start.elapsed() < SHOW_AFTER {
+            continue;
+        }
+        let status = format!(
+            "{} Scanned {} files in {:.1}s: ",
+            FRAMES[frame % FRAMES.len()],
+            crate::format_count(progress.files()),
+            start.elapsed().as_secs_f64()
+        );
+        let available = crate::line_ranges::output_width()
+            .saturating_sub(status.chars().count() + 1)
+            .max(10);
+        let path = linty::display_path(&progress.current());
+        eprint!("\r\x1b[2K{status}{}", tail(&path, available));
+        let _ = std::io::stderr().flush();
+        drawn = true;
+        frame += 1;
+    }
+    if drawn {
+        eprint!("\r\x1b[2K");
+        let _ = std::io::stderr().flush();
+    }
+}
+
+/// The end of `path` in at most `width` characters, with `...` where the start was cut.
+fn tail(path: &str, width: usize) -> String {
+    let length = path.chars().count();
+    if length <= width {
+        return path.to_owned();
+    }
+    let kept: String = path.chars().skip(length - width + 3).collect();
+    format!("...{kept}")
+}
@@ -0,0 +1,144 @@
+use serde::de::{Error, SeqAccess, Visitor};
+use serde::Deserializer;
+use std::fmt;
+
+/// Reads a config list of strings that can also be written as a single string, like
+/// `"includes": "**/*.rs"`. Null reads as no list.
+struct StringListVisitor;
+
+impl<'de> Visitor<'de> for StringListVisitor {
+    type Value = Option<Vec<String>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string or a list of strings")
+    }
+
+    fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(Some(vec![value.to_owned()]))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut strings = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(string) = seq.next_element::<String>()? {
+            strings.push(string);
+        }
+        Ok(Some(strings))
+    }
+
+    fn visit_none<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(StringListVisitor)
+    }
+}
+
+/// For optional list fields; pair with `#[serde(default)]` so a missing field is `None`.
+pub fn deserialize_optional<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<String>>, D::Error> {
+    deserializer.deserialize_any(StringListVisitor)
+}
+
+/// For required list fields.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    deserializer
+        .deserialize_any(StringListVisitor)?
+        .ok_or_else(|| D::Error::invalid_type(serde::de::Unexpected::Unit, &StringListVisitor))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_config, Config, ConfigFormat};
+
+    /// The first rule's includes and examples from a config in `format`.
+    fn lists(contents: &str, format: ConfigFormat) -> (Option<Vec<String>>, Vec<String>) {
+        let config = parse_config(contents, format, "test config").unwrap();
+        let rule = &config.rules[0];
+        let examples = rule.examples.as_ref().unwrap().matches.clone();
+        (rule.includes.clone(), examples)
+    }
+
+    fn strings(strings: &[&str]) -> Vec<String> {
+        strings.iter().map(|string| string.to_string()).collect()
+    }
+
+    #[test]
+    fn json_takes_a_string_or_a_list() {
+        let config = |includes: &str, matches: &str| {
+            format!(
+                r#"{{"rules":[{{"id":"R","message":"m","regex":"x","severity":"error",
+                    "includes":{includes},"examples":{{"matches":{matches}}}}}]}}"#
+            )
+        };
+        let single = lists(&config(r#""**/*.rs""#, r#""x""#), ConfigFormat::Json);
+        assert_eq!(single, (Some(strings(&["**/*.rs"])), strings(&["x"])));
+        let list = lists(
+            &config(r#"["**/*.rs","*.md"]"#, r#"["x","xx"]"#),
+            ConfigFormat::Json,
+        );
+        assert_eq!(
+            list,
+            (Some(strings(&["**/*.rs", "*.md"])), strings(&["x", "xx"]))
+        );
+        let empty = lists(&config("null", "[]"), ConfigFormat::Json);
+        assert_eq!(empty, (None, Vec::new()));
+    }
+
+    #[test]
+    fn toml_takes_a_string_or_a_list() {
+        let config = |includes: &str, matches: &str| {
+            format!(
+                "[[rules]]\nid = \"R\"\nmessage = \"m\"\nregex = \"x\"\nseverity = \"error\"\n\
+                 includes = {includes}\nexamples = {{ matches = {matches} }}\n"
+            )
+        };
+        let single = lists(&config(r#""**/*.rs""#, r#""x""#), ConfigFormat::Toml);
+        assert_eq!(single, (Some(strings(&["**/*.rs"])), strings(&["x"])));
+        let list = lists(
+            &config(r#"["**/*.rs", "*.md"]"#, r#"["x", "xx"]"#),
+            ConfigFormat::Toml,
+        );
+        assert_eq!(
+            list,
+            (Some(strings(&["**/*.rs", "*.md"])), strings(&["x", "xx"]))
+        );
+    }
+
+    #[test]
+    fn single_strings_are_written_back_as_lists() {
+        let config = parse_config(
+            r#"{"rules":[{"id":"R","message":"m","regex":"x","severity":"error","includes":"**/*.rs"}]}"#,
+            ConfigFormat::Json,
+            "test config",
+        )
+        .unwrap();
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains(r#""includes":["**/*.rs"]"#), "{json}");
+        let toml = toml::to_string(&config).unwrap();
+        for (written, format) in [(json, ConfigFormat::Json), (toml, ConfigFormat::Toml)] {
+            let reread: Config = parse_config(&written, format, "test config").unwrap();
+            assert_eq!(reread.rules[0].includes, Some(strings(&["**/*.rs"])));
+        }
+    }
+
+    #[test]
+    fn other_values_are_rejected() {
+        for (includes, expected) in [
+            ("5", "expected a string or a list of strings"),
+            (r#"{"a":"b"}"#, "expected a string or a list of strings"),
+            ("[5]", "expected a string"),
+        ] {
+            let config = format!(
+                r#"{{"rules":[{{"id":"R","message":"m","regex":"x","severity":"error","includes":{includes}}}]}}"#
+            );
+            let err = parse_config(&config, ConfigFormat::Json, "test config").unwrap_err();
+            assert!(format!("{err:#}").contains(expected), "{err:#}");
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::scan::Match;
+use std::ops::Range;
+
+/// Rule id of the warning recorded for a suppression comment that suppressed nothing,
+/// with `--report-unused-suppressions`.
+pub const UNUSED_SUPPRESSION_RULE_ID: &str = "linty::unused-suppression";
+
+/// `linty-disable-file` only counts in this many lines at the top of a file.
+const FILE_DIRECTIVE_LINES: usize = 10;
+
+const DIRECTIVE_PREFIX: &str = "linty-disable-";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    NextLine,
+    Line,
+    File,
+}
+
+/// A `linty-disable-next-line`, `linty-disable-line`, or `linty-disable-file` comment.
+pub struct Directive {
+    scope: Scope,
+    /// `None` suppresses every rule.
+    rule_ids: Option<Vec<String>>,
+}
+
+/// Finds the suppression comments in `text`, with the byte range of each.
+pub fn parse(text: &str) -> Vec<(Directive, Range<usize>)> {
+    let mut directives = Vec::new();
+    if !text.contains(DIRECTIVE_PREFIX) {
+        return directives;
+    }
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let Some(index) = line.find(DIRECTIVE_PREFIX) else {
+            continue;
+        };
+        let after = &line[index + DIRECTIVE_PREFIX.len()..];
+        let (scope, rest) = if let Some(rest) = after.strip_prefix("next-line") {
+            (Scope::NextLine, rest)
+        } else if let Some(rest) = after.strip_prefix("line") {
+            (Scope::Line, rest)
+        } else if let Some(rest) = after.strip_prefix("file") {
+            (Scope::File, rest)
+        } else {
+            continue;
+        };
+        // `linty-disable-lines` isn't a directive.
+        if rest.starts_with(is_id_char) {
+            continue;
+        }
+        let rule_ids = rule_ids(rest);
+        let start = line_start + index;
+        let end = line_start + line.len() - rest.len();
+        directives.push((
+            Directive {
+                scope,
+                rule_ids: (!rule_ids.is_empty()).then_some(rule_ids),
+            },
+            start..end,
+        ));
+    }
+    directives
+}
+
+fn is_id_char(character: char) -> bool {
+    character.is_alphanumeric() || "_-:/.".contains(character)
+}
+
+/// The comma- or space-separated rule ids after a directive, up to whatever ends the
+/// comment, like `*/` or a free-form reason.
+fn rule_ids(rest: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    for token in rest.split(|character: char| character == ',' || character.is_whitespace()) {
+        if token.is_empty() {
+            continue;
+        }
+        let id: String = token.chars().take_while(|&c| is_id_char(c)).collect();
+        if id.is_empty() || !id.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+            break;
+        }
+        let whole = id.len() == token.len();
+        ids.push(id);
+        if !whole {
+            break;
+        }
+    }
+    ids
+}
+
+/// The suppression comments found in one file, and whether each has suppressed a match.
+#[derive(Default)]
+pub struct Suppressions {
+    directives: Vec<(Directive, Match, bool)>,
+}
+
+impl Suppressions {
+    /// Records a directive written at `location`. `linty-disable-file` below the first
+    /// few lines is ignored.
+    pub fn add(&mut self, directive: Directive, location: Match) {
+        if directive.scope == Scope::File && location.line > FILE_DIRECTIVE_LINES {
+            return;
+        }
+        self.directives.push((directive, location, false));
+    }
+
+    /// Whether a match of `rule_id` starting on `line` is suppressed, marking the
+    /// directives that suppress it as used.
+    pub fn suppresses(&mut self, rule_id: &str, line: usize) -> bool {
+        let mut suppressed = false;
+        for (directive, location, used) in &mut self.directives {
+            let covers_line = match directive.scope {
+                Scope::NextLine => location.line + 1 == line,
+                Scope::Line => location.line == line,
+                Scope::File => true,
+            };
+            let covers_rule = directive
+                .rule_ids
+                .as_ref()
+                .is_none_or(|ids| ids.iter().any(|id| id == rule_id));
+            if covers_line && covers_rule {
+                *used = true;
+                suppressed = true;
+            }
+        }
+        suppressed
+    }
+
+    /// Where the directives that haven't suppressed anything are written.
+    pub fn unused(self) -> Vec<Match> {
+        self.directives
+            .into_iter()
+            .filter(|(_, _, used)| !used)
+            .map(|(_, location, _)| location)
+            .collect()
+    }
+}
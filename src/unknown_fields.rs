@@ -0,0 +1,148 @@
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use std::fmt;
+
+/// A field of a config that no part of linty reads, like a misspelled `severty`.
+struct UnknownField {
+    /// Where it is, like `rules[2] (id NoPrintln)`, or `None` at the top level.
+    location: Option<String>,
+    name: String,
+    /// The known field the name is closest to, if it's close enough to be a typo.
+    suggestion: Option<&'static str>,
+}
+
+impl fmt::Display for UnknownField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown field `{}`", self.name)?;
+        if let Some(location) = &self.location {
+            write!(f, " in {location}")?;
+        }
+        if let Some(suggestion) = self.suggestion {
+            write!(f, "; did you mean `{suggestion}`?")?;
+        }
+        Ok(())
+    }
+}
+
+/// One step of the path `serde_ignored` reports an ignored field at.
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// The steps of `path`, kept so the field can be described once the whole config, and so
+/// the ids of its rules, has been parsed.
+pub fn segments(path: &serde_ignored::Path) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    collect(path, &mut segments);
+    segments
+}
+
+/// Describes the ignored field at `segments`, naming the rule it's in by its index and the
+/// id from `rule_ids`, and suggesting a known field of the config or rule it's in. The
+/// `source` that `print-config` adds to rules is never read, but a config saved from its
+/// output still loads, so it's `None`.
+pub fn describe(mut segments: Vec<Segment>, rule_ids: &[String]) -> Option<String> {
+    let name = match segments.pop() {
+        Some(Segment::Key(key)) => key,
+        Some(Segment::Index(index)) => index.to_string(),
+        None => String::new(),
+    };
+    let (location, fields) = match &segments[..] {
+        [] => (None, field_names::<crate::Config>()),
+        [Segment::Key(rules), Segment::Index(_)] if rules == "rules" && name == "source" => {
+            return None
+        }
+        [Segment::Key(rules), Segment::Index(index)] if rules == "rules" => (
+            Some(match rule_ids.get(*index) {
+                Some(id) => format!("rules[{index}] (id {id})"),
+                None => format!("rules[{index}]"),
+            }),
+            field_names::<crate::RuleConfig>(),
+        ),
+        _ => (Some(display(&segments)), &[][..]),
+    };
+    let suggestion = fields
+        .iter()
+        .map(|field| (crate::languages::edit_distance(&name, field), *field))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, field)| field);
+    Some(
+        UnknownField {
+            location,
+            name,
+            suggestion,
+        }
+        .to_string(),
+    )
+}
+
+fn collect(path: &serde_ignored::Path, segments: &mut Vec<Segment>) {
+    use serde_ignored::Path;
+    match path {
+        Path::Root => {}
+        Path::Seq { parent, index } => {
+            collect(parent, segments);
+            segments.push(Segment::Index(*index));
+        }
+        Path::Map { parent, key } => {
+            collect(parent, segments);
+            segments.push(Segment::Key(key.clone()));
+        }
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => collect(parent, segments),
+    }
+}
+
+/// `segments` as a config path, like `profiles.ci` or `overrides[0]`.
+fn display(segments: &[Segment]) -> String {
+    let mut text = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Key(key) if text.is_empty() => text.push_str(key),
+            Segment::Key(key) => text.push_str(&format!(".{key}")),
+            Segment::Index(index) => text.push_str(&format!("[{index}]")),
+        }
+    }
+    text
+}
+
+/// The field names `T`'s derived `Deserialize` asks for, found by handing it a deserializer
+/// that only records them.
+fn field_names<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames {
+        fields: &mut fields,
+    });
+    fields
+}
+
+struct FieldNames<'a> {
+    fields: &'a mut &'static [&'static str],
+}
+
+impl<'de> Deserializer<'de> for FieldNames<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.fields = fields;
+        Err(de::Error::custom("only recording field names"))
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("only recording field names"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
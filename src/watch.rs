@@ -0,0 +1,204 @@
+use crate::desktop_notify::Notifier;
+use crate::display::DisplayTable;
+use crate::outcome::{self, Outcome};
+use crate::scan::{self, Match};
+use crate::{cancel, Violation};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// How long the tree has to stay quiet after a change before it's re-linted, so a save
+/// that touches several files, or a checkout, is linted once.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// What `--watch` reports with, carried over from the first run.
+pub struct Watch<'a> {
+    pub roots: &'a [PathBuf],
+    pub display: &'a DisplayTable,
+    pub messages: &'a HashMap<&'a str, &'a str>,
+    /// Set with `--notify`; kept across re-runs so only changed counts are sent.
+    pub notifier: Option<Notifier>,
+}
+
+impl Watch<'_> {
+    /// Re-lints the files that change under the roots until Ctrl-C, starting from the first
+    /// run's `violations` and `outcome`. `relint` scans the files at or under the changed
+    /// paths, or returns `None` when the walk skips all of them, and `outcome_of` decides
+    /// how a set of violations exits. Each re-run prints the matches it added and removed,
+    /// and the new totals.
+    ///
+    /// Ctrl-C between re-runs exits as the last one would have. One that interrupts a
+    /// re-run returns the outcome from before it, since its results are partial.
+    pub fn run(
+        mut self,
+        violations: Vec<Violation>,
+        mut outcome: Outcome,
+        mut relint: impl FnMut(&HashSet<PathBuf>) -> anyhow::Result<Option<Vec<Violation>>>,
+        outcome_of: impl Fn(&[Violation]) -> Outcome,
+    ) -> anyhow::Result<Outcome> {
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        let mut watched = HashSet::new();
+        for root in self.roots {
+            let root = scan::absolute(root);
+            // Editors often save by replacing the file, which would end a watch on the file
+            // itself, so a file root is watched through its directory.
+            let (path, mode) = match root.parent() {
+                Some(parent) if root.is_file() => (parent.to_owned(), RecursiveMode::NonRecursive),
+                _ => (root, RecursiveMode::Recursive),
+            };
+            if watched.insert(path.clone()) {
+                watcher.watch(&path, mode)?;
+            }
+        }
+
+        let mut current: HashMap<PathBuf, Vec<Violation>> = HashMap::new();
+        for violation in violations {
+            current
+                .entry(scan::absolute(&violation.path))
+                .or_default()
+                .push(violation);
+        }
+        outcome::set_idle_outcome(outcome.clone());
+        println!("Watching for changes; press Ctrl-C to stop");
+
+        loop {
+            let mut changed = HashSet::new();
+            add_paths(receiver.recv()?, &mut changed);
+            while let Ok(event) = receiver.recv_timeout(DEBOUNCE) {
+                add_paths(event, &mut changed);
+            }
+            if changed.is_empty() {
+                continue;
+            }
+
+            let found = match relint(&changed) {
+                Ok(found) => found,
+                Err(err) => {
+                    eprintln!("Error: {err:?}");
+                    continue;
+                }
+            };
+            if cancel::cancelled().is_some() {
+                return Ok(outcome);
+            }
+            let mut removed = Vec::new();
+            current.retain(|path, violations| {
+                let keep = !scan::is_changed(&changed, path);
+                if !keep {
+                    removed.append(violations);
+                }
+                keep
+            });
+            if found.is_none() && removed.is_empty() {
+                continue;
+            }
+            let found = found.unwrap_or_default();
+
+            let mut lines: Vec<(&str, usize, String)> = Vec::new();
+            for (violation, m) in unmatched(&found, &removed) {
+                lines.push((&violation.file, m.line, self.change_line("-", violation, m)));
+            }
+            for (violation, m) in unmatched(&removed, &found) {
+                lines.push((&violation.file, m.line, self.change_line("+", violation, m)));
+            }
+            lines.sort();
+            let (added, fixed) = (
+                lines.iter().filter(|line| line.2.starts_with('+')).count(),
+                lines.iter().filter(|line| line.2.starts_with('-')).count(),
+            );
+            for (_, _, line) in &lines {
+                println!("{line}");
+            }
+
+            for violation in found {
+                current
+                    .entry(scan::absolute(&violation.path))
+                    .or_default()
+                    .push(violation);
+            }
+            let mut all: Vec<Violation> = current.values().flatten().cloned().collect();
+            all.sort_by(|a, b| a.path.cmp(&b.path));
+            outcome = outcome_of(&all);
+            outcome::set_idle_outcome(outcome.clone());
+            let summary = crate::count_summary(self.display, &outcome);
+            println!("{added} new and {fixed} fixed match(es); now {summary}");
+            if let Some(notifier) = &mut self.notifier {
+                notifier.notify(outcome.errors, outcome.warnings, &summary);
+            }
+        }
+    }
+
+    /// One match gained or lost in a re-run, prefixed with `sign`.
+    fn change_line(&self, sign: &str, violation: &Violation, m: &Match) -> String {
+        let location = if m.has_position() {
+            format!("{}:{}", violation.file, m.line)
+        } else {
+            violation.file.clone()
+        };
+        format!(
+            "{sign} {}: {} {}: {}",
+            self.display.dim(&location),
+            self.display
+                .paint(violation.severity, self.display.label(violation.severity)),
+            self.display.bold(&violation.rule_id),
+            m.message(
+                self.messages
+                    .get(violation.rule_id.as_str())
+                    .copied()
+                    .unwrap_or_default()
+            )
+        )
+    }
+}
+
+/// The matches in `to` that aren't in `from`. A match is identified by its rule, file, and
+/// fingerprint rather than its line, so editing above it doesn't count as a change.
+fn unmatched<'a>(from: &[Violation], to: &'a [Violation]) -> Vec<(&'a Violation, &'a Match)> {
+    let mut remaining: HashMap<(&str, &Path, &str), usize> = HashMap::new();
+    for violation in from {
+        for m in &violation.matches {
+            *remaining
+                .entry((&violation.rule_id, &violation.path, &m.fingerprint))
+                .or_default() += 1;
+        }
+    }
+    let mut unmatched = Vec::new();
+    for violation in to {
+        for m in &violation.matches {
+            match remaining.get_mut(&(
+                violation.rule_id.as_str(),
+                violation.path.as_path(),
+                m.fingerprint.as_str(),
+            )) {
+                Some(count) if *count > 0 => *count -= 1,
+                _ => unmatched.push((violation, m)),
+            }
+        }
+    }
+    unmatched
+}
+
+/// Adds the paths a filesystem event changed. Reads, including linty's own, aren't
+/// changes, and git rewrites its internals on most commands, so neither re-lints.
+fn add_paths(event: notify::Result<notify::Event>, changed: &mut HashSet<PathBuf>) {
+    let event = match event {
+        Ok(event) => event,
+        Err(err) => {
+            eprintln!("Warning: {err}");
+            return;
+        }
+    };
+    if matches!(event.kind, EventKind::Access(_)) {
+        return;
+    }
+    changed.extend(
+        event
+            .paths
+            .iter()
+            .filter(|path| !path.components().any(|part| part.as_os_str() == ".git"))
+            .map(|path| scan::absolute(path)),
+    );
+}
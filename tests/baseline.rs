@@ -0,0 +1,103 @@
+mod common;
+
+use common::{all_output, exit_code, stdout, Fixture};
+
+const CONFIG: &str = r#"{"rules":[
+    {"id":"NoTodo","message":"No todos","regex":"TODO","severity":"error"},
+    {"id":"NoUnwrap","message":"Don't unwrap","regex":"unwrap\\(\\)","severity":"error"}
+]}"#;
+
+/// A fixture with a baseline of three todos and one unwrap, two todos and the unwrap of
+/// which are still there, beside a new todo.
+fn baselined() -> Fixture {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a.txt", "TODO one\nTODO two\nx.unwrap()\n")
+        .write("b.txt", "TODO three\n");
+    let output = fixture.run(&["baseline"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    fixture
+        .write("b.txt", "fixed\n")
+        .write("c.txt", "TODO new\n");
+    fixture
+}
+
+#[test]
+fn text_summary_counts_each_rule() {
+    let fixture = baselined();
+    let output = fixture.run(&["--no-confirm", "--baseline", ".lintybaseline.json"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(
+        report.contains(
+            "3 known match(es) hidden by the baseline (NoTodo: 2, NoUnwrap: 1); 1 resolved (NoTodo: 1), consider regenerating the baseline with linty baseline"
+        ),
+        "{report}"
+    );
+    assert!(report.contains("c.txt"), "{report}");
+}
+
+#[test]
+fn json_summary_counts_each_rule() {
+    let fixture = baselined();
+    let output = fixture.run(&[
+        "--no-confirm",
+        "--baseline",
+        ".lintybaseline.json",
+        "--format",
+        "json",
+    ]);
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    let summary = &report["summary"];
+    assert_eq!(
+        summary["baselined"],
+        serde_json::json!({"NoTodo": 2, "NoUnwrap": 1})
+    );
+    assert_eq!(
+        summary["baseline_resolved"],
+        serde_json::json!({"NoTodo": 1})
+    );
+    assert_eq!(summary["errors"], 1);
+}
+
+#[test]
+fn nothing_resolved_leaves_out_the_resolved_count() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a.txt", "TODO\n");
+    fixture.run(&["baseline"]);
+    let output = fixture.run(&["--no-confirm", "--baseline", ".lintybaseline.json"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    let report = stdout(&output);
+    assert!(
+        report.contains("1 known match(es) hidden by the baseline (NoTodo: 1)\n"),
+        "{report}"
+    );
+
+    let output = fixture.run(&[
+        "--no-confirm",
+        "--baseline",
+        ".lintybaseline.json",
+        "--format",
+        "json",
+    ]);
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(
+        report["summary"]["baseline_resolved"],
+        serde_json::json!({})
+    );
+}
+
+#[test]
+fn json_summary_without_a_baseline_has_no_counts() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a.txt", "TODO\n");
+    let output = fixture.run(&["--no-confirm", "--format", "json"]);
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert!(report["summary"].get("baselined").is_none());
+    assert!(report["summary"].get("baseline_resolved").is_none());
+}
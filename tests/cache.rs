@@ -0,0 +1,73 @@
+mod common;
+
+use common::{all_output, exit_code, stderr, Fixture};
+
+const CONFIG: &str =
+    r#"{"rules":[{"id":"NoTodo","message":"No todos","regex":"TODO","severity":"error"}]}"#;
+
+#[test]
+fn corrupt_cache_is_warned_about_and_replaced() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a.txt", "TODO\n")
+        .write("cache.json", "{ not json");
+    let output = fixture.run(&["--no-confirm", "--cache", "--cache-path", "cache.json"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert!(
+        stderr(&output).starts_with("Warning: Ignoring cache cache.json: "),
+        "{}",
+        stderr(&output)
+    );
+    assert!(all_output(&output).contains("a.txt"));
+
+    // The run wrote a cache that loads.
+    let output = fixture.run(&["--no-confirm", "--cache", "--cache-path", "cache.json"]);
+    assert!(
+        !stderr(&output).contains("Ignoring cache"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn summary_estimates_the_time_the_cache_saved() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a.txt", "TODO\n");
+    // Files modified in the last couple of seconds aren't cached.
+    let an_hour_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+    std::fs::File::options()
+        .write(true)
+        .open(fixture.path("a.txt"))
+        .unwrap()
+        .set_modified(an_hour_ago)
+        .unwrap();
+
+    let args = ["--no-confirm", "--cache", "--format", "json"];
+    let summary = |output: &std::process::Output| {
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        report["summary"].clone()
+    };
+    let first = summary(&fixture.run(&args));
+    assert!(first["walk_ms"].is_u64(), "{first}");
+    assert!(first["match_ms"].is_u64(), "{first}");
+    assert_eq!(first["cache_saved_ms"], 0, "{first}");
+    let cache = std::fs::read_to_string(fixture.path(".linty-cache")).unwrap();
+    assert!(cache.contains("\"cost_micros\":"), "{cache}");
+
+    let second = summary(&fixture.run(&args));
+    assert!(second["cache_saved_ms"].is_u64(), "{second}");
+
+    let output = fixture.run(&["--no-confirm", "--cache"]);
+    assert!(
+        all_output(&output)
+            .contains("Reused the results for 1 unchanged file(s) from the cache, saving about "),
+        "{}",
+        all_output(&output)
+    );
+
+    let uncached = summary(&fixture.run(&["--no-confirm", "--format", "json"]));
+    assert!(uncached.get("cache_saved_ms").is_none(), "{uncached}");
+}
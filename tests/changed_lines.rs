@@ -0,0 +1,28 @@
+mod common;
+
+use common::{all_output, exit_code, Fixture};
+
+const CONFIG: &str =
+    r#"{"rules":[{"id":"NoTodo","message":"No todos","regex":"TODO","severity":"error"}]}"#;
+
+#[test]
+fn only_staged_additions_are_reported() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("legacy.txt", "TODO old\nfine\nTODO old\n");
+    fixture.git_ok(&["add", "."]);
+    fixture.git_ok(&["commit", "-q", "-m", "legacy"]);
+    fixture.write(
+        "legacy.txt",
+        "TODO old\nfine\n++ TODO added\nTODO old\nTODO last",
+    );
+    fixture.git_ok(&["add", "legacy.txt"]);
+    let output = fixture.run(&["--no-confirm", "--pre-commit", "--changed-lines-only"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert!(
+        all_output(&output).contains("Error present in file: legacy.txt, lines: 3, 5"),
+        "{}",
+        all_output(&output)
+    );
+}
@@ -0,0 +1,114 @@
+// Each test binary uses only some of these helpers.
+#![allow(dead_code)]
+
+pub mod xml;
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_FIXTURE: AtomicUsize = AtomicUsize::new(0);
+
+/// A scratch directory for one test, removed when dropped. Linty runs in it with no
+/// global config, as it would on a clean machine.
+pub struct Fixture {
+    pub dir: PathBuf,
+}
+
+impl Fixture {
+    pub fn new() -> Fixture {
+        let dir = std::env::temp_dir().join(format!(
+            "linty-test-{}-{}",
+            std::process::id(),
+            NEXT_FIXTURE.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        Fixture {
+            dir: dir.canonicalize().unwrap(),
+        }
+    }
+
+    /// A fixture that's also a git repository.
+    pub fn git() -> Fixture {
+        let fixture = Fixture::new();
+        fixture.git_ok(&["init", "-q", "."]);
+        fixture
+    }
+
+    pub fn path(&self, path: &str) -> PathBuf {
+        self.dir.join(path)
+    }
+
+    /// Writes `contents` to `path`, creating its directories.
+    pub fn write(&self, path: &str, contents: impl AsRef<[u8]>) -> &Fixture {
+        let path = self.path(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+        self
+    }
+
+    /// Runs linty in the fixture's root.
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.run_in("", args)
+    }
+
+    /// Runs linty in `dir`, relative to the fixture's root.
+    pub fn run_in(&self, dir: &str, args: &[&str]) -> Output {
+        self.linty(dir).args(args).output().unwrap()
+    }
+
+    /// The linty command for `dir`, to add arguments or stdin to.
+    pub fn linty(&self, dir: &str) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_linty"));
+        command
+            .current_dir(self.dir.join(dir))
+            .env("XDG_CONFIG_HOME", self.dir.join(".no-global-config"))
+            .env("HOME", self.dir.join(".no-home"))
+            .env_remove("GITHUB_ACTIONS")
+            .env_remove("LINTY_PROFILE")
+            .env("NO_COLOR", "1");
+        command
+    }
+
+    /// Runs git in the fixture's root, failing the test if it fails.
+    pub fn git_ok(&self, args: &[&str]) {
+        let output = Command::new("git")
+            .current_dir(&self.dir)
+            .args(args)
+            .env("GIT_AUTHOR_NAME", "Test")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_NAME", "Test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+/// Both streams, for matching text wherever linty printed it.
+pub fn all_output(output: &Output) -> String {
+    format!("{}{}", stdout(output), stderr(output))
+}
+
+pub fn exit_code(output: &Output) -> i32 {
+    output.status.code().unwrap_or(-1)
+}
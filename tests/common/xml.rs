@@ -0,0 +1,148 @@
+//! Just enough of an XML parser to check linty's reports are well-formed: elements,
+//! attributes, text, and character references, with no DTDs, comments, or CDATA.
+
+#[derive(Debug)]
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    /// The text directly inside the element, unescaped.
+    pub text: String,
+}
+
+impl Element {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The child elements called `name`.
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
+
+/// The document's root element, or what made it malformed.
+pub fn parse(document: &str) -> Result<Element, String> {
+    let mut rest = document.trim_start();
+    if let Some(prolog) = rest.strip_prefix("<?xml") {
+        let end = prolog.find("?>").ok_or("unterminated prolog")?;
+        rest = prolog[end + 2..].trim_start();
+    }
+    let (root, rest) = element(rest)?;
+    if !rest.trim().is_empty() {
+        return Err(format!("text after the root element: {rest:?}"));
+    }
+    Ok(root)
+}
+
+/// The element `input` starts with, and what follows it.
+fn element(input: &str) -> Result<(Element, &str), String> {
+    let rest = input
+        .strip_prefix('<')
+        .ok_or_else(|| format!("expected an element at {input:?}"))?;
+    let (tag, mut rest) = name(rest)?;
+    let mut element = Element {
+        name: tag.to_owned(),
+        attributes: Vec::new(),
+        children: Vec::new(),
+        text: String::new(),
+    };
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("/>") {
+            return Ok((element, after));
+        }
+        if let Some(after) = rest.strip_prefix('>') {
+            rest = after;
+            break;
+        }
+        let (key, after) = name(rest)?;
+        let after = after
+            .trim_start()
+            .strip_prefix('=')
+            .ok_or_else(|| format!("expected = after {key}"))?
+            .trim_start();
+        let quote = after
+            .chars()
+            .next()
+            .filter(|quote| matches!(quote, '"' | '\''))
+            .ok_or_else(|| format!("unquoted value for {key}"))?;
+        let end = after[1..]
+            .find(quote)
+            .ok_or_else(|| format!("unterminated value for {key}"))?;
+        let value = &after[1..1 + end];
+        if value.contains('<') {
+            return Err(format!("unescaped < in {key}: {value:?}"));
+        }
+        if element.attribute(key).is_some() {
+            return Err(format!("duplicate attribute {key}"));
+        }
+        element.attributes.push((key.to_owned(), unescape(value)?));
+        rest = &after[end + 2..];
+    }
+    loop {
+        let end = rest
+            .find('<')
+            .ok_or_else(|| format!("unclosed element {}", element.name))?;
+        element.text.push_str(&unescape(&rest[..end])?);
+        rest = &rest[end..];
+        if let Some(after) = rest.strip_prefix("</") {
+            let (closed, after) = name(after)?;
+            if closed != element.name {
+                return Err(format!("</{closed}> closes <{}>", element.name));
+            }
+            let after = after
+                .trim_start()
+                .strip_prefix('>')
+                .ok_or_else(|| format!("unterminated </{closed}>"))?;
+            return Ok((element, after));
+        }
+        let (child, after) = self::element(rest)?;
+        element.children.push(child);
+        rest = after;
+    }
+}
+
+/// The element or attribute name `input` starts with, and what follows it.
+fn name(input: &str) -> Result<(&str, &str), String> {
+    let end = input
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':')))
+        .unwrap_or(input.len());
+    if end == 0 {
+        return Err(format!("expected a name at {input:?}"));
+    }
+    Ok(input.split_at(end))
+}
+
+/// `text` with its entity and character references replaced, failing on a bare `&`.
+fn unescape(text: &str) -> Result<String, String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .ok_or_else(|| format!("unescaped & in {text:?}"))?;
+        let reference = &rest[start + 1..start + end];
+        let character = match reference {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => reference
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| reference.strip_prefix('#').map(str::parse))
+                .and_then(|code| char::from_u32(code.ok()?))
+                .ok_or_else(|| format!("unknown reference &{reference}; in {text:?}"))?,
+        };
+        unescaped.push(character);
+        rest = &rest[start + end + 1..];
+    }
+    unescaped.push_str(rest);
+    Ok(unescaped)
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::{exit_code, stderr, Fixture};
+
+/// Runs linty over a fixture with `config` as its config, expecting a config error.
+fn config_error(config: &str) -> String {
+    let fixture = Fixture::new();
+    fixture.write(".lintyconfig.json", config);
+    let output = fixture.run(&["validate"]);
+    assert_eq!(exit_code(&output), 2, "{}", stderr(&output));
+    stderr(&output).replace(&format!("{}/", fixture.dir.display()), "")
+}
+
+#[test]
+fn invalid_regex_names_the_rule_and_its_position() {
+    let error = config_error(
+        r#"{"rules":[
+            {"id":"A","message":"a","regex":"a","severity":"error"},
+            {"id":"B","message":"b","regex":"b","severity":"error"},
+            {"id":"NoPrintln","message":"m","regex":"println!([abc","severity":"error"}]}"#,
+    );
+    assert!(
+        error.contains(
+            ".lintyconfig.json: Rule 'NoPrintln' (#3): invalid regex \"println!([abc\" at column 10: unclosed character class"
+        ),
+        "{error}"
+    );
+}
+
+#[test]
+fn every_invalid_rule_is_reported() {
+    let error = config_error(
+        r#"{"rules":[
+            {"id":"A","message":"a","regex":"(","severity":"error"},
+            {"id":"B","message":"b","regex":"b"}]}"#,
+    );
+    assert!(error.contains("Rule 'A' (#1): invalid regex"), "{error}");
+    assert!(
+        error.contains("Rule 'B' (#2) is missing a severity"),
+        "{error}"
+    );
+}
+
+#[test]
+fn typoed_field_suggests_the_real_one() {
+    let error = config_error(
+        r#"{"rules":[
+            {"id":"A","message":"a","regex":"a","severity":"error"},
+            {"id":"NoTodo","message":"m","regex":"TODO","severity":"error","include":["*.rs"]}]}"#,
+    );
+    assert!(
+        error.contains(
+            "Failed to parse config file at .lintyconfig.json as JSON: unknown field `include` in rules[1] (id NoTodo); did you mean `includes`?"
+        ),
+        "{error}"
+    );
+    assert!(
+        error.contains("Pass --lenient-config to ignore fields this version of linty doesn't know"),
+        "{error}"
+    );
+}
+
+#[test]
+fn typoed_field_is_ignored_with_lenient_config() {
+    let fixture = Fixture::new();
+    fixture.write(
+        ".lintyconfig.json",
+        r#"{"rules":[{"id":"NoTodo","message":"m","regex":"TODO","severity":"error","include":["*.rs"]}]}"#,
+    );
+    let output = fixture.run(&["--lenient-config", "validate"]);
+    assert_eq!(exit_code(&output), 0, "{}", stderr(&output));
+}
+
+#[test]
+fn invalid_glob_names_the_rule_and_field() {
+    let error = config_error(
+        r#"{"rules":[{"id":"Src","message":"m","regex":"x","severity":"error","excludes":["src/[a"]}]}"#,
+    );
+    assert!(
+        error.contains(
+            ".lintyconfig.json: Rule 'Src' (#1): invalid glob in excludes: error parsing glob 'src/[a': unclosed character class; missing ']'"
+        ),
+        "{error}"
+    );
+}
+
+#[test]
+fn duplicate_rule_id_is_rejected() {
+    let error = config_error(
+        r#"{"rules":[
+            {"id":"A","message":"a","regex":"a","severity":"error"},
+            {"id":"A","message":"b","regex":"b","severity":"warning"}]}"#,
+    );
+    assert!(
+        error.contains(
+            "Invalid config file at .lintyconfig.json: Rule A is defined more than once, as rules[0] and rules[1]"
+        ),
+        "{error}"
+    );
+}
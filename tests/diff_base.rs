@@ -0,0 +1,120 @@
+mod common;
+
+use common::{all_output, exit_code, stdout, Fixture};
+
+const CONFIG: &str = r#"{"rules":[{"id":"NoTodo","message":"No todos","regex":"TODO","severity":"error","excludes":["generated/**"]}]}"#;
+
+/// A repository whose `feature` branch forked from `base`, which has moved on since.
+fn forked() -> Fixture {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("old.txt", "TODO before the fork\n")
+        .write("moved.txt", "TODO before the fork\n")
+        .write("deleted.txt", "TODO before the fork\n")
+        .write("edited.txt", "TODO before the fork\nfine\n");
+    fixture.git_ok(&["add", "."]);
+    fixture.git_ok(&["commit", "-q", "-m", "start"]);
+    fixture.git_ok(&["branch", "base"]);
+    fixture.git_ok(&["checkout", "-q", "-b", "feature"]);
+
+    fixture
+        .write("added.txt", "TODO on the branch\n")
+        .write("edited.txt", "TODO before the fork\nTODO on the branch\n")
+        .write("generated/out.txt", "TODO generated\n");
+    fixture.git_ok(&["mv", "moved.txt", "renamed.txt"]);
+    fixture.git_ok(&["rm", "-q", "deleted.txt"]);
+    fixture.git_ok(&["add", "."]);
+    fixture.git_ok(&["commit", "-q", "-m", "feature"]);
+
+    // A change on base after the fork isn't the branch's.
+    fixture.git_ok(&["checkout", "-q", "base"]);
+    fixture.write("old.txt", "TODO changed on base\n");
+    fixture.git_ok(&["commit", "-q", "-am", "base moves on"]);
+    fixture.git_ok(&["checkout", "-q", "feature"]);
+    fixture
+}
+
+#[test]
+fn lints_files_changed_since_the_merge_base() {
+    let fixture = forked();
+    let output = fixture.run(&["--no-confirm", "--diff-base", "base"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(report.contains("file: added.txt, lines: 1"), "{report}");
+    assert!(report.contains("file: edited.txt, lines: 1-2"), "{report}");
+    assert!(report.contains("file: renamed.txt, lines: 1"), "{report}");
+    for unchanged in ["old.txt", "moved.txt", "deleted.txt", "generated/"] {
+        assert!(!report.contains(unchanged), "{unchanged}: {report}");
+    }
+}
+
+#[test]
+fn composes_with_changed_lines_only() {
+    let fixture = forked();
+    let output = fixture.run(&[
+        "--no-confirm",
+        "--diff-base",
+        "base",
+        "--changed-lines-only",
+    ]);
+    let report = all_output(&output);
+    assert!(report.contains("file: edited.txt, lines: 2"), "{report}");
+    assert!(report.contains("file: added.txt, lines: 1"), "{report}");
+}
+
+#[test]
+fn includes_uncommitted_changes() {
+    let fixture = forked();
+    fixture.write("old.txt", "TODO edited in the working tree\n");
+    let output = fixture.run(&["--no-confirm", "--diff-base", "base"]);
+    assert!(
+        all_output(&output).contains("file: old.txt, lines: 1"),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn only_globbed_out_changes_is_no_work() {
+    let fixture = forked();
+    fixture.git_ok(&["checkout", "-q", "-b", "only-generated", "base"]);
+    fixture.write("generated/new.txt", "TODO generated\n");
+    fixture.git_ok(&["add", "."]);
+    fixture.git_ok(&["commit", "-q", "-m", "generated"]);
+    let output = fixture.run(&["--no-confirm", "--diff-base", "base"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert!(
+        stdout(&output).contains("0 error(s) and 0 warning(s)"),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn unknown_ref_is_a_clear_error() {
+    let fixture = forked();
+    let output = fixture.run(&["--no-confirm", "--diff-base", "no-such-branch"]);
+    assert_eq!(exit_code(&output), 2, "{}", all_output(&output));
+    assert!(
+        all_output(&output).contains(
+            "--diff-base no-such-branch isn't a commit, branch, or tag in this repository"
+        ),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn unrelated_history_is_a_clear_error() {
+    let fixture = forked();
+    fixture.git_ok(&["checkout", "-q", "--orphan", "unrelated"]);
+    fixture.git_ok(&["commit", "-q", "-m", "unrelated"]);
+    let output = fixture.run(&["--no-confirm", "--diff-base", "feature"]);
+    assert_eq!(exit_code(&output), 2, "{}", all_output(&output));
+    assert!(
+        all_output(&output).contains("HEAD has no history in common with --diff-base feature"),
+        "{}",
+        all_output(&output)
+    );
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use common::{all_output, exit_code, stderr, Fixture};
+
+const EDITORCONFIG: &str =
+    "root = true\n[*]\nmax_line_length = 5\ntrim_trailing_whitespace = true\n";
+
+#[test]
+fn nested_root_editorconfig_stops_the_outer_one() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", r#"{"editorconfig":true,"rules":[]}"#)
+        .write(".editorconfig", EDITORCONFIG)
+        .write(
+            "vendor/.editorconfig",
+            "root = true\n[*.txt]\nmax_line_length = 20\n",
+        )
+        .write("a.txt", "abcdefgh \n")
+        .write("vendor/b.txt", "abcdefgh \n");
+    let output = fixture.run(&["--no-confirm"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(
+        report.contains(
+            "Found error editorconfig/max-line-length: Line is longer than 5 characters\n\
+             Error present in file: a.txt, lines: 1\n\
+             Found error editorconfig/trim-trailing-whitespace: Trailing whitespace\n\
+             Error present in file: a.txt, lines: 1\n"
+        ),
+        "{report}"
+    );
+    assert!(!report.contains("vendor/b.txt"), "{report}");
+}
+
+#[test]
+fn configured_rule_wins_over_the_generated_one_with_a_warning() {
+    let fixture = Fixture::new();
+    fixture
+        .write(
+            ".lintyconfig.json",
+            r#"{"editorconfig":true,"rules":[{"id":"editorconfig/max-line-length","message":"Too long!","regex":"(?m)^.{11,}","severity":"warning"}]}"#,
+        )
+        .write(".editorconfig", EDITORCONFIG)
+        .write("a.txt", "abcdefghijkl\nabcdefg\n");
+    let output = fixture.run(&["--no-confirm"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert!(stderr(&output).starts_with(
+        "Warning: Using the configured rule editorconfig/max-line-length instead of the one from .editorconfig\n"
+    ));
+    let report = all_output(&output);
+    // Only the configured rule's one long line is reported, not the generated rule's two.
+    assert!(
+        report.contains(
+            "Found warning editorconfig/max-line-length: Too long!\n\
+             Warning present in file: a.txt, lines: 1\n"
+        ),
+        "{report}"
+    );
+    assert!(!report.contains("longer than 5"), "{report}");
+}
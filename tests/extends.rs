@@ -0,0 +1,110 @@
+mod common;
+
+use common::{all_output, exit_code, stdout, Fixture};
+
+const BASE: &str = r#"{"rules":[
+    {"id":"NoTodo","message":"No todos","regex":"TODO","severity":"error"},
+    {"id":"NoFixme","message":"No fixmes","regex":"FIXME","severity":"error"}
+]}"#;
+
+fn list_rules(fixture: &Fixture) -> String {
+    let output = fixture.run(&["list-rules"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    stdout(&output)
+}
+
+#[test]
+fn diamond_reads_the_shared_base_once() {
+    let fixture = Fixture::git();
+    fixture
+        .write("shared/base.json", BASE)
+        // The left side softens NoTodo; the right side inherits it unchanged.
+        .write(
+            "shared/left.json",
+            r#"{"extends":["base.json"],"rules":[{"id":"NoTodo","message":"No todos","regex":"TODO","severity":"warning","override":true}]}"#,
+        )
+        .write(
+            "shared/right.json",
+            r#"{"extends":["base.json"],"rules":[{"id":"Right","message":"r","regex":"XXX","severity":"error"}]}"#,
+        )
+        .write(
+            ".lintyconfig.json",
+            r#"{"extends":["shared/left.json","shared/right.json"],"rules":[]}"#,
+        );
+    let listed = list_rules(&fixture);
+    assert!(listed.contains("NoTodo (warning)"), "{listed}");
+    assert!(!listed.contains("NoTodo (error)"), "{listed}");
+    assert_eq!(listed.matches("NoFixme (error)").count(), 1, "{listed}");
+    assert!(listed.contains("Right (error)"), "{listed}");
+}
+
+#[test]
+fn nearest_definition_wins() {
+    let fixture = Fixture::git();
+    fixture.write("base.json", BASE).write(
+        ".lintyconfig.json",
+        r#"{"extends":["base.json"],"rules":[{"id":"NoFixme","message":"No fixmes","regex":"FIXME","severity":"warning","override":true}]}"#,
+    );
+    let listed = list_rules(&fixture);
+    assert!(listed.contains("NoTodo (error)"), "{listed}");
+    assert!(listed.contains("NoFixme (warning)"), "{listed}");
+    assert_eq!(listed.matches("NoFixme").count(), 1, "{listed}");
+}
+
+#[test]
+fn cycle_is_an_error_naming_the_chain() {
+    let fixture = Fixture::git();
+    fixture
+        .write(
+            "a.json",
+            r#"{"extends":["b.json"],"rules":[{"id":"A","message":"a","regex":"a","severity":"error"}]}"#,
+        )
+        .write(
+            "b.json",
+            r#"{"extends":["a.json"],"rules":[{"id":"B","message":"b","regex":"b","severity":"error"}]}"#,
+        )
+        .write(".lintyconfig.json", r#"{"extends":["a.json"],"rules":[]}"#);
+    let output = fixture.run(&["list-rules"]);
+    assert_ne!(exit_code(&output), 0, "{}", all_output(&output));
+    let error = all_output(&output);
+    let dir = fixture.dir.display();
+    assert!(
+        error.contains(&format!(
+            "Config extends cycle: {dir}/.lintyconfig.json -> {dir}/a.json -> {dir}/b.json -> {dir}/a.json"
+        )),
+        "{error}"
+    );
+}
+
+#[test]
+fn self_extension_is_a_cycle() {
+    let fixture = Fixture::git();
+    fixture.write(
+        ".lintyconfig.json",
+        r#"{"extends":[".lintyconfig.json"],"rules":[]}"#,
+    );
+    let output = fixture.run(&["list-rules"]);
+    assert_ne!(exit_code(&output), 0, "{}", all_output(&output));
+    assert!(
+        all_output(&output).contains("Config extends cycle"),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn extended_globs_match_paths_being_linted() {
+    let fixture = Fixture::git();
+    fixture
+        .write(
+            "shared/base.json",
+            r#"{"rules":[{"id":"Src","message":"m","regex":"TODO","severity":"error","includes":["src/**"]}]}"#,
+        )
+        .write(".lintyconfig.json", r#"{"extends":["shared/base.json"],"rules":[]}"#)
+        .write("src/x.rs", "TODO\n")
+        .write("shared/src/y.rs", "TODO\n");
+    let output = fixture.run(&["--no-confirm"]);
+    let report = all_output(&output);
+    assert!(report.contains("file: src/x.rs,"), "{report}");
+    assert!(!report.contains("y.rs"), "{report}");
+}
@@ -0,0 +1,197 @@
+mod common;
+
+use common::{all_output, exit_code, stdout, Fixture};
+use std::io::Write;
+use std::process::{Output, Stdio};
+
+const CONFIG: &str =
+    r#"{"rules":[{"id":"NoTodo","message":"No todos","regex":"TODO","severity":"warning"}]}"#;
+
+/// Runs linty in `dir` of the fixture with `list` piped to its stdin.
+fn pipe(fixture: &Fixture, dir: &str, args: &[&str], list: &[u8]) -> Output {
+    let mut child = fixture
+        .linty(dir)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(list).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn large_nul_separated_list_scans_only_its_files() {
+    let fixture = Fixture::new();
+    fixture.write(".lintyconfig.json", CONFIG);
+    let mut listed = Vec::new();
+    for index in 0..3000 {
+        // Every file matches, so anything scanned beyond the list would show up.
+        let path = match index % 3 {
+            0 => format!("dir{}/file {index}.txt", index % 10),
+            1 => format!("dir{}/new\nline {index}.txt", index % 10),
+            _ => format!("unlisted/{index}.txt"),
+        };
+        fixture.write(&path, "TODO\n");
+        if !path.starts_with("unlisted/") {
+            listed.push(path);
+        }
+    }
+    let mut list = Vec::new();
+    for path in &listed {
+        list.extend_from_slice(path.as_bytes());
+        list.push(0);
+    }
+
+    // --null separates the printed paths too, which have newlines in them.
+    let output = pipe(
+        &fixture,
+        "",
+        &["--files-from", "-", "--null", "--files-with-matches"],
+        &list,
+    );
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    let printed = stdout(&output);
+    let mut found: Vec<&str> = printed.split_terminator('\0').collect();
+    found.sort_unstable();
+    let mut expected: Vec<&str> = listed.iter().map(String::as_str).collect();
+    expected.sort_unstable();
+    assert_eq!(found, expected);
+
+    let output = pipe(
+        &fixture,
+        "",
+        &["--files-from", "-", "--null", "--no-confirm"],
+        &list,
+    );
+    assert!(
+        stdout(&output).contains("(walked 2,000 files in"),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn files_from_excludes_other_inputs() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a.txt", "TODO\n");
+    for extra in [&["--pre-commit"][..], &["a.txt"][..]] {
+        let mut args = vec!["--files-from", "-"];
+        args.extend_from_slice(extra);
+        let output = pipe(&fixture, "", &args, b"a.txt\n");
+        assert_eq!(exit_code(&output), 2, "{extra:?}: {}", all_output(&output));
+        assert!(
+            all_output(&output).contains("cannot be used with"),
+            "{}",
+            all_output(&output)
+        );
+    }
+}
+
+#[test]
+fn newline_list_ignores_empty_lines_and_warns_about_missing_files() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a.txt", "TODO\n")
+        .write("b c.txt", "TODO\n")
+        .write("unlisted.txt", "TODO\n");
+    let output = pipe(
+        &fixture,
+        "",
+        &["--files-from", "-", "--files-with-matches"],
+        b"a.txt\n\n\nmissing.txt\nb c.txt\r\n",
+    );
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert_eq!(stdout(&output), "a.txt\nb c.txt\n");
+    assert!(
+        common::stderr(&output)
+            .contains("Warning: Skipping missing.txt from --files-from: not found"),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn missing_file_fails_with_strict_paths() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a.txt", "TODO\n");
+    let output = pipe(
+        &fixture,
+        "",
+        &["--files-from", "-", "--strict-paths", "--no-confirm"],
+        b"a.txt\nmissing.txt\n",
+    );
+    assert_ne!(exit_code(&output), 0, "{}", all_output(&output));
+    assert!(
+        all_output(&output).contains("missing.txt"),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn entries_are_relative_to_the_current_directory() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("src/a.txt", "TODO\n")
+        .write("docs/b.txt", "TODO\n");
+    let output = pipe(
+        &fixture,
+        "src",
+        &["--files-from", "-", "--files-with-matches"],
+        b"a.txt\n../docs/b.txt\n",
+    );
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert_eq!(stdout(&output), "../docs/b.txt\na.txt\n");
+}
+
+#[test]
+fn list_file_is_read_like_stdin() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a.txt", "TODO\n")
+        .write("b.txt", "TODO\n")
+        .write("list.txt", "b.txt\n");
+    let output = fixture.run(&["--files-from", "list.txt", "--files-with-matches"]);
+    assert_eq!(stdout(&output), "b.txt\n");
+}
+
+#[test]
+fn git_ls_files_pipes_through() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("docs/notes.md", "TODO\n")
+        .write("src/main.rs", "TODO\n")
+        .write("untracked.md", "TODO\n");
+    fixture.git_ok(&["add", ".lintyconfig.json", "docs/notes.md", "src/main.rs"]);
+    let listed = std::process::Command::new("git")
+        .current_dir(&fixture.dir)
+        .args(["ls-files", "-z", "--", "*.md", "*.rs"])
+        .output()
+        .unwrap();
+    let output = pipe(
+        &fixture,
+        "",
+        &["--files-from", "-", "--null", "--files-with-matches"],
+        &listed.stdout,
+    );
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert_eq!(stdout(&output), "docs/notes.md\0src/main.rs\0");
+}
+
+#[test]
+fn unreadable_list_is_a_usage_error() {
+    let fixture = Fixture::new();
+    fixture.write(".lintyconfig.json", CONFIG);
+    let output = fixture.run(&["--files-from", "no-such-list.txt"]);
+    assert_eq!(exit_code(&output), 2, "{}", all_output(&output));
+}
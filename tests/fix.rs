@@ -0,0 +1,102 @@
+mod common;
+
+use common::{all_output, exit_code, Fixture};
+
+const CONFIG: &str = r#"{"rules":[{"id":"NoUnwrap","message":"No unwraps","regex":"(\\w+)\\.unwrap\\(\\)","severity":"error","fix":"$1.expect(\"TODO\")"}]}"#;
+
+#[test]
+fn fix_rewrites_matches() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a.rs", "let x = y.unwrap();\n");
+    let output = fixture.run(&["--no-confirm", "--fix"]);
+    assert!(
+        all_output(&output).contains("Fixed 1 match(es) in a.rs"),
+        "{}",
+        all_output(&output)
+    );
+    assert_eq!(
+        std::fs::read_to_string(fixture.path("a.rs")).unwrap(),
+        "let x = y.expect(\"TODO\");\n"
+    );
+}
+
+/// Pre-commit matches are positioned in the staged contents, which the working tree file
+/// the fixes would be written to can differ from.
+#[test]
+fn fix_refuses_pre_commit() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a.rs", "\n\n\nlet x = y.unwrap();\n");
+    fixture.git_ok(&["add", "a.rs"]);
+    fixture.write("a.rs", "fine\n");
+    for flag in ["--fix", "--fix-dry-run"] {
+        let output = fixture.run(&["--no-confirm", "--pre-commit", flag]);
+        assert_eq!(exit_code(&output), 2, "{flag}: {}", all_output(&output));
+        assert!(
+            all_output(&output).contains("cannot be used with"),
+            "{}",
+            all_output(&output)
+        );
+        assert!(!all_output(&output).contains("panicked"));
+    }
+    assert_eq!(
+        std::fs::read_to_string(fixture.path("a.rs")).unwrap(),
+        "fine\n"
+    );
+}
+
+#[test]
+fn fix_leaves_baselined_matches() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a.rs", "old.unwrap();\n");
+    let output = fixture.run(&["baseline"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    fixture.write("a.rs", "old.unwrap();\nnew.unwrap();\n");
+    let output = fixture.run(&["--no-confirm", "--fix", "--baseline", ".lintybaseline.json"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert_eq!(
+        std::fs::read_to_string(fixture.path("a.rs")).unwrap(),
+        "old.unwrap();\nnew.expect(\"TODO\");\n"
+    );
+}
+
+#[test]
+fn fix_leaves_lines_outside_the_diff() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a.rs", "old.unwrap();\n");
+    fixture.git_ok(&["add", "."]);
+    fixture.git_ok(&["commit", "-q", "-m", "old"]);
+    fixture.write("a.rs", "old.unwrap();\nnew.unwrap();\n");
+    let output = fixture.run(&[
+        "--no-confirm",
+        "--fix",
+        "--changed-lines-only",
+        "--diff-base",
+        "HEAD",
+    ]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert_eq!(
+        std::fs::read_to_string(fixture.path("a.rs")).unwrap(),
+        "old.unwrap();\nnew.expect(\"TODO\");\n"
+    );
+
+    let output = fixture.run(&[
+        "--no-confirm",
+        "--fix-dry-run",
+        "--changed-lines-only",
+        "--diff-base",
+        "HEAD",
+    ]);
+    assert!(
+        !all_output(&output).contains("-old.unwrap();"),
+        "{}",
+        all_output(&output)
+    );
+}
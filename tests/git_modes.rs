@@ -0,0 +1,264 @@
+mod common;
+
+use common::{all_output, exit_code, stderr, stdout, Fixture};
+
+const CONFIG: &str =
+    r#"{"rules":[{"id":"NoTodo","message":"No todos","regex":"TODO","severity":"error"}]}"#;
+
+/// A fixture outside any git repository, even when the temp directory is inside one.
+fn outside_git() -> Fixture {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a.txt", "TODO\n");
+    fixture
+}
+
+fn run_outside_git(fixture: &Fixture, args: &[&str]) -> std::process::Output {
+    fixture
+        .linty("")
+        .env("GIT_CEILING_DIRECTORIES", fixture.dir.parent().unwrap())
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn pre_commit_outside_a_repository_fails_with_one_line() {
+    let fixture = outside_git();
+    let output = run_outside_git(&fixture, &["--no-confirm", "--pre-commit"]);
+    assert_eq!(exit_code(&output), 3, "{}", all_output(&output));
+    assert_eq!(
+        stderr(&output),
+        "--pre-commit needs a git repository, and the current directory isn't in one\n"
+    );
+}
+
+#[test]
+fn diff_base_outside_a_repository_fails_with_one_line() {
+    let fixture = outside_git();
+    let output = run_outside_git(&fixture, &["--no-confirm", "--diff-base", "main"]);
+    assert_eq!(exit_code(&output), 3, "{}", all_output(&output));
+    assert_eq!(
+        stderr(&output),
+        "--diff-base needs a git repository, and the current directory isn't in one\n"
+    );
+}
+
+#[test]
+fn missing_git_is_told_apart() {
+    let fixture = outside_git();
+    let output = fixture
+        .linty("")
+        .env("PATH", "")
+        .args(["--no-confirm", "--pre-commit"])
+        .output()
+        .unwrap();
+    assert_eq!(exit_code(&output), 3, "{}", all_output(&output));
+    assert_eq!(
+        stderr(&output),
+        "--pre-commit needs git, which isn't installed or isn't on PATH\n"
+    );
+}
+
+#[test]
+fn plain_run_outside_a_repository_needs_no_git() {
+    let fixture = outside_git();
+    let output = run_outside_git(&fixture, &["--no-confirm"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert!(
+        all_output(&output).contains("Error present in file: a.txt, lines: 1"),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn pre_commit_from_a_subdirectory_checks_staged_files_across_the_repository() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("src/main.rs", "TODO\n")
+        .write("docs/notes.md", "TODO\n")
+        .write("unstaged.txt", "TODO\n");
+    fixture.git_ok(&["add", ".lintyconfig.json", "src/main.rs", "docs/notes.md"]);
+    let output = fixture.run_in("src", &["--no-confirm", "--pre-commit"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(report.contains("file: main.rs,"), "{report}");
+    assert!(report.contains("file: ../docs/notes.md,"), "{report}");
+    assert!(!report.contains("unstaged.txt"), "{report}");
+}
+
+const YAML_CONFIG: &str = r#"{"rules":[{"id":"NoTabs","message":"No tabs in YAML","regex":"\t","severity":"error","includes":["*.yml"]}]}"#;
+
+#[test]
+fn staged_dotfile_is_linted_without_hidden() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", YAML_CONFIG)
+        .write(".github/workflows/ci.yml", "jobs:\n\tbuild: {}\n");
+    fixture.git_ok(&["add", "."]);
+
+    let output = fixture.run(&["--no-confirm", "--pre-commit"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert!(
+        all_output(&output).contains("Error present in file: .github/workflows/ci.yml, lines: 2"),
+        "{}",
+        all_output(&output)
+    );
+
+    // A walk leaves the hidden directory out.
+    let output = fixture.run(&["--no-confirm"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+}
+
+#[test]
+fn force_added_ignored_file_is_linted() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", YAML_CONFIG)
+        .write(".gitignore", "local/\n")
+        .write("local/settings.yml", "a:\n\tb: 1\n");
+    fixture.git_ok(&["add", "-f", "local/settings.yml"]);
+    let output = fixture.run(&["--no-confirm", "--pre-commit"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert!(
+        all_output(&output).contains("file: local/settings.yml, lines: 2"),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn dotfile_changed_since_a_base_is_linted() {
+    let fixture = Fixture::git();
+    fixture.write(".lintyconfig.json", YAML_CONFIG);
+    fixture.git_ok(&["add", "."]);
+    fixture.git_ok(&["commit", "-q", "-m", "start"]);
+    fixture.git_ok(&["branch", "base"]);
+    fixture.write(".github/workflows/ci.yml", "jobs:\n\tbuild: {}\n");
+    fixture.git_ok(&["add", "."]);
+    fixture.git_ok(&["commit", "-q", "-m", "ci"]);
+    let output = fixture.run(&["--no-confirm", "--diff-base", "base"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert!(
+        all_output(&output).contains("file: .github/workflows/ci.yml, lines: 2"),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn hooks_outside_a_repository_fail_with_one_line() {
+    let fixture = outside_git();
+    for command in ["install-hooks", "uninstall-hooks"] {
+        let output = run_outside_git(&fixture, &[command]);
+        assert_eq!(exit_code(&output), 3, "{}", all_output(&output));
+        assert_eq!(
+            stderr(&output),
+            format!("{command} needs a git repository, and the current directory isn't in one\n")
+        );
+    }
+}
+
+#[test]
+fn blame_outside_a_repository_fails_with_one_line() {
+    let fixture = outside_git();
+    let output = run_outside_git(&fixture, &["--no-confirm", "--blame"]);
+    assert_eq!(exit_code(&output), 3, "{}", all_output(&output));
+    assert!(
+        stderr(&output)
+            .ends_with("--blame needs a git repository, and the current directory isn't in one\n"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn hooks_from_a_subdirectory_go_in_the_repository() {
+    let fixture = Fixture::git();
+    fixture.write("src/lib.rs", "");
+    let output = fixture.run_in("src", &["install-hooks"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert!(fixture.path(".git/hooks/pre-commit").is_file());
+    assert!(!fixture.path("src/.git").exists());
+    assert!(
+        stdout(&output).starts_with("Wrote ../.git/hooks/pre-commit:"),
+        "{}",
+        stdout(&output)
+    );
+
+    let output = fixture.run_in("src", &["uninstall-hooks"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert!(!fixture.path(".git/hooks/pre-commit").exists());
+}
+
+#[test]
+fn blame_from_a_subdirectory_annotates_lines() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("src/a.txt", "TODO\n");
+    fixture.git_ok(&["add", "."]);
+    fixture.git_ok(&["commit", "-q", "-m", "start"]);
+    let output = fixture.run_in("src", &["--no-confirm", "--blame", "--format", "json"]);
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    let violation = &report["violations"][0];
+    assert_eq!(violation["file"], "a.txt");
+    assert_eq!(violation["blame"]["1"]["author"], "Test", "{violation}");
+}
+
+#[test]
+fn pre_commit_snippets_from_a_subdirectory_show_the_staged_lines() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("src/a.txt", "staged TODO\n");
+    fixture.git_ok(&["add", "."]);
+    fixture.write("src/a.txt", "on disk TODO\n");
+    let output = fixture.run_in(
+        "src",
+        &["--no-confirm", "--pre-commit", "--format", "pretty"],
+    );
+    let output = all_output(&output);
+    assert!(output.contains("staged TODO"), "{output}");
+    assert!(!output.contains("on disk"), "{output}");
+}
+
+#[test]
+fn gerrit_paths_from_a_subdirectory_start_at_the_repository_root() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("src/a.txt", "TODO\n");
+    let output = fixture.run_in("src", &["--no-confirm", "--format", "gerrit"]);
+    let review: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert!(review["robot_comments"]["src/a.txt"].is_array(), "{review}");
+}
+
+#[test]
+fn json_report_outside_a_repository_has_no_git_state() {
+    let fixture = outside_git();
+    let output = run_outside_git(&fixture, &["--no-confirm", "--format", "json"]);
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert!(report["meta"]["commit"].is_null(), "{report}");
+    assert!(report["meta"]["linty_version"].is_string(), "{report}");
+    assert!(!stderr(&output).contains("Warning"), "{}", stderr(&output));
+}
+
+#[test]
+fn json_report_from_a_subdirectory_has_the_git_state() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("src/a.txt", "TODO\n");
+    fixture.git_ok(&["checkout", "-q", "-b", "main"]);
+    fixture.git_ok(&["add", "."]);
+    fixture.git_ok(&["commit", "-q", "-m", "start"]);
+    let output = fixture.run_in("src", &["--no-confirm", "--format", "json"]);
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["meta"]["branch"], "main", "{report}");
+    assert_eq!(report["meta"]["commit"].as_str().unwrap().len(), 40);
+    assert_eq!(report["meta"]["dirty"], false);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{all_output, exit_code, Fixture};
+
+/// The global config's path, under the fixture's `XDG_CONFIG_HOME`.
+const GLOBAL: &str = ".no-global-config/linty/config.json";
+
+/// A project rule for todos, and a global config with its own todo rule and a fixme one.
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    fixture
+        .write(
+            ".lintyconfig.json",
+            r#"{"rules":[{"id":"NoTodo","message":"Project todos","regex":"TODO","severity":"warning"}]}"#,
+        )
+        .write(
+            GLOBAL,
+            r#"{"rules":[
+                {"id":"NoTodo","message":"Global todos","regex":"TODO","severity":"error"},
+                {"id":"NoFixme","message":"Global fixmes","regex":"FIXME","severity":"error"}
+            ]}"#,
+        )
+        .write("a.txt", "TODO\nFIXME\n");
+    fixture
+}
+
+#[test]
+fn project_rule_replaces_the_global_rule_with_its_id() {
+    let fixture = fixture();
+    let output = fixture.run(&["--no-confirm", "-v"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(
+        report.contains(": adds NoFixme; the project's rules replace NoTodo\n"),
+        "{report}"
+    );
+    assert!(
+        report.contains("Found warning NoTodo: Project todos\n"),
+        "{report}"
+    );
+    assert!(
+        report.contains("Found error NoFixme: Global fixmes\n"),
+        "{report}"
+    );
+    assert!(!report.contains("Global todos"), "{report}");
+}
+
+#[test]
+fn no_global_config_leaves_the_global_rules_out() {
+    let fixture = fixture();
+    let output = fixture.run(&["--no-confirm", "--no-global-config"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(!report.contains("NoFixme"), "{report}");
+    assert!(
+        report.contains("0 error(s) and 1 warning(s) from 1 rule(s)"),
+        "{report}"
+    );
+}
+
+#[test]
+fn several_global_configs_are_a_config_error() {
+    let fixture = fixture();
+    fixture.write(".no-global-config/linty/config.toml", "rules = []\n");
+    let output = fixture.run(&["--no-confirm"]);
+    assert_eq!(exit_code(&output), 2, "{}", all_output(&output));
+    assert!(all_output(&output).contains("Remove all but one, or pass --no-global-config\n"));
+
+    let output = fixture.run(&["--no-confirm", "--no-global-config"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::{all_output, exit_code, stdout, Fixture};
+
+fn round_trip(format: &str, path: &str) {
+    let fixture = Fixture::new();
+    let output = fixture.run(&["init", "--format", format]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert!(
+        stdout(&output).contains(&format!("Initialized example config at {path}")),
+        "{}",
+        all_output(&output)
+    );
+
+    let config = linty::read_config(&fixture.path(path), None).unwrap();
+    let ids: Vec<&str> = config.rules.iter().map(|rule| rule.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        ["WarnOnTodos", "NoMergeConflictMarkers", "NoPrivateKeys"]
+    );
+    linty::Linter::from_config(config).unwrap();
+
+    // The next run reads the config it wrote.
+    let output = fixture.run(&["list-rules"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert!(
+        stdout(&output).contains("WarnOnTodos (warning)"),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn json_config_reads_back() {
+    round_trip("json", ".lintyconfig.json");
+}
+
+#[test]
+fn toml_config_reads_back() {
+    round_trip("toml", ".lintyconfig.toml");
+}
+
+#[test]
+fn yaml_config_reads_back() {
+    round_trip("yaml", ".lintyconfig.yaml");
+}
+
+#[test]
+fn detected_project_excludes_read_back() {
+    let fixture = Fixture::new();
+    fixture.write("Cargo.toml", "[package]\nname = \"demo\"\n");
+    let output = fixture.run(&["init"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    let config = linty::read_config(&fixture.path(".lintyconfig.json"), None).unwrap();
+    assert!(
+        config.excludes.is_some_and(|excludes| !excludes.is_empty()),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn existing_config_is_kept_without_force() {
+    let fixture = Fixture::new();
+    fixture.write(".lintyconfig.json", "{\"rules\":[]}\n");
+    let output = fixture.run(&["init"]);
+    assert_eq!(exit_code(&output), 2, "{}", all_output(&output));
+    assert!(
+        all_output(&output)
+            .contains("A config already exists at .lintyconfig.json; pass --force to overwrite it"),
+        "{}",
+        all_output(&output)
+    );
+    assert_eq!(
+        std::fs::read_to_string(fixture.path(".lintyconfig.json")).unwrap(),
+        "{\"rules\":[]}\n"
+    );
+
+    let output = fixture.run(&["init", "--force"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    linty::read_config(&fixture.path(".lintyconfig.json"), None).unwrap();
+}
+
+#[test]
+fn config_in_another_format_is_not_doubled() {
+    let fixture = Fixture::new();
+    fixture.write(".lintyconfig.json", "{\"rules\":[]}\n");
+    let output = fixture.run(&["init", "--format", "toml", "--force"]);
+    assert_eq!(exit_code(&output), 2, "{}", all_output(&output));
+    assert!(!fixture.path(".lintyconfig.toml").exists());
+}
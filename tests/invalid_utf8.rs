@@ -0,0 +1,117 @@
+mod common;
+
+use common::{all_output, exit_code, stderr, stdout, Fixture};
+
+const CONFIG: &str =
+    r#"{"rules":[{"id":"NoTodo","message":"No todos","regex":"TODO","severity":"error"}]}"#;
+
+/// A Latin-1 text file with todos after its accented characters, a binary blob with a
+/// todo inside, and a clean UTF-8 file.
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("latin1.txt", b"caf\xe9\nna\xefve TODO\n\xff\xfe\nTODO\n")
+        .write("blob.bin", b"\x00\x01\x02TODO\x00\xff\n")
+        .write("utf8.txt", "café\n");
+    fixture
+}
+
+#[test]
+fn skip_leaves_latin1_files_out_quietly() {
+    let fixture = fixture();
+    let output = fixture.run(&["--no-confirm", "--invalid-utf8", "skip"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert_eq!(stderr(&output), "");
+    assert!(
+        stdout(&output).contains("walked 3 files and skipped 2 in"),
+        "{}",
+        stdout(&output)
+    );
+
+    let output = fixture.run(&["--no-confirm", "-v"]);
+    let report = all_output(&output);
+    assert!(
+        report.contains("Skipping ./latin1.txt: not valid UTF-8\n"),
+        "{report}"
+    );
+    assert!(
+        report.contains("Skipping ./blob.bin: looks binary\n"),
+        "{report}"
+    );
+}
+
+#[test]
+fn lossy_keeps_lines_and_columns_after_invalid_bytes() {
+    let fixture = fixture();
+    let output = fixture.run(&[
+        "--no-confirm",
+        "--invalid-utf8",
+        "lossy",
+        "--format",
+        "json",
+    ]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let violations = report["violations"].as_array().unwrap();
+    assert_eq!(violations.len(), 1, "{report}");
+    assert_eq!(violations[0]["file"], "latin1.txt");
+    // Each invalid byte is one replacement character, so columns count it once.
+    let positions: Vec<(u64, u64)> = violations[0]["matches"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| (m["line"].as_u64().unwrap(), m["column"].as_u64().unwrap()))
+        .collect();
+    assert_eq!(positions, [(2, 7), (4, 1)]);
+    // The binary blob is still skipped for looking binary.
+    assert_eq!(report["summary"]["files_skipped"], 1, "{report}");
+}
+
+#[test]
+fn lossy_checks_binary_files_that_arent_skipped() {
+    let fixture = fixture();
+    let output = fixture.run(&[
+        "--no-confirm",
+        "--no-skip-binary",
+        "--invalid-utf8",
+        "lossy",
+    ]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(
+        report.contains("Error present in file: blob.bin, lines: 1\n"),
+        "{report}"
+    );
+    assert!(
+        report.contains("Error present in file: latin1.txt, lines: 2, 4\n"),
+        "{report}"
+    );
+}
+
+#[test]
+fn error_reports_each_file_as_a_read_error() {
+    let fixture = fixture();
+    let output = fixture.run(&[
+        "--no-confirm",
+        "--no-skip-binary",
+        "--invalid-utf8",
+        "error",
+    ]);
+    let errors = stderr(&output);
+    for file in ["./latin1.txt", "./blob.bin"] {
+        assert!(
+            errors.contains(&format!(
+                "Error: Failed to read {file}\nReason: invalid utf-8"
+            )),
+            "{errors}"
+        );
+    }
+    // Neither file is checked, and the clean one still is.
+    assert!(!errors.contains("Error present"), "{errors}");
+    assert!(
+        stdout(&output).contains("walked 3 files in"),
+        "{}",
+        stdout(&output)
+    );
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use common::{all_output, exit_code, Fixture};
+use serde_json::{json, Value};
+
+const CONFIG: &str = r#"{"rules":[
+    {"id":"NoUnwrap","message":"Don't unwrap","description":"Handle the error instead.","regex":"(\\w+)\\.unwrap\\(\\)","fix":"$1.expect(\"TODO\")","severity":"error","url":"https://example.com/unwrap"},
+    {"id":"NoTodo","message":"No todos","regex":"TODO","severity":"warning"}
+]}"#;
+
+/// Replaces the value at `pointer` with `replacement` once `check` accepts it.
+fn normalize(
+    report: &mut Value,
+    pointer: &str,
+    check: impl Fn(&Value) -> bool,
+    replacement: Value,
+) {
+    let value = report.pointer_mut(pointer).unwrap();
+    assert!(check(value), "{pointer}: {value}");
+    *value = replacement;
+}
+
+#[test]
+fn report_has_the_documented_shape() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("src/main.rs", "fn main() {\n    let x = y.unwrap();\n}\n")
+        .write("notes.txt", "TODO\n");
+    // Outside a git work tree, so the git state is null.
+    let output = fixture
+        .linty("")
+        .env("GIT_CEILING_DIRECTORIES", fixture.dir.parent().unwrap())
+        .args(["--no-confirm", "--format", "json"])
+        .output()
+        .unwrap();
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+
+    // Only the hash of the config, the time, and how long phases took vary; they're
+    // checked for their form and then left out of the comparison.
+    let mut report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    normalize(
+        &mut report,
+        "/meta/config_hash",
+        |hash| {
+            hash.as_str()
+                .is_some_and(|hash| hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+        },
+        json!("HASH"),
+    );
+    normalize(
+        &mut report,
+        "/meta/timestamp",
+        |timestamp| {
+            timestamp
+                .as_str()
+                .is_some_and(|timestamp| timestamp.ends_with('Z'))
+        },
+        json!("TIMESTAMP"),
+    );
+    for phase in ["elapsed_ms", "walk_ms", "match_ms"] {
+        normalize(
+            &mut report,
+            &format!("/summary/{phase}"),
+            Value::is_u64,
+            json!(0),
+        );
+    }
+
+    let expected = json!({
+        "schema_version": 1,
+        "meta": {
+            "linty_version": env!("CARGO_PKG_VERSION"),
+            "config_hash": "HASH",
+            "timestamp": "TIMESTAMP",
+            "commit": null,
+            "branch": null,
+            "dirty": null
+        },
+        "violations": [
+            {
+                "rule_id": "NoTodo",
+                "message": "No todos",
+                "severity": "warning",
+                "file": "notes.txt",
+                "lines": [1],
+                "matches": [
+                    {"line": 1, "column": 1, "end_line": 1, "end_column": 5, "text": "TODO"}
+                ],
+                "fingerprints": ["44b0d2fa9c97719dc8dd"]
+            },
+            {
+                "rule_id": "NoUnwrap",
+                "message": "Don't unwrap",
+                "description": "Handle the error instead.",
+                "url": "https://example.com/unwrap",
+                "severity": "error",
+                "file": "src/main.rs",
+                "lines": [2],
+                "matches": [
+                    {"line": 2, "column": 13, "end_line": 2, "end_column": 23, "text": "y.unwrap()"}
+                ],
+                "fingerprints": ["6036ff687aea29598fa1"],
+                "suggestions": [
+                    {
+                        "line": 2,
+                        "column": 13,
+                        "end_line": 2,
+                        "end_column": 23,
+                        "replacement": "y.expect(\"TODO\")"
+                    }
+                ]
+            }
+        ],
+        "summary": {
+            "warnings": 1,
+            "errors": 1,
+            "files_walked": 2,
+            "files_scanned": 2,
+            "files_skipped": 0,
+            "rules": 2,
+            "elapsed_ms": 0,
+            "walk_ms": 0,
+            "match_ms": 0
+        },
+        "outcome": {
+            "exit_code": 1,
+            "reason": "violations",
+            "errors": 1,
+            "warnings": 1,
+            "failed_rules": ["NoUnwrap"]
+        }
+    });
+    assert_eq!(
+        report,
+        expected,
+        "{}",
+        serde_json::to_string_pretty(&report).unwrap()
+    );
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::{all_output, exit_code, stdout, Fixture};
+
+const CONFIG: &str = r#"{"rules":[
+    {"id":"NoTodo","message":"No todos","regex":"TODO","severity":"error"},
+    {"id":"NoBlock","message":"No blocks","regex":"BEGIN[^E]*END","severity":"error"}
+]}"#;
+
+#[test]
+fn report_tells_runs_of_lines_from_spanning_matches() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("runs.txt", "TODO\nTODO\n")
+        .write("span.txt", "BEGIN\nEND\n");
+    let report = all_output(&fixture.run(&["--no-confirm"]));
+    assert!(report.contains("file: runs.txt, lines: 1-2"), "{report}");
+    assert!(report.contains("file: span.txt, lines: [1-2]"), "{report}");
+}
+
+#[test]
+fn line_matched_twice_is_listed_once() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a.txt", "TODO\nfine\nTODO TODO\n");
+    let report = all_output(&fixture.run(&["--no-confirm"]));
+    assert!(report.contains("file: a.txt, lines: 1, 3\n"), "{report}");
+
+    let output = fixture.run(&["--no-confirm", "--format", "json"]);
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    let lines = &json["violations"][0]["lines"];
+    assert_eq!(lines, &serde_json::json!([1, 3]), "{json}");
+}
+
+/// Ten todos, on every other line.
+fn many_todos() -> String {
+    "TODO\nok\n".repeat(10)
+}
+
+#[test]
+fn truncated_list_summarizes_the_rest_and_counts_once() {
+    let fixture = Fixture::new();
+    fixture
+        .write(
+            ".lintyconfig.json",
+            r#"{"rules":[{"id":"NoTodo","message":"No todos","regex":"TODO","severity":"error","max_reported":3}]}"#,
+        )
+        .write("a.txt", many_todos());
+    let output = fixture.run(&["--no-confirm", "--explain-exit-code"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(
+        report.contains("Error present in file: a.txt, lines: 1, 3, 5, … and 7 more\n"),
+        "{report}"
+    );
+    assert!(report.contains("1 error(s) and 0 warning(s)"), "{report}");
+    assert!(
+        report.contains("linty: exit=1 reason=violations errors=1 warnings=0 failed_rules=NoTodo"),
+        "{report}"
+    );
+
+    // Structured output keeps every match.
+    let output = fixture.run(&["--no-confirm", "--format", "json"]);
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(json["violations"].as_array().unwrap().len(), 1, "{json}");
+    assert_eq!(
+        json["violations"][0]["matches"].as_array().unwrap().len(),
+        10
+    );
+    assert_eq!(json["summary"]["errors"], 1, "{json}");
+}
+
+#[test]
+fn flag_truncates_rules_without_their_own_limit() {
+    let fixture = Fixture::new();
+    fixture
+        .write(
+            ".lintyconfig.json",
+            r#"{"rules":[{"id":"NoTodo","message":"No todos","regex":"TODO","severity":"warning"}]}"#,
+        )
+        .write("a.txt", many_todos());
+    let output = fixture.run(&["--no-confirm", "--max-matches-per-file", "2"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(
+        report.contains("Warning present in file: a.txt, lines: 1, 3, … and 8 more\n"),
+        "{report}"
+    );
+    assert!(report.contains("0 error(s) and 1 warning(s)"), "{report}");
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use common::{all_output, exit_code, stdout, Fixture};
+
+const CONFIG: &str =
+    r#"{"rules":[{"id":"Todo","message":"No todos","regex":"TODO","severity":"warning"}]}"#;
+
+/// A fixture with a large `build/` tree beside two source files.
+fn with_build_tree() -> Fixture {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("src/a.txt", "TODO\n")
+        .write("src/b.txt", "TODO\n");
+    for index in 0..200 {
+        fixture.write(&format!("build/out/{index}.txt"), "TODO\n");
+    }
+    fixture
+}
+
+#[test]
+fn ignored_directory_is_never_walked() {
+    let fixture = with_build_tree();
+    fixture.write(".lintyignore", "build/\n");
+    let output = fixture.run(&["--no-confirm"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    let report = stdout(&output);
+    assert!(report.contains("(walked 2 files in"), "{report}");
+    assert!(!report.contains("build/"), "{report}");
+}
+
+#[test]
+fn ignored_directory_is_pruned_with_no_ignore() {
+    let fixture = with_build_tree();
+    fixture.write(".lintyignore", "build/\n");
+    let output = fixture.run(&["--no-confirm", "--no-ignore"]);
+    assert!(
+        stdout(&output).contains("(walked 2 files in"),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn config_excludes_are_walked_and_skipped_by_contrast() {
+    let fixture = with_build_tree();
+    fixture.write(
+        ".lintyconfig.json",
+        r#"{"excludes":["build/**"],"rules":[{"id":"Todo","message":"No todos","regex":"TODO","severity":"warning"}]}"#,
+    );
+    let output = fixture.run(&["--no-confirm"]);
+    assert!(
+        stdout(&output).contains("(walked 202 files and skipped 200 in"),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn negated_pattern_keeps_a_file_in() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write(".lintyignore", "*.gen\n!keep.gen\n")
+        .write("src/drop.gen", "TODO\n")
+        .write("src/keep.gen", "TODO\n");
+    let output = fixture.run(&["--no-confirm"]);
+    let report = all_output(&output);
+    assert!(report.contains("file: src/keep.gen,"), "{report}");
+    assert!(!report.contains("drop.gen"), "{report}");
+    assert!(report.contains("(walked 1 files in"), "{report}");
+}
+
+#[test]
+fn negated_directory_contents_are_walked() {
+    let fixture = with_build_tree();
+    fixture
+        .write(".lintyignore", "build/*\n!build/keep/\n")
+        .write("build/keep/kept.txt", "TODO\n");
+    let output = fixture.run(&["--no-confirm"]);
+    let report = all_output(&output);
+    assert!(report.contains("file: build/keep/kept.txt,"), "{report}");
+    assert!(report.contains("(walked 3 files in"), "{report}");
+}
+
+/// A fixture whose `.lintyignore` ignores `vendor/` and `*.gen.txt`, beside a file that
+/// isn't ignored.
+fn with_ignored_files(fixture: Fixture) -> Fixture {
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write(".lintyignore", "vendor/\n*.gen.txt\n")
+        .write("vendor/lib.txt", "TODO\n")
+        .write("src/out.gen.txt", "TODO\n")
+        .write("src/a.txt", "TODO\n");
+    fixture
+}
+
+#[test]
+fn named_files_are_ignored() {
+    let fixture = with_ignored_files(Fixture::new());
+    let output = fixture.run(&[
+        "--no-confirm",
+        "-v",
+        "vendor/lib.txt",
+        "src/out.gen.txt",
+        "src/a.txt",
+    ]);
+    let report = all_output(&output);
+    assert!(report.contains("src/a.txt"), "{report}");
+    assert!(!report.contains("Error present in file: vendor/lib.txt"));
+    assert!(
+        report.contains(&format!(
+            "Skipping ./vendor/lib.txt: ignored by {}",
+            fixture.path(".lintyignore").display()
+        )),
+        "{report}"
+    );
+    assert!(
+        report.contains("Skipping ./src/out.gen.txt: ignored by"),
+        "{report}"
+    );
+    assert!(stdout(&output).contains("skipped 2"), "{report}");
+}
+
+#[test]
+fn staged_files_are_ignored() {
+    let fixture = with_ignored_files(Fixture::git());
+    fixture.git_ok(&["add", "."]);
+    let output = fixture.run(&["--no-confirm", "--pre-commit"]);
+    let report = all_output(&output);
+    assert!(report.contains("src/a.txt"), "{report}");
+    assert!(!report.contains("vendor/lib.txt"), "{report}");
+    assert!(!report.contains("out.gen.txt"), "{report}");
+}
+
+#[test]
+fn files_from_a_list_are_ignored() {
+    let fixture = with_ignored_files(Fixture::new());
+    fixture.write("list.txt", "vendor/lib.txt\nsrc/out.gen.txt\nsrc/a.txt\n");
+    let output = fixture.run(&["--no-confirm", "--files-from", "list.txt"]);
+    let report = all_output(&output);
+    assert!(report.contains("src/a.txt"), "{report}");
+    assert!(!report.contains("vendor/lib.txt"), "{report}");
+    assert!(!report.contains("out.gen.txt"), "{report}");
+}
+
+#[test]
+fn named_files_follow_nested_ignore_files_like_the_walk() {
+    let fixture = with_ignored_files(Fixture::new());
+    // A nearer file takes a pattern back, but not for a file in an ignored directory.
+    fixture
+        .write("src/.lintyignore", "!keep.gen.txt\n")
+        .write("src/keep.gen.txt", "TODO\n")
+        .write("vendor/.lintyignore", "!lib.txt\n");
+    let named = fixture.run(&[
+        "--no-confirm",
+        "src/keep.gen.txt",
+        "src/out.gen.txt",
+        "vendor/lib.txt",
+    ]);
+    let walked = fixture.run(&["--no-confirm"]);
+    for output in [&named, &walked] {
+        let report = all_output(output);
+        assert!(report.contains("src/keep.gen.txt"), "{report}");
+        assert!(!report.contains("out.gen.txt"), "{report}");
+        assert!(!report.contains("vendor/lib.txt"), "{report}");
+    }
+}
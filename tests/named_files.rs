@@ -0,0 +1,177 @@
+mod common;
+
+use common::{all_output, exit_code, Fixture};
+
+const LIB_CONFIG: &str = r#"{"rules":[{"id":"Lib","message":"No todos in lib","regex":"TODO","severity":"error","includes":["lib/**"]}]}"#;
+
+#[test]
+fn named_file_outside_the_current_directory_matches_the_configs_globs() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", LIB_CONFIG)
+        .write("lib/src/x.rs", "TODO\n")
+        .write("src/main.rs", "");
+    let output = fixture.run_in("src", &["--no-confirm", "../lib/src/x.rs"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert!(
+        all_output(&output).contains("Error present in file: ../lib/src/x.rs, lines: 1"),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn absolute_named_file_under_the_config_is_reported_relative_to_it() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", LIB_CONFIG)
+        .write("lib/src/x.rs", "TODO\n")
+        .write("src/main.rs", "");
+    let file = fixture.path("lib/src/x.rs");
+    let output = fixture.run_in("src", &["--no-confirm", file.to_str().unwrap()]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(report.contains("file: ../lib/src/x.rs,"), "{report}");
+    assert!(!report.contains(fixture.dir.to_str().unwrap()), "{report}");
+}
+
+const TODO_CONFIG: &str =
+    r#"{"rules":[{"id":"NoTodo","message":"No todos","regex":"TODO","severity":"error"}]}"#;
+
+/// A repository with todos inside and outside `src`, some of them hidden or ignored.
+fn tree() -> Fixture {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", TODO_CONFIG)
+        .write(".gitignore", "src/a/ignored.txt\n")
+        .write("src/top.txt", "TODO\n")
+        .write("src/a/b/deep.txt", "TODO\n")
+        .write("src/a/ignored.txt", "TODO\n")
+        .write("src/.hidden.txt", "TODO\n")
+        .write("other/o.txt", "TODO\n")
+        .write("README.md", "TODO\n");
+    fixture
+}
+
+/// The files a run reported, in order.
+fn reported_files(fixture: &Fixture, args: &[&str]) -> Vec<String> {
+    let output = fixture.run(&[&["--no-confirm"], args].concat());
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    all_output(&output)
+        .lines()
+        .filter_map(|line| line.strip_prefix("Error present in file: "))
+        .map(|line| line.split(", lines: ").next().unwrap().to_owned())
+        .collect()
+}
+
+#[test]
+fn named_directory_lints_everything_nested_under_it() {
+    let fixture = tree();
+    assert_eq!(
+        reported_files(&fixture, &["src"]),
+        ["src/a/b/deep.txt", "src/top.txt"]
+    );
+    assert_eq!(reported_files(&fixture, &["src/a"]), ["src/a/b/deep.txt"]);
+}
+
+#[test]
+fn trailing_slashes_name_the_same_directory() {
+    let fixture = tree();
+    for dir in ["src/", "src//", "./src/"] {
+        assert_eq!(
+            reported_files(&fixture, &[dir]),
+            ["src/a/b/deep.txt", "src/top.txt"],
+            "{dir}"
+        );
+    }
+}
+
+#[test]
+fn overlapping_arguments_report_each_file_once() {
+    let fixture = tree();
+    assert_eq!(
+        reported_files(&fixture, &["src", "src/a", "src/a/b/deep.txt"]),
+        ["src/a/b/deep.txt", "src/top.txt"]
+    );
+    assert_eq!(
+        reported_files(&fixture, &["src/a/b/deep.txt", "src/a/"]),
+        ["src/a/b/deep.txt"]
+    );
+    assert_eq!(
+        reported_files(&fixture, &["src/", "README.md"]),
+        ["README.md", "src/a/b/deep.txt", "src/top.txt"]
+    );
+}
+
+#[test]
+fn redundant_arguments_scan_each_file_once() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", TODO_CONFIG)
+        .write("src/main.rs", "TODO\n")
+        .write("src/parser/mod.rs", "TODO\nTODO\n")
+        .write("src/parser/lex.rs", "fine\n");
+    let paths = ["src", "src/parser", "src/parser/mod.rs"];
+    let output = fixture.run(&[&["--no-confirm", "--format", "json"][..], &paths].concat());
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["summary"]["files_walked"], 3, "{report}");
+    assert_eq!(report["summary"]["files_scanned"], 3, "{report}");
+    assert_eq!(report["summary"]["errors"], 2, "{report}");
+    let violations = report["violations"].as_array().unwrap();
+    let found: Vec<(&str, usize)> = violations
+        .iter()
+        .map(|violation| {
+            let matches = violation["matches"].as_array().unwrap().len();
+            (violation["file"].as_str().unwrap(), matches)
+        })
+        .collect();
+    assert_eq!(found, [("src/main.rs", 1), ("src/parser/mod.rs", 2)]);
+
+    let output = fixture.run(&[&["--no-confirm", "-v"][..], &paths].concat());
+    let report = all_output(&output);
+    assert!(
+        report.contains(
+            "Skipping argument src/parser: already covered by src\n\
+             Skipping argument src/parser/mod.rs: already covered by src\n"
+        ),
+        "{report}"
+    );
+}
+
+#[test]
+fn missing_argument_is_skipped_among_files_and_directories() {
+    let fixture = tree();
+    let args = ["--no-confirm", "src/top.txt", "missing.txt", "other"];
+    let output = fixture.run(&args);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(
+        report.contains("Warning: Skipping missing.txt: not found\n"),
+        "{report}"
+    );
+    assert_eq!(
+        reported_files(&fixture, &args[1..]),
+        ["other/o.txt", "src/top.txt"]
+    );
+    assert!(report.contains("walked 2 files and skipped 1"), "{report}");
+}
+
+#[test]
+fn missing_argument_fails_among_files_and_directories_with_strict_paths() {
+    let fixture = tree();
+    let output = fixture.run(&[
+        "--no-confirm",
+        "--strict-paths",
+        "src/top.txt",
+        "missing.txt",
+        "other",
+    ]);
+    assert_eq!(exit_code(&output), 2, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(
+        report.contains("Error: missing.txt: not found\n"),
+        "{report}"
+    );
+    assert!(!report.contains("present in file"), "{report}");
+}
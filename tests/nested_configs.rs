@@ -0,0 +1,185 @@
+mod common;
+
+use common::{all_output, exit_code, stdout, Fixture};
+
+const ROOT_CONFIG: &str =
+    r#"{"rules":[{"id":"Root","message":"No todos","regex":"TODO","severity":"error"}]}"#;
+
+#[test]
+fn explicit_config_from_outside_the_tree_has_no_nested_configs() {
+    let fixture = Fixture::git();
+    fixture
+        .write("proj/.lintyconfig.json", ROOT_CONFIG)
+        .write("proj/x.txt", "TODO\n")
+        .write(
+            "cfgs/shared.json",
+            r#"{"rules":[{"id":"Shared","message":"Shared","regex":"TODO","severity":"warning"}]}"#,
+        );
+    let shared = fixture.path("cfgs/shared.json");
+    let output = fixture.run_in("proj", &["-c", shared.to_str().unwrap(), "list-rules"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    let listed = stdout(&output);
+    assert!(listed.contains("Shared (warning)"), "{listed}");
+    assert!(!listed.contains("Root"), "{listed}");
+}
+
+#[test]
+fn config_in_the_main_configs_directory_is_not_layered() {
+    let fixture = Fixture::git();
+    fixture.write(".lintyconfig.json", ROOT_CONFIG).write(
+        "linty.json",
+        r#"{"rules":[{"id":"Other","message":"Other","regex":"x","severity":"error"}]}"#,
+    );
+    let output = fixture.run(&["-c", ".lintyconfig.json", "list-rules"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert!(!stdout(&output).contains("Other"), "{}", stdout(&output));
+}
+
+#[test]
+fn nested_rule_is_limited_to_its_directory() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", ROOT_CONFIG)
+        .write(
+            "backend/.lintyconfig.json",
+            r#"{"rules":[{"id":"BE","message":"No fixmes","regex":"FIXME","severity":"error"}]}"#,
+        )
+        .write("backend/a.txt", "FIXME\n")
+        .write("b.txt", "FIXME\n");
+    let output = fixture.run(&["--no-confirm"]);
+    let report = all_output(&output);
+    assert_eq!(exit_code(&output), 1, "{report}");
+    assert!(report.contains("Found error BE: No fixmes"), "{report}");
+    assert!(report.contains("backend/a.txt"), "{report}");
+    assert!(!report.contains("file: b.txt"), "{report}");
+
+    let rules = stdout(&fixture.run(&["list-rules"]));
+    assert!(rules.contains("[in backend]"), "{rules}");
+    assert!(!rules.contains(".//"), "{rules}");
+}
+
+#[test]
+fn nested_slashed_globs_are_anchored_at_the_nested_configs_directory() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", ROOT_CONFIG)
+        .write(
+            "backend/.lintyconfig.json",
+            r#"{"rules":[{"id":"BE","message":"No fixmes","regex":"FIXME","severity":"error","includes":["src/**"],"excludes":["*.md"]}],"excludes":["gen/**"]}"#,
+        )
+        .write("backend/src/a.txt", "FIXME\n")
+        .write("backend/src/notes.md", "FIXME\n")
+        .write("backend/other.txt", "FIXME\n")
+        .write("backend/gen/b.txt", "TODO\n")
+        .write("src/c.txt", "FIXME\n")
+        .write("gen/d.txt", "TODO\n");
+    let output = fixture.run(&["--no-confirm"]);
+    let report = all_output(&output);
+    assert!(report.contains("file: backend/src/a.txt"), "{report}");
+    for skipped in [
+        "backend/src/notes.md",
+        "backend/other.txt",
+        "file: src/c.txt",
+    ] {
+        assert!(!report.contains(skipped), "{skipped} in {report}");
+    }
+    // The nested excludes only cover the nested directory.
+    assert!(!report.contains("backend/gen/b.txt"), "{report}");
+    assert!(report.contains("file: gen/d.txt"), "{report}");
+}
+
+#[test]
+fn saved_print_config_keeps_nested_rules_in_their_directory() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", ROOT_CONFIG)
+        .write(
+            "backend/.lintyconfig.json",
+            r#"{"rules":[{"id":"BE","message":"No fixmes","regex":"FIXME","severity":"error"}]}"#,
+        )
+        .write("backend/a.txt", "FIXME\n")
+        .write("b.txt", "FIXME\n");
+    let output = fixture.run(&["print-config"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    let printed = stdout(&output);
+    assert!(printed.contains(r#""directory": "backend""#), "{printed}");
+
+    // Saved somewhere no nested config layers over it, it applies in the same place.
+    fixture.write("saved/config.json", &printed);
+    let saved = fixture.path("saved/config.json");
+    let output = fixture.run(&["--no-confirm", "-c", saved.to_str().unwrap()]);
+    let report = all_output(&output);
+    assert_eq!(exit_code(&output), 1, "{report}");
+    assert!(report.contains("backend/a.txt"), "{report}");
+    assert!(!report.contains("file: b.txt"), "{report}");
+}
+
+#[test]
+fn directory_set_by_hand_limits_the_rule() {
+    let fixture = Fixture::new();
+    fixture
+        .write(
+            ".lintyconfig.json",
+            r#"{"rules":[{"id":"BE","message":"No fixmes","regex":"FIXME","severity":"error","directory":"backend/"}]}"#,
+        )
+        .write("backend/a.txt", "FIXME\n")
+        .write("b.txt", "FIXME\n");
+    let output = fixture.run(&["--no-confirm"]);
+    let report = all_output(&output);
+    assert_eq!(exit_code(&output), 1, "{report}");
+    assert!(report.contains("backend/a.txt"), "{report}");
+    assert!(!report.contains("file: b.txt"), "{report}");
+}
+
+#[test]
+fn nested_rule_directory_is_below_the_nested_config() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", ROOT_CONFIG)
+        .write(
+            "backend/.lintyconfig.json",
+            r#"{"rules":[{"id":"BE","message":"No fixmes","regex":"FIXME","severity":"error","directory":"api"}]}"#,
+        )
+        .write("backend/api/a.txt", "FIXME\n")
+        .write("backend/b.txt", "FIXME\n");
+    let output = fixture.run(&["--no-confirm"]);
+    let report = all_output(&output);
+    assert!(report.contains("backend/api/a.txt"), "{report}");
+    assert!(!report.contains("backend/b.txt"), "{report}");
+}
+
+#[test]
+fn nested_config_disables_a_root_rule_and_adds_its_own_error_rule() {
+    let fixture = Fixture::git();
+    fixture
+        .write(".lintyconfig.json", ROOT_CONFIG)
+        .write(
+            "frontend/.lintyconfig.json",
+            r#"{"rules":[
+                {"id":"Root","enabled":false},
+                {"id":"NoConsole","message":"No console.log","regex":"console\\.log","severity":"error"}]}"#,
+        )
+        .write("frontend/app.js", "// TODO\nconsole.log(1);\n")
+        .write("frontend/deep/lib.js", "// TODO\n")
+        .write("server.js", "// TODO\nconsole.log(1);\n");
+    let output = fixture.run(&["--no-confirm"]);
+    let report = all_output(&output);
+    assert_eq!(exit_code(&output), 1, "{report}");
+    // The root rule still applies outside frontend, and only there; the nested rule
+    // only inside it.
+    assert!(
+        report.contains("Found error Root: No todos\nError present in file: server.js, lines: 1\n"),
+        "{report}"
+    );
+    assert!(
+        report.contains(
+            "Found error NoConsole: No console.log\nError present in file: frontend/app.js, lines: 2\n"
+        ),
+        "{report}"
+    );
+    assert!(!report.contains("frontend/deep/lib.js"), "{report}");
+    assert!(
+        report.contains("2 error(s) and 0 warning(s) from 2 rule(s)"),
+        "{report}"
+    );
+}
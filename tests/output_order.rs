@@ -0,0 +1,89 @@
+mod common;
+
+use common::{all_output, stdout, Fixture};
+
+/// Rules listed out of alphabetical order, so config order and id order differ.
+const CONFIG: &str = r#"{"rules":[
+    {"id":"Zeta","message":"No todos","regex":"TODO","severity":"error"},
+    {"id":"Alpha","message":"No fixmes","regex":"FIXME","severity":"error"},
+    {"id":"Middle","message":"No xxx","regex":"XXX","severity":"warning"}
+]}"#;
+
+/// Many files in several directories, each with matches of every rule, so a walk on
+/// several threads finishes them in a different order each run.
+fn tree() -> Fixture {
+    let fixture = Fixture::new();
+    fixture.write(".lintyconfig.json", CONFIG);
+    for dir in ["b", "a", "c/d", "c"] {
+        for index in 0..25 {
+            fixture.write(
+                &format!("{dir}/file{index}.txt"),
+                "XXX FIXME\nTODO\nfine\nTODO FIXME XXX\n",
+            );
+        }
+    }
+    fixture
+}
+
+/// The report without the summary line, whose timings differ between runs.
+fn without_timings(report: &str) -> String {
+    report
+        .lines()
+        .filter(|line| !line.contains(" in all)"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn text_report_is_byte_identical_between_runs() {
+    let fixture = tree();
+    let args = ["--no-confirm", "--jobs", "8"];
+    let first = without_timings(&all_output(&fixture.run(&args)));
+    for _ in 0..3 {
+        assert_eq!(without_timings(&all_output(&fixture.run(&args))), first);
+    }
+}
+
+#[test]
+fn json_report_is_byte_identical_between_runs() {
+    let fixture = tree();
+    let run = || {
+        let output = fixture.run(&["--no-confirm", "--jobs", "8", "--format", "json"]);
+        // The timestamp and the times taken are the only things meant to differ.
+        stdout(&output)
+            .lines()
+            .filter(|line| !line.contains("\"timestamp\":") && !line.contains("_ms\":"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let first = run();
+    for _ in 0..3 {
+        assert_eq!(run(), first);
+    }
+}
+
+#[test]
+fn rules_follow_the_config_and_files_their_paths() {
+    let fixture = tree();
+    let report = all_output(&fixture.run(&["--no-confirm", "--jobs", "8"]));
+    let rule = |id: &str| report.find(&format!(" {id}: ")).unwrap();
+    // Warnings come before errors, each in config order.
+    assert!(rule("Middle") < rule("Zeta"), "{report}");
+    assert!(rule("Zeta") < rule("Alpha"), "{report}");
+
+    let files: Vec<&str> = report
+        .lines()
+        .skip_while(|line| !line.contains(" Zeta: "))
+        .skip(1)
+        .take_while(|line| line.contains("present in file"))
+        .map(|line| line.split("file: ").nth(1).unwrap())
+        .collect();
+    assert_eq!(files.len(), 100, "{report}");
+    let mut sorted = files.clone();
+    sorted.sort();
+    assert_eq!(files, sorted);
+    assert!(
+        files.iter().all(|file| file.ends_with(", lines: 2, 4")),
+        "{files:?}"
+    );
+}
@@ -0,0 +1,108 @@
+//! Paths and line endings as Windows checkouts have them, which run the same on every
+//! platform so a regression shows up in any CI job.
+
+mod common;
+
+use common::{all_output, exit_code, Fixture};
+
+#[test]
+fn reports_use_forward_slashes_for_nested_files() {
+    let fixture = Fixture::new();
+    fixture
+        .write(
+            ".lintyconfig.json",
+            r#"{"rules":[{"id":"Deep","message":"m","regex":"TODO","severity":"error","includes":["src/deep/**"]}]}"#,
+        )
+        .write("src/deep/er/x.rs", "TODO\n");
+    let output = fixture.run(&["--no-confirm"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert!(
+        all_output(&output).contains("Error present in file: src/deep/er/x.rs, lines: 1"),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn named_files_match_globs_however_they_are_written() {
+    let fixture = Fixture::new();
+    fixture
+        .write(
+            ".lintyconfig.json",
+            r#"{"rules":[{"id":"Src","message":"m","regex":"TODO","severity":"error","includes":["src/*.rs"]}]}"#,
+        )
+        .write("src/x.rs", "TODO\n");
+    let absolute = fixture.path("src/x.rs");
+    for named in [
+        "src/x.rs",
+        "./src/x.rs",
+        "src/../src/x.rs",
+        absolute.to_str().unwrap(),
+    ] {
+        let output = fixture.run(&["--no-confirm", named]);
+        assert!(
+            all_output(&output).contains("Error present in file: src/x.rs, lines: 1"),
+            "{named}: {}",
+            all_output(&output)
+        );
+    }
+}
+
+#[test]
+fn staged_paths_match_the_walked_file() {
+    let fixture = Fixture::git();
+    fixture
+        .write(
+            ".lintyconfig.json",
+            r#"{"rules":[{"id":"Src","message":"m","regex":"TODO","severity":"error","includes":["src/**"]}]}"#,
+        )
+        .write("src/a/b.rs", "TODO\n");
+    fixture.git_ok(&["add", "."]);
+    let output = fixture.run_in("src", &["--no-confirm", "--pre-commit"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert!(
+        all_output(&output).contains("Error present in file: a/b.rs, lines: 1"),
+        "{}",
+        all_output(&output)
+    );
+}
+
+const CRLF_CONFIG: &str = r#"{"rules":[
+    {"id":"TrailingSemi","message":"m","regex":";$","severity":"error","multi_line":true,"crlf":true},
+    {"id":"Todo","message":"m","regex":"TODO","severity":"warning"}
+]}"#;
+
+#[test]
+fn crlf_file_reports_the_lines_of_its_lf_twin() {
+    let fixture = Fixture::new();
+    let lines = "let a = 1;\nif a {\n  TODO;\n}\nlet b;\n";
+    fixture
+        .write(".lintyconfig.json", CRLF_CONFIG)
+        .write("lf.txt", lines)
+        .write("crlf.txt", lines.replace('\n', "\r\n"));
+    let output = fixture.run(&["--no-confirm"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report = all_output(&output);
+    for file in ["lf.txt", "crlf.txt"] {
+        assert!(
+            report.contains(&format!("Error present in file: {file}, lines: 1, 3, 5")),
+            "{report}"
+        );
+        assert!(
+            report.contains(&format!("Warning present in file: {file}, lines: 3")),
+            "{report}"
+        );
+    }
+}
+
+#[test]
+fn crlf_json_positions_match_lf() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CRLF_CONFIG)
+        .write("crlf.txt", "a;\r\nb\r\n");
+    let output = fixture.run(&["--no-confirm", "--format", "json"]);
+    let report = common::stdout(&output);
+    assert!(report.contains("\"line\": 1"), "{report}");
+    assert!(report.contains("\"column\": 2"), "{report}");
+}
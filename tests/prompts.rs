@@ -0,0 +1,173 @@
+mod common;
+
+use common::{all_output, exit_code, stdout, Fixture};
+use std::io::Write;
+use std::process::{Output, Stdio};
+
+const CONFIG: &str = r#"{"rules":[
+    {"id":"NoTodo","message":"No todos","regex":"TODO","severity":"error"},
+    {"id":"NoFixme","message":"No fixmes","regex":"FIXME","severity":"warning"},
+    {"id":"NoXxx","message":"No xxx","regex":"XXX","severity":"warning"}
+]}"#;
+
+/// Two warning rules' matches, and an error's too when `with_error`.
+fn fixture(with_error: bool) -> Fixture {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a.txt", "FIXME\nXXX\n");
+    if with_error {
+        fixture.write("b.txt", "TODO\n");
+    }
+    fixture
+}
+
+/// Runs linty with `--confirm` and `answers` piped to its stdin, so it prompts as it
+/// would on a terminal.
+fn answer(fixture: &Fixture, args: &[&str], answers: &str) -> Output {
+    let mut child = fixture
+        .linty("")
+        .arg("--confirm")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(answers.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn errors_skip_the_warning_prompts() {
+    let fixture = fixture(true);
+    let output = answer(&fixture, &[], "y\ny\n");
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(!report.contains("Ignore warning?"), "{report}");
+    assert!(
+        report.ends_with("Failing due to errors (2 warning rule(s) not individually reviewed)\n"),
+        "{report}"
+    );
+}
+
+#[test]
+fn exceeded_warning_budget_skips_the_prompts() {
+    let fixture = fixture(false);
+    let output = answer(&fixture, &["--max-warnings", "1"], "y\ny\n");
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(!report.contains("Ignore warning?"), "{report}");
+    assert!(
+        report.contains("warnings: 2 (budget 1), failing"),
+        "{report}"
+    );
+}
+
+#[test]
+fn warnings_alone_are_prompted_for() {
+    let fixture = fixture(false);
+    let output = answer(&fixture, &[], "y\ny\n");
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert_eq!(stdout(&output).matches("Ignore warning?").count(), 2);
+    assert!(!all_output(&output).contains("not individually reviewed"));
+}
+
+#[test]
+fn no_confirm_wins_over_an_earlier_confirm() {
+    let fixture = fixture(false);
+    let output = answer(&fixture, &["--no-confirm"], "");
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert!(!stdout(&output).contains("Ignore warning?"));
+}
+
+/// The rule ids the run prompted about, in order, once per prompt.
+fn prompts(output: &Output) -> Vec<String> {
+    let report = stdout(output);
+    report
+        .match_indices("Ignore warning?")
+        .map(|(at, _)| {
+            // The rule being asked about is the last one headed before the prompt.
+            let heading = "warning rules reviewed] ";
+            let rule = &report[report[..at].rfind(heading).unwrap() + heading.len()..];
+            rule[..rule.find(':').unwrap()].to_owned()
+        })
+        .collect()
+}
+
+#[test]
+fn y_ignores_each_rule_in_turn_with_a_count_of_those_reviewed() {
+    let fixture = fixture(false);
+    let output = answer(&fixture, &[], "y\ny\n");
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    let report = stdout(&output);
+    assert!(
+        report.contains("[0 of 2 warning rules reviewed] NoFixme: No fixmes\n"),
+        "{report}"
+    );
+    assert!(
+        report.contains("[1 of 2 warning rules reviewed] NoXxx: No xxx\n"),
+        "{report}"
+    );
+    assert_eq!(prompts(&output), ["NoFixme", "NoXxx"]);
+}
+
+#[test]
+fn n_fails_once_the_rest_are_reviewed() {
+    let fixture = fixture(false);
+    let output = answer(&fixture, &[], "n\ny\n");
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert_eq!(prompts(&output), ["NoFixme", "NoXxx"]);
+    assert!(all_output(&output).ends_with("Failing due to warnings\n"));
+}
+
+#[test]
+fn a_ignores_every_remaining_rule() {
+    let fixture = fixture(false);
+    let output = answer(&fixture, &[], "a\n");
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert_eq!(prompts(&output), ["NoFixme"]);
+}
+
+#[test]
+fn v_shows_the_matches_and_asks_again() {
+    let fixture = fixture(false);
+    let output = answer(&fixture, &[], "v\ny\ny\n");
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert_eq!(prompts(&output), ["NoFixme", "NoFixme", "NoXxx"]);
+    let report = stdout(&output);
+    assert!(report.contains(" --> a.txt:1:1\n"), "{report}");
+    assert!(report.contains("1 | FIXME\n"), "{report}");
+}
+
+#[test]
+fn q_fails_without_reviewing_the_rest() {
+    let fixture = fixture(false);
+    let output = answer(&fixture, &[], "q\ny\n");
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert_eq!(prompts(&output), ["NoFixme"]);
+    assert!(all_output(&output).ends_with("Failing due to warnings\n"));
+}
+
+#[test]
+fn other_answers_list_the_choices_and_ask_again() {
+    let fixture = fixture(false);
+    let output = answer(&fixture, &[], "?\nmaybe\ny\ny\n");
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert_eq!(prompts(&output), ["NoFixme", "NoFixme", "NoFixme", "NoXxx"]);
+    assert_eq!(stdout(&output).matches("  q - fail now").count(), 2);
+}
+
+#[test]
+fn running_out_of_answers_fails() {
+    let fixture = fixture(false);
+    let output = answer(&fixture, &[], "y\n");
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert_eq!(prompts(&output), ["NoFixme", "NoXxx"]);
+    assert!(all_output(&output).ends_with("Failing due to warnings (no answer on stdin)\n"));
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::{all_output, exit_code, stdout, Fixture};
+
+/// A report_once rule, with three matches in one file and one in another.
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    fixture
+        .write(
+            ".lintyconfig.json",
+            r#"{"rules":[{"id":"Legacy","message":"Uses the legacy framework","regex":"legacy::","severity":"warning","report_once":true}]}"#,
+        )
+        .write("a.txt", "ok\nuse legacy::a;\nuse legacy::b;\nuse legacy::c;\n")
+        .write("b.txt", "legacy::x\n");
+    fixture
+}
+
+#[test]
+fn each_file_is_reported_and_counted_once() {
+    let fixture = fixture();
+    let output = fixture.run(&["--no-confirm", "--max-warnings", "2"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(
+        report.contains(
+            "Warning present in file: a.txt, first occurrence at line 2 (report_once)\n\
+             Warning present in file: b.txt, first occurrence at line 1 (report_once)\n"
+        ),
+        "{report}"
+    );
+    assert!(report.contains("0 error(s) and 2 warning(s)"), "{report}");
+
+    // Only the first match is kept, so a.txt counts once against the budget.
+    let output = fixture.run(&["--no-confirm", "--max-warnings", "1"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert!(all_output(&output).contains("warnings: 2 (budget 1), failing\n"));
+
+    let output = fixture.run(&["--no-confirm", "--format", "json"]);
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    let matches: Vec<usize> = json["violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|violation| violation["matches"].as_array().unwrap().len())
+        .collect();
+    assert_eq!(matches, [1, 1], "{json}");
+}
+
+#[test]
+fn suppressing_the_first_match_hides_the_files_finding() {
+    let fixture = fixture();
+    fixture.write(
+        "a.txt",
+        "ok\n// linty-disable-next-line Legacy\nuse legacy::a;\nuse legacy::b;\n",
+    );
+    let output = fixture.run(&["--no-confirm"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(!report.contains("file: a.txt"), "{report}");
+    assert!(report.contains("file: b.txt"), "{report}");
+}
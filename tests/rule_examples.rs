@@ -0,0 +1,59 @@
+mod common;
+
+use common::{all_output, exit_code, stdout, Fixture};
+
+/// A rule whose examples pass, one with none, and, when `broken`, a disabled rule whose
+/// examples fail both ways.
+fn run(broken: bool) -> std::process::Output {
+    let broken = if broken {
+        r#",{"id":"Broken","message":"m","regex":"FIXME","severity":"error","enabled":false,
+             "examples":{"matches":["fixme"],"no_matches":["FIXME later"]}}"#
+    } else {
+        ""
+    };
+    let fixture = Fixture::new();
+    fixture.write(
+        ".lintyconfig.json",
+        format!(
+            r#"{{"rules":[
+                {{"id":"NoTodo","message":"m","regex":"TODO","severity":"error",
+                  "examples":{{"matches":["// TODO: x"],"no_matches":["todo"]}}}},
+                {{"id":"Plain","message":"m","regex":"XXX","severity":"error"}}{broken}
+            ]}}"#
+        ),
+    );
+    fixture.run(&["test"])
+}
+
+#[test]
+fn passing_examples_exit_zero_with_what_matched() {
+    let output = run(false);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert_eq!(
+        stdout(&output),
+        "Rule NoTodo:\n\
+         \x20 pass matches \"// TODO: x\": matched \"TODO\" at line 1, column 4\n\
+         \x20 pass no_matches \"todo\": nothing matched\n\
+         Rule Plain: no examples, skipped\n\
+         2 example(s) passed and 0 failed; 1 rule(s) have no examples\n"
+    );
+}
+
+#[test]
+fn failing_examples_exit_one_even_for_a_disabled_rule() {
+    let output = run(true);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report = stdout(&output);
+    assert!(
+        report.contains(
+            "Rule Broken:\n\
+             \x20 FAIL matches \"fixme\": nothing matched\n\
+             \x20 FAIL no_matches \"FIXME later\": matched \"FIXME\" at line 1, column 1\n"
+        ),
+        "{report}"
+    );
+    assert!(
+        report.ends_with("2 example(s) passed and 2 failed; 1 rule(s) have no examples\n"),
+        "{report}"
+    );
+}
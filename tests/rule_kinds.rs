@@ -0,0 +1,139 @@
+#![cfg(feature = "rule-kinds")]
+
+mod common;
+
+use common::Fixture;
+use linty::rule_kind::{FileContext, Finding, RuleKind};
+use linty::{parse_config, ConfigFormat, Linter, Severity, Violation};
+use std::path::Path;
+
+/// Reports each tab, with the line it's on in the message.
+struct NoTabs;
+
+impl RuleKind for NoTabs {
+    fn name(&self) -> &str {
+        "NoTabs"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, file: &FileContext) -> Vec<Finding> {
+        let (Ok(contents), Ok(lines)) = (file.contents(), file.line_index()) else {
+            return Vec::new();
+        };
+        contents
+            .match_indices('\t')
+            .map(|(offset, _)| Finding::At {
+                range: offset..offset + 1,
+                message: Some(format!("Tab on line {}", lines.line(offset))),
+            })
+            .collect()
+    }
+}
+
+/// Reports empty files from their metadata, without reading them.
+struct NoEmptyFiles;
+
+impl RuleKind for NoEmptyFiles {
+    fn name(&self) -> &str {
+        "NoEmptyFiles"
+    }
+
+    fn reads_contents(&self) -> bool {
+        false
+    }
+
+    fn check(&self, file: &FileContext) -> Vec<Finding> {
+        match file.metadata() {
+            Some(metadata) if metadata.len() == 0 => vec![Finding::File],
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn linter(config: &str) -> Linter {
+    let config = parse_config(config, ConfigFormat::Json, "test config").unwrap();
+    let mut linter = Linter::from_config(config).unwrap();
+    linter.register_rule_kind(Box::new(NoTabs));
+    linter.register_rule_kind(Box::new(NoEmptyFiles));
+    linter
+}
+
+fn found(violations: &[Violation]) -> Vec<(String, String, Vec<usize>)> {
+    violations
+        .iter()
+        .map(|v| {
+            (
+                v.rule_id.clone(),
+                v.file.rsplit('/').next().unwrap().to_owned(),
+                v.lines(),
+            )
+        })
+        .collect()
+}
+
+const CONFIG: &str =
+    r#"{"rules":[{"id":"NoTodo","message":"No todos","regex":"TODO","severity":"error"}]}"#;
+
+#[test]
+fn registered_kinds_are_reported_beside_config_rules() {
+    let linter = linter(CONFIG);
+    assert_eq!(
+        linter
+            .rules()
+            .iter()
+            .map(|rule| rule.id.as_str())
+            .collect::<Vec<_>>(),
+        ["NoTodo", "NoTabs", "NoEmptyFiles"]
+    );
+    let violations = linter
+        .check_str("TODO\nfine\n\tindented\n", Path::new("a.txt"))
+        .unwrap();
+    assert_eq!(
+        found(&violations),
+        [
+            (String::from("NoTodo"), String::from("a.txt"), vec![1]),
+            (String::from("NoTabs"), String::from("a.txt"), vec![3]),
+        ]
+    );
+    let tabs = violations.iter().find(|v| v.rule_id == "NoTabs").unwrap();
+    assert_eq!(tabs.severity, Severity::Warning);
+    assert_eq!(tabs.matches[0].column, 1);
+    assert_eq!(tabs.matches[0].message.as_deref(), Some("Tab on line 3"));
+}
+
+#[test]
+fn registered_kinds_check_walked_files() {
+    let fixture = Fixture::new();
+    fixture
+        .write("empty.txt", "")
+        .write("tabs.txt", "a\n\tb\n")
+        .write("clean.txt", "fine\n");
+    let violations = linter(CONFIG).check_path(&fixture.dir).unwrap();
+    assert_eq!(
+        found(&violations),
+        [
+            (
+                String::from("NoEmptyFiles"),
+                String::from("empty.txt"),
+                vec![]
+            ),
+            (String::from("NoTabs"), String::from("tabs.txt"), vec![2]),
+        ]
+    );
+}
+
+#[test]
+fn suppression_comments_apply_to_registered_kinds() {
+    let linter = linter(CONFIG);
+    assert!(linter
+        .check_str("\tx // linty-disable-line NoTabs\n", Path::new("a.txt"))
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        found(&linter.check_str("\tx\n", Path::new("a.txt")).unwrap()),
+        [(String::from("NoTabs"), String::from("a.txt"), vec![1])]
+    );
+}
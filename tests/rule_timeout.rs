@@ -0,0 +1,58 @@
+mod common;
+
+use common::{all_output, exit_code, stderr, Fixture};
+
+/// A line-local pattern the regex engine spends a long time failing to match on every
+/// line of prose, and a quick rule for a small file.
+const CONFIG: &str = r#"{"rules":[
+    {"id":"Slow","message":"slow","regex":"(\\w+ +){12}\\d","severity":"error","includes":["big.txt"]},
+    {"id":"Quick","message":"quick","regex":"^end$","multi_line":true,"severity":"error","includes":["small.txt"]}
+]}"#;
+
+const PROSE_LINE: &str = "word word word word word word word word word word word word word\n";
+
+#[test]
+fn slow_rule_is_abandoned_for_the_file_and_the_run_goes_on() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("big.txt", PROSE_LINE.repeat(60_000))
+        .write("small.txt", "start\nend\n");
+    let output = fixture.run(&["--no-confirm", "--rule-timeout", "20ms"]);
+    let stderr = stderr(&output);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert!(
+        stderr.contains("Skipping rule Slow for big.txt: matching took over --rule-timeout 20ms"),
+        "{stderr}"
+    );
+    assert!(!stderr.contains("Skipping rule Quick"), "{stderr}");
+    assert!(
+        all_output(&output).contains("Error present in file: small.txt, lines: 2"),
+        "{}",
+        all_output(&output)
+    );
+    assert!(
+        all_output(&output).contains("Slowest rules by time spent matching:\n  Slow: "),
+        "{}",
+        all_output(&output)
+    );
+}
+
+#[test]
+fn without_a_timeout_the_slow_rule_finishes() {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write(
+            "big.txt",
+            PROSE_LINE.repeat(2_000) + "a b c d e f g h i j k l 1\n",
+        )
+        .write("small.txt", "start\n");
+    let output = fixture.run(&["--no-confirm"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert!(
+        all_output(&output).contains("Error present in file: big.txt, lines: 2001"),
+        "{}",
+        all_output(&output)
+    );
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use common::{stdout, Fixture};
+use serde_json::Value;
+
+const CONFIG: &str = r#"{"rules":[
+    {"id":"NoUnwrap","message":"Don't unwrap","regex":"(\\w+)\\.unwrap\\(\\)","fix":"$1.expect(\"TODO\")","severity":"error","url":"https://example.com/unwrap"},
+    {"id":"NoTodo","message":"No todos","regex":"TODO","severity":"warning"}
+]}"#;
+
+/// The SARIF log linty prints for the fixture, read back through serde.
+fn sarif_log(fixture: &Fixture) -> Value {
+    let output = fixture.run(&["--format", "sarif"]);
+    serde_json::from_str(&stdout(&output)).expect("the SARIF log is JSON")
+}
+
+fn write_sources(fixture: &Fixture) {
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("src/main.rs", "fn main() {\n    let x = y.unwrap();\n}\n")
+        .write("notes file.txt", "TODO\n");
+}
+
+#[test]
+fn describes_the_tool_rules_and_results() {
+    let fixture = Fixture::new();
+    write_sources(&fixture);
+    let log = sarif_log(&fixture);
+
+    assert_eq!(log["version"], "2.1.0");
+    assert!(log["$schema"].as_str().unwrap().contains("sarif"));
+    let runs = log["runs"].as_array().unwrap();
+    assert_eq!(runs.len(), 1);
+    let run = &runs[0];
+    assert_eq!(run["tool"]["driver"]["name"], "linty");
+    assert_eq!(run["columnKind"], "unicodeCodePoints");
+
+    let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+    let ids: Vec<&str> = rules.iter().map(|r| r["id"].as_str().unwrap()).collect();
+    assert_eq!(ids, ["NoUnwrap", "NoTodo"]);
+    assert_eq!(rules[0]["helpUri"], "https://example.com/unwrap");
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    let unwrap = results.iter().find(|r| r["ruleId"] == "NoUnwrap").unwrap();
+    assert_eq!(unwrap["level"], "error");
+    assert_eq!(unwrap["message"]["text"], "Don't unwrap");
+    assert_eq!(unwrap["ruleIndex"], 0);
+    let location = &unwrap["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "src/main.rs");
+    assert_eq!(location["artifactLocation"]["uriBaseId"], "%SRCROOT%");
+    assert_eq!(location["region"]["startLine"], 2);
+    assert_eq!(location["region"]["startColumn"], 13);
+
+    let todo = results.iter().find(|r| r["ruleId"] == "NoTodo").unwrap();
+    assert_eq!(todo["level"], "warning");
+    assert_eq!(
+        todo["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        "notes%20file.txt"
+    );
+    // Outside git there's no commit to record.
+    assert!(run.get("versionControlProvenance").is_none());
+}
+
+#[test]
+fn carries_rule_fixes_as_replacements() {
+    let fixture = Fixture::new();
+    write_sources(&fixture);
+    let log = sarif_log(&fixture);
+    let results = log["runs"][0]["results"].as_array().unwrap();
+
+    let unwrap = results.iter().find(|r| r["ruleId"] == "NoUnwrap").unwrap();
+    let fixes = unwrap["fixes"].as_array().unwrap();
+    assert_eq!(fixes.len(), 1);
+    let change = &fixes[0]["artifactChanges"][0];
+    assert_eq!(change["artifactLocation"]["uri"], "src/main.rs");
+    let replacement = &change["replacements"][0];
+    assert_eq!(
+        replacement["deletedRegion"],
+        unwrap["locations"][0]["physicalLocation"]["region"]
+    );
+    assert_eq!(replacement["insertedContent"]["text"], "y.expect(\"TODO\")");
+
+    // A rule without a fix has no fixes at all, rather than an empty list.
+    let todo = results.iter().find(|r| r["ruleId"] == "NoTodo").unwrap();
+    assert!(todo.get("fixes").is_none());
+}
+
+#[test]
+fn records_the_commit_and_branch_in_a_repository() {
+    let fixture = Fixture::git();
+    write_sources(&fixture);
+    fixture.git_ok(&["checkout", "-q", "-b", "main"]);
+    fixture.git_ok(&["add", "."]);
+    fixture.git_ok(&["commit", "-q", "-m", "start"]);
+    fixture.git_ok(&["remote", "add", "origin", "git@github.com:owner/repo.git"]);
+    let head = std::process::Command::new("git")
+        .current_dir(&fixture.dir)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .unwrap();
+    let head = String::from_utf8(head.stdout).unwrap();
+
+    let log = sarif_log(&fixture);
+    let provenance = log["runs"][0]["versionControlProvenance"]
+        .as_array()
+        .unwrap();
+    assert_eq!(provenance.len(), 1);
+    assert_eq!(
+        provenance[0]["repositoryUri"],
+        "ssh://git@github.com/owner/repo.git"
+    );
+    assert_eq!(provenance[0]["revisionId"], head.trim());
+    assert_eq!(provenance[0]["branch"], "main");
+}
+
+#[test]
+fn uses_the_work_tree_without_a_remote() {
+    let fixture = Fixture::git();
+    write_sources(&fixture);
+    fixture.git_ok(&["add", "."]);
+    fixture.git_ok(&["commit", "-q", "-m", "start"]);
+
+    let log = sarif_log(&fixture);
+    let provenance = &log["runs"][0]["versionControlProvenance"][0];
+    assert_eq!(
+        provenance["repositoryUri"],
+        format!("file://{}", fixture.dir.display())
+    );
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::{all_output, exit_code, stdout, Fixture};
+
+/// Warnings and errors across several files, written in an order the report shouldn't
+/// follow.
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    fixture
+        .write(
+            ".lintyconfig.json",
+            r#"{"rules":[
+                {"id":"NoTodos","message":"No todos","regex":"TODO","severity":"error"},
+                {"id":"NoFixmes","message":"No fixmes","regex":"FIXME","severity":"warning"},
+                {"id":"Unmatched","message":"m","regex":"nothing here","severity":"error"}]}"#,
+        )
+        .write("src/z.rs", "TODO\nTODO\nFIXME\n")
+        .write("src/a.rs", "FIXME\n")
+        .write("docs/with space.md", "TODO\n")
+        .write("clean.txt", "fine\n");
+    fixture
+}
+
+#[test]
+fn count_prints_one_line_per_rule_and_totals() {
+    let fixture = fixture();
+    let output = fixture.run(&["--count"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert_eq!(
+        stdout(&output),
+        "NoTodos: 2 file(s), 3 match(es)\n\
+         NoFixmes: 2 file(s), 2 match(es)\n\
+         Total: 3 file(s), 5 match(es)\n"
+    );
+}
+
+#[test]
+fn count_with_only_counts_that_rule() {
+    let fixture = fixture();
+    let output = fixture.run(&["--count", "--only", "NoFixmes"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert_eq!(
+        stdout(&output),
+        "NoFixmes: 2 file(s), 2 match(es)\nTotal: 2 file(s), 2 match(es)\n"
+    );
+}
+
+#[test]
+fn files_with_matches_lists_each_file_once_in_order() {
+    let fixture = fixture();
+    let output = fixture.run(&["--files-with-matches"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert_eq!(stdout(&output), "docs/with space.md\nsrc/a.rs\nsrc/z.rs\n");
+}
+
+#[test]
+fn files_with_matches_composes_with_only() {
+    let fixture = fixture();
+    let output = fixture.run(&["--files-with-matches", "--only", "NoTodos"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    assert_eq!(stdout(&output), "docs/with space.md\nsrc/z.rs\n");
+
+    // Warnings alone don't fail the run, and nothing prompts.
+    let output = fixture.run(&["--files-with-matches", "--only", "NoFixmes"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert_eq!(stdout(&output), "src/a.rs\nsrc/z.rs\n");
+}
+
+#[test]
+fn files_with_matches_separates_with_nul() {
+    let fixture = fixture();
+    let output = fixture.run(&["--files-with-matches", "--null"]);
+    assert_eq!(stdout(&output), "docs/with space.md\0src/a.rs\0src/z.rs\0");
+}
+
+#[test]
+fn clean_run_prints_nothing() {
+    let fixture = fixture();
+    let output = fixture.run(&["--files-with-matches", "--only", "Unmatched"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    assert_eq!(stdout(&output), "");
+
+    let output = fixture.run(&["--count", "--only", "Unmatched"]);
+    assert_eq!(stdout(&output), "Total: 0 file(s), 0 match(es)\n");
+}
+
+#[test]
+fn null_without_a_list_is_a_usage_error() {
+    let fixture = fixture();
+    let output = fixture.run(&["--null"]);
+    assert_eq!(exit_code(&output), 2, "{}", all_output(&output));
+    assert!(
+        all_output(&output).contains("--null needs --files-from or --files-with-matches"),
+        "{}",
+        all_output(&output)
+    );
+}
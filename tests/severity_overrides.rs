@@ -0,0 +1,60 @@
+mod common;
+
+use common::{all_output, exit_code, Fixture};
+
+/// An error rule and a warning rule, each matching once.
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    fixture
+        .write(
+            ".lintyconfig.json",
+            r#"{"rules":[
+                {"id":"E","message":"No todos","regex":"TODO","severity":"error"},
+                {"id":"W","message":"No fixmes","regex":"FIXME","severity":"warning"}
+            ]}"#,
+        )
+        .write("a.txt", "TODO\nFIXME\n");
+    fixture
+}
+
+#[test]
+fn demoting_the_failing_rule_passes_the_run() {
+    let fixture = fixture();
+    let output = fixture.run(&["--no-confirm"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+
+    let output = fixture.run(&["--no-confirm", "--severity", "E=warning"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(report.contains("Found warning E: No todos\n"), "{report}");
+    assert!(report.contains("0 error(s) and 2 warning(s)"), "{report}");
+}
+
+#[test]
+fn later_severity_flags_win() {
+    let fixture = fixture();
+    let output = fixture.run(&[
+        "--no-confirm",
+        "--severity",
+        "E=warning",
+        "--severity",
+        "all=error",
+    ]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(report.contains("Found error W: No fixmes\n"), "{report}");
+    assert!(report.contains("2 error(s) and 0 warning(s)"), "{report}");
+}
+
+#[test]
+fn unknown_rule_is_a_config_error() {
+    let fixture = fixture();
+    let output = fixture.run(&["--no-confirm", "--severity", "Nope=warning"]);
+    assert_eq!(exit_code(&output), 2, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(
+        report.contains("Unknown rule Nope in --severity\n"),
+        "{report}"
+    );
+    assert!(report.contains("Valid rule ids: all, E, W\n"), "{report}");
+}
@@ -0,0 +1,85 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{all_output, exit_code, Fixture};
+use std::os::unix::fs::symlink;
+
+/// A `repo` whose `proto` directory and `notes.txt` are symlinks into a `shared`
+/// directory outside it, and whose `loop` links back to itself.
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    fixture
+        .write(
+            "repo/.lintyconfig.json",
+            r#"{"rules":[
+                {"id":"Proto","message":"No todos in protos","regex":"TODO","severity":"error","includes":["proto/**"]},
+                {"id":"NoFixme","message":"No fixmes","regex":"FIXME","severity":"error"}
+            ]}"#,
+        )
+        .write("repo/ok.txt", "ok\n")
+        .write("shared/proto/a.proto", "TODO\n")
+        .write("shared/notes.txt", "FIXME\n");
+    symlink("../shared/proto", fixture.path("repo/proto")).unwrap();
+    symlink("../shared/notes.txt", fixture.path("repo/notes.txt")).unwrap();
+    symlink(".", fixture.path("repo/loop")).unwrap();
+    fixture
+}
+
+/// The files a run in `repo` reported, in order, after checking it failed and never
+/// named a link's target.
+fn reported_files(fixture: &Fixture, args: &[&str]) -> Vec<String> {
+    let output = fixture.run_in("repo", &[&["--no-confirm"], args].concat());
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(!report.contains("shared"), "{report}");
+    report
+        .lines()
+        .filter_map(|line| line.strip_prefix("Error present in file: "))
+        .map(|line| line.split(", lines: ").next().unwrap().to_owned())
+        .collect()
+}
+
+#[test]
+fn file_links_are_linted_but_directory_links_are_skipped_by_default() {
+    let fixture = fixture();
+    assert_eq!(reported_files(&fixture, &[]), ["notes.txt"]);
+    let output = fixture.run_in("repo", &["--no-confirm", "-v"]);
+    let report = all_output(&output);
+    assert!(
+        report.contains(
+            "Skipping ./proto: a symlink to a directory; pass --follow-symlinks to lint it\n"
+        ),
+        "{report}"
+    );
+}
+
+#[test]
+fn followed_target_outside_the_root_matches_includes_under_the_links_path() {
+    let fixture = fixture();
+    assert_eq!(
+        reported_files(&fixture, &["--follow-symlinks"]),
+        ["proto/a.proto", "notes.txt"]
+    );
+}
+
+#[test]
+fn link_back_to_an_ancestor_is_warned_about_once() {
+    let fixture = fixture();
+    let output = fixture.run_in("repo", &["--no-confirm", "--follow-symlinks"]);
+    let report = all_output(&output);
+    assert_eq!(
+        report
+            .matches("Warning: Skipping ./loop: it links back to ./\n")
+            .count(),
+        1,
+        "{report}"
+    );
+}
+
+#[test]
+fn linked_arguments_are_reported_under_their_own_path() {
+    let fixture = fixture();
+    assert_eq!(reported_files(&fixture, &["proto"]), ["proto/a.proto"]);
+    assert_eq!(reported_files(&fixture, &["notes.txt"]), ["notes.txt"]);
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::{all_output, exit_code, stdout, Fixture};
+
+/// Three warning matches from two violations: two lines of todos and a fixme.
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    fixture
+        .write(
+            ".lintyconfig.json",
+            r#"{"rules":[
+                {"id":"NoTodo","message":"No todos","regex":"TODO","severity":"warning"},
+                {"id":"NoFixme","message":"No fixmes","regex":"FIXME","severity":"warning"}
+            ]}"#,
+        )
+        .write("a.txt", "TODO\nTODO\nFIXME\n");
+    fixture
+}
+
+#[test]
+fn exactly_at_the_budget_passes() {
+    let fixture = fixture();
+    let output = fixture.run(&["--no-confirm", "--max-warnings", "3", "--explain-exit-code"]);
+    assert_eq!(exit_code(&output), 0, "{}", all_output(&output));
+    let report = all_output(&output);
+    assert!(!report.contains("(budget"), "{report}");
+    assert!(
+        report.contains("linty: exit=0 reason=clean errors=0 warnings=2 failed_rules=\n"),
+        "{report}"
+    );
+}
+
+#[test]
+fn one_over_the_budget_fails() {
+    let fixture = fixture();
+    let output = fixture.run(&["--no-confirm", "--max-warnings", "2", "--explain-exit-code"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let report = all_output(&output);
+    // Matches count against the budget, not violations, of which there are only two.
+    assert!(
+        report.contains("warnings: 3 (budget 2), failing\n"),
+        "{report}"
+    );
+    assert!(
+        report.contains(
+            "linty: exit=1 reason=warning_budget errors=0 warnings=2 failed_rules=NoFixme,NoTodo\n"
+        ),
+        "{report}"
+    );
+
+    let output = fixture.run(&["--no-confirm", "--max-warnings", "2", "--format", "json"]);
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(json["outcome"]["exit_code"], 1, "{json}");
+    assert_eq!(json["outcome"]["reason"], "warning_budget", "{json}");
+}
+
+#[test]
+fn error_on_warning_overrides_the_budget() {
+    let fixture = fixture();
+    let output = fixture.run(&["--no-confirm", "--max-warnings", "3", "--error-on-warning"]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::xml::{self, Element};
+use common::{all_output, exit_code, stdout, Fixture};
+
+/// Markup characters in a rule's id and message, the text it matches, and the file's
+/// name, so each has to be escaped to keep the report well-formed.
+const CONFIG: &str = r#"{"rules":[
+    {"id":"NoMarkup<&\"","message":"Don't write <b> & \"quotes\" or 'apostrophes'","regex":"<b>&\"x\"","severity":"error"},
+    {"id":"NoBell","message":"No bells","regex":"\\x07","severity":"warning"},
+    {"id":"NoTodo","message":"Never matches","regex":"TODO","severity":"warning"}
+]}"#;
+
+const MESSAGE: &str = r#"Don't write <b> & "quotes" or 'apostrophes'"#;
+
+/// The report linty writes in `format` over the fixture tree, parsed.
+fn report(format: &str) -> Element {
+    let fixture = Fixture::new();
+    fixture
+        .write(".lintyconfig.json", CONFIG)
+        .write("a&b.txt", "ok\nsay <b>&\"x\"\nagain <b>&\"x\"\n")
+        .write("src/<bell>.txt", "ding\x07\n");
+    let output = fixture.run(&["--no-confirm", "--format", format]);
+    assert_eq!(exit_code(&output), 1, "{}", all_output(&output));
+    let document = stdout(&output);
+    xml::parse(&document).unwrap_or_else(|err| panic!("{err}\n{document}"))
+}
+
+#[test]
+fn checkstyle_report_stays_well_formed_with_markup_in_messages() {
+    let report = report("checkstyle");
+    assert_eq!(report.name, "checkstyle");
+    let files: Vec<&Element> = report.children("file").collect();
+    let names: Vec<&str> = files
+        .iter()
+        .map(|file| file.attribute("name").unwrap())
+        .collect();
+    assert_eq!(names, ["a&b.txt", "src/<bell>.txt"]);
+
+    let errors: Vec<Vec<Option<&str>>> = files[0]
+        .children("error")
+        .map(|error| {
+            ["line", "column", "severity", "message", "source"]
+                .iter()
+                .map(|name| error.attribute(name))
+                .collect()
+        })
+        .collect();
+    let error = |line, column| {
+        vec![
+            Some(line),
+            Some(column),
+            Some("error"),
+            Some(MESSAGE),
+            Some("NoMarkup<&\""),
+        ]
+    };
+    assert_eq!(errors, [error("2", "5"), error("3", "7")]);
+
+    let bells: Vec<&Element> = files[1].children("error").collect();
+    assert_eq!(bells.len(), 1);
+    assert_eq!(bells[0].attribute("severity"), Some("warning"));
+    assert_eq!(bells[0].attribute("source"), Some("NoBell"));
+}
+
+#[test]
+fn junit_report_stays_well_formed_with_markup_in_messages() {
+    let report = report("junit");
+    assert_eq!(report.name, "testsuites");
+    assert_eq!(report.attribute("tests"), Some("3"));
+    assert_eq!(report.attribute("failures"), Some("2"));
+    let suites: Vec<&Element> = report.children("testsuite").collect();
+    let names: Vec<&str> = suites
+        .iter()
+        .map(|suite| suite.attribute("name").unwrap())
+        .collect();
+    assert_eq!(names, ["NoMarkup<&\"", "NoBell", "NoTodo"]);
+
+    let cases: Vec<&Element> = suites[0].children("testcase").collect();
+    assert_eq!(cases.len(), 1);
+    assert_eq!(cases[0].attribute("name"), Some("a&b.txt"));
+    assert_eq!(cases[0].attribute("classname"), Some("NoMarkup<&\""));
+    let failure = cases[0].children("failure").next().unwrap();
+    assert_eq!(
+        failure.attribute("message"),
+        Some(format!("{MESSAGE} (lines: 2, 3)").as_str())
+    );
+    assert_eq!(failure.attribute("type"), Some("error"));
+    assert_eq!(
+        failure.text,
+        "a&b.txt:2:5: <b>&\"x\"\na&b.txt:3:7: <b>&\"x\""
+    );
+
+    // XML can't hold a control character even escaped, so it's replaced.
+    let bell = suites[1].children("testcase").next().unwrap();
+    assert_eq!(bell.attribute("name"), Some("src/<bell>.txt"));
+    let failure = bell.children("failure").next().unwrap();
+    assert_eq!(failure.text, "src/<bell>.txt:1:5: \u{fffd}");
+
+    // A rule that matched nothing passes, with an empty test case.
+    let passing: Vec<&Element> = suites[2].children("testcase").collect();
+    assert_eq!(passing.len(), 1);
+    assert!(passing[0].children.is_empty());
+}
+
+#[test]
+fn parser_rejects_unescaped_markup() {
+    assert!(xml::parse(r#"<a b="x"/>"#).is_ok());
+    assert!(xml::parse(r#"<a b="<"/>"#).is_err());
+    assert!(xml::parse("<a>&</a>").is_err());
+    assert!(xml::parse("<a>&bogus;</a>").is_err());
+    assert!(xml::parse("<a><b></a>").is_err());
+    assert!(xml::parse("<a></a><b/>").is_err());
+}